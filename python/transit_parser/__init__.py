@@ -49,8 +49,10 @@ __version__ = "0.1.0"
 from transit_parser._core import (
     # Data models
     Agency,
+    CachingValidator,
     Calendar,
     CalendarDate,
+    ColumnMappingError,
     ConversionOptions,
     ConversionResult,
     ConversionStats,
@@ -59,15 +61,21 @@ from transit_parser._core import (
     DeadheadInferenceResult,
     # GTFS
     GtfsFeed,
+    InferenceConfig,
     JsonDocument,
     LazyGtfsFeed,
+    ProgressCounter,
+    ReadOptions,
     Route,
     # Schedule
     Schedule,
     ScheduleRow,
+    ScheduleValidationError,
     Shape,
     Stop,
     StopTime,
+    # Errors raised by the Rust core
+    TransitParseError,
     Trip,
     # TXC
     TxcDocument,
@@ -135,10 +143,17 @@ __all__ = [
     "CalendarConversionError",
     "FilterError",
     "InvalidDateError",
+    "TransitParseError",
+    "ColumnMappingError",
+    "ScheduleValidationError",
     # Schedule
     "Schedule",
     "ScheduleRow",
+    "ReadOptions",
+    "ProgressCounter",
     "ValidationConfig",
     "ValidationResult",
+    "CachingValidator",
+    "InferenceConfig",
     "DeadheadInferenceResult",
 ]
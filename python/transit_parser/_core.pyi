@@ -1,5 +1,25 @@
 """Type stubs for the Rust extension module."""
 
+from typing import Any, Iterator, overload
+
+
+# Exceptions
+
+class TransitParseError(OSError):
+    """Input could not be parsed (malformed CSV, XML, JSON or values)."""
+
+    line: int | None
+    """1-based source line of the failure, or None when not known."""
+
+class ColumnMappingError(TransitParseError):
+    """A column mapping could not be loaded or a required field has no column."""
+
+class ScheduleValidationError(ValueError):
+    """A schedule failed validation."""
+
+    errors: list[str]
+    """Each validation error as "CODE: message"."""
+
 
 # Data Models
 
@@ -393,7 +413,41 @@ class ScheduleRow:
     break, or relief.
     """
 
-    def __init__(self) -> None: ...
+    def __init__(
+        self,
+        *,
+        run_number: str | None = None,
+        block: str | None = None,
+        start_place: str | None = None,
+        end_place: str | None = None,
+        start_time: str | None = None,
+        end_time: str | None = None,
+        trip_id: str | None = None,
+        depot: str | None = None,
+        vehicle_class: str | None = None,
+        vehicle_type: str | None = None,
+        start_lat: float | None = None,
+        start_lon: float | None = None,
+        end_lat: float | None = None,
+        end_lon: float | None = None,
+        route_shape_id: str | None = None,
+        row_type: str | None = None,
+        duty_id: str | None = None,
+        shift_id: str | None = None,
+        route_short_name: str | None = None,
+        headsign: str | None = None,
+        service_id: str | None = None,
+        block_sequence: int | None = None,
+    ) -> None:
+        """Create a row, optionally setting any field by keyword.
+
+        `row_type` is parsed like the setter (e.g. "revenue", "pull_out");
+        it defaults to "revenue".
+
+        Raises:
+            ValueError: If `row_type` is not a known row type.
+        """
+        ...
 
     @property
     def run_number(self) -> str | None: ...
@@ -437,37 +491,249 @@ class ScheduleRow:
 
     @property
     def vehicle_class(self) -> str | None: ...
+    @vehicle_class.setter
+    def vehicle_class(self, value: str | None) -> None: ...
+
     @property
     def vehicle_type(self) -> str | None: ...
+    @vehicle_type.setter
+    def vehicle_type(self, value: str | None) -> None: ...
+
     @property
     def start_lat(self) -> float | None: ...
+    @start_lat.setter
+    def start_lat(self, value: float | None) -> None: ...
+
     @property
     def start_lon(self) -> float | None: ...
+    @start_lon.setter
+    def start_lon(self, value: float | None) -> None: ...
+
     @property
     def end_lat(self) -> float | None: ...
+    @end_lat.setter
+    def end_lat(self, value: float | None) -> None: ...
+
     @property
     def end_lon(self) -> float | None: ...
+    @end_lon.setter
+    def end_lon(self, value: float | None) -> None: ...
+
     @property
     def route_shape_id(self) -> str | None: ...
+    @route_shape_id.setter
+    def route_shape_id(self, value: str | None) -> None: ...
+
     @property
     def row_type(self) -> str: ...
+    @row_type.setter
+    def row_type(self, value: str) -> None: ...
+
     @property
     def duty_id(self) -> str | None: ...
+    @duty_id.setter
+    def duty_id(self, value: str | None) -> None: ...
+
     @property
     def shift_id(self) -> str | None: ...
+    @shift_id.setter
+    def shift_id(self, value: str | None) -> None: ...
+
+    @property
+    def route_short_name(self) -> str | None: ...
+    @route_short_name.setter
+    def route_short_name(self, value: str | None) -> None: ...
+
+    @property
+    def headsign(self) -> str | None: ...
+    @headsign.setter
+    def headsign(self, value: str | None) -> None: ...
+
+    @property
+    def service_id(self) -> str | None: ...
+    @service_id.setter
+    def service_id(self, value: str | None) -> None: ...
+
+    @property
+    def block_sequence(self) -> int | None: ...
+    @block_sequence.setter
+    def block_sequence(self, value: int | None) -> None: ...
 
     def is_revenue(self) -> bool:
         """Check if this is a revenue (passenger-carrying) trip."""
         ...
 
+    def has_valid_trip_id(self) -> bool:
+        """Check that the trip ID is set and not blank."""
+        ...
+
     def is_deadhead(self) -> bool:
         """Check if this is a deadhead movement."""
         ...
 
+    def is_layover(self) -> bool:
+        """Check if this is a layover (the vehicle waiting in place)."""
+        ...
+
     def duration_seconds(self) -> int | None:
         """Get duration in seconds."""
         ...
 
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
+
+
+class ProgressCounter:
+    """Counter polled from another thread while a read or validation runs."""
+
+    def __init__(self) -> None: ...
+
+    @property
+    def processed(self) -> int:
+        """Units of work processed so far."""
+        ...
+
+    @property
+    def total(self) -> int:
+        """Total units of work expected, or 0 if unknown."""
+        ...
+
+    def reset(self) -> None:
+        """Reset both counts to zero."""
+        ...
+
+
+class ReadOptions:
+    """Options for reading schedule CSV files."""
+
+    def __init__(
+        self,
+        column_mapping: dict[str, str] | None = None,
+        auto_detect_columns: bool = True,
+        delimiter: str | None = None,
+        has_headers: bool = True,
+        skip_empty_rows: bool = True,
+        progress: ProgressCounter | None = None,
+        fill_missing_end_times: bool = False,
+        null_tokens: list[str] | None = None,
+        untrimmed_fields: set[str] | None = None,
+        max_total_rows: int | None = None,
+        max_file_bytes: int | None = None,
+        row_type_aliases: dict[str, str] | None = None,
+        skip_preamble_lines: int = 0,
+        capture_preamble: bool = False,
+        stop_list_column: str | None = None,
+        stop_list_delimiter: str | None = None,
+        stop_time_separator: str | None = None,
+        strict_coordinates: bool = False,
+        comma_decimal_coordinates: bool = False,
+    ) -> None: ...
+
+    @staticmethod
+    def from_mapping_file(path: str) -> ReadOptions:
+        """Create options from a column mapping file (`.toml`, otherwise JSON)."""
+        ...
+
+    @property
+    def auto_detect_columns(self) -> bool: ...
+    @auto_detect_columns.setter
+    def auto_detect_columns(self, value: bool) -> None: ...
+
+    @property
+    def delimiter(self) -> str | None: ...
+    @delimiter.setter
+    def delimiter(self, value: str | None) -> None: ...
+
+    @property
+    def has_headers(self) -> bool: ...
+    @has_headers.setter
+    def has_headers(self, value: bool) -> None: ...
+
+    @property
+    def skip_empty_rows(self) -> bool: ...
+    @skip_empty_rows.setter
+    def skip_empty_rows(self, value: bool) -> None: ...
+
+    @property
+    def fill_missing_end_times(self) -> bool: ...
+    @fill_missing_end_times.setter
+    def fill_missing_end_times(self, value: bool) -> None: ...
+
+    @property
+    def null_tokens(self) -> list[str]: ...
+    @null_tokens.setter
+    def null_tokens(self, value: list[str]) -> None: ...
+
+    @property
+    def untrimmed_fields(self) -> set[str]: ...
+    @untrimmed_fields.setter
+    def untrimmed_fields(self, value: set[str]) -> None: ...
+
+    @property
+    def max_total_rows(self) -> int | None: ...
+    @max_total_rows.setter
+    def max_total_rows(self, value: int | None) -> None: ...
+
+    @property
+    def max_file_bytes(self) -> int | None: ...
+    @max_file_bytes.setter
+    def max_file_bytes(self, value: int | None) -> None: ...
+
+    @property
+    def row_type_aliases(self) -> dict[str, str]: ...
+    @row_type_aliases.setter
+    def row_type_aliases(self, value: dict[str, str]) -> None: ...
+
+    @property
+    def skip_preamble_lines(self) -> int: ...
+    @skip_preamble_lines.setter
+    def skip_preamble_lines(self, value: int) -> None: ...
+
+    @property
+    def capture_preamble(self) -> bool: ...
+    @capture_preamble.setter
+    def capture_preamble(self, value: bool) -> None: ...
+
+    @property
+    def stop_list_column(self) -> str | None: ...
+    @stop_list_column.setter
+    def stop_list_column(self, value: str | None) -> None: ...
+
+    @property
+    def stop_list_delimiter(self) -> str | None: ...
+    @stop_list_delimiter.setter
+    def stop_list_delimiter(self, value: str | None) -> None: ...
+
+    @property
+    def stop_time_separator(self) -> str | None: ...
+    @stop_time_separator.setter
+    def stop_time_separator(self, value: str | None) -> None: ...
+
+    @property
+    def strict_coordinates(self) -> bool: ...
+    @strict_coordinates.setter
+    def strict_coordinates(self, value: bool) -> None: ...
+
+    @property
+    def comma_decimal_coordinates(self) -> bool: ...
+    @comma_decimal_coordinates.setter
+    def comma_decimal_coordinates(self, value: bool) -> None: ...
+
+    @property
+    def progress(self) -> ProgressCounter | None: ...
+    @progress.setter
+    def progress(self, value: ProgressCounter | None) -> None: ...
+
+    def add_mapping(self, field: str, column: str) -> None:
+        """Map a standard field name to a CSV column name."""
+        ...
+
+    def add_constant(self, field: str, value: str) -> None:
+        """Give a standard field the same literal value on every row."""
+        ...
+
 
 class Schedule:
     """A transit schedule containing rows, blocks, and duties."""
@@ -475,8 +741,31 @@ class Schedule:
     def __init__(self) -> None: ...
 
     @staticmethod
-    def from_csv(path: str) -> Schedule:
-        """Load a schedule from a CSV file."""
+    def from_csv(path: str, options: ReadOptions | None = None) -> Schedule:
+        """Load a schedule from a CSV file, optionally with read options."""
+        ...
+
+    @staticmethod
+    def from_fixed_width(
+        path: str,
+        field_specs: list[tuple[str, int, int]],
+        options: ReadOptions | None = None,
+    ) -> Schedule:
+        """Load a schedule from a fixed-width file."""
+        ...
+
+    @staticmethod
+    def from_ndjson(path: str, options: ReadOptions | None = None) -> Schedule:
+        """Load a schedule from a JSON Lines (NDJSON) file, one row per line.
+
+        Blank lines are skipped. Raises TransitParseError naming the line
+        number if a line isn't a valid row.
+        """
+        ...
+
+    @staticmethod
+    def from_ndjson_string(ndjson: str) -> Schedule:
+        """Load a schedule from a JSON Lines (NDJSON) string."""
         ...
 
     @staticmethod
@@ -493,6 +782,19 @@ class Schedule:
         ...
 
     def __len__(self) -> int: ...
+    @overload
+    def __getitem__(self, index: int) -> ScheduleRow: ...
+    @overload
+    def __getitem__(self, index: slice) -> list[ScheduleRow]: ...
+    def __iter__(self) -> Iterator[ScheduleRow]: ...
+
+    def add_row(self, row: ScheduleRow) -> None:
+        """Append a copy of a row to the schedule."""
+        ...
+
+    def extend(self, rows: list[ScheduleRow]) -> None:
+        """Append copies of several rows to the schedule, in order."""
+        ...
 
     @property
     def rows(self) -> list[ScheduleRow]: ...
@@ -500,10 +802,48 @@ class Schedule:
     @property
     def revenue_trip_count(self) -> int: ...
 
+    @property
+    def metadata(self) -> dict[str, Any]:
+        """File-level metadata: source_file, name, operator, start_date,
+        end_date, preamble (lines captured before the CSV header),
+        parse_warnings (values left empty because they couldn't be parsed) and
+        block_depot_conflicts (block ID -> depots, for blocks whose rows name
+        more than one depot, found when blocks were last derived) and
+        extracted ("block B1" or "duty D1" for a schedule made by
+        extract_block or extract_duty)."""
+        ...
+
     def block_ids(self) -> list[str]:
         """Get unique block IDs."""
         ...
 
+    def derive_blocks_by(self, fields: list[str]) -> None:
+        """Group rows into blocks by the given fields from now on.
+
+        Block IDs become the field values joined with ':', e.g. "NORTH:12"
+        for ["depot", "block"]. Use ["block"] to go back to the default.
+
+        Raises:
+            ValueError: If the list is empty or has a field that can't be used.
+        """
+        ...
+
+    @property
+    def block_key(self) -> list[str]:
+        """Fields that identify a row's block (["block"] by default)."""
+        ...
+
+    def namespace_blocks_by_depot(self) -> int:
+        """Prefix block IDs shared by several depots with the depot.
+
+        Rows of block "B1" in depots D1 and D2 become blocks "D1:B1" and
+        "D2:B1". Rows without a depot keep their block ID.
+
+        Returns:
+            Number of block IDs that were split.
+        """
+        ...
+
     def run_numbers(self) -> list[str]:
         """Get unique run numbers."""
         ...
@@ -512,25 +852,400 @@ class Schedule:
         """Get unique depot codes."""
         ...
 
+    def infer_likely_depots(self) -> list[tuple[str, int]]:
+        """Places ranked by how often they start or end a block, as likely depots.
+
+        A heuristic for schedules without depot data; the top place may be a
+        terminus rather than a depot.
+        """
+        ...
+
     def trip_ids(self) -> list[str]:
         """Get unique trip IDs."""
         ...
 
+    def row_for_trip(self, trip_id: str) -> ScheduleRow | None:
+        """Get a copy of the first row with this trip ID, or None."""
+        ...
+
+    def index_by_trip_id(self) -> dict[str, int]:
+        """Map each trip ID to the index of its first row."""
+        ...
+
+    def service_ids(self) -> list[str]:
+        """Get unique service IDs."""
+        ...
+
+    def filter_service(self, service_id: str) -> Schedule:
+        """Copy the rows for one service into a new schedule."""
+        ...
+
+    def extract_block(self, block_id: str) -> Schedule | None:
+        """Copy the rows of one block into a new schedule, or None if there are none."""
+        ...
+
+    def extract_duty(self, duty_id: str) -> Schedule | None:
+        """Copy the rows of one duty (by duty_id, else run_number) into a new
+        schedule, or None if there are none."""
+        ...
+
+    def sort_by_time(self) -> None:
+        """Sort rows by start time (stable)."""
+        ...
+
+    def sort_by_block_and_time(self) -> None:
+        """Sort rows by block, then start time (stable). Rows without a block go last.
+
+        Blocks are identified by the block key set with derive_blocks_by.
+        """
+        ...
+
+    def assign_block_sequences(self) -> None:
+        """Number each row by its chronological position within its block."""
+        ...
+
+    def split_block(self, block_id: str, at_seconds: int) -> tuple[str, str]:
+        """Split a block at a time boundary into `{id}a` and `{id}b`.
+
+        Args:
+            block_id: Block to split.
+            at_seconds: Boundary in seconds since midnight. No row may span it.
+
+        Returns:
+            The two new block IDs.
+
+        Raises:
+            KeyError: If the block doesn't exist.
+            ValueError: If a row spans the boundary or lacks a start time, all
+                rows fall on one side, or a new ID is already in use.
+        """
+        ...
+
+    def move_trip(self, trip_id: str, to_block: str) -> None:
+        """Reassign a revenue trip to another block.
+
+        The row's block_sequence is cleared; call assign_block_sequences()
+        to renumber.
+
+        Raises:
+            KeyError: If no revenue row has the trip ID.
+            ValueError: If more than one revenue row has the trip ID.
+        """
+        ...
+
+    def duplicate_row_indices(self) -> list[int]:
+        """Indices of rows that duplicate an earlier row (dry run of deduplicate_rows)."""
+        ...
+
+    def deduplicate_rows(self) -> int:
+        """Remove exact duplicate rows, returning how many were removed."""
+        ...
+
+    def anonymize(
+        self,
+        salt: str | None = None,
+        anonymize_blocks: bool = False,
+        coordinate_jitter_meters: float | None = None,
+    ) -> dict[str, dict[str, str]]:
+        """Scrub run numbers, duty/shift IDs and optionally blocks and coordinates."""
+        ...
+
+    def normalize_ids(
+        self,
+        *,
+        normalize_blocks: bool = True,
+        normalize_runs: bool = True,
+        block_prefix: str = "B",
+        run_prefix: str = "R",
+        width: int = 4,
+    ) -> dict[str, dict[str, str]]:
+        """Renumber block IDs and run numbers into one consistent scheme.
+
+        Returns:
+            Dict with blocks and run_numbers, each mapping original to new values.
+        """
+        ...
+
+    def assign_blocks_by_vehicle_continuity(
+        self,
+        min_layover_seconds: int = 0,
+        max_layover_seconds: int = 1800,
+        block_id_prefix: str = "AUTO",
+    ) -> dict[str, Any]:
+        """Chain unblocked revenue trips into generated blocks.
+
+        A trip follows another when it starts where the other ends, within the
+        layover range. Trips that chain with no other trip keep no block.
+
+        Returns:
+            Dict with trips_blocked, trips_unmatched and block_ids.
+        """
+        ...
+
+    def rows_missing_times(self) -> list[int]:
+        """Indices of rows whose start or end time is missing or unparseable."""
+        ...
+
+    def backfill_end_times(self, gtfs: GtfsFeed) -> int:
+        """Fill missing revenue trip end times from GTFS stop_times."""
+        ...
+
+    def interlining_edges(self) -> list[tuple[str, str, int | None, bool]]:
+        """Connections between consecutive revenue trips in each block."""
+        ...
+
+    def normalize_overnight_times(self) -> int:
+        """Add 24 hours to end times that wrap past midnight, returning rows changed."""
+        ...
+
+    def backfill_coordinates(self, gtfs: GtfsFeed) -> int:
+        """Fill missing row coordinates from GTFS stops, returning rows changed."""
+        ...
+
+    def expand_stop_times(self, gtfs: GtfsFeed) -> dict[str, Any]:
+        """Expand revenue rows into their GTFS stop_times.
+
+        Returns a dict with ``stop_times`` (list of dicts carrying the row's
+        block, run and duty) and ``rows_skipped`` (revenue rows without a
+        trip ID or whose trip has no stop times in the feed).
+        """
+        ...
+
+    def run_pipeline(
+        self,
+        gtfs: GtfsFeed | None = None,
+        config: InferenceConfig | None = None,
+        normalize_overnight: bool = True,
+        sort: bool = True,
+        infer_deadheads: bool = True,
+        backfill_coordinates: bool = True,
+        assign_sequences: bool = True,
+    ) -> dict[str, Any]:
+        """Run the standard cleanup steps in order, returning a report of what changed."""
+        ...
+
     def summary(self) -> dict[str, int]:
         """Get summary statistics."""
         ...
 
+    def summary_by_service(self) -> list[tuple[str | None, dict[str, int]]]:
+        """Summary statistics per service, rows without a service first."""
+        ...
+
+    def deadhead_stats(self) -> dict[str, int]:
+        """Deadhead counts and seconds by type: pull-outs, pull-ins and interlinings."""
+        ...
+
+    def deadhead_hotspots(self) -> list[tuple[str, int]]:
+        """Places ranked by how many interlining deadheads start or end there."""
+        ...
+
+    def depot_deadhead_hotspots(self) -> list[tuple[str, int]]:
+        """Places ranked by how many pull-outs and pull-ins start or end there."""
+        ...
+
+    def peak_vehicle_count(self) -> tuple[int, int]:
+        """Peak number of blocks in service at once, as (count, seconds since midnight)."""
+        ...
+
+    def vehicles_in_service_at(self, seconds: int) -> int:
+        """Number of blocks in service at the given time (seconds since midnight)."""
+        ...
+
+    def rows_active_at(self, seconds: int, place: str | None = None) -> list[ScheduleRow]:
+        """Rows running at the given time, optionally only those starting or ending at `place`."""
+        ...
+
+    def departure_histogram(
+        self, bucket_seconds: int = 3600, route: str | None = None
+    ) -> list[tuple[int, int]]:
+        """Count revenue departures per time bucket, as (bucket start seconds, count).
+
+        Only trips with the given route_short_name are counted if `route` is
+        set. Buckets without departures are left out.
+
+        Raises:
+            ValueError: If `bucket_seconds` is 0.
+        """
+        ...
+
+    def service_span(self) -> tuple[int, int] | None:
+        """Earliest start and latest end across revenue rows, or None if there are none."""
+        ...
+
+    def bounding_box(self) -> tuple[float, float, float, float] | None:
+        """Extent of the schedule's coordinates as (min_lat, min_lon, max_lat, max_lon), or None if no row has coordinates."""
+        ...
+
+    def centroid(self) -> tuple[float, float] | None:
+        """Mean (lat, lon) of all start and end points, or None if no row has coordinates."""
+        ...
+
+    def route_summaries(self) -> dict[str, dict[str, Any]]:
+        """Get summary statistics per route, keyed by route short name."""
+        ...
+
+    def detect_frequencies(
+        self, route: str, min_trips: int = 3, headway_tolerance_seconds: int = 60
+    ) -> list[dict[str, int]]:
+        """Bands of a route's trips departing at a regular headway, in time order.
+
+        Each band has start_time_seconds, end_time_seconds (one headway after
+        the last departure, as in GTFS frequencies.txt), headway_seconds and
+        trip_count. Trips with irregular headways are in no band.
+        """
+        ...
+
+    def block_kpi_table(self) -> list[dict[str, Any]]:
+        """Get one row of KPIs per block, sorted by block ID."""
+        ...
+
+    def duty_kpi_table(self) -> list[dict[str, Any]]:
+        """Get one row of KPIs per duty, sorted by duty ID."""
+        ...
+
+    def compact_block_summaries(self) -> list[dict[str, Any]]:
+        """Depot, start/end and ordered routes served per block, sorted by block ID."""
+        ...
+
+    def block_timeline(self, block_id: str) -> list[dict[str, Any]]:
+        """Classified time segments covering one block, for a Gantt-style view.
+
+        Raises:
+            KeyError: If the block doesn't exist.
+        """
+        ...
+
+    def block_coverage(self) -> list[dict[str, Any]]:
+        """Duty coverage of each block, sorted by block ID.
+
+        Each dict has block_id, fully_covered, uncovered_seconds, covered and
+        uncovered ((start, end) second intervals) and duty_ids.
+        """
+        ...
+
+    def block_cumulative_distances(self, block_id: str) -> list[float | None]:
+        """Running distance in meters at the end of each row of one block.
+
+        Rows with an unknown distance are None and add nothing; later rows
+        carry on from the last known total.
+
+        Raises:
+            KeyError: If the block doesn't exist.
+        """
+        ...
+
+    def duty_break_compliance(
+        self,
+        duty_id: str,
+        max_work_before_break_seconds: int,
+        min_break_seconds: int,
+    ) -> dict[str, Any]:
+        """Whether one duty gets a qualifying break within the working window.
+
+        Returns a dict with is_compliant, has_qualifying_break, breaks (list of
+        (start, end) seconds), longest_stretch ((start, end) or None) and
+        longest_stretch_seconds.
+
+        Raises:
+            KeyError: If the duty doesn't exist.
+        """
+        ...
+
+    def total_driving_seconds(self) -> dict[str, int]:
+        """Driving time summed over all duties.
+
+        Returns a dict with seconds, duties_counted and duties_excluded (duties
+        left out because a row is missing its start or end time).
+        """
+        ...
+
+    def total_break_seconds(self) -> dict[str, int]:
+        """Break and relief time summed over all duties, like total_driving_seconds."""
+        ...
+
+    def total_paid_seconds(
+        self, paid_breaks: bool = False, minimum_paid_seconds: int = 0
+    ) -> dict[str, int]:
+        """Paid time summed over all duties, like total_driving_seconds.
+
+        Each duty is paid its length, less breaks unless paid_breaks is set,
+        and at least minimum_paid_seconds.
+        """
+        ...
+
+    def lint(self) -> list[dict]:
+        """Check the schedule for style and consistency issues, without GTFS."""
+        ...
+
     def validate(
         self,
         gtfs: GtfsFeed,
         config: ValidationConfig | None = None,
+        progress: ProgressCounter | None = None,
     ) -> ValidationResult:
         """Validate the schedule against GTFS data."""
         ...
 
+    def validate_multi(
+        self,
+        feeds: list[GtfsFeed],
+        config: ValidationConfig | None = None,
+        progress: ProgressCounter | None = None,
+    ) -> ValidationResult:
+        """Validate against several GTFS feeds at once, e.g. one per operator.
+
+        Trip, stop and shape IDs from every feed are accepted.
+        """
+        ...
+
+    def validate_and_fix(
+        self,
+        gtfs: GtfsFeed | None = None,
+        config: ValidationConfig | None = None,
+    ) -> tuple[dict[str, Any], ValidationResult]:
+        """Apply safe fixes in place, then validate the fixed schedule.
+
+        Trims whitespace, moves overnight end times past midnight, removes
+        duplicate rows and sorts by block and time. Without a GTFS feed only
+        structure and business rules are validated.
+
+        Returns:
+            (fixes, result), where fixes has trimmed_values (row, field,
+            original), overnight_end_times (row, original), duplicate_rows_removed
+            and rows_moved. Row indices refer to the schedule before fixing.
+        """
+        ...
+
+    def validate_by_service(
+        self,
+        gtfs: GtfsFeed,
+        config: ValidationConfig | None = None,
+    ) -> list[tuple[str | None, ValidationResult]]:
+        """Validate each service separately.
+
+        Row indices in error contexts are relative to that service's rows.
+        """
+        ...
+
+    def validate_rows(
+        self,
+        gtfs: GtfsFeed,
+        row_indices: list[int],
+        config: ValidationConfig | None = None,
+    ) -> ValidationResult:
+        """Revalidate only the given rows, e.g. after editing them.
+
+        Runs per-row GTFS and business rule checks, plus layover checks with
+        the neighbouring rows. Block and duty checks need a full validate().
+        Out-of-range indices are ignored.
+        """
+        ...
+
     def validate_structure(
         self,
         config: ValidationConfig | None = None,
+        progress: ProgressCounter | None = None,
     ) -> ValidationResult:
         """Validate schedule structure (without GTFS)."""
         ...
@@ -539,6 +1254,7 @@ class Schedule:
         self,
         gtfs: GtfsFeed | None = None,
         default_depot: str | None = None,
+        config: InferenceConfig | None = None,
     ) -> DeadheadInferenceResult:
         """Infer missing deadheads."""
         ...
@@ -548,6 +1264,13 @@ class Schedule:
         path: str,
         columns: list[str] | None = None,
         preset: str | None = None,
+        header_prefix: str | None = None,
+        line_terminator: str = "lf",
+        encoding: str | None = None,
+        lossy_encoding: bool = False,
+        exclude_columns: list[str] | None = None,
+        round_to_seconds: int | None = None,
+        coordinate_precision: int | None = 6,
     ) -> None:
         """Export to CSV file.
 
@@ -556,17 +1279,73 @@ class Schedule:
             columns: Custom column list to export.
             preset: Export preset name (default, minimal, extended,
                     optibus, hastus, gtfs_block).
+            header_prefix: Prefix added to every header, e.g. "tp_".
+            line_terminator: "lf" or "crlf".
+            encoding: Output encoding label, e.g. "windows-1252" (default UTF-8).
+            lossy_encoding: Write "?" for characters the encoding can't
+                    represent instead of raising.
+            exclude_columns: Fields to drop from the chosen columns or preset.
+            round_to_seconds: Round times to the nearest multiple of this many
+                    seconds, e.g. 60 for whole minutes.
+            coordinate_precision: Decimal places for coordinates (default 6,
+                    about 0.1m); None writes full precision.
         """
         ...
 
+    def to_csv_by_block(
+        self,
+        dir: str,
+        filename_template: str = "{block_id}",
+        columns: list[str] | None = None,
+        preset: str | None = None,
+        header_prefix: str | None = None,
+        line_terminator: str = "lf",
+        encoding: str | None = None,
+        lossy_encoding: bool = False,
+        exclude_columns: list[str] | None = None,
+        round_to_seconds: int | None = None,
+        coordinate_precision: int | None = 6,
+    ) -> list[str]:
+        """Export one CSV file per block into a directory, returning the paths."""
+        ...
+
     def to_csv_string(
         self,
         columns: list[str] | None = None,
         preset: str | None = None,
+        header_prefix: str | None = None,
+        line_terminator: str = "lf",
+        exclude_columns: list[str] | None = None,
+        round_to_seconds: int | None = None,
+        coordinate_precision: int | None = 6,
     ) -> str:
         """Export to CSV string."""
         ...
 
+    def to_ndjson(self, path: str, append: bool = False) -> None:
+        """Export rows as JSON Lines (NDJSON), optionally appending to the file."""
+        ...
+
+    def to_ndjson_string(self) -> str:
+        """Export rows as a JSON Lines (NDJSON) string."""
+        ...
+
+    def content_hash(self) -> int:
+        """Stable hash of all rows, for detecting changes. Depends on row order."""
+        ...
+
+    def equivalent_to(self, other: Schedule, compare_metadata: bool = False) -> bool:
+        """Whether both schedules hold the same rows, in any order.
+
+        Rows are compared as multisets. Metadata is ignored unless
+        compare_metadata is set. Use == for an order-sensitive comparison.
+        """
+        ...
+
+    def __eq__(self, other: object) -> bool: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
+
 
 class ValidationConfig:
     """Configuration for schedule validation."""
@@ -583,6 +1362,21 @@ class ValidationConfig:
         validate_block_continuity: bool | None = None,
         validate_duty_constraints: bool | None = None,
         generate_warnings: bool | None = None,
+        *,
+        min_block_duration_seconds: int | None = None,
+        max_block_duration_seconds: int | None = None,
+        flag_orphan_trips: bool | None = None,
+        flag_missing_coordinates: bool | None = None,
+        headway_deviation_threshold: float | None = None,
+        max_layover_seconds: int | None = None,
+        max_deadhead_speed_mps: float | None = None,
+        min_deadhead_duration_seconds: int | None = None,
+        max_revenue_speed_mps: float | None = None,
+        chronology_tolerance_seconds: int | None = None,
+        layover_exempt_route_types: list[int] | None = None,
+        continuous_driving_reset: str | None = None,
+        warnings_as_errors: bool | None = None,
+        discontinuity_as_error: bool | None = None,
     ) -> None:
         """Create validation config.
 
@@ -597,6 +1391,33 @@ class ValidationConfig:
             validate_block_continuity: Whether to validate block continuity.
             validate_duty_constraints: Whether to validate duty constraints.
             generate_warnings: Whether to generate warnings.
+            min_block_duration_seconds: Minimum block length (default: 0, no minimum).
+            max_block_duration_seconds: Maximum block length (default: 57600).
+            flag_orphan_trips: Warn on trips without a block (default: True).
+            flag_missing_coordinates: Warn on trips without coordinates (default: False).
+            headway_deviation_threshold: Headway deviation in std devs (default: 2.0).
+            max_layover_seconds: Same-place layover before an idle warning (default: 7200).
+            max_deadhead_speed_mps: Maximum plausible deadhead speed (default: 30.0).
+            min_deadhead_duration_seconds: Minimum deadhead duration (default: 60).
+            max_revenue_speed_mps: Maximum plausible straight-line speed of a
+                revenue trip with coordinates before warning W206, e.g. 25.0
+                (default: None, no check).
+            chronology_tolerance_seconds: Tolerated overlap between rows in a block (default: 0).
+            layover_exempt_route_types: GTFS route_type codes whose trips skip layover
+                checks (default: none).
+            continuous_driving_reset: What ends a stretch of continuous driving:
+                "any_break" (default), where any break or relief ends it, or
+                "qualifying_break_only", where only time off of
+                min_break_duration_seconds or more does.
+            warnings_as_errors: Fail validation when any warning is reported
+                (default: False). Warnings still count towards warning_count.
+            discontinuity_as_error: Report a location discontinuity with no
+                deadhead between the rows as an error instead of W101
+                (default: False; always on with strict compliance).
+
+        Raises:
+            ValueError: If the compliance level, a route type code or the
+                continuous driving reset is unknown.
         """
         ...
 
@@ -610,18 +1431,25 @@ class ValidationConfig:
         """Create a lenient validation config."""
         ...
 
+    @property
+    def headway_deviation_threshold(self) -> float | None:
+        """Headway deviation threshold in standard deviations; None turns the check off."""
+        ...
+    @headway_deviation_threshold.setter
+    def headway_deviation_threshold(self, value: float | None) -> None: ...
+
 
 class ValidationResult:
     """Result of schedule validation."""
 
     @property
     def is_valid(self) -> bool:
-        """Check if validation passed (no errors)."""
+        """Check if validation passed (no errors, and no warnings if warnings_as_errors)."""
         ...
 
     @property
     def error_count(self) -> int:
-        """Get the number of errors."""
+        """Get the number of errors. Promoted warnings are not included."""
         ...
 
     @property
@@ -631,12 +1459,12 @@ class ValidationResult:
 
     @property
     def errors(self) -> list[dict[str, str]]:
-        """Get all errors as dicts with code, category, message, context."""
+        """Get all errors as dicts with code, category, message, context, severity."""
         ...
 
     @property
     def warnings(self) -> list[dict[str, str]]:
-        """Get all warnings as dicts with code, category, message, context."""
+        """Get all warnings as dicts with code, category, message, context, severity."""
         ...
 
     @property
@@ -649,6 +1477,142 @@ class ValidationResult:
         """Get number of blocks validated."""
         ...
 
+    @property
+    def warnings_as_errors(self) -> bool:
+        """Whether warnings fail validation (from ValidationConfig)."""
+        ...
+
+    def raise_for_errors(self) -> None:
+        """Raise ScheduleValidationError if validation failed.
+
+        With warnings_as_errors, the exception's errors also list the warnings.
+        """
+        ...
+
+
+class CachingValidator:
+    """Validator that reuses its last result for an unchanged schedule and feed."""
+
+    def __init__(self, config: ValidationConfig | None = None) -> None: ...
+    def validate(self, schedule: Schedule, gtfs: GtfsFeed, gtfs_hash: int) -> ValidationResult:
+        """Validate, reusing the last result if the schedule and GTFS hash are unchanged.
+
+        Args:
+            schedule: Schedule to validate, identified by its content_hash()
+                and block_key.
+            gtfs: GTFS feed to validate against.
+            gtfs_hash: Caller-chosen value that changes when the feed changes,
+                e.g. the feed's modification time.
+        """
+        ...
+
+    @property
+    def cache_hits(self) -> int:
+        """Number of validations answered from the cache."""
+        ...
+
+    def clear(self) -> None:
+        """Forget the cached result."""
+        ...
+
+class InferenceConfig:
+    """Configuration for deadhead inference."""
+
+    def __init__(
+        self,
+        default_depot: str | None = None,
+        average_speed_mps: float | None = None,
+        min_gap_seconds: int | None = None,
+        infer_interlining: bool | None = None,
+        distance_model: str | None = None,
+        road_factor: float | None = None,
+        depot_locations: dict[str, str] | None = None,
+        symmetric_overrides: bool | None = None,
+        infer_default_depot: bool | None = None,
+    ) -> None:
+        """Create inference config.
+
+        Args:
+            default_depot: Depot used for pull-outs/pull-ins when rows have none.
+            average_speed_mps: Deadhead speed for time estimates (default: 8.33).
+            min_gap_seconds: Minimum gap before inferring a deadhead (default: 60).
+            infer_interlining: Whether to infer interlining deadheads (default: True).
+            distance_model: haversine, haversine_with_factor or manhattan.
+            road_factor: Circuity factor for haversine_with_factor (default: 1.3).
+                Raises ValueError with any other distance_model.
+            depot_locations: Maps stop IDs to depot codes.
+            symmetric_overrides: Also apply distance overrides in reverse (default: False).
+            infer_default_depot: Without default_depot, use the schedule's most
+                likely depot (see Schedule.infer_likely_depots) (default: False).
+        """
+        ...
+
+    @property
+    def default_depot(self) -> str | None: ...
+    @default_depot.setter
+    def default_depot(self, value: str | None) -> None: ...
+
+    @property
+    def average_speed_mps(self) -> float: ...
+    @average_speed_mps.setter
+    def average_speed_mps(self, value: float) -> None: ...
+
+    @property
+    def min_gap_seconds(self) -> int: ...
+    @min_gap_seconds.setter
+    def min_gap_seconds(self, value: int) -> None: ...
+
+    @property
+    def infer_interlining(self) -> bool: ...
+    @infer_interlining.setter
+    def infer_interlining(self, value: bool) -> None: ...
+
+    @property
+    def distance_model(self) -> str: ...
+    @distance_model.setter
+    def distance_model(self, value: str) -> None: ...
+
+    @property
+    def road_factor(self) -> float | None:
+        """Circuity factor of the haversine_with_factor model, else None.
+
+        Setting a factor selects haversine_with_factor; setting None switches
+        that model to haversine and leaves other models unchanged.
+        """
+        ...
+    @road_factor.setter
+    def road_factor(self, value: float | None) -> None: ...
+
+    @property
+    def depot_locations(self) -> dict[str, str]:
+        """Stop IDs mapped to depot codes."""
+        ...
+
+    def add_depot(self, stop_id: str, depot_code: str) -> None:
+        """Mark a stop as a depot location."""
+        ...
+
+    @property
+    def distance_overrides(self) -> dict[tuple[str, str], tuple[float, int]]:
+        """Measured deadheads as {(from, to): (meters, seconds)}."""
+        ...
+
+    @property
+    def symmetric_overrides(self) -> bool: ...
+    @symmetric_overrides.setter
+    def symmetric_overrides(self, value: bool) -> None: ...
+
+    @property
+    def infer_default_depot(self) -> bool: ...
+    @infer_default_depot.setter
+    def infer_default_depot(self, value: bool) -> None: ...
+
+    def add_distance_override(
+        self, from_place: str, to_place: str, meters: float, seconds: int
+    ) -> None:
+        """Use a measured distance and time for deadheads from one place to another."""
+        ...
+
 
 class DeadheadInferenceResult:
     """Result of deadhead inference."""
@@ -677,3 +1641,30 @@ class DeadheadInferenceResult:
     def incomplete_blocks(self) -> list[str]:
         """Blocks that couldn't have deadheads inferred."""
         ...
+
+    @property
+    def skipped_interlinings(self) -> list[tuple[str, int, str]]:
+        """Trip pairs whose interlining couldn't be checked, as (block, row index, reason)."""
+        ...
+
+    @property
+    def total_added_seconds(self) -> int:
+        """Total duration of the inferred deadheads in seconds."""
+        ...
+
+    @property
+    def total_added_meters(self) -> float:
+        """Total estimated distance of the inferred deadheads in meters."""
+        ...
+
+    def totals_by_block(self) -> dict[str, tuple[int, float]]:
+        """Added (seconds, meters) per block ID."""
+        ...
+
+    def to_csv(self, path: str) -> None:
+        """Write the inferred deadheads to a CSV file for review."""
+        ...
+
+    def to_csv_string(self) -> str:
+        """Export the inferred deadheads to a CSV string."""
+        ...
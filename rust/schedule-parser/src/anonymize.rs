@@ -0,0 +1,250 @@
+//! Identifier scrubbing for sharing schedules outside the agency.
+
+use crate::hash::Fnv1a;
+use crate::models::ScheduleRow;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How anonymized identifiers are generated.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum IdStrategy {
+    /// Number values in order of first appearance (`R1`, `R2`, ...).
+    #[default]
+    Sequential,
+    /// Salted hash of the original value, stable across schedules that
+    /// share the salt (`R3f9a...`).
+    Hash { salt: String },
+}
+
+/// Options for [`Schedule::anonymize`](crate::Schedule::anonymize).
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizeOptions {
+    /// How replacement identifiers are generated.
+    pub strategy: IdStrategy,
+    /// Also replace block IDs.
+    pub anonymize_blocks: bool,
+    /// Move each distinct coordinate by up to this many meters in latitude
+    /// and longitude. The same point always moves the same way.
+    pub coordinate_jitter_meters: Option<f64>,
+}
+
+impl AnonymizeOptions {
+    /// Create default options (sequential IDs, blocks and coordinates kept).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the identifier strategy.
+    pub fn with_strategy(mut self, strategy: IdStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set whether block IDs are replaced.
+    pub fn with_anonymize_blocks(mut self, anonymize: bool) -> Self {
+        self.anonymize_blocks = anonymize;
+        self
+    }
+
+    /// Jitter coordinates by up to the given distance.
+    pub fn with_coordinate_jitter(mut self, meters: f64) -> Self {
+        self.coordinate_jitter_meters = Some(meters);
+        self
+    }
+}
+
+/// Lookup from anonymized values back to the originals.
+///
+/// Keep this private to the agency; it is what turns findings about the
+/// anonymized schedule back into real run numbers, duties and blocks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnonymizationKey {
+    /// Anonymized run number -> original.
+    pub run_numbers: HashMap<String, String>,
+    /// Anonymized duty ID -> original.
+    pub duty_ids: HashMap<String, String>,
+    /// Anonymized shift ID -> original.
+    pub shift_ids: HashMap<String, String>,
+    /// Anonymized block ID -> original (empty unless blocks were anonymized).
+    pub blocks: HashMap<String, String>,
+}
+
+/// Replaces the values of one field, keeping equal values equal.
+struct FieldAnonymizer<'a> {
+    prefix: &'static str,
+    strategy: &'a IdStrategy,
+    replacements: HashMap<String, String>,
+}
+
+impl<'a> FieldAnonymizer<'a> {
+    fn new(prefix: &'static str, strategy: &'a IdStrategy) -> Self {
+        Self {
+            prefix,
+            strategy,
+            replacements: HashMap::new(),
+        }
+    }
+
+    fn apply(&mut self, value: &mut Option<String>) {
+        let Some(original) = value.as_ref() else {
+            return;
+        };
+        let next = self.replacements.len() + 1;
+        let replacement = self
+            .replacements
+            .entry(original.clone())
+            .or_insert_with(|| match self.strategy {
+                IdStrategy::Sequential => format!("{}{}", self.prefix, next),
+                IdStrategy::Hash { salt } => {
+                    format!("{}{:016x}", self.prefix, fnv1a(salt, original))
+                }
+            });
+        *value = Some(replacement.clone());
+    }
+
+    /// Invert into anonymized -> original.
+    fn into_key(self) -> HashMap<String, String> {
+        self.replacements
+            .into_iter()
+            .map(|(original, anonymized)| (anonymized, original))
+            .collect()
+    }
+}
+
+/// Anonymize rows in place, returning the key to reverse it.
+pub(crate) fn anonymize_rows(
+    rows: &mut [ScheduleRow],
+    options: &AnonymizeOptions,
+) -> AnonymizationKey {
+    let mut runs = FieldAnonymizer::new("R", &options.strategy);
+    let mut duties = FieldAnonymizer::new("D", &options.strategy);
+    let mut shifts = FieldAnonymizer::new("S", &options.strategy);
+    let mut blocks = FieldAnonymizer::new("B", &options.strategy);
+
+    let salt = match &options.strategy {
+        IdStrategy::Hash { salt } => salt.as_str(),
+        IdStrategy::Sequential => "",
+    };
+
+    for row in rows.iter_mut() {
+        runs.apply(&mut row.run_number);
+        duties.apply(&mut row.duty_id);
+        shifts.apply(&mut row.shift_id);
+        if options.anonymize_blocks {
+            blocks.apply(&mut row.block);
+        }
+
+        if let Some(meters) = options.coordinate_jitter_meters {
+            jitter(&mut row.start_lat, &mut row.start_lon, meters, salt);
+            jitter(&mut row.end_lat, &mut row.end_lon, meters, salt);
+        }
+    }
+
+    AnonymizationKey {
+        run_numbers: runs.into_key(),
+        duty_ids: duties.into_key(),
+        shift_ids: shifts.into_key(),
+        blocks: blocks.into_key(),
+    }
+}
+
+/// Move a coordinate by a pseudo-random offset derived from the point itself.
+fn jitter(lat: &mut Option<f64>, lon: &mut Option<f64>, meters: f64, salt: &str) {
+    let (Some(lat_deg), Some(lon_deg)) = (*lat, *lon) else {
+        return;
+    };
+
+    let hash = fnv1a(
+        salt,
+        &format!("{}:{}", lat_deg.to_bits(), lon_deg.to_bits()),
+    );
+    // Two offsets in [-1, 1] from the high and low halves of the hash
+    let unit = |bits: u64| (bits & 0xFFFF_FFFF) as f64 / u32::MAX as f64 * 2.0 - 1.0;
+    let north = unit(hash >> 32) * meters;
+    let east = unit(hash) * meters;
+
+    const METERS_PER_DEGREE: f64 = 111_320.0;
+    *lat = Some(lat_deg + north / METERS_PER_DEGREE);
+    *lon = Some(lon_deg + east / (METERS_PER_DEGREE * lat_deg.to_radians().cos().max(0.01)));
+}
+
+/// 64-bit FNV-1a hash of `salt` and `value`, stable across builds.
+fn fnv1a(salt: &str, value: &str) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(value.as_bytes());
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Schedule;
+
+    fn make_row(run: &str, duty: &str, block: &str) -> ScheduleRow {
+        ScheduleRow {
+            run_number: Some(run.to_string()),
+            duty_id: Some(duty.to_string()),
+            block: Some(block.to_string()),
+            start_lat: Some(51.5),
+            start_lon: Some(-0.12),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sequential_keeps_references() {
+        let mut schedule = Schedule::from_rows(vec![
+            make_row("Run 42", "Duty 7", "BLK1"),
+            make_row("Run 43", "Duty 7", "BLK1"),
+            make_row("Run 42", "Duty 8", "BLK2"),
+        ]);
+
+        let key = schedule.anonymize(AnonymizeOptions::new());
+
+        let runs: Vec<_> = schedule
+            .rows
+            .iter()
+            .map(|r| r.run_number.clone().unwrap())
+            .collect();
+        assert_eq!(runs, vec!["R1", "R2", "R1"]);
+        assert_eq!(schedule.rows[1].duty_id.as_deref(), Some("D1"));
+        assert_eq!(schedule.rows[2].duty_id.as_deref(), Some("D2"));
+        assert_eq!(schedule.rows[0].block.as_deref(), Some("BLK1"));
+        assert_eq!(schedule.rows[0].start_lat, Some(51.5));
+
+        assert_eq!(key.run_numbers["R2"], "Run 43");
+        assert_eq!(key.duty_ids["D2"], "Duty 8");
+        assert!(key.blocks.is_empty());
+        assert_eq!(schedule.duties().len(), 2);
+    }
+
+    #[test]
+    fn test_hash_is_deterministic() {
+        let options = AnonymizeOptions::new()
+            .with_strategy(IdStrategy::Hash {
+                salt: "secret".to_string(),
+            })
+            .with_anonymize_blocks(true)
+            .with_coordinate_jitter(200.0);
+
+        let mut first = Schedule::from_rows(vec![make_row("Run 42", "Duty 7", "BLK1")]);
+        let mut second = Schedule::from_rows(vec![
+            make_row("Run 1", "Duty 1", "BLK9"),
+            make_row("Run 42", "Duty 7", "BLK1"),
+        ]);
+        let key = first.anonymize(options.clone());
+        second.anonymize(options);
+
+        assert_eq!(first.rows[0], second.rows[1]);
+        let block = first.rows[0].block.clone().unwrap();
+        assert!(block.starts_with('B') && block != "BLK1");
+        assert_eq!(key.blocks[&block], "BLK1");
+
+        let lat = first.rows[0].start_lat.unwrap();
+        let lon = first.rows[0].start_lon.unwrap();
+        assert_ne!((lat, lon), (51.5, -0.12));
+        assert!((lat - 51.5).abs() * 111_320.0 <= 200.0);
+    }
+}
@@ -0,0 +1,240 @@
+//! Heuristic block assignment for revenue trips that have none.
+
+use crate::models::ScheduleRow;
+use std::collections::HashSet;
+
+/// Options for
+/// [`Schedule::assign_blocks_by_vehicle_continuity`](crate::Schedule::assign_blocks_by_vehicle_continuity).
+#[derive(Debug, Clone)]
+pub struct BlockingConfig {
+    /// Shortest wait between one trip's end and the next trip's start for
+    /// the same vehicle to run both (default: 0).
+    pub min_layover_seconds: u32,
+    /// Longest such wait (default: 1800 = 30 min).
+    pub max_layover_seconds: u32,
+    /// Prefix of generated block IDs, which are numbered from 1 (default: `"AUTO"`).
+    pub block_id_prefix: String,
+}
+
+impl Default for BlockingConfig {
+    fn default() -> Self {
+        Self {
+            min_layover_seconds: 0,
+            max_layover_seconds: 1800,
+            block_id_prefix: "AUTO".to_string(),
+        }
+    }
+}
+
+impl BlockingConfig {
+    /// Create default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the allowed wait between chained trips.
+    pub fn with_layover_range(mut self, min_seconds: u32, max_seconds: u32) -> Self {
+        self.min_layover_seconds = min_seconds;
+        self.max_layover_seconds = max_seconds;
+        self
+    }
+
+    /// Set the prefix of generated block IDs.
+    pub fn with_block_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.block_id_prefix = prefix.into();
+        self
+    }
+}
+
+/// What [`Schedule::assign_blocks_by_vehicle_continuity`](crate::Schedule::assign_blocks_by_vehicle_continuity) changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockingReport {
+    /// Unblocked revenue trips that were given a block.
+    pub trips_blocked: usize,
+    /// Unblocked revenue trips left without a block because no other trip
+    /// chains with them, or they have no start or end time.
+    pub trips_unmatched: usize,
+    /// Generated block IDs, in order of their first trip.
+    pub block_ids: Vec<String>,
+}
+
+/// An open chain of trips, waiting for a trip that starts where it ends.
+struct Chain {
+    rows: Vec<usize>,
+    end_seconds: u32,
+    end_place: Option<String>,
+}
+
+/// Chain unblocked revenue rows into generated blocks.
+///
+/// Trips are taken in start-time order (ties by row order) and each joins
+/// the open chain that ends at its start place with the shortest allowed
+/// wait, or starts a new chain. Chains of a single trip are left unblocked.
+///
+/// Only the raw `block` field is considered, whatever the schedule's block
+/// key: it is the only field this writes, so a row that has a `block` but
+/// lacks another key field is left alone rather than moved to a new block.
+pub(crate) fn assign_blocks(rows: &mut [ScheduleRow], config: &BlockingConfig) -> BlockingReport {
+    let mut report = BlockingReport::default();
+
+    let mut orphans: Vec<(u32, u32, usize)> = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        if !row.is_revenue() || row.block.is_some() {
+            continue;
+        }
+        match (row.start_time_seconds(), row.end_time_seconds()) {
+            (Some(start), Some(end)) => orphans.push((start, end, idx)),
+            _ => report.trips_unmatched += 1,
+        }
+    }
+    orphans.sort_by_key(|&(start, _, idx)| (start, idx));
+
+    let mut chains: Vec<Chain> = Vec::new();
+    for (start, end, idx) in orphans {
+        let start_place = rows[idx].start_place.as_deref();
+        let best = chains
+            .iter()
+            .enumerate()
+            .filter(|(_, chain)| {
+                start_place.is_some()
+                    && chain.end_place.as_deref() == start_place
+                    && start >= chain.end_seconds
+                    && (config.min_layover_seconds..=config.max_layover_seconds)
+                        .contains(&(start - chain.end_seconds))
+            })
+            .min_by_key(|(pos, chain)| (start - chain.end_seconds, *pos))
+            .map(|(pos, _)| pos);
+
+        let end_place = rows[idx].end_place.clone();
+        match best {
+            Some(pos) => {
+                let chain = &mut chains[pos];
+                chain.rows.push(idx);
+                chain.end_seconds = end;
+                chain.end_place = end_place;
+            }
+            None => chains.push(Chain {
+                rows: vec![idx],
+                end_seconds: end,
+                end_place,
+            }),
+        }
+    }
+
+    let mut used: HashSet<String> = rows.iter().filter_map(|r| r.block.clone()).collect();
+    let mut next_number = 1;
+    for chain in chains {
+        if chain.rows.len() < 2 {
+            report.trips_unmatched += 1;
+            continue;
+        }
+
+        let block_id = loop {
+            let candidate = format!("{}{}", config.block_id_prefix, next_number);
+            next_number += 1;
+            if !used.contains(&candidate) {
+                break candidate;
+            }
+        };
+        used.insert(block_id.clone());
+
+        for idx in &chain.rows {
+            rows[*idx].block = Some(block_id.clone());
+        }
+        report.trips_blocked += chain.rows.len();
+        report.block_ids.push(block_id);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trip(trip_id: &str, from: &str, to: &str, start: &str, end: &str) -> ScheduleRow {
+        ScheduleRow {
+            trip_id: Some(trip_id.to_string()),
+            start_place: Some(from.to_string()),
+            end_place: Some(to.to_string()),
+            start_time: Some(start.to_string()),
+            end_time: Some(end.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_assign_blocks() {
+        let mut existing = trip("T0", "A", "B", "06:00:00", "07:00:00");
+        existing.block = Some("AUTO1".to_string());
+        let mut rows = vec![
+            existing,
+            trip("T3", "B", "A", "09:10:00", "10:00:00"),
+            trip("T1", "A", "B", "08:00:00", "09:00:00"),
+            // Starts where T3 ends, but after too long a wait
+            trip("T4", "A", "C", "11:00:00", "12:00:00"),
+            trip("T2", "C", "D", "08:00:00", "08:30:00"),
+            trip("T5", "D", "C", "08:40:00", "09:00:00"),
+            ScheduleRow {
+                start_time: None,
+                ..trip("T6", "C", "D", "", "10:00:00")
+            },
+        ];
+
+        let report = assign_blocks(&mut rows, &BlockingConfig::new());
+        assert_eq!(report.block_ids, vec!["AUTO2", "AUTO3"]);
+        assert_eq!(report.trips_blocked, 4);
+        assert_eq!(report.trips_unmatched, 2);
+
+        let blocks: Vec<(&str, Option<&str>)> = rows
+            .iter()
+            .map(|r| (r.trip_id.as_deref().unwrap(), r.block.as_deref()))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                ("T0", Some("AUTO1")),
+                ("T3", Some("AUTO2")),
+                ("T1", Some("AUTO2")),
+                ("T4", None),
+                ("T2", Some("AUTO3")),
+                ("T5", Some("AUTO3")),
+                ("T6", None),
+            ]
+        );
+
+        // Running again changes nothing
+        let report = assign_blocks(&mut rows, &BlockingConfig::new());
+        assert_eq!(report.trips_blocked, 0);
+    }
+
+    #[test]
+    fn test_assigned_blocks_are_exported() {
+        let mut schedule = crate::Schedule::from_rows(vec![
+            trip("T1", "A", "B", "08:00:00", "09:00:00"),
+            trip("T2", "B", "A", "09:10:00", "10:00:00"),
+        ]);
+        schedule.assign_blocks_by_vehicle_continuity(BlockingConfig::new());
+
+        let exporter =
+            crate::CsvExporter::new(crate::ExportConfig::with_columns(vec!["block", "trip_id"]));
+        let csv = exporter.export_to_string(&schedule).unwrap();
+        assert_eq!(csv, "block,trip_id\nAUTO1,T1\nAUTO1,T2\n");
+    }
+
+    #[test]
+    fn test_layover_range_and_prefix() {
+        let mut rows = vec![
+            trip("T1", "A", "B", "08:00:00", "09:00:00"),
+            trip("T2", "B", "A", "09:02:00", "10:00:00"),
+        ];
+        let config = BlockingConfig::new()
+            .with_layover_range(300, 1800)
+            .with_block_id_prefix("X");
+        assert_eq!(assign_blocks(&mut rows, &config).trips_blocked, 0);
+
+        let config = config.with_layover_range(0, 1800);
+        let report = assign_blocks(&mut rows, &config);
+        assert_eq!(report.block_ids, vec!["X1"]);
+    }
+}
@@ -1,9 +1,46 @@
 //! Deadhead inference from schedule and GTFS data.
 
-use crate::models::{Deadhead, DeadheadInferenceResult, Schedule};
+use crate::models::{
+    haversine_distance, Block, Deadhead, DeadheadInferenceResult, RowType, Schedule, ScheduleRow,
+};
 use gtfs_parser::GtfsFeed;
 use std::collections::HashMap;
 
+/// How to estimate road distance between two coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceModel {
+    /// Straight-line (great-circle) distance.
+    Haversine,
+    /// Straight-line distance multiplied by a road circuity factor.
+    HaversineWithFactor(f64),
+    /// Sum of the north-south and east-west legs (grid street networks).
+    Manhattan,
+}
+
+impl Default for DistanceModel {
+    fn default() -> Self {
+        // Buses rarely travel in a straight line; 1.3 is a typical circuity factor.
+        DistanceModel::HaversineWithFactor(1.3)
+    }
+}
+
+impl DistanceModel {
+    /// Estimated distance in meters between two coordinates.
+    pub fn distance(&self, lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+        match self {
+            DistanceModel::Haversine => haversine_distance(lat1, lon1, lat2, lon2),
+            DistanceModel::HaversineWithFactor(factor) => {
+                haversine_distance(lat1, lon1, lat2, lon2) * factor
+            }
+            DistanceModel::Manhattan => {
+                let mid_lat = (lat1 + lat2) / 2.0;
+                haversine_distance(lat1, lon1, lat2, lon1)
+                    + haversine_distance(mid_lat, lon1, mid_lat, lon2)
+            }
+        }
+    }
+}
+
 /// Configuration for deadhead inference.
 #[derive(Debug, Clone)]
 pub struct InferenceConfig {
@@ -21,6 +58,22 @@ pub struct InferenceConfig {
 
     /// Whether to infer interlining deadheads.
     pub infer_interlining: bool,
+
+    /// Distance model used for time estimates and deadhead distances.
+    pub distance_model: DistanceModel,
+
+    /// Measured deadhead (meters, seconds) keyed by (from, to) place,
+    /// consulted before any coordinate estimate.
+    pub distance_overrides: HashMap<(String, String), (f64, u32)>,
+
+    /// Also use an override for the reverse trip when only (to, from) is
+    /// listed.
+    pub symmetric_overrides: bool,
+
+    /// Without a default depot, fall back to the schedule's most likely
+    /// depot (see [`Schedule::infer_likely_depots`]) for blocks that have
+    /// none, instead of reporting them as incomplete.
+    pub infer_default_depot: bool,
 }
 
 impl Default for InferenceConfig {
@@ -31,6 +84,10 @@ impl Default for InferenceConfig {
             average_speed_mps: 8.33, // ~30 km/h
             min_gap_seconds: 60,
             infer_interlining: true,
+            distance_model: DistanceModel::default(),
+            distance_overrides: HashMap::new(),
+            symmetric_overrides: false,
+            infer_default_depot: false,
         }
     }
 }
@@ -53,6 +110,62 @@ impl InferenceConfig {
         self.default_depot = Some(depot.into());
         self
     }
+
+    /// Set the distance model.
+    pub fn with_distance_model(mut self, model: DistanceModel) -> Self {
+        self.distance_model = model;
+        self
+    }
+
+    /// Add a measured deadhead distance and time between two places.
+    pub fn add_distance_override(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        meters: f64,
+        seconds: u32,
+    ) -> Self {
+        self.distance_overrides
+            .insert((from.into(), to.into()), (meters, seconds));
+        self
+    }
+
+    /// Set whether a missing default depot is inferred from the schedule.
+    pub fn with_infer_default_depot(mut self, infer: bool) -> Self {
+        self.infer_default_depot = infer;
+        self
+    }
+
+    /// Set whether overrides also apply in the reverse direction.
+    pub fn with_symmetric_overrides(mut self, symmetric: bool) -> Self {
+        self.symmetric_overrides = symmetric;
+        self
+    }
+
+    /// Whether `place` is the depot itself or a stop mapped to it in
+    /// [`depot_locations`](Self::depot_locations).
+    fn is_depot_place(&self, place: &str, depot: &str) -> bool {
+        place == depot
+            || self
+                .depot_locations
+                .get(place)
+                .is_some_and(|code| code == depot)
+    }
+
+    /// Measured (meters, seconds) for a deadhead, if one is configured.
+    ///
+    /// An exact (from, to) entry wins over a reversed one.
+    fn distance_override(&self, from: &str, to: &str) -> Option<(f64, u32)> {
+        let key = (from.to_string(), to.to_string());
+        if let Some(&measured) = self.distance_overrides.get(&key) {
+            return Some(measured);
+        }
+        if self.symmetric_overrides {
+            let reversed = (key.1, key.0);
+            return self.distance_overrides.get(&reversed).copied();
+        }
+        None
+    }
 }
 
 /// Infers missing deadheads from schedule data.
@@ -91,16 +204,37 @@ impl<'a> DeadheadInferrer<'a> {
     }
 
     /// Infer all missing deadheads for a schedule.
-    pub fn infer(&self, schedule: &mut Schedule) -> DeadheadInferenceResult {
+    ///
+    /// The schedule is only read: already-derived blocks are reused and
+    /// otherwise derived into a local map, so a shared schedule can be
+    /// inferred from several threads at once.
+    pub fn infer(&self, schedule: &Schedule) -> DeadheadInferenceResult {
         let mut result = DeadheadInferenceResult::default();
+        let blocks = schedule.blocks_ref();
+        let inferred_depot =
+            if self.config.infer_default_depot && self.config.default_depot.is_none() {
+                schedule
+                    .infer_likely_depots()
+                    .into_iter()
+                    .next()
+                    .map(|(place, _)| place)
+            } else {
+                None
+            };
 
         // Process each block
         for block_id in schedule.block_ids() {
-            match self.infer_block_deadheads(schedule, &block_id) {
+            let Some(block) = blocks.get(&block_id) else {
+                continue;
+            };
+            match self.infer_block_deadheads(block, inferred_depot.as_deref()) {
                 Ok(block_result) => {
                     result.pull_outs.extend(block_result.pull_outs);
                     result.pull_ins.extend(block_result.pull_ins);
                     result.interlinings.extend(block_result.interlinings);
+                    result
+                        .skipped_interlinings
+                        .extend(block_result.skipped_interlinings);
                 }
                 Err(_) => {
                     result.incomplete_blocks.push(block_id);
@@ -111,39 +245,74 @@ impl<'a> DeadheadInferrer<'a> {
         result
     }
 
-    /// Infer deadheads for a single block.
+    /// Infer missing deadheads and append them to the schedule as rows.
+    ///
+    /// Rows are appended at the end; sort the schedule afterwards if row
+    /// order matters. Each new row takes the [block key](Schedule::block_key)
+    /// fields of its block's first row, so it joins that block. Returns the
+    /// same result as [`infer`](Self::infer).
+    pub fn infer_and_apply(&self, schedule: &mut Schedule) -> DeadheadInferenceResult {
+        let result = self.infer(schedule);
+        let rows: Vec<ScheduleRow> = {
+            let blocks = schedule.blocks_ref();
+            result
+                .all_deadheads()
+                .map(|deadhead| {
+                    let mut row = deadhead.to_schedule_row();
+                    let first_row = deadhead
+                        .block_id
+                        .as_deref()
+                        .and_then(|block_id| blocks.get(block_id))
+                        .and_then(|block| block.rows.first());
+                    if let Some(first_row) = first_row {
+                        schedule.block_key().copy_fields(first_row, &mut row);
+                    }
+                    row
+                })
+                .collect()
+        };
+        schedule.extend_rows(rows);
+        result
+    }
+
+    /// Infer deadheads for a single block, using `fallback_depot` when
+    /// neither the block nor the config names a depot.
     fn infer_block_deadheads(
         &self,
-        schedule: &mut Schedule,
-        block_id: &str,
+        block: &Block,
+        fallback_depot: Option<&str>,
     ) -> Result<DeadheadInferenceResult, &'static str> {
         let mut result = DeadheadInferenceResult::default();
-
-        let block = schedule.get_block(block_id).ok_or("Block not found")?;
-        let block = block.clone(); // Clone to avoid borrow issues
+        let block_id = block.block_id.as_str();
 
         // Get depot for this block
         let depot = block
             .depot
             .clone()
             .or_else(|| self.config.default_depot.clone())
+            .or_else(|| fallback_depot.map(String::from))
             .ok_or("No depot available")?;
 
         // Find first revenue trip
         let first_trip = block.rows.iter().find(|r| r.is_revenue());
         if let Some(first) = first_trip {
             if let Some(start_place) = &first.start_place {
-                // Check if pull-out already exists
-                if block.pull_out().is_none() {
+                // Skip if a pull-out already exists or the trip leaves from the depot
+                if block.pull_out().is_none() && !self.config.is_depot_place(start_place, &depot) {
                     let mut pull_out = Deadhead::pull_out(depot.clone(), start_place)
                         .with_block(block_id)
                         .inferred();
 
                     // Add coordinates if available
+                    if let Some(&(lat, lon)) = self.stop_coords.get(&depot) {
+                        pull_out.from_lat = Some(lat);
+                        pull_out.from_lon = Some(lon);
+                    }
                     if let Some(&(lat, lon)) = self.stop_coords.get(start_place) {
                         pull_out.to_lat = Some(lat);
                         pull_out.to_lon = Some(lon);
                     }
+                    pull_out.distance_meters = self.estimate_distance(&depot, start_place);
 
                     // Estimate time if first trip has a start time
                     if let Some(trip_start) = first.start_time_seconds() {
@@ -163,8 +332,8 @@ impl<'a> DeadheadInferrer<'a> {
         let last_trip = block.rows.iter().rev().find(|r| r.is_revenue());
         if let Some(last) = last_trip {
             if let Some(end_place) = &last.end_place {
-                // Check if pull-in already exists
-                if block.pull_in().is_none() {
+                // Skip if a pull-in already exists or the trip ends at the depot
+                if block.pull_in().is_none() && !self.config.is_depot_place(end_place, &depot) {
                     let mut pull_in = Deadhead::pull_in(end_place, depot.clone())
                         .with_block(block_id)
                         .inferred();
@@ -174,6 +343,11 @@ impl<'a> DeadheadInferrer<'a> {
                         pull_in.from_lat = Some(lat);
                         pull_in.from_lon = Some(lon);
                     }
+                    if let Some(&(lat, lon)) = self.stop_coords.get(&depot) {
+                        pull_in.to_lat = Some(lat);
+                        pull_in.to_lon = Some(lon);
+                    }
+                    pull_in.distance_meters = self.estimate_distance(end_place, &depot);
 
                     // Estimate time if last trip has an end time
                     if let Some(trip_end) = last.end_time_seconds() {
@@ -189,65 +363,93 @@ impl<'a> DeadheadInferrer<'a> {
 
         // Infer interlining deadheads
         if self.config.infer_interlining {
-            let revenue_trips: Vec<_> = block.rows.iter().filter(|r| r.is_revenue()).collect();
-
-            for i in 0..revenue_trips.len().saturating_sub(1) {
-                let prev = revenue_trips[i];
-                let next = revenue_trips[i + 1];
-
-                // Check for location discontinuity
-                if let (Some(end_place), Some(start_place)) = (&prev.end_place, &next.start_place) {
-                    if end_place != start_place {
-                        // Check if there's a time gap
-                        let needs_deadhead =
-                            match (prev.end_time_seconds(), next.start_time_seconds()) {
-                                (Some(end), Some(start)) => {
-                                    start > end + self.config.min_gap_seconds
-                                }
-                                _ => true, // If no times, assume we need it
-                            };
-
-                        if needs_deadhead {
-                            let mut interlining = Deadhead::interlining(end_place, start_place)
-                                .with_block(block_id)
-                                .with_trips(prev.trip_id.clone(), next.trip_id.clone())
-                                .inferred();
-
-                            // Add coordinates
-                            if let Some(&(lat, lon)) = self.stop_coords.get(end_place) {
-                                interlining.from_lat = Some(lat);
-                                interlining.from_lon = Some(lon);
-                            }
-                            if let Some(&(lat, lon)) = self.stop_coords.get(start_place) {
-                                interlining.to_lat = Some(lat);
-                                interlining.to_lon = Some(lon);
-                            }
-
-                            // Set times
-                            if let (Some(end), Some(start)) =
-                                (prev.end_time_seconds(), next.start_time_seconds())
-                            {
-                                interlining.start_time_seconds = Some(end);
-                                interlining.end_time_seconds = Some(start);
-                            }
-
-                            result.interlinings.push(interlining);
-                        }
-                    }
+            for (prev_idx, next_idx) in block.consecutive_rows_where(ScheduleRow::is_revenue) {
+                let prev = &block.rows[prev_idx];
+                let next = &block.rows[next_idx];
+
+                // An explicit deadhead or layover between the trips already
+                // accounts for the move
+                let bridged = block.rows[prev_idx + 1..next_idx]
+                    .iter()
+                    .any(|r| r.is_deadhead() || r.row_type == RowType::Layover);
+                if bridged {
+                    continue;
                 }
+
+                let (Some(end_place), Some(start_place)) = (&prev.end_place, &next.start_place)
+                else {
+                    result.skipped_interlinings.push((
+                        block_id.to_string(),
+                        next_idx,
+                        "missing location".to_string(),
+                    ));
+                    continue;
+                };
+
+                // Only a location discontinuity needs a deadhead
+                if end_place == start_place {
+                    continue;
+                }
+
+                let (Some(end), Some(start)) = (prev.end_time_seconds(), next.start_time_seconds())
+                else {
+                    result.skipped_interlinings.push((
+                        block_id.to_string(),
+                        next_idx,
+                        "missing time".to_string(),
+                    ));
+                    continue;
+                };
+
+                if start <= end + self.config.min_gap_seconds {
+                    continue;
+                }
+
+                let mut interlining = Deadhead::interlining(end_place, start_place)
+                    .with_block(block_id)
+                    .with_trips(prev.trip_id.clone(), next.trip_id.clone())
+                    .inferred();
+
+                // Add coordinates
+                if let Some(&(lat, lon)) = self.stop_coords.get(end_place) {
+                    interlining.from_lat = Some(lat);
+                    interlining.from_lon = Some(lon);
+                }
+                if let Some(&(lat, lon)) = self.stop_coords.get(start_place) {
+                    interlining.to_lat = Some(lat);
+                    interlining.to_lon = Some(lon);
+                }
+                interlining.distance_meters = self.estimate_distance(end_place, start_place);
+
+                interlining.start_time_seconds = Some(end);
+                interlining.end_time_seconds = Some(start);
+
+                result.interlinings.push(interlining);
             }
         }
 
         Ok(result)
     }
 
+    /// Estimate deadhead distance in meters from an override or the
+    /// configured distance model.
+    fn estimate_distance(&self, from: &str, to: &str) -> Option<f64> {
+        if let Some((meters, _)) = self.config.distance_override(from, to) {
+            return Some(meters);
+        }
+        let &(lat1, lon1) = self.stop_coords.get(from)?;
+        let &(lat2, lon2) = self.stop_coords.get(to)?;
+        Some(self.config.distance_model.distance(lat1, lon1, lat2, lon2))
+    }
+
     /// Estimate deadhead duration based on distance and average speed.
     fn estimate_duration(&self, from: &str, to: &str) -> u32 {
+        if let Some((_, seconds)) = self.config.distance_override(from, to) {
+            return seconds;
+        }
+
         // Try to calculate from coordinates
-        if let (Some(&(lat1, lon1)), Some(&(lat2, lon2))) =
-            (self.stop_coords.get(from), self.stop_coords.get(to))
-        {
-            let distance = haversine_distance(lat1, lon1, lat2, lon2);
+        if let Some(distance) = self.estimate_distance(from, to) {
             let time = distance / self.config.average_speed_mps;
             return time as u32;
         }
@@ -257,26 +459,10 @@ impl<'a> DeadheadInferrer<'a> {
     }
 }
 
-/// Calculate Haversine distance between two coordinates in meters.
-fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
-    const EARTH_RADIUS_M: f64 = 6_371_000.0;
-
-    let lat1_rad = lat1.to_radians();
-    let lat2_rad = lat2.to_radians();
-    let delta_lat = (lat2 - lat1).to_radians();
-    let delta_lon = (lon2 - lon1).to_radians();
-
-    let a = (delta_lat / 2.0).sin().powi(2)
-        + lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
-    let c = 2.0 * a.sqrt().asin();
-
-    EARTH_RADIUS_M * c
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{RowType, ScheduleRow};
+    use crate::models::{BlockKey, RowType, ScheduleRow};
 
     fn make_row(
         trip_id: &str,
@@ -303,12 +489,12 @@ mod tests {
         let config = InferenceConfig::new().with_default_depot("DEPOT");
         let inferrer = DeadheadInferrer::new(config);
 
-        let mut schedule = Schedule::from_rows(vec![
+        let schedule = Schedule::from_rows(vec![
             make_row("T1", "B1", "STOP_A", "STOP_B", "08:00:00", "09:00:00"),
             make_row("T2", "B1", "STOP_B", "STOP_C", "09:15:00", "10:00:00"),
         ]);
 
-        let result = inferrer.infer(&mut schedule);
+        let result = inferrer.infer(&schedule);
 
         assert_eq!(result.pull_outs.len(), 1);
         assert_eq!(result.pull_ins.len(), 1);
@@ -323,18 +509,80 @@ mod tests {
         assert_eq!(pull_in.to_location, "DEPOT");
     }
 
+    #[test]
+    fn test_infer_default_depot() {
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "GARAGE", "STOP_A", "08:00:00", "09:00:00"),
+            make_row("T2", "B1", "STOP_A", "GARAGE", "09:15:00", "10:00:00"),
+            make_row("T3", "B2", "STOP_B", "STOP_C", "08:00:00", "09:00:00"),
+        ]);
+
+        let result = DeadheadInferrer::new(InferenceConfig::new()).infer(&schedule);
+        assert_eq!(result.incomplete_blocks.len(), 2);
+
+        let config = InferenceConfig::new().with_infer_default_depot(true);
+        let result = DeadheadInferrer::new(config).infer(&schedule);
+        assert!(result.incomplete_blocks.is_empty());
+        assert_eq!(result.pull_outs.len(), 1);
+        assert_eq!(result.pull_outs[0].from_location, "GARAGE");
+        assert_eq!(result.pull_ins[0].to_location, "GARAGE");
+    }
+
+    #[test]
+    fn test_no_pull_out_or_in_at_depot() {
+        let config = InferenceConfig::new()
+            .with_default_depot("DEPOT")
+            .add_depot("GARAGE_GATE", "DEPOT");
+        let inferrer = DeadheadInferrer::new(config);
+
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "DEPOT", "STOP_B", "08:00:00", "09:00:00"),
+            make_row("T2", "B1", "STOP_B", "GARAGE_GATE", "09:15:00", "10:00:00"),
+            make_row("T3", "B2", "STOP_A", "STOP_B", "08:00:00", "09:00:00"),
+        ]);
+
+        let result = inferrer.infer(&schedule);
+
+        assert_eq!(result.pull_outs.len(), 1);
+        assert_eq!(result.pull_outs[0].block_id.as_deref(), Some("B2"));
+        assert_eq!(result.pull_ins.len(), 1);
+        assert_eq!(result.pull_ins[0].block_id.as_deref(), Some("B2"));
+    }
+
+    #[test]
+    fn test_infer_shared_schedule() {
+        let inferrer = DeadheadInferrer::new(InferenceConfig::new().with_default_depot("DEPOT"));
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "STOP_A", "STOP_B", "08:00:00", "09:00:00"),
+            make_row("T2", "B2", "STOP_C", "STOP_D", "08:00:00", "09:00:00"),
+        ]);
+
+        let counts: Vec<usize> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| inferrer.infer(&schedule).total_count()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(counts, vec![4, 4]);
+
+        // Reusing cached blocks gives the same answer
+        let mut cached = schedule.clone();
+        cached.derive_blocks();
+        assert_eq!(inferrer.infer(&cached).total_count(), 4);
+    }
+
     #[test]
     fn test_infer_interlining() {
         let config = InferenceConfig::new().with_default_depot("DEPOT");
         let inferrer = DeadheadInferrer::new(config);
 
-        let mut schedule = Schedule::from_rows(vec![
+        let schedule = Schedule::from_rows(vec![
             make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00"),
             // Location discontinuity: B -> C
             make_row("T2", "B1", "C", "D", "09:15:00", "10:00:00"),
         ]);
 
-        let result = inferrer.infer(&mut schedule);
+        let result = inferrer.infer(&schedule);
 
         assert_eq!(result.interlinings.len(), 1);
 
@@ -350,25 +598,181 @@ mod tests {
         let config = InferenceConfig::new().with_default_depot("DEPOT");
         let inferrer = DeadheadInferrer::new(config);
 
-        let mut schedule = Schedule::from_rows(vec![
+        let schedule = Schedule::from_rows(vec![
             make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00"),
             make_row("T2", "B1", "B", "C", "09:15:00", "10:00:00"), // Continuous
         ]);
 
-        let result = inferrer.infer(&mut schedule);
+        let result = inferrer.infer(&schedule);
 
         assert_eq!(result.interlinings.len(), 0);
     }
 
+    #[test]
+    fn test_no_interlining_when_deadhead_bridges_gap() {
+        let config = InferenceConfig::new().with_default_depot("DEPOT");
+        let inferrer = DeadheadInferrer::new(config);
+
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00"),
+            ScheduleRow {
+                block: Some("B1".to_string()),
+                start_place: Some("B".to_string()),
+                end_place: Some("C".to_string()),
+                start_time: Some("09:00:00".to_string()),
+                end_time: Some("09:10:00".to_string()),
+                row_type: RowType::Deadhead,
+                ..Default::default()
+            },
+            make_row("T2", "B1", "C", "D", "09:15:00", "10:00:00"),
+        ]);
+
+        let result = inferrer.infer(&schedule);
+
+        assert!(result.interlinings.is_empty());
+    }
+
+    #[test]
+    fn test_skipped_interlinings_report_reason() {
+        let config = InferenceConfig::new().with_default_depot("DEPOT");
+        let inferrer = DeadheadInferrer::new(config);
+
+        let mut no_place = make_row("T2", "B1", "C", "D", "09:15:00", "10:00:00");
+        no_place.start_place = None;
+        let mut no_time = make_row("T3", "B1", "E", "F", "10:30:00", "11:00:00");
+        no_time.start_time = None;
+        no_time.end_time = None;
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00"),
+            no_place,
+            no_time,
+        ]);
+
+        let result = inferrer.infer(&schedule);
+
+        assert!(result.interlinings.is_empty());
+        // Rows without a start time sort first within the block
+        assert_eq!(
+            result.skipped_interlinings,
+            vec![
+                ("B1".to_string(), 1, "missing time".to_string()),
+                ("B1".to_string(), 2, "missing location".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distance_models() {
+        let (lat1, lon1, lat2, lon2) = (51.50, -0.12, 51.52, -0.10);
+        let straight = DistanceModel::Haversine.distance(lat1, lon1, lat2, lon2);
+        let factored = DistanceModel::HaversineWithFactor(1.3).distance(lat1, lon1, lat2, lon2);
+        let manhattan = DistanceModel::Manhattan.distance(lat1, lon1, lat2, lon2);
+
+        assert!((factored - straight * 1.3).abs() < 1e-6);
+        assert!(manhattan > straight);
+        assert_eq!(
+            DistanceModel::default(),
+            DistanceModel::HaversineWithFactor(1.3)
+        );
+    }
+
+    #[test]
+    fn test_inferred_distance_uses_model() {
+        use transit_core::Stop;
+
+        let mut gtfs = GtfsFeed::new();
+        gtfs.feed
+            .stops
+            .push(Stop::new("DEPOT", "Depot", 51.50, -0.12));
+        gtfs.feed.stops.push(Stop::new("A", "Stop A", 51.52, -0.10));
+        gtfs.feed.stops.push(Stop::new("B", "Stop B", 51.53, -0.11));
+
+        let schedule =
+            Schedule::from_rows(vec![make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00")]);
+
+        let straight = haversine_distance(51.50, -0.12, 51.52, -0.10);
+
+        let config = InferenceConfig::new()
+            .with_default_depot("DEPOT")
+            .with_distance_model(DistanceModel::Haversine);
+        let result = DeadheadInferrer::with_gtfs(config, &gtfs).infer(&schedule);
+        let distance = result.pull_outs[0].distance_meters.unwrap();
+        assert!((distance - straight).abs() < 1e-6);
+
+        let config = InferenceConfig::new().with_default_depot("DEPOT");
+        let result = DeadheadInferrer::with_gtfs(config, &gtfs).infer(&schedule);
+        let pull_out = &result.pull_outs[0];
+        assert!((pull_out.distance_meters.unwrap() - straight * 1.3).abs() < 1e-6);
+        assert_eq!(
+            pull_out.duration_seconds(),
+            Some((straight * 1.3 / 8.33) as u32)
+        );
+    }
+
+    #[test]
+    fn test_distance_override_beats_estimate() {
+        use transit_core::Stop;
+
+        let mut gtfs = GtfsFeed::new();
+        gtfs.feed.stops.push(Stop::new("A", "Stop A", 51.52, -0.10));
+        gtfs.feed.stops.push(Stop::new("B", "Stop B", 51.53, -0.11));
+        gtfs.feed.stops.push(Stop::new("C", "Stop C", 51.54, -0.12));
+
+        let config = InferenceConfig::new()
+            .with_default_depot("DEPOT")
+            .add_distance_override("B", "C", 4200.0, 720);
+        let inferrer = DeadheadInferrer::with_gtfs(config.clone(), &gtfs);
+        assert_eq!(inferrer.estimate_distance("B", "C"), Some(4200.0));
+        assert_eq!(inferrer.estimate_duration("B", "C"), 720);
+
+        // Order-sensitive unless symmetric
+        assert_ne!(inferrer.estimate_distance("C", "B"), Some(4200.0));
+        let symmetric = DeadheadInferrer::with_gtfs(config.with_symmetric_overrides(true), &gtfs);
+        assert_eq!(symmetric.estimate_distance("C", "B"), Some(4200.0));
+        assert_eq!(symmetric.estimate_duration("C", "B"), 720);
+
+        let schedule = Schedule::from_rows(vec![
+            make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00"),
+            make_row("T2", "B1", "C", "A", "09:15:00", "10:00:00"),
+        ]);
+        let result = inferrer.infer(&schedule);
+        assert_eq!(result.interlinings[0].distance_meters, Some(4200.0));
+    }
+
+    #[test]
+    fn test_infer_with_composite_block_key() {
+        let in_depot = |depot: &str, trip_id: &str, start: &str, end: &str| ScheduleRow {
+            depot: Some(depot.to_string()),
+            ..make_row(trip_id, "1", "A", "B", start, end)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            in_depot("NORTH", "T1", "08:00:00", "09:00:00"),
+            in_depot("SOUTH", "T2", "08:30:00", "09:30:00"),
+        ]);
+        schedule.derive_blocks_by(BlockKey::depot_block());
+
+        let result = DeadheadInferrer::new(InferenceConfig::new()).infer_and_apply(&mut schedule);
+        assert_eq!(result.pull_outs.len(), 2);
+        assert_eq!(schedule.block_ids(), vec!["NORTH:1", "SOUTH:1"]);
+
+        let north = schedule.get_block("NORTH:1").unwrap();
+        assert_eq!(north.rows.len(), 3);
+        assert_eq!(
+            north.pull_out().unwrap().start_place.as_deref(),
+            Some("NORTH")
+        );
+        assert!(north.rows.iter().all(|r| r.block.as_deref() == Some("1")));
+    }
+
     #[test]
     fn test_incomplete_block_no_depot() {
         let config = InferenceConfig::new(); // No default depot
         let inferrer = DeadheadInferrer::new(config);
 
-        let mut schedule =
+        let schedule =
             Schedule::from_rows(vec![make_row("T1", "B1", "A", "B", "08:00:00", "09:00:00")]);
 
-        let result = inferrer.infer(&mut schedule);
+        let result = inferrer.infer(&schedule);
 
         assert!(result.incomplete_blocks.contains(&"B1".to_string()));
     }
@@ -0,0 +1,153 @@
+//! Conservative automatic fixes for common data-entry problems.
+
+use crate::models::{Schedule, ScheduleRow};
+
+/// What [`Schedule::apply_safe_fixes`](crate::Schedule::apply_safe_fixes)
+/// changed.
+///
+/// Row indices are positions in the schedule before any fix was applied,
+/// and original values are kept so every change can be reviewed or undone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FixReport {
+    /// Values trimmed of leading or trailing whitespace, as (row, field,
+    /// original value).
+    pub trimmed_values: Vec<(usize, &'static str, String)>,
+    /// End times moved past midnight so they follow the start, as (row,
+    /// original end time).
+    pub overnight_end_times: Vec<(usize, String)>,
+    /// Rows removed as duplicates of an earlier row (see
+    /// [`Schedule::duplicate_row_indices`](crate::Schedule::duplicate_row_indices)).
+    pub duplicate_rows_removed: Vec<usize>,
+    /// Rows that changed position when sorted by block and time.
+    pub rows_moved: usize,
+}
+
+impl FixReport {
+    /// Total number of changes made.
+    pub fn change_count(&self) -> usize {
+        self.trimmed_values.len()
+            + self.overnight_end_times.len()
+            + self.duplicate_rows_removed.len()
+            + self.rows_moved
+    }
+
+    /// Whether nothing needed fixing.
+    pub fn is_empty(&self) -> bool {
+        self.change_count() == 0
+    }
+}
+
+/// The text fields of a row, by name.
+fn text_fields(row: &mut ScheduleRow) -> [(&'static str, &mut Option<String>); 16] {
+    [
+        ("run_number", &mut row.run_number),
+        ("block", &mut row.block),
+        ("start_place", &mut row.start_place),
+        ("end_place", &mut row.end_place),
+        ("start_time", &mut row.start_time),
+        ("end_time", &mut row.end_time),
+        ("trip_id", &mut row.trip_id),
+        ("depot", &mut row.depot),
+        ("vehicle_class", &mut row.vehicle_class),
+        ("vehicle_type", &mut row.vehicle_type),
+        ("route_shape_id", &mut row.route_shape_id),
+        ("duty_id", &mut row.duty_id),
+        ("shift_id", &mut row.shift_id),
+        ("route_short_name", &mut row.route_short_name),
+        ("headsign", &mut row.headsign),
+        ("service_id", &mut row.service_id),
+    ]
+}
+
+/// Trim whitespace, normalize overnight end times, remove duplicates and
+/// sort, in that order, so duplicates that differ only in whitespace are
+/// caught.
+pub(crate) fn apply_safe_fixes(schedule: &mut Schedule) -> FixReport {
+    let mut report = FixReport::default();
+
+    for (idx, row) in schedule.rows.iter_mut().enumerate() {
+        for (field, value) in text_fields(row) {
+            if let Some(text) = value {
+                let trimmed = text.trim();
+                if trimmed.len() != text.len() {
+                    let trimmed = trimmed.to_string();
+                    report
+                        .trimmed_values
+                        .push((idx, field, std::mem::replace(text, trimmed)));
+                }
+            }
+        }
+    }
+    if !report.trimmed_values.is_empty() {
+        schedule.invalidate_caches();
+    }
+
+    report.overnight_end_times = schedule
+        .rows
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(idx, row)| match (row.start_time_seconds(), row.end_time_seconds()) {
+                (Some(start), Some(end)) if end < start => Some((idx, row.end_time.clone()?)),
+                _ => None,
+            },
+        )
+        .collect();
+    schedule.normalize_overnight_times();
+
+    report.duplicate_rows_removed = schedule.duplicate_row_indices();
+    schedule.deduplicate_rows();
+
+    report.rows_moved = schedule.rows_moved_by_sort();
+    schedule.sort_by_block_and_time();
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RowType;
+
+    fn make_row(block: &str, trip: &str, start: &str, end: &str) -> ScheduleRow {
+        ScheduleRow {
+            block: Some(block.to_string()),
+            trip_id: Some(trip.to_string()),
+            start_time: Some(start.to_string()),
+            end_time: Some(end.to_string()),
+            row_type: RowType::Revenue,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_safe_fixes() {
+        let mut schedule = Schedule::from_rows(vec![
+            make_row("B2", "T3", "23:40:00", "00:20:00"),
+            make_row("B1", "T1", "08:00:00", "09:00:00"),
+            make_row("B1 ", "T1", "08:00:00", "09:00:00"),
+            make_row("B1", "T2", "10:00:00", "11:00:00"),
+        ]);
+
+        let report = apply_safe_fixes(&mut schedule);
+        assert_eq!(report.trimmed_values, vec![(2, "block", "B1 ".to_string())]);
+        assert_eq!(
+            report.overnight_end_times,
+            vec![(0, "00:20:00".to_string())]
+        );
+        assert_eq!(report.duplicate_rows_removed, vec![2]);
+        assert_eq!(report.rows_moved, 3);
+        assert_eq!(report.change_count(), 6);
+
+        let trips: Vec<&str> = schedule
+            .rows
+            .iter()
+            .map(|r| r.trip_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(trips, vec!["T1", "T2", "T3"]);
+        assert_eq!(schedule.rows[2].end_time.as_deref(), Some("24:20:00"));
+
+        // Fixing again finds nothing
+        assert!(apply_safe_fixes(&mut schedule).is_empty());
+    }
+}
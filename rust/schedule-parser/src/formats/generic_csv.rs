@@ -1,11 +1,17 @@
 //! Generic CSV exporter with configurable columns.
 
-use crate::models::{seconds_to_time_string, Schedule, ScheduleRow};
-use csv::Writer;
+use crate::models::{
+    seconds_to_time_string, BlockSummary, DeadheadInferenceResult, DutySummary, Schedule,
+    ScheduleRow,
+};
+use crate::time::parse_time_to_seconds;
+use csv::{Terminator, WriterBuilder};
+use encoding_rs::{EncoderResult, Encoding, UTF_8};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use transit_core::ParseError;
 
 /// Time format for export.
@@ -20,6 +26,16 @@ pub enum TimeFormat {
     Seconds,
 }
 
+/// Line ending for export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineTerminator {
+    /// `\n`.
+    #[default]
+    Lf,
+    /// `\r\n`, as expected by many Windows tools.
+    Crlf,
+}
+
 /// Column configuration for export.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnConfig {
@@ -64,6 +80,35 @@ pub struct ExportConfig {
     pub include_header: bool,
     /// Value for null/empty fields.
     pub null_value: String,
+    /// Prefix added to every output header, e.g. `"tp_"` turns `block`
+    /// into `tp_block`. Field names are unchanged.
+    #[serde(default)]
+    pub header_prefix: Option<String>,
+    /// Line ending written after every record.
+    #[serde(default)]
+    pub line_terminator: LineTerminator,
+    /// Output encoding as a WHATWG label, e.g. `"windows-1252"`. `None`
+    /// writes UTF-8.
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Write `?` for characters the output encoding can't represent instead
+    /// of failing.
+    #[serde(default)]
+    pub lossy_encoding: bool,
+    /// Round times to the nearest multiple of this many seconds before
+    /// formatting, e.g. `60` for whole minutes. Halves round up, and times
+    /// past midnight stay past midnight (`25:59:45` becomes `26:00:00`).
+    /// `None` or `0` leaves times as they are.
+    #[serde(default)]
+    pub round_to_seconds: Option<u32>,
+    /// Decimal places for coordinates, e.g. `6` writes `40.712800` (about
+    /// 0.1m). `None` writes the shortest form that reads back exactly.
+    #[serde(default = "default_coordinate_precision")]
+    pub coordinate_precision: Option<usize>,
+}
+
+fn default_coordinate_precision() -> Option<usize> {
+    Some(6)
 }
 
 impl Default for ExportConfig {
@@ -74,6 +119,12 @@ impl Default for ExportConfig {
             delimiter: b',',
             include_header: true,
             null_value: String::new(),
+            header_prefix: None,
+            line_terminator: LineTerminator::Lf,
+            encoding: None,
+            lossy_encoding: false,
+            round_to_seconds: None,
+            coordinate_precision: default_coordinate_precision(),
         }
     }
 }
@@ -110,6 +161,46 @@ impl ExportConfig {
         }
     }
 
+    /// Exclude the given fields, keeping the other columns as they are.
+    ///
+    /// Matching columns are marked as not included rather than removed, so
+    /// [`with`](Self::with) can bring them back with their header and
+    /// position. Fields without a column are ignored.
+    ///
+    /// ```rust,ignore
+    /// let config = ExportPreset::Extended
+    ///     .to_config()
+    ///     .without(&["start_lat", "start_lon", "end_lat", "end_lon"]);
+    /// ```
+    pub fn without(mut self, fields: &[&str]) -> Self {
+        for column in &mut self.columns {
+            if fields.contains(&column.field.as_str()) {
+                column.include = false;
+            }
+        }
+        self
+    }
+
+    /// Include the given fields.
+    ///
+    /// A field that already has a column is included in its place, keeping
+    /// its header (or using the field name if it has none); other fields
+    /// are added at the end with the field name as header.
+    pub fn with(mut self, fields: &[&str]) -> Self {
+        for &field in fields {
+            match self.columns.iter_mut().find(|c| c.field == field) {
+                Some(column) => {
+                    column.include = true;
+                    if column.header.is_empty() {
+                        column.header = field.to_string();
+                    }
+                }
+                None => self.columns.push(ColumnConfig::new(field, field)),
+            }
+        }
+        self
+    }
+
     /// Set time format.
     pub fn time_format(mut self, format: TimeFormat) -> Self {
         self.time_format = format;
@@ -128,6 +219,42 @@ impl ExportConfig {
         self
     }
 
+    /// Set the prefix added to every output header.
+    pub fn header_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.header_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the line ending.
+    pub fn line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.line_terminator = terminator;
+        self
+    }
+
+    /// Set the output encoding by label, e.g. `"windows-1252"`.
+    pub fn encoding(mut self, label: impl Into<String>) -> Self {
+        self.encoding = Some(label.into());
+        self
+    }
+
+    /// Set whether unencodable characters are replaced with `?`.
+    pub fn lossy_encoding(mut self, lossy: bool) -> Self {
+        self.lossy_encoding = lossy;
+        self
+    }
+
+    /// Round exported times to the nearest multiple of `step` seconds.
+    pub fn round_to_seconds(mut self, step: u32) -> Self {
+        self.round_to_seconds = Some(step);
+        self
+    }
+
+    /// Set the decimal places for coordinates, or `None` for full precision.
+    pub fn coordinate_precision(mut self, precision: Option<usize>) -> Self {
+        self.coordinate_precision = precision;
+        self
+    }
+
     /// Default column configuration.
     fn default_columns() -> Vec<ColumnConfig> {
         vec![
@@ -172,35 +299,332 @@ impl CsvExporter {
         schedule: &Schedule,
         path: impl AsRef<Path>,
     ) -> Result<(), ParseError> {
+        // Reject a bad encoding before creating an empty file
+        self.output_encoding()?;
         let file = File::create(path).map_err(ParseError::Io)?;
         self.export_to_writer(schedule, file)
     }
 
+    /// Open `path` for writing rows one at a time, appending to any existing
+    /// content.
+    ///
+    /// The header is written only if the file is new or empty, so a file
+    /// that already has one is continued as is; its columns are assumed to
+    /// match this exporter's. See [`AppendWriter`].
+    pub fn open_append(&self, path: impl AsRef<Path>) -> Result<AppendWriter, ParseError> {
+        // Reject a bad encoding before creating an empty file
+        self.output_encoding()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(ParseError::Io)?;
+        let is_empty = file.metadata().map_err(ParseError::Io)?.len() == 0;
+
+        let mut writer = BufWriter::new(file);
+        if is_empty && self.config.include_header {
+            self.write_rows(std::iter::empty(), &mut writer)?;
+        }
+
+        let exporter = CsvExporter::new(ExportConfig {
+            include_header: false,
+            ..self.config.clone()
+        });
+        Ok(AppendWriter {
+            exporter,
+            writer,
+            rows_written: 0,
+        })
+    }
+
+    /// Write one CSV file per block into `dir`.
+    ///
+    /// Each file is named by replacing `{block_id}` in `filename_template`
+    /// and appending `.csv`, e.g. `"block_{block_id}"` gives `block_B1.csv`.
+    /// Characters in block IDs that aren't safe in file names are replaced
+    /// with `_`. Rows in each file are sorted by start time and every file
+    /// gets its own header. The directory is created if needed. Blocks follow
+    /// the schedule's [block key](Schedule::block_key), and rows without a
+    /// block are not written.
+    ///
+    /// Returns the written paths in block ID order.
+    pub fn export_by_block(
+        &self,
+        schedule: &Schedule,
+        dir: &Path,
+        filename_template: &str,
+    ) -> Result<Vec<PathBuf>, ParseError> {
+        let mut blocks: HashMap<String, Vec<&ScheduleRow>> = HashMap::new();
+        for row in &schedule.rows {
+            if let Some(block_id) = schedule.block_key().key_for(row) {
+                blocks.entry(block_id).or_default().push(row);
+            }
+        }
+
+        let mut block_ids: Vec<&str> = blocks.keys().map(String::as_str).collect();
+        block_ids.sort();
+
+        // Resolve all file names first so a collision doesn't leave a
+        // partial export behind
+        let mut paths: Vec<PathBuf> = Vec::with_capacity(block_ids.len());
+        let mut seen: HashMap<PathBuf, &str> = HashMap::new();
+        for &block_id in &block_ids {
+            let filename = filename_template.replace("{block_id}", &sanitize_filename(block_id));
+            let path = dir.join(format!("{}.csv", filename));
+            if let Some(other) = seen.insert(path.clone(), block_id) {
+                return Err(ParseError::InvalidData(format!(
+                    "Blocks '{}' and '{}' would both be written to {}",
+                    other,
+                    block_id,
+                    path.display()
+                )));
+            }
+            paths.push(path);
+        }
+
+        self.output_encoding()?;
+        fs::create_dir_all(dir).map_err(ParseError::Io)?;
+
+        for (block_id, path) in block_ids.iter().zip(&paths) {
+            let mut rows = blocks[*block_id].clone();
+            rows.sort_by_key(|r| r.start_time_seconds().unwrap_or(0));
+
+            let file = File::create(path).map_err(ParseError::Io)?;
+            self.write_rows(rows.iter().copied(), file)?;
+        }
+
+        Ok(paths)
+    }
+
     /// Export schedule to a writer.
     pub fn export_to_writer<W: Write>(
         &self,
         schedule: &Schedule,
         writer: W,
     ) -> Result<(), ParseError> {
-        let mut csv_writer = Writer::from_writer(writer);
+        self.write_rows(schedule.rows.iter(), writer)
+    }
+
+    /// Write the header and the given rows as CSV.
+    fn write_rows<'r, W: Write>(
+        &self,
+        rows: impl Iterator<Item = &'r ScheduleRow>,
+        writer: W,
+    ) -> Result<(), ParseError> {
+        let headers: Vec<&str> = self
+            .config
+            .columns
+            .iter()
+            .filter(|c| c.include)
+            .map(|c| c.header.as_str())
+            .collect();
+        let records = rows.map(|row| self.row_to_record(row));
+        self.write_table(&headers, records, writer)
+    }
+
+    /// Export schedule to string.
+    ///
+    /// The configured encoding doesn't apply here; the string is always
+    /// UTF-8. Use [`export_to_bytes`](Self::export_to_bytes) for encoded
+    /// output.
+    pub fn export_to_string(&self, schedule: &Schedule) -> Result<String, ParseError> {
+        let mut buffer = Vec::new();
+        if self.config.encoding.is_some() {
+            let config = ExportConfig {
+                encoding: None,
+                ..self.config.clone()
+            };
+            CsvExporter::new(config).export_to_writer(schedule, &mut buffer)?;
+        } else {
+            self.export_to_writer(schedule, &mut buffer)?;
+        }
+        String::from_utf8(buffer).map_err(|e| ParseError::Csv(e.to_string()))
+    }
+
+    /// Export schedule to bytes.
+    pub fn export_to_bytes(&self, schedule: &Schedule) -> Result<Vec<u8>, ParseError> {
+        let mut buffer = Vec::new();
+        self.export_to_writer(schedule, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Write a block KPI table (see [`Schedule::block_kpi_table`]) as CSV.
+    ///
+    /// Uses the fixed columns `block_id`, `depot`, `total_rows`,
+    /// `revenue_trips`, `first_time`, `last_time`, `duration_seconds`,
+    /// `revenue_time_seconds`, `deadhead_time_seconds`,
+    /// `layover_time_seconds` and `deadhead_ratio`. Times follow the
+    /// configured time format and missing values use the null value.
+    pub fn export_block_kpis<W: Write>(
+        &self,
+        table: &[BlockSummary],
+        writer: W,
+    ) -> Result<(), ParseError> {
+        let headers = [
+            "block_id",
+            "depot",
+            "total_rows",
+            "revenue_trips",
+            "first_time",
+            "last_time",
+            "duration_seconds",
+            "revenue_time_seconds",
+            "deadhead_time_seconds",
+            "layover_time_seconds",
+            "deadhead_ratio",
+        ];
+        let records = table.iter().map(|s| {
+            vec![
+                s.block_id.clone(),
+                self.optional(s.depot.clone()),
+                s.total_rows.to_string(),
+                s.revenue_trips.to_string(),
+                self.optional_time(s.start_time_seconds),
+                self.optional_time(s.end_time_seconds),
+                self.optional(s.duration_seconds.map(|v| v.to_string())),
+                s.revenue_time_seconds.to_string(),
+                s.deadhead_time_seconds.to_string(),
+                s.layover_time_seconds.to_string(),
+                self.optional(s.deadhead_ratio.map(|v| format!("{:.3}", v))),
+            ]
+        });
+        self.write_table(&headers, records, writer)
+    }
+
+    /// Write a duty KPI table (see [`Schedule::duty_kpi_table`]) as CSV.
+    ///
+    /// Uses the fixed columns `duty_id`, `total_rows`, `first_time`,
+    /// `last_time`, `duration_seconds`, `driving_time_seconds`,
+    /// `break_time_seconds`, `layover_time_seconds`, `pieces_of_work` and
+    /// `blocks_worked`.
+    pub fn export_duty_kpis<W: Write>(
+        &self,
+        table: &[DutySummary],
+        writer: W,
+    ) -> Result<(), ParseError> {
+        let headers = [
+            "duty_id",
+            "total_rows",
+            "first_time",
+            "last_time",
+            "duration_seconds",
+            "driving_time_seconds",
+            "break_time_seconds",
+            "layover_time_seconds",
+            "pieces_of_work",
+            "blocks_worked",
+        ];
+        let records = table.iter().map(|s| {
+            vec![
+                s.duty_id.clone(),
+                s.total_rows.to_string(),
+                self.optional_time(s.start_time_seconds),
+                self.optional_time(s.end_time_seconds),
+                self.optional(s.duration_seconds.map(|v| v.to_string())),
+                s.driving_time_seconds.to_string(),
+                s.break_time_seconds.to_string(),
+                s.layover_time_seconds.to_string(),
+                s.pieces_of_work.to_string(),
+                s.blocks_worked.to_string(),
+            ]
+        });
+        self.write_table(&headers, records, writer)
+    }
+
+    /// Write the deadheads from an inference result as CSV, for review
+    /// before they are applied.
+    ///
+    /// Uses the fixed columns `deadhead_type`, `block`, `from_location`,
+    /// `to_location`, `start_time`, `end_time`, `duration_seconds`,
+    /// `distance_meters`, `from_trip_id`, `to_trip_id` and `inferred`.
+    /// Rows follow [`DeadheadInferenceResult::all_deadheads`] order (pull-outs,
+    /// pull-ins, then interlinings). Times follow the configured time format
+    /// and missing values use the null value.
+    pub fn export_deadheads<W: Write>(
+        &self,
+        result: &DeadheadInferenceResult,
+        writer: W,
+    ) -> Result<(), ParseError> {
+        let headers = [
+            "deadhead_type",
+            "block",
+            "from_location",
+            "to_location",
+            "start_time",
+            "end_time",
+            "duration_seconds",
+            "distance_meters",
+            "from_trip_id",
+            "to_trip_id",
+            "inferred",
+        ];
+        let records = result.all_deadheads().map(|d| {
+            vec![
+                d.deadhead_type.as_str().to_string(),
+                self.optional(d.block_id.clone()),
+                d.from_location.clone(),
+                d.to_location.clone(),
+                self.optional_time(d.start_time_seconds),
+                self.optional_time(d.end_time_seconds),
+                self.optional(d.duration_seconds().map(|v| v.to_string())),
+                self.optional(d.distance_meters.map(|v| format!("{:.0}", v))),
+                self.optional(d.from_trip_id.clone()),
+                self.optional(d.to_trip_id.clone()),
+                d.is_inferred.to_string(),
+            ]
+        });
+        self.write_table(&headers, records, writer)
+    }
+
+    /// Write a header (if configured) and pre-formatted records as CSV.
+    ///
+    /// Every table goes through here, so the header prefix, line ending and
+    /// encoding apply the same way to schedule columns and to the fixed KPI
+    /// and deadhead columns. Non-UTF-8 output is built in memory first and
+    /// transcoded, so nothing is written if a character can't be encoded.
+    fn write_table<W: Write>(
+        &self,
+        headers: &[&str],
+        records: impl Iterator<Item = Vec<String>>,
+        mut writer: W,
+    ) -> Result<(), ParseError> {
+        let encoding = self.output_encoding()?;
+        if encoding == UTF_8 {
+            return self.write_csv(headers, records, writer);
+        }
+
+        let mut buffer = Vec::new();
+        self.write_csv(headers, records, &mut buffer)?;
+        // The CSV writer only ever produces UTF-8 from `String` fields
+        let text = String::from_utf8(buffer).map_err(|e| ParseError::Csv(e.to_string()))?;
+        let bytes = encode(&text, encoding, self.config.lossy_encoding)?;
+        writer.write_all(&bytes).map_err(ParseError::Io)
+    }
+
+    /// Write the header and records as UTF-8 CSV.
+    fn write_csv<W: Write>(
+        &self,
+        headers: &[&str],
+        records: impl Iterator<Item = Vec<String>>,
+        writer: W,
+    ) -> Result<(), ParseError> {
+        let terminator = match self.config.line_terminator {
+            LineTerminator::Lf => Terminator::Any(b'\n'),
+            LineTerminator::Crlf => Terminator::CRLF,
+        };
+        let mut csv_writer = WriterBuilder::new()
+            .delimiter(self.config.delimiter)
+            .terminator(terminator)
+            .from_writer(writer);
 
-        // Write header
         if self.config.include_header {
-            let headers: Vec<&str> = self
-                .config
-                .columns
-                .iter()
-                .filter(|c| c.include)
-                .map(|c| c.header.as_str())
-                .collect();
+            let prefix = self.config.header_prefix.as_deref().unwrap_or("");
             csv_writer
-                .write_record(&headers)
+                .write_record(headers.iter().map(|h| format!("{}{}", prefix, h)))
                 .map_err(|e| ParseError::Csv(e.to_string()))?;
         }
 
-        // Write rows
-        for row in &schedule.rows {
-            let record = self.row_to_record(row);
+        for record in records {
             csv_writer
                 .write_record(&record)
                 .map_err(|e| ParseError::Csv(e.to_string()))?;
@@ -212,18 +636,32 @@ impl CsvExporter {
         Ok(())
     }
 
-    /// Export schedule to string.
-    pub fn export_to_string(&self, schedule: &Schedule) -> Result<String, ParseError> {
-        let mut buffer = Vec::new();
-        self.export_to_writer(schedule, &mut buffer)?;
-        String::from_utf8(buffer).map_err(|e| ParseError::Csv(e.to_string()))
+    /// Resolve the configured encoding label.
+    fn output_encoding(&self) -> Result<&'static Encoding, ParseError> {
+        let Some(label) = self.config.encoding.as_deref() else {
+            return Ok(UTF_8);
+        };
+        let encoding = Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+            ParseError::InvalidData(format!("Unknown output encoding '{}'", label))
+        })?;
+        // UTF-16 and replacement can be decoded but not encoded
+        if encoding.output_encoding() != encoding {
+            return Err(ParseError::InvalidData(format!(
+                "Encoding '{}' can't be used for output",
+                label
+            )));
+        }
+        Ok(encoding)
     }
 
-    /// Export schedule to bytes.
-    pub fn export_to_bytes(&self, schedule: &Schedule) -> Result<Vec<u8>, ParseError> {
-        let mut buffer = Vec::new();
-        self.export_to_writer(schedule, &mut buffer)?;
-        Ok(buffer)
+    /// Use the configured null value for a missing value.
+    fn optional(&self, value: Option<String>) -> String {
+        value.unwrap_or_else(|| self.config.null_value.clone())
+    }
+
+    /// Format seconds since midnight with the configured time format.
+    fn optional_time(&self, seconds: Option<u32>) -> String {
+        self.optional(seconds.map(|secs| self.format_time(&secs.to_string())))
     }
 
     /// Convert a schedule row to a CSV record.
@@ -249,24 +687,45 @@ impl CsvExporter {
             "depot" => row.depot.clone(),
             "vehicle_class" => row.vehicle_class.clone(),
             "vehicle_type" => row.vehicle_type.clone(),
-            "start_lat" => row.start_lat.map(|v| v.to_string()),
-            "start_lon" => row.start_lon.map(|v| v.to_string()),
-            "end_lat" => row.end_lat.map(|v| v.to_string()),
-            "end_lon" => row.end_lon.map(|v| v.to_string()),
+            "start_lat" => row.start_lat.map(|v| self.format_coordinate(v)),
+            "start_lon" => row.start_lon.map(|v| self.format_coordinate(v)),
+            "end_lat" => row.end_lat.map(|v| self.format_coordinate(v)),
+            "end_lon" => row.end_lon.map(|v| self.format_coordinate(v)),
             "route_shape_id" => row.route_shape_id.clone(),
             "row_type" => Some(format!("{:?}", row.row_type).to_lowercase()),
             "duty_id" => row.duty_id.clone(),
             "shift_id" => row.shift_id.clone(),
             "route_short_name" => row.route_short_name.clone(),
             "headsign" => row.headsign.clone(),
+            "service_id" => row.service_id.clone(),
+            "block_sequence" => row.block_sequence.map(|v| v.to_string()),
             _ => None,
         };
 
         value.unwrap_or_else(|| self.config.null_value.clone())
     }
 
+    /// Format a coordinate with the configured precision.
+    fn format_coordinate(&self, value: f64) -> String {
+        match self.config.coordinate_precision {
+            Some(precision) => format!("{:.*}", precision, value),
+            None => value.to_string(),
+        }
+    }
+
     /// Format a time string according to config.
     fn format_time(&self, time: &str) -> String {
+        if let Some(step) = self.config.round_to_seconds.filter(|&step| step > 0) {
+            if let Some(secs) = parse_time_to_seconds(time) {
+                let rounded = secs.saturating_add(step / 2) / step * step;
+                return self.format_unrounded(&rounded.to_string());
+            }
+        }
+        self.format_unrounded(time)
+    }
+
+    /// Format a time string according to the time format alone.
+    fn format_unrounded(&self, time: &str) -> String {
         match self.config.time_format {
             TimeFormat::HhMmSs => {
                 // Already in HH:MM:SS or convert from seconds
@@ -321,6 +780,98 @@ impl CsvExporter {
     }
 }
 
+/// Number of rows an [`AppendWriter`] buffers before flushing to the file.
+const APPEND_FLUSH_ROWS: usize = 1000;
+
+/// Writes schedule rows to a CSV file one at a time, created by
+/// [`CsvExporter::open_append`].
+///
+/// Rows are buffered and flushed every 1000 rows, on [`flush`](Self::flush)
+/// and when the writer is dropped. Dropping ignores flush errors, so call
+/// [`flush`](Self::flush) at the end to see them.
+pub struct AppendWriter {
+    exporter: CsvExporter,
+    writer: BufWriter<File>,
+    rows_written: usize,
+}
+
+impl AppendWriter {
+    /// Write one row.
+    pub fn write_row(&mut self, row: &ScheduleRow) -> Result<(), ParseError> {
+        self.exporter
+            .write_rows(std::iter::once(row), &mut self.writer)?;
+        self.rows_written += 1;
+        if self.rows_written % APPEND_FLUSH_ROWS == 0 {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered rows to the file.
+    pub fn flush(&mut self) -> Result<(), ParseError> {
+        self.writer.flush().map_err(ParseError::Io)
+    }
+
+    /// Number of rows written through this writer.
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+}
+
+/// Replace characters that aren't safe in file names with `_`.
+fn sanitize_filename(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    // Avoid empty names and "." / ".." path components
+    if sanitized.chars().all(|c| c == '.') {
+        "_".repeat(sanitized.len().max(1))
+    } else {
+        sanitized
+    }
+}
+
+/// Transcode UTF-8 `text` into `encoding`, replacing characters it can't
+/// represent with `?` when `lossy`, or failing on the first one otherwise.
+fn encode(text: &str, encoding: &'static Encoding, lossy: bool) -> Result<Vec<u8>, ParseError> {
+    let mut encoder = encoding.new_encoder();
+    let capacity = encoder
+        .max_buffer_length_from_utf8_without_replacement(text.len())
+        .unwrap_or(text.len());
+    let mut output = Vec::with_capacity(capacity);
+    let mut input = text;
+    loop {
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(input, &mut output, true);
+        input = &input[read..];
+        match result {
+            EncoderResult::InputEmpty => return Ok(output),
+            EncoderResult::OutputFull => {
+                let extra = encoder
+                    .max_buffer_length_from_utf8_without_replacement(input.len())
+                    .unwrap_or(input.len());
+                output.reserve(extra.max(1));
+            }
+            EncoderResult::Unmappable(_) if lossy => output.push(b'?'),
+            EncoderResult::Unmappable(c) => {
+                return Err(ParseError::InvalidData(format!(
+                    "Character '{}' can't be written as {}",
+                    c,
+                    encoding.name()
+                )));
+            }
+        }
+    }
+}
+
 impl Default for CsvExporter {
     fn default() -> Self {
         Self::default_config()
@@ -330,7 +881,7 @@ impl Default for CsvExporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::RowType;
+    use crate::models::{BlockKey, RowType};
 
     fn make_row() -> ScheduleRow {
         ScheduleRow {
@@ -371,6 +922,29 @@ mod tests {
         assert!(!result.contains("start_place"));
     }
 
+    #[test]
+    fn test_without_and_with_columns() {
+        let schedule = Schedule::from_rows(vec![make_row()]);
+        let config = ExportConfig::with_column_mapping(vec![
+            ("run_number", "driver"),
+            ("block", "block"),
+            ("trip_id", "trip"),
+        ])
+        .without(&["run_number", "trip_id", "not_a_column"]);
+        let result = CsvExporter::new(config.clone())
+            .export_to_string(&schedule)
+            .unwrap();
+        assert_eq!(result, "block\nB1\n");
+        assert_eq!(config.columns.len(), 3);
+
+        // Re-included columns keep their header and position
+        let config = config.with(&["run_number", "depot"]);
+        let result = CsvExporter::new(config)
+            .export_to_string(&schedule)
+            .unwrap();
+        assert_eq!(result.lines().next(), Some("driver,block,depot"));
+    }
+
     #[test]
     fn test_export_column_mapping() {
         let schedule = Schedule::from_rows(vec![make_row()]);
@@ -411,6 +985,88 @@ mod tests {
         assert!(!result.contains("08:00:00"));
     }
 
+    #[test]
+    fn test_round_to_seconds() {
+        let times = ["08:00:29", "08:00:31", "08:00:44", "25:59:45"];
+        let schedule = Schedule::from_rows(
+            times
+                .iter()
+                .map(|t| ScheduleRow {
+                    start_time: Some(t.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let export = |config: ExportConfig| {
+            let config = ExportConfig {
+                columns: vec![ColumnConfig::new("start_time", "start_time")],
+                ..config
+            };
+            let csv = CsvExporter::new(config)
+                .export_to_string(&schedule)
+                .unwrap();
+            csv.lines().skip(1).map(String::from).collect::<Vec<_>>()
+        };
+
+        let minutes = export(ExportConfig::new().round_to_seconds(60));
+        assert_eq!(minutes, ["08:00:00", "08:01:00", "08:01:00", "26:00:00"]);
+
+        let half_minutes = export(ExportConfig::new().round_to_seconds(30));
+        assert_eq!(
+            half_minutes,
+            ["08:00:30", "08:00:30", "08:00:30", "26:00:00"]
+        );
+
+        let hhmm = export(
+            ExportConfig::new()
+                .round_to_seconds(60)
+                .time_format(TimeFormat::HhMm),
+        );
+        assert_eq!(hhmm, ["08:00", "08:01", "08:01", "26:00"]);
+
+        let seconds = export(
+            ExportConfig::new()
+                .round_to_seconds(60)
+                .time_format(TimeFormat::Seconds),
+        );
+        assert_eq!(seconds[1], "28860");
+    }
+
+    #[test]
+    fn test_coordinate_precision() {
+        let schedule = Schedule::from_rows(vec![ScheduleRow {
+            start_lat: Some(40.7128),
+            start_lon: Some(-74.006),
+            // Float noise as left by arithmetic or an f32 round trip.
+            end_lat: Some(40.7128 + 1e-13),
+            ..Default::default()
+        }]);
+        let export = |config: ExportConfig| {
+            let config = ExportConfig {
+                columns: ["start_lat", "start_lon", "end_lat", "end_lon"]
+                    .iter()
+                    .map(|f| ColumnConfig::new(*f, *f))
+                    .collect(),
+                ..config
+            };
+            let csv = CsvExporter::new(config)
+                .export_to_string(&schedule)
+                .unwrap();
+            csv.lines().nth(1).unwrap().to_string()
+        };
+
+        assert_eq!(
+            export(ExportConfig::new()),
+            "40.712800,-74.006000,40.712800,"
+        );
+        assert_eq!(
+            export(ExportConfig::new().coordinate_precision(Some(3))),
+            "40.713,-74.006,40.713,"
+        );
+        let full = export(ExportConfig::new().coordinate_precision(None));
+        assert_eq!(full, "40.7128,-74.006,40.7128000000001,");
+    }
+
     #[test]
     fn test_null_value() {
         let mut row = make_row();
@@ -424,4 +1080,248 @@ mod tests {
 
         assert!(result.contains("R1,N/A"));
     }
+
+    #[test]
+    fn test_header_prefix() {
+        let schedule = Schedule::from_rows(vec![make_row()]);
+        let config =
+            ExportConfig::with_column_mapping(vec![("run_number", "driver"), ("block", "block")])
+                .header_prefix("tp_");
+        let exporter = CsvExporter::new(config);
+
+        let result = exporter.export_to_string(&schedule).unwrap();
+        assert_eq!(result, "tp_driver,tp_block\nR1,B1\n");
+
+        // Fixed-column tables get the same prefix
+        let mut schedule = schedule;
+        let mut buffer = Vec::new();
+        exporter
+            .export_block_kpis(&schedule.block_kpi_table(), &mut buffer)
+            .unwrap();
+        let result = String::from_utf8(buffer).unwrap();
+        assert!(result.starts_with("tp_block_id,tp_depot,"));
+    }
+
+    #[test]
+    fn test_line_terminator_and_encoding() {
+        let mut row = make_row();
+        row.start_place = Some("Café".to_string());
+        let schedule = Schedule::from_rows(vec![row]);
+        let config = ExportConfig::with_columns(vec!["block", "start_place"])
+            .line_terminator(LineTerminator::Crlf)
+            .encoding("windows-1252");
+        let exporter = CsvExporter::new(config.clone());
+
+        let bytes = exporter.export_to_bytes(&schedule).unwrap();
+        assert_eq!(bytes, b"block,start_place\r\nB1,Caf\xE9\r\n");
+        // Strings stay UTF-8 whatever the encoding
+        assert_eq!(
+            exporter.export_to_string(&schedule).unwrap(),
+            "block,start_place\r\nB1,Café\r\n"
+        );
+
+        // Characters outside the encoding fail unless lossy output is allowed
+        let mut schedule = schedule;
+        schedule.rows[0].start_place = Some("Łódź".to_string());
+        let err = exporter.export_to_bytes(&schedule).unwrap_err();
+        assert!(err.to_string().contains("'Ł'"));
+        let exporter = CsvExporter::new(config.lossy_encoding(true));
+        let bytes = exporter.export_to_bytes(&schedule).unwrap();
+        assert!(bytes.ends_with(b"B1,?\xF3d?\r\n"));
+
+        for label in ["no-such-encoding", "utf-16le"] {
+            let exporter = CsvExporter::new(ExportConfig::new().encoding(label));
+            assert!(exporter.export_to_bytes(&schedule).is_err());
+        }
+    }
+
+    #[test]
+    fn test_export_by_block() {
+        let mut late = make_row();
+        late.trip_id = Some("TRIP2".to_string());
+        late.start_time = Some("10:00:00".to_string());
+        let mut other = make_row();
+        other.block = Some("B2/north".to_string());
+        let mut unassigned = make_row();
+        unassigned.block = None;
+
+        let schedule = Schedule::from_rows(vec![late, make_row(), other, unassigned]);
+        let config = ExportConfig::with_columns(vec!["block", "trip_id"]);
+        let exporter = CsvExporter::new(config);
+
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("blocks");
+        let paths = exporter
+            .export_by_block(&schedule, &out_dir, "vehicle_{block_id}")
+            .unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                out_dir.join("vehicle_B1.csv"),
+                out_dir.join("vehicle_B2_north.csv")
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(&paths[0]).unwrap(),
+            "block,trip_id\nB1,TRIP1\nB1,TRIP2\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&paths[1]).unwrap(),
+            "block,trip_id\nB2/north,TRIP1\n"
+        );
+    }
+
+    #[test]
+    fn test_export_by_block_composite_key() {
+        let mut north = make_row();
+        north.depot = Some("NORTH".to_string());
+        let mut south = make_row();
+        south.depot = Some("SOUTH".to_string());
+        south.trip_id = Some("TRIP2".to_string());
+
+        let mut schedule = Schedule::from_rows(vec![north, south]);
+        schedule.derive_blocks_by(BlockKey::depot_block());
+        let exporter = CsvExporter::new(ExportConfig::with_columns(vec!["depot", "trip_id"]));
+        let dir = tempfile::tempdir().unwrap();
+        let paths = exporter
+            .export_by_block(&schedule, dir.path(), "{block_id}")
+            .unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                dir.path().join("NORTH_B1.csv"),
+                dir.path().join("SOUTH_B1.csv")
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(&paths[1]).unwrap(),
+            "depot,trip_id\nSOUTH,TRIP2\n"
+        );
+    }
+
+    #[test]
+    fn test_export_by_block_name_collision() {
+        let mut slash = make_row();
+        slash.block = Some("A/B".to_string());
+        let mut underscore = make_row();
+        underscore.block = Some("A_B".to_string());
+
+        let schedule = Schedule::from_rows(vec![slash, underscore]);
+        let dir = tempfile::tempdir().unwrap();
+        let result =
+            CsvExporter::default_config().export_by_block(&schedule, dir.path(), "{block_id}");
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_open_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.csv");
+        let exporter = CsvExporter::new(ExportConfig::with_columns(vec!["run_number", "block"]));
+
+        let mut writer = exporter.open_append(&path).unwrap();
+        writer.write_row(&make_row()).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.rows_written(), 1);
+        drop(writer);
+
+        // Reopening continues the file without a second header
+        let mut row = make_row();
+        row.block = Some("B2".to_string());
+        let mut writer = exporter.open_append(&path).unwrap();
+        writer.write_row(&row).unwrap();
+        drop(writer);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "run_number,block\nR1,B1\nR1,B2\n"
+        );
+
+        let bad = CsvExporter::new(ExportConfig::new().encoding("bogus"));
+        let missing = dir.path().join("never.csv");
+        assert!(bad.open_append(&missing).is_err());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_export_kpi_tables() {
+        let mut deadhead = make_row();
+        deadhead.trip_id = None;
+        deadhead.row_type = RowType::Deadhead;
+        deadhead.start_time = Some("09:00:00".to_string());
+        deadhead.end_time = Some("09:20:00".to_string());
+        let mut first = make_row();
+        first.duty_id = Some("D1".to_string());
+
+        let mut schedule = Schedule::from_rows(vec![first, deadhead]);
+        let blocks = schedule.block_kpi_table();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].deadhead_ratio, Some(0.25));
+
+        let exporter = CsvExporter::new(ExportConfig::default().time_format(TimeFormat::HhMm));
+        let mut buffer = Vec::new();
+        exporter.export_block_kpis(&blocks, &mut buffer).unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "block_id,depot,total_rows,revenue_trips,first_time,last_time,duration_seconds,\
+             revenue_time_seconds,deadhead_time_seconds,layover_time_seconds,deadhead_ratio"
+        );
+        assert_eq!(lines[1], "B1,,2,1,08:00,09:20,4800,3600,1200,0,0.250");
+
+        let duties = schedule.duty_kpi_table();
+        let mut buffer = Vec::new();
+        exporter.export_duty_kpis(&duties, &mut buffer).unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("D1,1,08:00,09:00,3600,3600,0,0,1,1")
+        );
+    }
+
+    #[test]
+    fn test_export_deadheads() {
+        use crate::models::Deadhead;
+
+        let result = DeadheadInferenceResult {
+            pull_outs: vec![Deadhead::pull_out("DEPOT", "A")
+                .with_block("B1")
+                .with_times(27900, 28800)
+                .inferred()],
+            interlinings: vec![Deadhead {
+                distance_meters: Some(1234.4),
+                ..Deadhead::interlining("B", "C")
+                    .with_block("B1")
+                    .with_trips(Some("T1"), Some("T2"))
+                    .inferred()
+            }],
+            ..Default::default()
+        };
+
+        let exporter = CsvExporter::new(ExportConfig::new().null_value("-"));
+        let mut buffer = Vec::new();
+        exporter.export_deadheads(&result, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("deadhead_type,block,from_location"));
+        assert_eq!(
+            lines[1],
+            "pull_out,B1,DEPOT,A,07:45:00,08:00:00,900,-,-,-,true"
+        );
+        assert_eq!(lines[2], "interlining,B1,B,C,-,-,-,1234,T1,T2,true");
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("B1"), "B1");
+        assert_eq!(sanitize_filename("a b:c\\d"), "a_b_c_d");
+        assert_eq!(sanitize_filename(".."), "__");
+        assert_eq!(sanitize_filename(""), "_");
+    }
 }
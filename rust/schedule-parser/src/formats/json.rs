@@ -0,0 +1,75 @@
+//! JSON Lines (NDJSON) export of schedule rows.
+
+use crate::models::Schedule;
+use std::io::Write;
+use transit_core::ParseError;
+
+/// Schedule exporter for JSON formats.
+///
+/// Rows are written with their serde field names, so every field round-trips
+/// through [`ScheduleReader::read_ndjson`](crate::ScheduleReader::read_ndjson)
+/// without a column mapping.
+pub struct JsonExporter;
+
+impl JsonExporter {
+    /// Write each row as one JSON object per line (NDJSON).
+    ///
+    /// Every line, including the last, ends with `\n`, so the output of
+    /// several calls can be appended to the same file.
+    pub fn export_ndjson<W: Write>(schedule: &Schedule, writer: W) -> Result<(), ParseError> {
+        let mut writer = std::io::BufWriter::new(writer);
+        for row in &schedule.rows {
+            serde_json::to_writer(&mut writer, row).map_err(|e| ParseError::Json(e.to_string()))?;
+            writer.write_all(b"\n").map_err(ParseError::Io)?;
+        }
+        writer.flush().map_err(ParseError::Io)
+    }
+
+    /// Export rows as an NDJSON string.
+    pub fn export_ndjson_string(schedule: &Schedule) -> Result<String, ParseError> {
+        let mut buffer = Vec::new();
+        Self::export_ndjson(schedule, &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| ParseError::Json(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RowType, ScheduleRow};
+    use crate::{ReadOptions, ScheduleReader};
+
+    #[test]
+    fn test_ndjson_round_trip() {
+        let schedule = Schedule::from_rows(vec![
+            ScheduleRow {
+                block: Some("B1".to_string()),
+                trip_id: Some("T1".to_string()),
+                start_time: Some("08:00:00".to_string()),
+                start_lat: Some(51.5),
+                ..Default::default()
+            },
+            ScheduleRow {
+                block: Some("B1".to_string()),
+                row_type: RowType::PullIn,
+                ..Default::default()
+            },
+        ]);
+
+        let ndjson = JsonExporter::export_ndjson_string(&schedule).unwrap();
+        assert_eq!(ndjson.lines().count(), 2);
+        assert!(ndjson.ends_with('\n'));
+
+        let with_blank = format!("{}\n", ndjson.replacen('\n', "\n  \n", 1));
+        let read = ScheduleReader::read_ndjson(with_blank.as_bytes(), ReadOptions::new()).unwrap();
+        assert_eq!(read.rows, schedule.rows);
+
+        let minimal = r#"{"trip_id": "T9"}"#;
+        let read = ScheduleReader::read_ndjson(minimal.as_bytes(), ReadOptions::new()).unwrap();
+        assert_eq!(read.rows[0].row_type, RowType::Revenue);
+
+        let bad = format!("{}\n{{\"block\": 7}}\n", minimal);
+        let err = ScheduleReader::read_ndjson(bad.as_bytes(), ReadOptions::new()).unwrap_err();
+        assert!(matches!(err, ParseError::Json(msg) if msg.starts_with("line 2: ")));
+    }
+}
@@ -1,7 +1,9 @@
 //! Export formats and column configuration.
 
 pub mod generic_csv;
+pub mod json;
 pub mod presets;
 
-pub use generic_csv::{CsvExporter, ExportConfig};
+pub use generic_csv::{AppendWriter, CsvExporter, ExportConfig};
+pub use json::JsonExporter;
 pub use presets::ExportPreset;
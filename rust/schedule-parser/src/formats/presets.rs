@@ -68,6 +68,7 @@ impl ExportPreset {
                 ColumnConfig::new("end_lon", "end_lon"),
                 ColumnConfig::new("route_shape_id", "route_shape_id"),
                 ColumnConfig::new("row_type", "row_type"),
+                ColumnConfig::new("service_id", "service_id"),
             ],
             ..Default::default()
         }
@@ -152,6 +153,9 @@ mod tests {
 
         let config = ExportPreset::Extended.to_config();
         assert!(config.columns.len() > 15);
+        // New columns go last so existing column positions don't shift
+        assert_eq!(config.columns[0].field, "run_number");
+        assert_eq!(config.columns.last().unwrap().field, "service_id");
     }
 
     #[test]
@@ -3,10 +3,12 @@
 //! This crate provides tools for working with transit schedules:
 //!
 //! - **Parsing**: Load schedules from CSV files with flexible column mapping
+//! - **Sources**: Load CSV, NDJSON or fixed-width input through one [`ScheduleSource`] trait
 //! - **Validation**: Validate schedules against GTFS data and business rules
 //! - **Generation**: Export schedules in various formats (Optibus-like, Hastus-like, custom)
 //! - **Rostering**: Work with blocks (vehicle assignments) and duties (driver assignments)
 //! - **Deadheads**: Infer missing deadhead movements (pull-out, pull-in, interlining)
+//! - **Pipeline**: Run the standard cleanup steps in the recommended order
 //!
 //! # Quick Start
 //!
@@ -30,21 +32,44 @@
 //! }
 //! ```
 
+pub mod anonymize;
+pub mod blocking;
 pub mod deadhead;
+pub mod fix;
 pub mod formats;
+mod hash;
 pub mod models;
+pub mod normalize;
+pub mod pipeline;
+pub mod progress;
 pub mod reader;
+pub mod source;
+pub mod time;
 pub mod validation;
 
 // Re-exports
-pub use deadhead::{inferrer::InferenceConfig, DeadheadInferrer};
-pub use formats::{CsvExporter, ExportConfig, ExportPreset};
+pub use anonymize::{AnonymizationKey, AnonymizeOptions, IdStrategy};
+pub use blocking::{BlockingConfig, BlockingReport};
+pub use deadhead::{
+    inferrer::{DistanceModel, InferenceConfig},
+    DeadheadInferrer,
+};
+pub use fix::FixReport;
+pub use formats::{AppendWriter, CsvExporter, ExportConfig, ExportPreset, JsonExporter};
 pub use models::{
-    Block, BlockSummary, Break, Deadhead, DeadheadInferenceResult, DeadheadType, Duty, DutySummary,
-    PieceOfWork, RowType, Schedule, ScheduleMetadata, ScheduleRow, ScheduleSummary, Shift,
-    ShiftSummary,
+    Block, BlockCoverage, BlockKey, BlockSummary, Break, BreakCompliance, CompactBlockSummary,
+    Deadhead, DeadheadInferenceResult, DeadheadStats, DeadheadType, Duty, DutySummary, DutyTotal,
+    ExpandedStopTime, ExpandedStopTimes, FrequencyBand, ParseWarning, PayRules, PieceOfWork,
+    RouteSummary, RowType, Schedule, ScheduleMetadata, ScheduleRow, ScheduleSummary, Shift,
+    ShiftSummary, TimelineSegment, TimelineSegmentKind,
 };
+pub use normalize::{IdMapping, NormalizeIdsOptions};
+pub use pipeline::{PipelineReport, PipelineStep, SchedulePipeline};
+pub use progress::ProgressCounter;
 pub use reader::{ColumnMapping, ReadOptions, ScheduleReader};
+pub use source::{CsvSource, FixedWidthSource, NdjsonSource, ScheduleSource};
+pub use time::{parse_time_to_seconds, seconds_to_time_string};
 pub use validation::{
-    BusinessRules, GtfsComplianceLevel, ValidationConfig, ValidationResult, Validator,
+    BusinessRules, CachingValidator, ContinuousDrivingReset, GtfsComplianceLevel, ValidationConfig,
+    ValidationResult, Validator,
 };
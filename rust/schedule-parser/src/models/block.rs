@@ -1,5 +1,6 @@
 //! Block model - vehicle assignment grouping trips.
 
+use super::duty::{split_into_pieces, PieceOfWork};
 use super::schedule_row::{RowType, ScheduleRow};
 use serde::{Deserialize, Serialize};
 
@@ -65,6 +66,18 @@ impl Block {
         });
     }
 
+    /// Get rows paired with their 1-based position in the block.
+    ///
+    /// Positions follow the current row order, which is chronological once
+    /// the block has been sorted (as `Schedule::derive_blocks` does).
+    pub fn sequenced_rows(&self) -> Vec<(u32, &ScheduleRow)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (i as u32 + 1, row))
+            .collect()
+    }
+
     /// Get number of rows in this block.
     pub fn len(&self) -> usize {
         self.rows.len()
@@ -85,6 +98,23 @@ impl Block {
         self.rows.iter().filter(|r| r.is_revenue()).count()
     }
 
+    /// Index pairs of consecutive rows matching `keep`, in row order.
+    ///
+    /// The skipped rows between a pair are `rows[prev + 1..next]`.
+    pub(crate) fn consecutive_rows_where(
+        &self,
+        keep: impl Fn(&ScheduleRow) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let indices: Vec<usize> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| keep(r))
+            .map(|(idx, _)| idx)
+            .collect();
+        indices.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
     /// Get the pull-out row (first deadhead from depot).
     pub fn pull_out(&self) -> Option<&ScheduleRow> {
         self.rows.iter().find(|r| r.row_type == RowType::PullOut)
@@ -147,6 +177,23 @@ impl Block {
             .sum()
     }
 
+    /// Calculate total layover time in seconds.
+    ///
+    /// Layover is time the vehicle waits between movements: explicit
+    /// `Layover` rows plus any unfilled gaps between consecutive rows.
+    /// Together with revenue time, deadhead time and the duration of any
+    /// break or relief rows it adds up to the block duration.
+    pub fn layover_time_seconds(&self) -> u32 {
+        let explicit: u32 = self
+            .rows
+            .iter()
+            .filter(|r| r.is_layover())
+            .filter_map(|r| r.duration_seconds())
+            .sum();
+        let gaps: u32 = self.find_gaps().iter().map(|&(_, gap)| gap).sum();
+        explicit + gaps
+    }
+
     /// Check if there's a gap between consecutive rows.
     ///
     /// Returns pairs of (row_index, gap_seconds) where gaps exist.
@@ -167,6 +214,124 @@ impl Block {
         gaps
     }
 
+    /// Distance covered by one row in meters, `None` if unknown.
+    ///
+    /// Moving rows need start and end coordinates. Layover and break rows
+    /// count as stationary when their coordinates are missing.
+    fn row_distance_meters(row: &ScheduleRow) -> Option<f64> {
+        row.distance_meters()
+            .or_else(|| matches!(row.row_type, RowType::Layover | RowType::Break).then_some(0.0))
+    }
+
+    /// Running distance in meters at the end of each row, in row order, like
+    /// GTFS `shape_dist_traveled`.
+    ///
+    /// Row distances are straight lines between start and end coordinates
+    /// (see [`ScheduleRow::distance_meters`]), so backfill coordinates from
+    /// GTFS first if they are missing. A row whose distance is unknown gets
+    /// `None` and adds nothing; later rows carry on from the last known
+    /// total, so one gap doesn't lose the rest of the block.
+    pub fn cumulative_distances(&self) -> Vec<Option<f64>> {
+        let mut total = 0.0;
+        self.rows
+            .iter()
+            .map(|row| {
+                let distance = Self::row_distance_meters(row)?;
+                total += distance;
+                Some(total)
+            })
+            .collect()
+    }
+
+    /// Total distance of the rows with a known distance, in meters.
+    ///
+    /// The last known value of
+    /// [`cumulative_distances`](Self::cumulative_distances).
+    pub fn total_distance_meters(&self) -> f64 {
+        self.rows
+            .iter()
+            .filter_map(Self::row_distance_meters)
+            .fold(0.0, |total, distance| total + distance)
+    }
+
+    /// Get pieces of work from the vehicle's point of view.
+    ///
+    /// Unlike [`Duty::pieces_of_work`](crate::models::Duty::pieces_of_work),
+    /// only `Break` rows end a piece. A `Relief` is a driver changeover
+    /// while the vehicle stays in service, so it is kept in the current
+    /// piece. Times are normalized across midnight in the same way.
+    pub fn pieces_of_work(&self) -> Vec<PieceOfWork> {
+        split_into_pieces(&self.rows, |row| row.row_type == RowType::Break)
+    }
+
+    /// Classified time segments covering the block, for a Gantt-style view.
+    ///
+    /// Each timed row becomes one segment, in start-time order. Idle time
+    /// between rows is filled with an implicit segment: a
+    /// [`Layover`](TimelineSegmentKind::Layover) when the vehicle waits
+    /// where it stopped (or a place is unknown), or a
+    /// [`Gap`](TimelineSegmentKind::Gap) when the next row starts somewhere
+    /// else with no movement recorded in between. Rows missing a start or
+    /// end time, or ending before they start, are left out.
+    pub fn timeline(&self) -> Vec<TimelineSegment> {
+        let mut timed: Vec<(usize, u32, u32)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(idx, row)| match (row.start_time_seconds(), row.end_time_seconds()) {
+                    (Some(start), Some(end)) if end >= start => Some((idx, start, end)),
+                    _ => None,
+                },
+            )
+            .collect();
+        timed.sort_by_key(|&(_, start, _)| start);
+
+        let mut segments = Vec::with_capacity(timed.len() * 2);
+        let mut previous: Option<(usize, u32)> = None;
+        for (idx, start, end) in timed {
+            let row = &self.rows[idx];
+
+            if let Some((prev_idx, prev_end)) = previous {
+                if start > prev_end {
+                    let from = self.rows[prev_idx].end_place.clone();
+                    let to = row.start_place.clone();
+                    let kind = match (&from, &to) {
+                        (Some(from), Some(to)) if from != to => TimelineSegmentKind::Gap,
+                        _ => TimelineSegmentKind::Layover,
+                    };
+                    segments.push(TimelineSegment {
+                        kind,
+                        start_seconds: prev_end,
+                        end_seconds: start,
+                        start_place: from,
+                        end_place: to,
+                        row_index: None,
+                        trip_id: None,
+                    });
+                }
+            }
+
+            segments.push(TimelineSegment {
+                kind: TimelineSegmentKind::from(row.row_type),
+                start_seconds: start,
+                end_seconds: end,
+                start_place: row.start_place.clone(),
+                end_place: row.end_place.clone(),
+                row_index: Some(idx),
+                trip_id: row.trip_id.clone(),
+            });
+
+            // Overlapping rows don't move the cursor backwards
+            match previous {
+                Some((_, prev_end)) if end < prev_end => {}
+                _ => previous = Some((idx, end)),
+            }
+        }
+
+        segments
+    }
+
     /// Check if there's a location discontinuity between consecutive rows.
     ///
     /// Returns indices where end_place of row N != start_place of row N+1.
@@ -187,32 +352,164 @@ impl Block {
         discontinuities
     }
 
+    /// Routes served by the block's revenue trips, in row order, with
+    /// consecutive repeats collapsed (e.g. `["12", "7", "12"]`).
+    ///
+    /// Rows without a route short name are skipped, so they don't split a
+    /// run of the same route.
+    pub fn route_sequence(&self) -> Vec<String> {
+        let mut sequence: Vec<String> = Vec::new();
+        for route in self
+            .revenue_trips()
+            .filter_map(|r| r.route_short_name.as_deref())
+        {
+            if sequence.last().map(String::as_str) != Some(route) {
+                sequence.push(route.to_string());
+            }
+        }
+        sequence
+    }
+
+    /// High-level view of the block for diagrams: where and when it starts
+    /// and ends, and the routes it serves in order.
+    pub fn compact_summary(&self) -> CompactBlockSummary {
+        CompactBlockSummary {
+            block_id: self.block_id.clone(),
+            depot: self.depot.clone(),
+            start_time_seconds: self.start_time_seconds(),
+            end_time_seconds: self.end_time_seconds(),
+            start_place: self.first_row().and_then(|r| r.start_place.clone()),
+            end_place: self.last_row().and_then(|r| r.end_place.clone()),
+            route_sequence: self.route_sequence(),
+        }
+    }
+
     /// Get summary statistics for this block.
     pub fn summary(&self) -> BlockSummary {
+        let revenue_time_seconds = self.revenue_time_seconds();
+        let deadhead_time_seconds = self.deadhead_time_seconds();
+        let moving_seconds = revenue_time_seconds + deadhead_time_seconds;
+        let deadhead_ratio = if moving_seconds > 0 {
+            Some(deadhead_time_seconds as f64 / moving_seconds as f64)
+        } else {
+            None
+        };
+
         BlockSummary {
             block_id: self.block_id.clone(),
             total_rows: self.rows.len(),
             revenue_trips: self.revenue_trip_count(),
+            start_time_seconds: self.start_time_seconds(),
+            end_time_seconds: self.end_time_seconds(),
             duration_seconds: self.duration_seconds(),
-            revenue_time_seconds: self.revenue_time_seconds(),
-            deadhead_time_seconds: self.deadhead_time_seconds(),
+            revenue_time_seconds,
+            deadhead_time_seconds,
+            layover_time_seconds: self.layover_time_seconds(),
+            deadhead_ratio,
             depot: self.depot.clone(),
         }
     }
 }
 
+/// What a vehicle is doing during a [`TimelineSegment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineSegmentKind {
+    /// In passenger service.
+    Revenue,
+    /// Moving without passengers (pull-out, pull-in or deadhead).
+    Deadhead,
+    /// Driver break or relief.
+    Break,
+    /// Waiting in place, from a layover row or idle time between rows.
+    Layover,
+    /// Idle time after which the vehicle is somewhere else, with no
+    /// recorded movement.
+    Gap,
+}
+
+impl TimelineSegmentKind {
+    /// Snake-case name of the kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimelineSegmentKind::Revenue => "revenue",
+            TimelineSegmentKind::Deadhead => "deadhead",
+            TimelineSegmentKind::Break => "break",
+            TimelineSegmentKind::Layover => "layover",
+            TimelineSegmentKind::Gap => "gap",
+        }
+    }
+}
+
+impl From<RowType> for TimelineSegmentKind {
+    fn from(row_type: RowType) -> Self {
+        match row_type {
+            RowType::Revenue => TimelineSegmentKind::Revenue,
+            RowType::PullOut | RowType::PullIn | RowType::Deadhead => TimelineSegmentKind::Deadhead,
+            RowType::Break | RowType::Relief => TimelineSegmentKind::Break,
+            RowType::Layover => TimelineSegmentKind::Layover,
+        }
+    }
+}
+
+/// One period of a block timeline, from [`Block::timeline`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineSegment {
+    /// What the vehicle is doing.
+    pub kind: TimelineSegmentKind,
+    /// Start, in seconds since midnight.
+    pub start_seconds: u32,
+    /// End, in seconds since midnight.
+    pub end_seconds: u32,
+    /// Where the segment starts.
+    pub start_place: Option<String>,
+    /// Where the segment ends.
+    pub end_place: Option<String>,
+    /// Index into [`Block::rows`], or `None` for implicit filler segments.
+    pub row_index: Option<usize>,
+    /// Trip ID of the row, if any.
+    pub trip_id: Option<String>,
+}
+
+impl TimelineSegment {
+    /// Length of the segment in seconds.
+    pub fn duration_seconds(&self) -> u32 {
+        self.end_seconds - self.start_seconds
+    }
+}
+
 /// Summary statistics for a block.
 #[derive(Debug, Clone)]
 pub struct BlockSummary {
     pub block_id: String,
     pub total_rows: usize,
     pub revenue_trips: usize,
+    pub start_time_seconds: Option<u32>,
+    pub end_time_seconds: Option<u32>,
     pub duration_seconds: Option<u32>,
     pub revenue_time_seconds: u32,
     pub deadhead_time_seconds: u32,
+    pub layover_time_seconds: u32,
+    /// Share of moving time spent deadheading: deadhead / (revenue + deadhead).
+    pub deadhead_ratio: Option<f64>,
     pub depot: Option<String>,
 }
 
+/// Compact description of a block, from [`Block::compact_summary`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactBlockSummary {
+    pub block_id: String,
+    pub depot: Option<String>,
+    pub start_time_seconds: Option<u32>,
+    pub end_time_seconds: Option<u32>,
+    /// Start place of the first row.
+    pub start_place: Option<String>,
+    /// End place of the last row.
+    pub end_place: Option<String>,
+    /// See [`Block::route_sequence`].
+    pub route_sequence: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,6 +536,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pieces_of_work_continue_through_relief() {
+        let rows = [
+            ("06:00:00", "08:00:00", RowType::Revenue),
+            ("08:00:00", "08:05:00", RowType::Relief),
+            ("08:05:00", "10:00:00", RowType::Revenue),
+            ("10:00:00", "10:30:00", RowType::Break),
+            ("10:30:00", "11:00:00", RowType::Revenue),
+        ];
+        let mut block = Block::new("B1".to_string());
+        let mut duty = crate::models::Duty::new("D1".to_string());
+        for (start, end, row_type) in rows {
+            block.add_row(make_row(start, end, row_type, None, None));
+            duty.add_row(make_row(start, end, row_type, None, None));
+        }
+
+        // The vehicle keeps working through the relief
+        let pieces = block.pieces_of_work();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].rows.len(), 3);
+        assert_eq!(pieces[0].duration_seconds(), Some(4 * 3600));
+        assert_eq!(pieces[1].start_time_seconds, Some(10 * 3600 + 1800));
+
+        // The driver's work ends at both
+        assert_eq!(duty.pieces_of_work().len(), 3);
+    }
+
+    #[test]
+    fn test_cumulative_distances() {
+        let moving = |row_type, lat: Option<f64>| ScheduleRow {
+            row_type,
+            start_lat: Some(51.5),
+            start_lon: Some(-0.1),
+            end_lat: lat,
+            end_lon: Some(-0.1),
+            ..Default::default()
+        };
+        let mut block = Block::new("B1".to_string());
+        block.add_row(moving(RowType::PullOut, Some(51.51)));
+        block.add_row(moving(RowType::Revenue, None));
+        block.add_row(ScheduleRow {
+            row_type: RowType::Layover,
+            ..Default::default()
+        });
+        block.add_row(moving(RowType::Revenue, Some(51.49)));
+
+        let leg = block.rows[0].distance_meters().unwrap();
+        let distances = block.cumulative_distances();
+        assert_eq!(distances.len(), 4);
+        assert!((distances[0].unwrap() - leg).abs() < 1e-6);
+        assert_eq!(distances[1], None);
+        assert_eq!(distances[2], distances[0]);
+        assert!((distances[3].unwrap() - 2.0 * leg).abs() < 1.0);
+        assert_eq!(block.total_distance_meters(), distances[3].unwrap());
+
+        assert_eq!(Block::new("B2".to_string()).total_distance_meters(), 0.0);
+    }
+
     #[test]
     fn test_block_duration() {
         let mut block = Block::new("B1".to_string());
@@ -285,6 +640,111 @@ mod tests {
         assert_eq!(gaps[0], (0, 1800)); // 30 minutes
     }
 
+    #[test]
+    fn test_layover_time() {
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_row(
+            "08:00:00",
+            "09:00:00",
+            RowType::Revenue,
+            None,
+            None,
+        ));
+        block.add_row(make_row(
+            "09:30:00",
+            "10:30:00",
+            RowType::Revenue,
+            None,
+            None,
+        )); // 30 min gap
+        block.add_row(make_row(
+            "10:30:00",
+            "10:40:00",
+            RowType::Layover,
+            None,
+            None,
+        ));
+        block.add_row(make_row(
+            "10:40:00",
+            "11:00:00",
+            RowType::Deadhead,
+            None,
+            None,
+        ));
+
+        assert_eq!(block.layover_time_seconds(), 2400); // 30 min gap + 10 min layover
+        let summary = block.summary();
+        assert_eq!(summary.layover_time_seconds, 2400);
+        assert_eq!(
+            summary.revenue_time_seconds
+                + summary.deadhead_time_seconds
+                + summary.layover_time_seconds,
+            summary.duration_seconds.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_timeline() {
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_row(
+            "07:45:00",
+            "08:00:00",
+            RowType::PullOut,
+            Some("DEPOT"),
+            Some("A"),
+        ));
+        block.add_row(make_row(
+            "08:00:00",
+            "09:00:00",
+            RowType::Revenue,
+            Some("A"),
+            Some("B"),
+        ));
+        block.add_row(make_row(
+            "09:10:00",
+            "10:00:00",
+            RowType::Revenue,
+            Some("B"),
+            Some("C"),
+        )); // 10 min wait at B
+        block.add_row(make_row(
+            "10:30:00",
+            "11:00:00",
+            RowType::Revenue,
+            Some("D"),
+            Some("E"),
+        )); // 30 min unexplained jump from C to D
+        block.add_row(ScheduleRow {
+            start_time: None,
+            ..make_row("00:00:00", "00:00:00", RowType::Break, None, None)
+        }); // untimed, left out
+
+        let timeline = block.timeline();
+        let kinds: Vec<TimelineSegmentKind> = timeline.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TimelineSegmentKind::Deadhead,
+                TimelineSegmentKind::Revenue,
+                TimelineSegmentKind::Layover,
+                TimelineSegmentKind::Revenue,
+                TimelineSegmentKind::Gap,
+                TimelineSegmentKind::Revenue,
+            ]
+        );
+
+        let layover = &timeline[2];
+        assert_eq!((layover.start_seconds, layover.end_seconds), (32400, 33000));
+        assert_eq!(layover.start_place.as_deref(), Some("B"));
+        assert_eq!(layover.row_index, None);
+
+        let gap = &timeline[4];
+        assert_eq!(gap.duration_seconds(), 1800);
+        assert_eq!(gap.start_place.as_deref(), Some("C"));
+        assert_eq!(gap.end_place.as_deref(), Some("D"));
+        assert_eq!(timeline[5].row_index, Some(3));
+    }
+
     #[test]
     fn test_location_discontinuities() {
         let mut block = Block::new("B1".to_string());
@@ -308,6 +768,50 @@ mod tests {
         assert_eq!(discs[0], 0);
     }
 
+    #[test]
+    fn test_route_sequence_and_compact_summary() {
+        let route_row = |start: &str, end: &str, route: Option<&str>| ScheduleRow {
+            route_short_name: route.map(String::from),
+            ..make_row(start, end, RowType::Revenue, Some("A"), Some("A"))
+        };
+        let mut block = Block::new("B1".to_string());
+        block.add_row(ScheduleRow {
+            depot: Some("D1".to_string()),
+            ..make_row(
+                "07:30:00",
+                "08:00:00",
+                RowType::PullOut,
+                Some("DEPOT"),
+                Some("A"),
+            )
+        });
+        block.add_row(route_row("08:00:00", "09:00:00", Some("12")));
+        block.add_row(route_row("09:00:00", "10:00:00", Some("12")));
+        block.add_row(route_row("10:00:00", "11:00:00", None));
+        block.add_row(route_row("11:00:00", "12:00:00", Some("12")));
+        block.add_row(route_row("12:00:00", "13:00:00", Some("7")));
+        block.add_row(route_row("13:00:00", "14:00:00", Some("12")));
+        block.add_row(make_row(
+            "14:00:00",
+            "14:30:00",
+            RowType::PullIn,
+            Some("A"),
+            Some("DEPOT"),
+        ));
+
+        assert_eq!(block.route_sequence(), vec!["12", "7", "12"]);
+
+        let summary = block.compact_summary();
+        assert_eq!(summary.depot.as_deref(), Some("D1"));
+        assert_eq!(summary.start_time_seconds, Some(27000));
+        assert_eq!(summary.end_time_seconds, Some(52200));
+        assert_eq!(summary.start_place.as_deref(), Some("DEPOT"));
+        assert_eq!(summary.end_place.as_deref(), Some("DEPOT"));
+        assert_eq!(summary.route_sequence, vec!["12", "7", "12"]);
+
+        assert!(Block::new("B2".to_string()).route_sequence().is_empty());
+    }
+
     #[test]
     fn test_revenue_time() {
         let mut block = Block::new("B1".to_string());
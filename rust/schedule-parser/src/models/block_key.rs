@@ -0,0 +1,176 @@
+//! Block key - which row fields identify a vehicle block.
+
+use super::schedule_row::ScheduleRow;
+use serde::{Deserialize, Serialize};
+use transit_core::ParseError;
+
+/// Fields that can form a block key.
+const KEY_FIELDS: [&str; 10] = [
+    "block",
+    "run_number",
+    "depot",
+    "vehicle_class",
+    "vehicle_type",
+    "route_shape_id",
+    "duty_id",
+    "shift_id",
+    "route_short_name",
+    "service_id",
+];
+
+/// The row fields that identify a block, for
+/// [`Schedule::derive_blocks_by`](crate::Schedule::derive_blocks_by).
+///
+/// The default groups rows by `block`. A key of several fields joins their
+/// values with [`SEPARATOR`](Self::SEPARATOR), so [`depot_block`](Self::depot_block)
+/// keys a row in depot `NORTH` and block `12` as `NORTH:12`. A row missing
+/// any key field belongs to no block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockKey {
+    fields: Vec<String>,
+}
+
+impl Default for BlockKey {
+    fn default() -> Self {
+        Self::block()
+    }
+}
+
+impl BlockKey {
+    /// Separator between the field values of a composite key.
+    pub const SEPARATOR: char = ':';
+
+    /// Group rows by `block` (the default).
+    pub fn block() -> Self {
+        Self {
+            fields: vec!["block".to_string()],
+        }
+    }
+
+    /// Group rows by `depot` and `block`, for feeds that reuse block IDs
+    /// across depots.
+    pub fn depot_block() -> Self {
+        Self {
+            fields: vec!["depot".to_string(), "block".to_string()],
+        }
+    }
+
+    /// Group rows by the given fields, in order.
+    ///
+    /// Fields are standard field names such as `vehicle_type`; only the
+    /// identifier fields `block`, `run_number`, `depot`, `vehicle_class`,
+    /// `vehicle_type`, `route_shape_id`, `duty_id`, `shift_id`,
+    /// `route_short_name` and `service_id` can be used. Fails on an empty
+    /// list or any other field.
+    pub fn from_fields<I, S>(fields: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+        if fields.is_empty() {
+            return Err(ParseError::InvalidData(
+                "Block key needs at least one field".to_string(),
+            ));
+        }
+        if let Some(field) = fields.iter().find(|f| !KEY_FIELDS.contains(&f.as_str())) {
+            return Err(ParseError::InvalidData(format!(
+                "Field '{}' can't be used in a block key",
+                field
+            )));
+        }
+        Ok(Self { fields })
+    }
+
+    /// The key fields, in order.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// The block a row belongs to under this key, if it has every key field.
+    pub fn key_for(&self, row: &ScheduleRow) -> Option<String> {
+        let mut key = String::new();
+        for (idx, field) in self.fields.iter().enumerate() {
+            if idx > 0 {
+                key.push(Self::SEPARATOR);
+            }
+            key.push_str(field_value(row, field)?);
+        }
+        Some(key)
+    }
+
+    /// Copy the key fields from `from` to `to`, so `to` joins the same block.
+    pub(crate) fn copy_fields(&self, from: &ScheduleRow, to: &mut ScheduleRow) {
+        for field in &self.fields {
+            let value = field_value(from, field).map(String::from);
+            if let Some(slot) = field_slot(to, field) {
+                *slot = value;
+            }
+        }
+    }
+}
+
+/// A key field's value on a row.
+fn field_value<'a>(row: &'a ScheduleRow, field: &str) -> Option<&'a str> {
+    match field {
+        "block" => row.block.as_deref(),
+        "run_number" => row.run_number.as_deref(),
+        "depot" => row.depot.as_deref(),
+        "vehicle_class" => row.vehicle_class.as_deref(),
+        "vehicle_type" => row.vehicle_type.as_deref(),
+        "route_shape_id" => row.route_shape_id.as_deref(),
+        "duty_id" => row.duty_id.as_deref(),
+        "shift_id" => row.shift_id.as_deref(),
+        "route_short_name" => row.route_short_name.as_deref(),
+        "service_id" => row.service_id.as_deref(),
+        _ => None,
+    }
+}
+
+/// A key field of a row, for writing.
+fn field_slot<'a>(row: &'a mut ScheduleRow, field: &str) -> Option<&'a mut Option<String>> {
+    match field {
+        "block" => Some(&mut row.block),
+        "run_number" => Some(&mut row.run_number),
+        "depot" => Some(&mut row.depot),
+        "vehicle_class" => Some(&mut row.vehicle_class),
+        "vehicle_type" => Some(&mut row.vehicle_type),
+        "route_shape_id" => Some(&mut row.route_shape_id),
+        "duty_id" => Some(&mut row.duty_id),
+        "shift_id" => Some(&mut row.shift_id),
+        "route_short_name" => Some(&mut row.route_short_name),
+        "service_id" => Some(&mut row.service_id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_key() {
+        let row = ScheduleRow {
+            block: Some("12".to_string()),
+            depot: Some("NORTH".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(BlockKey::default().key_for(&row).as_deref(), Some("12"));
+        assert_eq!(
+            BlockKey::depot_block().key_for(&row).as_deref(),
+            Some("NORTH:12")
+        );
+        let by_vehicle = BlockKey::from_fields(["vehicle_type"]).unwrap();
+        assert_eq!(by_vehicle.key_for(&row), None);
+
+        let mut copy = ScheduleRow::default();
+        BlockKey::depot_block().copy_fields(&row, &mut copy);
+        assert_eq!(
+            BlockKey::depot_block().key_for(&copy).as_deref(),
+            Some("NORTH:12")
+        );
+
+        assert!(BlockKey::from_fields(Vec::<String>::new()).is_err());
+        assert!(BlockKey::from_fields(["start_time"]).is_err());
+    }
+}
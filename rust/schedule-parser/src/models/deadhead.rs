@@ -1,6 +1,9 @@
 //! Deadhead model - non-revenue vehicle movements.
 
+use super::schedule_row::{RowType, ScheduleRow};
+use crate::time::seconds_to_time_string;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Type of deadhead movement.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,6 +16,17 @@ pub enum DeadheadType {
     Interlining,
 }
 
+impl DeadheadType {
+    /// Snake-case name of the type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadheadType::PullOut => "pull_out",
+            DeadheadType::PullIn => "pull_in",
+            DeadheadType::Interlining => "interlining",
+        }
+    }
+}
+
 /// A deadhead movement - non-revenue vehicle repositioning.
 ///
 /// Deadheads represent vehicle movements that don't carry passengers:
@@ -178,8 +192,13 @@ impl Deadhead {
         }
     }
 
-    /// Calculate distance using Haversine formula if coordinates are available.
+    /// Distance in meters: [`distance_meters`](Self::distance_meters) if
+    /// set, as estimated by the inferrer's distance model, otherwise the
+    /// straight-line (Haversine) distance if coordinates are available.
     pub fn calculate_distance(&self) -> Option<f64> {
+        if self.distance_meters.is_some() {
+            return self.distance_meters;
+        }
         match (self.from_lat, self.from_lon, self.to_lat, self.to_lon) {
             (Some(lat1), Some(lon1), Some(lat2), Some(lon2)) => {
                 Some(haversine_distance(lat1, lon1, lat2, lon2))
@@ -195,10 +214,37 @@ impl Deadhead {
             DeadheadType::PullOut | DeadheadType::PullIn
         )
     }
+
+    /// Convert to a schedule row so the movement can be added to a schedule.
+    ///
+    /// Interlinings become [`RowType::Deadhead`] rows. The depot end of a
+    /// pull-out or pull-in is also recorded as the row's depot.
+    pub fn to_schedule_row(&self) -> ScheduleRow {
+        let (row_type, depot) = match self.deadhead_type {
+            DeadheadType::PullOut => (RowType::PullOut, Some(self.from_location.clone())),
+            DeadheadType::PullIn => (RowType::PullIn, Some(self.to_location.clone())),
+            DeadheadType::Interlining => (RowType::Deadhead, None),
+        };
+
+        ScheduleRow {
+            block: self.block_id.clone(),
+            start_place: Some(self.from_location.clone()),
+            end_place: Some(self.to_location.clone()),
+            start_time: self.start_time_seconds.map(seconds_to_time_string),
+            end_time: self.end_time_seconds.map(seconds_to_time_string),
+            depot,
+            start_lat: self.from_lat,
+            start_lon: self.from_lon,
+            end_lat: self.to_lat,
+            end_lon: self.to_lon,
+            row_type,
+            ..Default::default()
+        }
+    }
 }
 
 /// Calculate Haversine distance between two coordinates in meters.
-fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     const EARTH_RADIUS_M: f64 = 6_371_000.0;
 
     let lat1_rad = lat1.to_radians();
@@ -224,6 +270,13 @@ pub struct DeadheadInferenceResult {
     pub interlinings: Vec<Deadhead>,
     /// Blocks that couldn't have deadheads inferred (missing info).
     pub incomplete_blocks: Vec<String>,
+    /// Consecutive revenue trips whose interlining couldn't be checked, as
+    /// (block ID, index of the later trip within the block's time-sorted
+    /// rows, reason). The reason is `"missing location"` when a trip lacks
+    /// the place needed to detect a discontinuity, or `"missing time"` when
+    /// the places differ but a trip lacks the time needed to place the
+    /// deadhead.
+    pub skipped_interlinings: Vec<(String, usize, String)>,
 }
 
 impl DeadheadInferenceResult {
@@ -239,6 +292,41 @@ impl DeadheadInferenceResult {
             .chain(self.pull_ins.iter())
             .chain(self.interlinings.iter())
     }
+
+    /// Total duration of all inferred deadheads in seconds, i.e. the
+    /// deadhead time applying them would add. Deadheads without times
+    /// count as zero.
+    pub fn total_added_seconds(&self) -> u32 {
+        self.all_deadheads()
+            .filter_map(|dh| dh.duration_seconds())
+            .sum()
+    }
+
+    /// Total estimated distance of all inferred deadheads in meters.
+    /// Deadheads without a distance estimate count as zero.
+    pub fn total_added_meters(&self) -> f64 {
+        // Start from 0.0, since an empty float sum is -0.0
+        self.all_deadheads()
+            .filter_map(|dh| dh.distance_meters)
+            .fold(0.0, |total, meters| total + meters)
+    }
+
+    /// Added `(seconds, meters)` per block, totalled the same way as
+    /// [`total_added_seconds`](Self::total_added_seconds) and
+    /// [`total_added_meters`](Self::total_added_meters). Deadheads without a
+    /// block are left out.
+    pub fn totals_by_block(&self) -> BTreeMap<String, (u32, f64)> {
+        let mut totals: BTreeMap<String, (u32, f64)> = BTreeMap::new();
+        for dh in self.all_deadheads() {
+            let Some(block_id) = &dh.block_id else {
+                continue;
+            };
+            let entry = totals.entry(block_id.clone()).or_default();
+            entry.0 += dh.duration_seconds().unwrap_or(0);
+            entry.1 += dh.distance_meters.unwrap_or(0.0);
+        }
+        totals
+    }
 }
 
 #[cfg(test)]
@@ -268,6 +356,35 @@ mod tests {
         assert_eq!(dh.to_trip_id, Some("TRIP2".to_string()));
     }
 
+    #[test]
+    fn test_added_totals() {
+        let mut pull_out = Deadhead::pull_out("DEPOT", "A")
+            .with_block("B1")
+            .with_times(21000, 21600);
+        pull_out.distance_meters = Some(3000.0);
+        let mut interlining = Deadhead::interlining("B", "C")
+            .with_block("B1")
+            .with_times(30000, 30300);
+        interlining.distance_meters = Some(1500.0);
+        // No times or distance: counted as zero
+        let pull_in = Deadhead::pull_in("D", "DEPOT").with_block("B2");
+
+        let result = DeadheadInferenceResult {
+            pull_outs: vec![pull_out],
+            pull_ins: vec![pull_in],
+            interlinings: vec![interlining],
+            ..Default::default()
+        };
+        assert_eq!(result.total_added_seconds(), 900);
+        assert_eq!(result.total_added_meters(), 4500.0);
+
+        let by_block = result.totals_by_block();
+        assert_eq!(by_block["B1"], (900, 4500.0));
+        assert_eq!(by_block["B2"], (0, 0.0));
+        let empty = DeadheadInferenceResult::default().total_added_meters();
+        assert!(empty == 0.0 && empty.is_sign_positive());
+    }
+
     #[test]
     fn test_haversine_distance() {
         // NYC to LA is approximately 3,940 km
@@ -283,5 +400,26 @@ mod tests {
         let dist = dh.calculate_distance();
         assert!(dist.is_some());
         assert!(dist.unwrap() > 4000.0 && dist.unwrap() < 6000.0);
+
+        // An estimate from the inferrer's distance model wins
+        let mut dh = dh;
+        dh.distance_meters = Some(7000.0);
+        assert_eq!(dh.calculate_distance(), Some(7000.0));
+    }
+
+    #[test]
+    fn test_to_schedule_row() {
+        let row = Deadhead::pull_in("STOP_C", "DEPOT1")
+            .with_block("B1")
+            .with_times(36000, 36900)
+            .to_schedule_row();
+
+        assert_eq!(row.row_type, RowType::PullIn);
+        assert_eq!(row.block.as_deref(), Some("B1"));
+        assert_eq!(row.start_place.as_deref(), Some("STOP_C"));
+        assert_eq!(row.depot.as_deref(), Some("DEPOT1"));
+        assert_eq!(row.start_time.as_deref(), Some("10:00:00"));
+        assert_eq!(row.end_time.as_deref(), Some("10:15:00"));
+        assert!(row.is_deadhead());
     }
 }
@@ -2,6 +2,7 @@
 
 use super::schedule_row::{RowType, ScheduleRow};
 use super::shift::{Break, Shift};
+use crate::time::parse_time_to_seconds;
 use serde::{Deserialize, Serialize};
 
 /// A driver duty - the work assigned to a single driver for a day.
@@ -124,6 +125,47 @@ impl Duty {
             .sum()
     }
 
+    /// Calculate total time spent on `Layover` rows in seconds.
+    ///
+    /// Unlike [`Block::layover_time_seconds`](crate::models::Block::layover_time_seconds),
+    /// gaps between rows are not counted, since a duty's gaps are often
+    /// unpaid time between pieces of work.
+    pub fn layover_time_seconds(&self) -> u32 {
+        self.rows
+            .iter()
+            .filter(|r| r.is_layover())
+            .filter_map(|r| r.duration_seconds())
+            .sum()
+    }
+
+    /// Paid time in seconds under the given pay rules.
+    ///
+    /// The duty's length, less break and relief time unless breaks are
+    /// paid, and at least the guaranteed minimum. `None` if the duty has no
+    /// valid length.
+    pub fn paid_time_seconds(&self, rules: &PayRules) -> Option<u32> {
+        let duration = self.duration_seconds()?;
+        let unpaid = if rules.paid_breaks {
+            0
+        } else {
+            self.break_time_seconds()
+        };
+        Some(
+            duration
+                .saturating_sub(unpaid)
+                .max(rules.minimum_paid_seconds),
+        )
+    }
+
+    /// Check that every row has a readable start and end time.
+    ///
+    /// Time totals of a duty failing this leave out the rows without times.
+    pub fn has_complete_times(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|r| r.start_time_seconds().is_some() && r.end_time_seconds().is_some())
+    }
+
     /// Get all breaks in this duty.
     pub fn breaks(&self) -> Vec<&ScheduleRow> {
         self.rows
@@ -148,63 +190,91 @@ impl Duty {
         ids
     }
 
+    /// Row start/end times in row order, normalized across midnight.
+    pub(crate) fn normalized_row_times(&self) -> Vec<(Option<u32>, Option<u32>)> {
+        normalized_row_times(&self.rows)
+    }
+
     /// Check if this is a split duty (has a long gap in the middle).
     ///
     /// A split duty has a gap of `min_gap_seconds` or more between work periods.
+    /// Rows are taken in order and times are normalized across midnight.
     pub fn is_split_duty(&self, min_gap_seconds: u32) -> bool {
-        for i in 0..self.rows.len().saturating_sub(1) {
-            if let (Some(end), Some(start)) = (
-                self.rows[i].end_time_seconds(),
-                self.rows[i + 1].start_time_seconds(),
-            ) {
-                if start > end && (start - end) >= min_gap_seconds {
-                    return true;
-                }
-            }
-        }
-        false
+        self.normalized_row_times().windows(2).any(|pair| {
+            matches!(
+                (pair[0].1, pair[1].0),
+                (Some(end), Some(start)) if start > end && (start - end) >= min_gap_seconds
+            )
+        })
     }
 
     /// Get pieces of work (continuous driving segments).
     ///
     /// A piece of work is a continuous sequence of driving (trips + deadheads)
-    /// without any breaks.
+    /// without any breaks. Both `Break` and `Relief` rows end a piece, since
+    /// a relief is where this driver hands the vehicle over. Times are
+    /// normalized across midnight, so a piece running past midnight ends
+    /// after 24:00:00.
+    ///
+    /// See [`Block::pieces_of_work`](crate::models::Block::pieces_of_work)
+    /// for the vehicle's view, where a relief doesn't interrupt the work.
     pub fn pieces_of_work(&self) -> Vec<PieceOfWork> {
-        let mut pieces = Vec::new();
-        let mut current_piece: Option<PieceOfWork> = None;
-
-        for row in &self.rows {
-            if row.is_break_or_relief() {
-                // End current piece
-                if let Some(piece) = current_piece.take() {
-                    pieces.push(piece);
-                }
-            } else {
-                // Add to current piece or start new one
-                match current_piece.as_mut() {
-                    Some(piece) => {
-                        piece.rows.push(row.clone());
-                        if let Some(end) = row.end_time_seconds() {
-                            piece.end_time_seconds = Some(end);
-                        }
-                    }
-                    None => {
-                        current_piece = Some(PieceOfWork {
-                            rows: vec![row.clone()],
-                            start_time_seconds: row.start_time_seconds(),
-                            end_time_seconds: row.end_time_seconds(),
-                        });
-                    }
+        split_into_pieces(&self.rows, ScheduleRow::is_break_or_relief)
+    }
+
+    /// Check that the driver gets a qualifying break often enough.
+    ///
+    /// Break and relief rows and gaps between rows are time off; a run of
+    /// time off lasting `min_break_seconds` or more is a qualifying break.
+    /// Everything between qualifying breaks, including short breaks and
+    /// layovers, counts as working time, and the duty is compliant when no
+    /// such stretch exceeds `max_work_before_break_seconds`. Rows without
+    /// times are ignored, and times are normalized across midnight.
+    pub fn break_compliance(
+        &self,
+        max_work_before_break_seconds: u32,
+        min_break_seconds: u32,
+    ) -> BreakCompliance {
+        let working = self
+            .rows
+            .iter()
+            .zip(self.normalized_row_times())
+            .filter(|(row, _)| !row.is_break_or_relief())
+            .filter_map(|(_, times)| match times {
+                (Some(start), Some(end)) => Some((start, end.max(start))),
+                _ => None,
+            });
+
+        let mut breaks = Vec::new();
+        let mut stretches = Vec::new();
+        let mut current: Option<(u32, u32)> = None;
+        for (start, end) in working {
+            current = match current {
+                Some((stretch_start, stretch_end))
+                    if start > stretch_end && start - stretch_end >= min_break_seconds =>
+                {
+                    breaks.push((stretch_end, start));
+                    stretches.push((stretch_start, stretch_end));
+                    Some((start, end))
                 }
-            }
+                Some((stretch_start, stretch_end)) => Some((stretch_start, stretch_end.max(end))),
+                None => Some((start, end)),
+            };
         }
-
-        // Don't forget the last piece
-        if let Some(piece) = current_piece {
-            pieces.push(piece);
+        stretches.extend(current);
+
+        // The first of equally long stretches is reported
+        let longest_stretch = stretches
+            .into_iter()
+            .rev()
+            .max_by_key(|(start, end)| end - start);
+        let longest_stretch_seconds = longest_stretch.map_or(0, |(start, end)| end - start);
+        BreakCompliance {
+            is_compliant: longest_stretch_seconds <= max_work_before_break_seconds,
+            breaks,
+            longest_stretch,
+            longest_stretch_seconds,
         }
-
-        pieces
     }
 
     /// Convert this duty into a shift (adds break/relief tracking).
@@ -237,16 +307,101 @@ impl Duty {
         DutySummary {
             duty_id: self.duty_id.clone(),
             total_rows: self.rows.len(),
+            start_time_seconds: self.start_time_seconds(),
+            end_time_seconds: self.end_time_seconds(),
             duration_seconds: self.duration_seconds(),
             driving_time_seconds: self.driving_time_seconds(),
             break_time_seconds: self.break_time_seconds(),
+            layover_time_seconds: self.layover_time_seconds(),
             pieces_of_work: self.pieces_of_work().len(),
             blocks_worked: self.block_ids().len(),
         }
     }
 }
 
-/// A piece of work - continuous driving segment within a duty.
+/// Row start/end times in row order, normalized across midnight.
+///
+/// Exports often write post-midnight times as `00:30:00` rather than
+/// `24:30:00`. A row whose end is before its start, or that starts more
+/// than 12 hours before the previous row ended, is taken to be on the
+/// next day, and it and all later rows are shifted by 24 hours.
+pub(super) fn normalized_row_times(rows: &[ScheduleRow]) -> Vec<(Option<u32>, Option<u32>)> {
+    const DAY_SECONDS: u32 = 86_400;
+    const OVERNIGHT_THRESHOLD_SECONDS: u32 = 12 * 3600;
+
+    let mut offset = 0;
+    let mut prev_end: Option<u32> = None;
+
+    rows.iter()
+        .map(|row| {
+            let mut start = row.start_time_seconds().map(|t| t + offset);
+            if let (Some(s), Some(prev)) = (start, prev_end) {
+                if s + OVERNIGHT_THRESHOLD_SECONDS < prev {
+                    offset += DAY_SECONDS;
+                    start = Some(s + DAY_SECONDS);
+                }
+            }
+
+            let mut end = row.end_time_seconds().map(|t| t + offset);
+            if let (Some(s), Some(e)) = (start, end) {
+                if e < s {
+                    offset += DAY_SECONDS;
+                    end = Some(e + DAY_SECONDS);
+                }
+            }
+
+            if end.is_some() {
+                prev_end = end;
+            }
+            (start, end)
+        })
+        .collect()
+}
+
+/// Split rows into pieces of work, ending a piece at every row for which
+/// `ends_piece` is true. Such rows belong to no piece.
+pub(super) fn split_into_pieces(
+    rows: &[ScheduleRow],
+    ends_piece: impl Fn(&ScheduleRow) -> bool,
+) -> Vec<PieceOfWork> {
+    let mut pieces = Vec::new();
+    let mut current_piece: Option<PieceOfWork> = None;
+
+    for (row, (start, end)) in rows.iter().zip(normalized_row_times(rows)) {
+        if ends_piece(row) {
+            // End current piece
+            if let Some(piece) = current_piece.take() {
+                pieces.push(piece);
+            }
+        } else {
+            // Add to current piece or start new one
+            match current_piece.as_mut() {
+                Some(piece) => {
+                    piece.rows.push(row.clone());
+                    if end.is_some() {
+                        piece.end_time_seconds = end;
+                    }
+                }
+                None => {
+                    current_piece = Some(PieceOfWork {
+                        rows: vec![row.clone()],
+                        start_time_seconds: start,
+                        end_time_seconds: end,
+                    });
+                }
+            }
+        }
+    }
+
+    // Don't forget the last piece
+    if let Some(piece) = current_piece {
+        pieces.push(piece);
+    }
+
+    pieces
+}
+
+/// A piece of work - continuous driving segment within a duty or block.
 #[derive(Debug, Clone)]
 pub struct PieceOfWork {
     pub rows: Vec<ScheduleRow>,
@@ -264,40 +419,54 @@ impl PieceOfWork {
     }
 }
 
+/// How a duty's paid time is worked out, for [`Duty::paid_time_seconds`].
+///
+/// The default pays the duty's length less its breaks, with no minimum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayRules {
+    /// Pay break and relief time.
+    pub paid_breaks: bool,
+    /// Minimum paid time per duty in seconds (a guarantee), 0 for none.
+    pub minimum_paid_seconds: u32,
+}
+
+/// Break compliance of a duty, from [`Duty::break_compliance`].
+///
+/// Times are seconds since midnight of the duty's first day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakCompliance {
+    /// Whether no stretch of work exceeds the maximum.
+    pub is_compliant: bool,
+    /// Qualifying breaks as (start, end), in time order.
+    pub breaks: Vec<(u32, u32)>,
+    /// Longest stretch of work without a qualifying break, as (start, end).
+    pub longest_stretch: Option<(u32, u32)>,
+    /// Length of the longest stretch in seconds (0 for a duty without times).
+    pub longest_stretch_seconds: u32,
+}
+
+impl BreakCompliance {
+    /// Whether the duty has any qualifying break.
+    pub fn has_qualifying_break(&self) -> bool {
+        !self.breaks.is_empty()
+    }
+}
+
 /// Summary statistics for a duty.
 #[derive(Debug, Clone)]
 pub struct DutySummary {
     pub duty_id: String,
     pub total_rows: usize,
+    pub start_time_seconds: Option<u32>,
+    pub end_time_seconds: Option<u32>,
     pub duration_seconds: Option<u32>,
     pub driving_time_seconds: u32,
     pub break_time_seconds: u32,
+    pub layover_time_seconds: u32,
     pub pieces_of_work: usize,
     pub blocks_worked: usize,
 }
 
-/// Parse a time string to seconds (same as in schedule_row, but we need it here too).
-fn parse_time_to_seconds(time: &str) -> Option<u32> {
-    if let Ok(secs) = time.parse::<u32>() {
-        return Some(secs);
-    }
-    let parts: Vec<&str> = time.split(':').collect();
-    match parts.len() {
-        3 => {
-            let hours: u32 = parts[0].parse().ok()?;
-            let minutes: u32 = parts[1].parse().ok()?;
-            let seconds: u32 = parts[2].parse().ok()?;
-            Some(hours * 3600 + minutes * 60 + seconds)
-        }
-        2 => {
-            let hours: u32 = parts[0].parse().ok()?;
-            let minutes: u32 = parts[1].parse().ok()?;
-            Some(hours * 3600 + minutes * 60)
-        }
-        _ => None,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,6 +516,33 @@ mod tests {
         assert_eq!(pieces.len(), 2);
     }
 
+    #[test]
+    fn test_break_compliance() {
+        // Five hours of work before the first qualifying break
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("06:00:00", "08:00:00", RowType::Revenue));
+        duty.add_row(make_row("08:00:00", "08:15:00", RowType::Break));
+        duty.add_row(make_row("08:15:00", "11:00:00", RowType::Revenue));
+        duty.add_row(make_row("11:00:00", "11:30:00", RowType::Break));
+        duty.add_row(make_row("11:45:00", "14:00:00", RowType::Revenue));
+
+        let compliance = duty.break_compliance(4 * 3600 + 1800, 1800);
+        assert!(!compliance.is_compliant);
+        assert_eq!(compliance.breaks, vec![(11 * 3600, 11 * 3600 + 2700)]);
+        assert_eq!(compliance.longest_stretch, Some((6 * 3600, 11 * 3600)));
+        assert_eq!(compliance.longest_stretch_seconds, 5 * 3600);
+
+        // The 15-minute break qualifies under a looser rule
+        let compliance = duty.break_compliance(4 * 3600 + 1800, 900);
+        assert!(compliance.is_compliant);
+        assert_eq!(compliance.breaks.len(), 2);
+        assert_eq!(compliance.longest_stretch_seconds, 3 * 3600 - 900);
+
+        let compliance = Duty::new("D2".to_string()).break_compliance(3600, 900);
+        assert!(compliance.is_compliant && !compliance.has_qualifying_break());
+        assert_eq!(compliance.longest_stretch, None);
+    }
+
     #[test]
     fn test_break_time() {
         let mut duty = Duty::new("D1".to_string());
@@ -357,4 +553,67 @@ mod tests {
 
         assert_eq!(duty.break_time_seconds(), 2700); // 45 minutes
     }
+
+    #[test]
+    fn test_layover_time() {
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("06:00:00", "07:00:00", RowType::Revenue));
+        duty.add_row(make_row("07:00:00", "07:10:00", RowType::Layover)); // 10 min
+        duty.add_row(make_row("07:10:00", "08:00:00", RowType::Revenue));
+        duty.add_row(make_row("08:00:00", "08:30:00", RowType::Break));
+        // Unfilled 30-minute gap, not counted as layover
+        duty.add_row(make_row("09:00:00", "10:00:00", RowType::Revenue));
+
+        assert!(duty.rows[1].is_layover());
+        assert!(!duty.rows[1].is_revenue() && !duty.rows[1].is_deadhead());
+        assert_eq!(duty.layover_time_seconds(), 600);
+
+        let summary = duty.summary();
+        assert_eq!(summary.layover_time_seconds, 600);
+        assert_eq!(summary.driving_time_seconds, 3 * 3600 - 600);
+        assert_eq!(summary.break_time_seconds, 1800);
+        // Categories plus the unfilled gap make up the whole duty
+        assert_eq!(
+            summary.driving_time_seconds
+                + summary.break_time_seconds
+                + summary.layover_time_seconds
+                + 1800,
+            summary.duration_seconds.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_overnight_duty() {
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("22:00:00", "23:30:00", RowType::Revenue));
+        duty.add_row(make_row("23:30:00", "00:30:00", RowType::Revenue));
+        duty.add_row(make_row("00:30:00", "01:00:00", RowType::Break));
+        duty.add_row(make_row("01:00:00", "02:00:00", RowType::Revenue));
+
+        let pieces = duty.pieces_of_work();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].start_time_seconds, Some(22 * 3600));
+        assert_eq!(pieces[0].end_time_seconds, Some(24 * 3600 + 1800));
+        assert_eq!(pieces[0].duration_seconds(), Some(2 * 3600 + 1800));
+        assert_eq!(pieces[1].start_time_seconds, Some(25 * 3600));
+        assert_eq!(pieces[1].duration_seconds(), Some(3600));
+
+        // Rows are back to back across midnight, so there is no gap at all
+        assert!(!duty.is_split_duty(60));
+    }
+
+    #[test]
+    fn test_overnight_gap_between_rows() {
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("21:00:00", "23:30:00", RowType::Revenue));
+        duty.add_row(make_row("00:30:00", "02:00:00", RowType::Revenue));
+
+        // One-hour gap across midnight
+        assert!(duty.is_split_duty(3600));
+        assert!(!duty.is_split_duty(3601));
+
+        let pieces = duty.pieces_of_work();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].end_time_seconds, Some(26 * 3600));
+    }
 }
@@ -1,15 +1,22 @@
 //! Schedule data models.
 
 pub mod block;
+pub mod block_key;
 pub mod deadhead;
 pub mod duty;
 pub mod schedule;
 pub mod schedule_row;
 pub mod shift;
 
-pub use block::{Block, BlockSummary};
-pub use deadhead::{Deadhead, DeadheadInferenceResult, DeadheadType};
-pub use duty::{Duty, DutySummary, PieceOfWork};
-pub use schedule::{Schedule, ScheduleMetadata, ScheduleSummary};
-pub use schedule_row::{seconds_to_time_string, RowType, ScheduleRow};
+pub use block::{Block, BlockSummary, CompactBlockSummary, TimelineSegment, TimelineSegmentKind};
+pub use block_key::BlockKey;
+pub use deadhead::{haversine_distance, Deadhead, DeadheadInferenceResult, DeadheadType};
+pub use duty::{BreakCompliance, Duty, DutySummary, PayRules, PieceOfWork};
+pub use schedule::{
+    BlockCoverage, DeadheadStats, DutyTotal, ExpandedStopTime, ExpandedStopTimes, FrequencyBand,
+    ParseWarning, RouteSummary, Schedule, ScheduleMetadata, ScheduleSummary,
+};
+pub use schedule_row::{RowType, ScheduleRow};
 pub use shift::{Break, Shift, ShiftSummary};
+
+pub use crate::time::seconds_to_time_string;
@@ -1,10 +1,24 @@
 //! Schedule container - holds all schedule rows and derived data.
 
-use super::block::Block;
-use super::duty::Duty;
-use super::schedule_row::ScheduleRow;
+use super::block::{Block, BlockSummary};
+use super::block_key::BlockKey;
+use super::duty::{Duty, DutySummary, PayRules};
+use super::schedule_row::{RowType, ScheduleRow};
+use crate::anonymize::{anonymize_rows, AnonymizationKey, AnonymizeOptions};
+use crate::blocking::{assign_blocks, BlockingConfig, BlockingReport};
+use crate::fix::{apply_safe_fixes, FixReport};
+use crate::hash::Fnv1a;
+use crate::normalize::{normalize_ids, IdMapping, NormalizeIdsOptions};
+use crate::time::seconds_to_time_string;
+use crate::validation::lint::lint_rows;
+use crate::validation::validator::{ValidationResult, ValidationWarning};
+use crate::validation::{ValidationConfig, Validator};
+use gtfs_parser::GtfsFeed;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use transit_core::{ParseError, StopTime};
 
 /// A complete schedule containing all rows and derived rostering data.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -20,12 +34,20 @@ pub struct Schedule {
     #[serde(skip)]
     duties: Option<HashMap<String, Duty>>,
 
+    /// Index of the first row for each trip ID.
+    #[serde(skip)]
+    trip_index: Option<HashMap<String, usize>>,
+
+    /// Fields that identify a row's block.
+    #[serde(skip)]
+    block_key: BlockKey,
+
     /// Metadata about the schedule.
     pub metadata: ScheduleMetadata,
 }
 
 /// Metadata about a schedule file.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ScheduleMetadata {
     /// Source filename.
     pub source_file: Option<String>,
@@ -44,6 +66,41 @@ pub struct ScheduleMetadata {
 
     /// Column mapping used during import.
     pub column_mapping: Option<HashMap<String, String>>,
+
+    /// Lines read before the CSV header, when
+    /// [`ReadOptions::capture_preamble`](crate::ReadOptions::capture_preamble)
+    /// is set.
+    #[serde(default)]
+    pub preamble: Vec<String>,
+
+    /// Values the reader couldn't parse and left empty, when
+    /// [`ReadOptions::strict_coordinates`](crate::ReadOptions::strict_coordinates)
+    /// is set.
+    #[serde(default)]
+    pub parse_warnings: Vec<ParseWarning>,
+
+    /// Blocks whose rows name more than one depot, as (block ID, depots),
+    /// found when blocks were last derived. Usually two operators' feeds
+    /// reusing the same block ID; see
+    /// [`Schedule::namespace_blocks_by_depot`].
+    #[serde(default)]
+    pub block_depot_conflicts: Vec<(String, Vec<String>)>,
+
+    /// The block or duty this schedule was extracted from, as `block B1` or
+    /// `duty D1`; see [`Schedule::extract_block`].
+    #[serde(default)]
+    pub extracted: Option<String>,
+}
+
+/// A field value the reader couldn't parse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// 0-based index of the row in [`Schedule::rows`].
+    pub row_index: usize,
+    /// Standard field name, e.g. `start_lat`.
+    pub field: String,
+    /// The value as read.
+    pub value: String,
 }
 
 impl Schedule {
@@ -58,6 +115,8 @@ impl Schedule {
             rows,
             blocks: None,
             duties: None,
+            trip_index: None,
+            block_key: BlockKey::default(),
             metadata: ScheduleMetadata::default(),
         }
     }
@@ -86,7 +145,7 @@ impl Schedule {
     pub fn rows_for_block(&self, block_id: &str) -> Vec<&ScheduleRow> {
         self.rows
             .iter()
-            .filter(|r| r.block.as_deref() == Some(block_id))
+            .filter(|r| self.block_key.key_for(r).as_deref() == Some(block_id))
             .collect()
     }
 
@@ -98,9 +157,13 @@ impl Schedule {
             .collect()
     }
 
-    /// Get unique block IDs.
+    /// Get unique block IDs, under the [block key](Self::block_key).
     pub fn block_ids(&self) -> Vec<String> {
-        let mut ids: Vec<String> = self.rows.iter().filter_map(|r| r.block.clone()).collect();
+        let mut ids: Vec<String> = self
+            .rows
+            .iter()
+            .filter_map(|r| self.block_key.key_for(r))
+            .collect();
         ids.sort();
         ids.dedup();
         ids
@@ -126,6 +189,126 @@ impl Schedule {
         depots
     }
 
+    /// Places ranked by how likely they are to be a depot, as (place,
+    /// count) pairs.
+    ///
+    /// This is a heuristic for schedules without depot data: vehicles
+    /// usually start and end their blocks at the depot, so each block's
+    /// first start place and last end place are counted, and the most
+    /// frequent come first (ties by place name). The top candidate is only
+    /// the most common block endpoint, which may be a terminus rather than a
+    /// depot; check it before relying on it.
+    pub fn infer_likely_depots(&self) -> Vec<(String, usize)> {
+        let blocks = self.blocks_ref();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for block in blocks.values() {
+            let start = block.first_row().and_then(|r| r.start_place.as_deref());
+            let end = block.last_row().and_then(|r| r.end_place.as_deref());
+            for place in start.into_iter().chain(end) {
+                *counts.entry(place).or_insert(0) += 1;
+            }
+        }
+
+        let mut ranked: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(place, count)| (place.to_string(), count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+    }
+
+    /// Get unique service IDs.
+    pub fn service_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .rows
+            .iter()
+            .filter_map(|r| r.service_id.clone())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Copy the rows belonging to one service into a new schedule.
+    ///
+    /// Rows keep their relative order, and the metadata is carried over.
+    pub fn filter_service(&self, service_id: &str) -> Schedule {
+        let rows = self
+            .rows
+            .iter()
+            .filter(|r| r.service_id.as_deref() == Some(service_id))
+            .cloned()
+            .collect();
+        self.with_rows(rows)
+    }
+
+    /// Copy the rows of one block into a new schedule, to export or validate
+    /// a single vehicle on its own.
+    ///
+    /// Rows keep their relative order. The metadata is carried over, with
+    /// [`extracted`](ScheduleMetadata::extracted) set to `block {id}`.
+    /// Returns `None` if no row belongs to the block.
+    pub fn extract_block(&self, block_id: &str) -> Option<Schedule> {
+        let rows: Vec<ScheduleRow> = self.rows_for_block(block_id).into_iter().cloned().collect();
+        self.extracted(rows, format!("block {}", block_id))
+    }
+
+    /// Copy the rows of one duty into a new schedule, to export or validate
+    /// a single driver's work on its own.
+    ///
+    /// Rows are matched by `duty_id`, or `run_number` where there is no duty
+    /// ID, as in [`duties`](Self::duties). Otherwise as
+    /// [`extract_block`](Self::extract_block), with `extracted` set to
+    /// `duty {id}`.
+    pub fn extract_duty(&self, duty_id: &str) -> Option<Schedule> {
+        let rows: Vec<ScheduleRow> = self
+            .rows
+            .iter()
+            .filter(|r| r.duty_id.as_deref().or(r.run_number.as_deref()) == Some(duty_id))
+            .cloned()
+            .collect();
+        self.extracted(rows, format!("duty {}", duty_id))
+    }
+
+    /// A new schedule of `rows` noting where they came from, or `None` if empty.
+    fn extracted(&self, rows: Vec<ScheduleRow>, extracted: String) -> Option<Schedule> {
+        if rows.is_empty() {
+            return None;
+        }
+        let mut schedule = self.with_rows(rows);
+        schedule.metadata.extracted = Some(extracted);
+        Some(schedule)
+    }
+
+    /// A new schedule of `rows`, with this schedule's block key and metadata.
+    fn with_rows(&self, rows: Vec<ScheduleRow>) -> Schedule {
+        Schedule {
+            block_key: self.block_key.clone(),
+            metadata: self.metadata.clone(),
+            ..Schedule::from_rows(rows)
+        }
+    }
+
+    /// Split the rows into one schedule per service.
+    ///
+    /// Segments are ordered by service ID, with rows that have no service
+    /// (`None`) first. A schedule without any service IDs yields a single
+    /// `None` segment holding every row.
+    pub fn split_by_service(&self) -> Vec<(Option<String>, Schedule)> {
+        let mut groups: BTreeMap<Option<&str>, Vec<ScheduleRow>> = BTreeMap::new();
+        for row in &self.rows {
+            groups
+                .entry(row.service_id.as_deref())
+                .or_default()
+                .push(row.clone());
+        }
+
+        groups
+            .into_iter()
+            .map(|(service_id, rows)| (service_id.map(str::to_string), self.with_rows(rows)))
+            .collect()
+    }
+
     /// Get unique trip IDs (revenue trips only).
     pub fn trip_ids(&self) -> Vec<String> {
         let mut ids: Vec<String> = self
@@ -139,17 +322,119 @@ impl Schedule {
         ids
     }
 
+    /// Map each trip ID to the index of its row in [`rows`](Self::rows)
+    /// (built if not already done).
+    ///
+    /// Rows of any type with a non-empty `trip_id` are indexed. If a trip ID
+    /// appears on several rows, it maps to the first and the others are not
+    /// reachable through the index (see
+    /// [`duplicate_row_indices`](Self::duplicate_row_indices) for exact
+    /// duplicate rows).
+    pub fn index_by_trip_id(&mut self) -> &HashMap<String, usize> {
+        self.trip_index.get_or_insert_with(|| {
+            let mut index = HashMap::new();
+            for (idx, row) in self.rows.iter().enumerate() {
+                if let Some(trip_id) = row.trip_id.as_deref().filter(|t| !t.is_empty()) {
+                    index.entry(trip_id.to_string()).or_insert(idx);
+                }
+            }
+            index
+        })
+    }
+
+    /// Get the row for a trip ID, using the cached index.
+    ///
+    /// Returns the first row if the trip ID is duplicated.
+    pub fn row_for_trip(&mut self, trip_id: &str) -> Option<&ScheduleRow> {
+        let idx = *self.index_by_trip_id().get(trip_id)?;
+        self.rows.get(idx)
+    }
+
     /// Derive blocks from schedule rows.
     ///
     /// Groups rows by block ID and creates Block objects.
+    ///
+    /// Blocks whose rows name more than one depot are recorded in
+    /// [`ScheduleMetadata::block_depot_conflicts`].
     pub fn derive_blocks(&mut self) {
+        let blocks = self.build_blocks();
+        self.metadata.block_depot_conflicts = depot_conflicts(&blocks);
+        self.blocks = Some(blocks);
+    }
+
+    /// Prefix the block ID of rows in blocks that span several depots with
+    /// their depot, e.g. `B1` becomes `D1:B1` and `D2:B1`.
+    ///
+    /// Works on the `block` field, so it applies whatever the
+    /// [block key](Self::block_key). Rows without a depot keep their block
+    /// ID. Returns the number of block IDs that were split.
+    pub fn namespace_blocks_by_depot(&mut self) -> usize {
+        let mut depots: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+        for row in &self.rows {
+            if let (Some(block_id), Some(depot)) = (row.block.as_deref(), row.depot.as_deref()) {
+                depots.entry(block_id).or_default().insert(depot);
+            }
+        }
+        let conflicting: HashSet<String> = depots
+            .into_iter()
+            .filter(|(_, depots)| depots.len() > 1)
+            .map(|(block_id, _)| block_id.to_string())
+            .collect();
+        if conflicting.is_empty() {
+            return 0;
+        }
+
+        for row in &mut self.rows {
+            if let (Some(block_id), Some(depot)) = (&row.block, &row.depot) {
+                if conflicting.contains(block_id) {
+                    row.block = Some(format!("{}{}{}", depot, BlockKey::SEPARATOR, block_id));
+                }
+            }
+        }
+        self.invalidate_caches();
+        self.metadata.block_depot_conflicts.clear();
+        conflicting.len()
+    }
+
+    /// Derive blocks grouping rows by `key` instead of the `block` field.
+    ///
+    /// The key is kept: block IDs, derived blocks, deadhead inference and
+    /// validation all use it from now on, and block IDs are the key values,
+    /// e.g. `NORTH:12` for [`BlockKey::depot_block`]. Row fields are not
+    /// changed. Pass [`BlockKey::block`] to go back to the default.
+    pub fn derive_blocks_by(&mut self, key: BlockKey) {
+        self.block_key = key;
+        self.invalidate_caches();
+        self.derive_blocks();
+    }
+
+    /// The fields that identify a row's block, `block` unless set with
+    /// [`derive_blocks_by`](Self::derive_blocks_by).
+    pub fn block_key(&self) -> &BlockKey {
+        &self.block_key
+    }
+
+    /// Derived blocks without touching the cache.
+    ///
+    /// Borrows the cached blocks if they have already been derived, and
+    /// otherwise builds them into a fresh map, so callers holding only a
+    /// `&Schedule` can work with blocks.
+    pub fn blocks_ref(&self) -> Cow<'_, HashMap<String, Block>> {
+        match &self.blocks {
+            Some(blocks) => Cow::Borrowed(blocks),
+            None => Cow::Owned(self.build_blocks()),
+        }
+    }
+
+    /// Group rows by block ID into time-sorted blocks.
+    fn build_blocks(&self) -> HashMap<String, Block> {
         let mut blocks: HashMap<String, Block> = HashMap::new();
 
         for row in &self.rows {
-            if let Some(block_id) = &row.block {
+            if let Some(block_id) = self.block_key.key_for(row) {
                 blocks
                     .entry(block_id.clone())
-                    .or_insert_with(|| Block::new(block_id.clone()))
+                    .or_insert_with(|| Block::new(block_id))
                     .add_row(row.clone());
             }
         }
@@ -159,10 +444,13 @@ impl Schedule {
             block.sort_rows_by_time();
         }
 
-        self.blocks = Some(blocks);
+        blocks
     }
 
     /// Get derived blocks (derives if not already done).
+    ///
+    /// The map's iteration order is arbitrary and may change between runs;
+    /// use [`blocks_sorted`](Self::blocks_sorted) for a stable order.
     pub fn blocks(&mut self) -> &HashMap<String, Block> {
         if self.blocks.is_none() {
             self.derive_blocks();
@@ -170,6 +458,16 @@ impl Schedule {
         self.blocks.as_ref().unwrap()
     }
 
+    /// Derived blocks as (block ID, block) pairs, sorted by block ID.
+    ///
+    /// The order is the same on every run, unlike iterating
+    /// [`blocks`](Self::blocks).
+    pub fn blocks_sorted(&mut self) -> Vec<(&String, &Block)> {
+        let mut blocks: Vec<(&String, &Block)> = self.blocks().iter().collect();
+        blocks.sort_by(|a, b| a.0.cmp(b.0));
+        blocks
+    }
+
     /// Get a specific block by ID.
     pub fn get_block(&mut self, block_id: &str) -> Option<&Block> {
         self.blocks().get(block_id)
@@ -202,6 +500,9 @@ impl Schedule {
     }
 
     /// Get derived duties (derives if not already done).
+    ///
+    /// The map's iteration order is arbitrary and may change between runs;
+    /// use [`duties_sorted`](Self::duties_sorted) for a stable order.
     pub fn duties(&mut self) -> &HashMap<String, Duty> {
         if self.duties.is_none() {
             self.derive_duties();
@@ -209,96 +510,2361 @@ impl Schedule {
         self.duties.as_ref().unwrap()
     }
 
+    /// Derived duties as (duty ID, duty) pairs, sorted by duty ID.
+    ///
+    /// The order is the same on every run, unlike iterating
+    /// [`duties`](Self::duties).
+    pub fn duties_sorted(&mut self) -> Vec<(&String, &Duty)> {
+        let mut duties: Vec<(&String, &Duty)> = self.duties().iter().collect();
+        duties.sort_by(|a, b| a.0.cmp(b.0));
+        duties
+    }
+
     /// Get a specific duty by ID.
     pub fn get_duty(&mut self, duty_id: &str) -> Option<&Duty> {
         self.duties().get(duty_id)
     }
 
+    /// One summary per block, sorted by block ID.
+    ///
+    /// Write it out with [`CsvExporter::export_block_kpis`](crate::CsvExporter::export_block_kpis).
+    pub fn block_kpi_table(&mut self) -> Vec<BlockSummary> {
+        self.blocks_sorted()
+            .into_iter()
+            .map(|(_, block)| block.summary())
+            .collect()
+    }
+
+    /// One summary per duty, sorted by duty ID.
+    ///
+    /// Write it out with [`CsvExporter::export_duty_kpis`](crate::CsvExporter::export_duty_kpis).
+    pub fn duty_kpi_table(&mut self) -> Vec<DutySummary> {
+        self.duties_sorted()
+            .into_iter()
+            .map(|(_, duty)| duty.summary())
+            .collect()
+    }
+
+    /// Driving time (revenue and deadhead rows) summed over all duties.
+    ///
+    /// Duties with a row missing its start or end time are left out and
+    /// counted in [`DutyTotal::duties_excluded`], as for the other duty
+    /// totals.
+    pub fn total_driving_seconds(&mut self) -> DutyTotal {
+        self.total_over_duties(|duty| Some(duty.driving_time_seconds()))
+    }
+
+    /// Break and relief time summed over all duties.
+    pub fn total_break_seconds(&mut self) -> DutyTotal {
+        self.total_over_duties(|duty| Some(duty.break_time_seconds()))
+    }
+
+    /// Paid time under `rules` summed over all duties.
+    ///
+    /// Duties without a valid length are also left out.
+    pub fn total_paid_seconds(&mut self, rules: &PayRules) -> DutyTotal {
+        self.total_over_duties(|duty| duty.paid_time_seconds(rules))
+    }
+
+    /// Sum `seconds` over the duties with complete times.
+    fn total_over_duties(&mut self, seconds: impl Fn(&Duty) -> Option<u32>) -> DutyTotal {
+        let mut total = DutyTotal::default();
+        for duty in self.duties().values() {
+            match seconds(duty).filter(|_| duty.has_complete_times()) {
+                Some(value) => {
+                    total.seconds += value;
+                    total.duties_counted += 1;
+                }
+                None => total.duties_excluded += 1,
+            }
+        }
+        total
+    }
+
+    /// Connections between consecutive revenue trips within each block, as
+    /// `(from_trip, to_trip, gap_seconds, bridged)`.
+    ///
+    /// Blocks are taken in ID order and trips (rows of type
+    /// [`RowType::Revenue`]) in time order. A trip without an ID is keyed
+    /// `block:index`, its position in the block's time-sorted rows.
+    /// `gap_seconds` is `None` when either time is missing or the trips
+    /// overlap, and `bridged` is true when an explicit deadhead row
+    /// (pull-out, pull-in or deadhead) lies between the trips.
+    pub fn interlining_edges(&mut self) -> Vec<(String, String, Option<u32>, bool)> {
+        let blocks = self.blocks();
+        let mut block_ids: Vec<&String> = blocks.keys().collect();
+        block_ids.sort();
+
+        let mut edges = Vec::new();
+        for block_id in block_ids {
+            let block = &blocks[block_id];
+            let trip_key = |idx: usize| match block.rows[idx].trip_id.as_deref() {
+                Some(trip_id) if !trip_id.is_empty() => trip_id.to_string(),
+                _ => format!("{}:{}", block_id, idx),
+            };
+
+            let trips = block.consecutive_rows_where(|r| r.row_type == RowType::Revenue);
+            for (prev_idx, next_idx) in trips {
+                let prev = &block.rows[prev_idx];
+                let next = &block.rows[next_idx];
+                let gap = match (prev.end_time_seconds(), next.start_time_seconds()) {
+                    (Some(end), Some(start)) => start.checked_sub(end),
+                    _ => None,
+                };
+                let bridged = block.rows[prev_idx + 1..next_idx]
+                    .iter()
+                    .any(ScheduleRow::is_deadhead);
+                edges.push((trip_key(prev_idx), trip_key(next_idx), gap, bridged));
+            }
+        }
+        edges
+    }
+
+    /// How well each block is covered by driver duties, sorted by block ID.
+    ///
+    /// A block needs a driver whenever it has a row other than a break or
+    /// relief. A duty covers the time of its rows that belong to the block,
+    /// so vehicle time with no duty row on that block is uncovered: a vehicle
+    /// running with no driver assigned. Rows without both times are left
+    /// out, and an end time before the start is taken as the next day.
+    pub fn block_coverage(&mut self) -> Vec<BlockCoverage> {
+        self.blocks();
+        self.duties();
+        let blocks = self.blocks.as_ref().expect("blocks were just derived");
+        let duties = self.duties.as_ref().expect("duties were just derived");
+
+        // Duty time and covering duties, by block ID
+        let mut duty_time: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+        let mut duty_ids: HashMap<String, BTreeSet<&str>> = HashMap::new();
+        for duty in duties.values() {
+            for row in &duty.rows {
+                if let (Some(block), Some(interval)) =
+                    (self.block_key.key_for(row), row_interval(row))
+                {
+                    duty_time.entry(block.clone()).or_default().push(interval);
+                    duty_ids
+                        .entry(block)
+                        .or_default()
+                        .insert(duty.duty_id.as_str());
+                }
+            }
+        }
+
+        let mut report: Vec<BlockCoverage> = blocks
+            .values()
+            .map(|block| {
+                let block_id = block.block_id.as_str();
+                let covered = merge_intervals(duty_time.remove(block_id).unwrap_or_default());
+                let vehicle_time = merge_intervals(
+                    block
+                        .rows
+                        .iter()
+                        .filter(|r| !r.is_break_or_relief())
+                        .filter_map(row_interval)
+                        .collect(),
+                );
+                let uncovered = vehicle_time
+                    .iter()
+                    .flat_map(|&interval| subtract_intervals(interval, &covered))
+                    .collect();
+                BlockCoverage {
+                    block_id: block.block_id.clone(),
+                    covered,
+                    uncovered,
+                    duty_ids: duty_ids
+                        .remove(block_id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| a.block_id.cmp(&b.block_id));
+        report
+    }
+
+    /// Hash of every field of every row, for detecting whether a schedule
+    /// has changed.
+    ///
+    /// The hash is stable across runs and platforms for the same rows, so it
+    /// can be stored. It depends on row order; sort the schedule first (e.g.
+    /// with [`sort_by_block_and_time`](Self::sort_by_block_and_time)) if
+    /// reordered rows should hash the same. Metadata is not included.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for row in &self.rows {
+            // Serializing plain rows into an in-memory hasher can't fail
+            serde_json::to_writer(&mut hasher, row).expect("schedule row serializes");
+            hasher.update(b"\n");
+        }
+        hasher.finish()
+    }
+
+    /// Whether both schedules hold the same rows, in any order.
+    ///
+    /// Rows are compared as multisets, so a row repeated twice must be
+    /// repeated in both. Metadata is ignored; compare
+    /// [`metadata`](Self::metadata) as well if it matters.
+    pub fn equivalent_to(&self, other: &Schedule) -> bool {
+        if self.rows.len() != other.rows.len() {
+            return false;
+        }
+        // Serialized rows give a total order over every field, floats included
+        let canonical = |schedule: &Schedule| {
+            let mut rows: Vec<String> = schedule
+                .rows
+                .iter()
+                .map(|row| serde_json::to_string(row).expect("schedule row serializes"))
+                .collect();
+            rows.sort_unstable();
+            rows
+        };
+        canonical(self) == canonical(other)
+    }
+
     /// Add a row to the schedule.
     pub fn add_row(&mut self, row: ScheduleRow) {
         self.rows.push(row);
-        // Invalidate caches
+        self.invalidate_caches();
+    }
+
+    /// Add several rows to the schedule, in order.
+    pub fn extend_rows(&mut self, rows: impl IntoIterator<Item = ScheduleRow>) {
+        self.rows.extend(rows);
+        self.invalidate_caches();
+    }
+
+    /// Drop derived blocks, duties and the trip index so they are rebuilt
+    /// from the rows.
+    pub(crate) fn invalidate_caches(&mut self) {
         self.blocks = None;
         self.duties = None;
+        self.trip_index = None;
     }
 
-    /// Get summary statistics about the schedule.
-    pub fn summary(&self) -> ScheduleSummary {
-        let revenue_count = self.rows.iter().filter(|r| r.is_revenue()).count();
-        let deadhead_count = self.rows.iter().filter(|r| r.is_deadhead()).count();
-        let break_count = self.rows.iter().filter(|r| r.is_break_or_relief()).count();
+    /// Sort rows by start time.
+    ///
+    /// The sort is stable; rows without a start time sort as midnight, the
+    /// same as within derived blocks.
+    pub fn sort_by_time(&mut self) {
+        self.rows
+            .sort_by_key(|row| row.start_time_seconds().unwrap_or(0));
+        self.invalidate_caches();
+    }
 
-        ScheduleSummary {
-            total_rows: self.rows.len(),
-            revenue_trips: revenue_count,
-            deadheads: deadhead_count,
-            breaks_and_reliefs: break_count,
-            unique_blocks: self.block_ids().len(),
-            unique_runs: self.run_numbers().len(),
-            unique_depots: self.depots().len(),
-        }
+    /// Sort rows by block ID under the [block key](Self::block_key), then
+    /// by start time within each block.
+    ///
+    /// The sort is stable. Rows without a block are placed last.
+    pub fn sort_by_block_and_time(&mut self) {
+        let mut keyed: Vec<(Option<String>, ScheduleRow)> = std::mem::take(&mut self.rows)
+            .into_iter()
+            .map(|row| (self.block_key.key_for(&row), row))
+            .collect();
+        keyed.sort_by(|(a_block, a), (b_block, b)| block_and_time_order(a_block, a, b_block, b));
+        self.rows = keyed.into_iter().map(|(_, row)| row).collect();
+        self.invalidate_caches();
     }
-}
 
-/// Summary statistics for a schedule.
-#[derive(Debug, Clone, Default)]
-pub struct ScheduleSummary {
-    pub total_rows: usize,
-    pub revenue_trips: usize,
-    pub deadheads: usize,
-    pub breaks_and_reliefs: usize,
-    pub unique_blocks: usize,
-    pub unique_runs: usize,
-    pub unique_depots: usize,
-}
+    /// Number of rows [`sort_by_block_and_time`](Self::sort_by_block_and_time)
+    /// would move.
+    pub(crate) fn rows_moved_by_sort(&self) -> usize {
+        let blocks: Vec<Option<String>> = self
+            .rows
+            .iter()
+            .map(|r| self.block_key.key_for(r))
+            .collect();
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        order.sort_by(|&a, &b| {
+            block_and_time_order(&blocks[a], &self.rows[a], &blocks[b], &self.rows[b])
+        });
+        order
+            .iter()
+            .enumerate()
+            .filter(|&(pos, &idx)| pos != idx)
+            .count()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::schedule_row::RowType;
+    /// Replace run numbers, duty IDs, shift IDs and optionally block IDs and
+    /// coordinates so the schedule can be shared without identifying staff or
+    /// vehicles.
+    ///
+    /// Equal values get equal replacements, so blocks, duties and shifts keep
+    /// their structure. Returns the key for mapping results back.
+    pub fn anonymize(&mut self, opts: AnonymizeOptions) -> AnonymizationKey {
+        let key = anonymize_rows(&mut self.rows, &opts);
+        self.invalidate_caches();
+        key
+    }
 
-    fn sample_row(block: &str, trip_id: Option<&str>, start: &str) -> ScheduleRow {
-        ScheduleRow {
-            block: Some(block.to_string()),
-            trip_id: trip_id.map(|s| s.to_string()),
-            start_time: Some(start.to_string()),
-            row_type: if trip_id.is_some() {
-                RowType::Revenue
-            } else {
-                RowType::Deadhead
-            },
-            ..Default::default()
+    /// Renumber block IDs and run numbers into one scheme, such as `B0001`
+    /// and `R0001`.
+    ///
+    /// Values are numbered in order of first appearance, and the same
+    /// original always gets the same new ID, so blocks and runs keep their
+    /// structure. Duty IDs equal to an original run number (as when duties
+    /// are keyed by run) are renamed with it; new run numbers skip any other
+    /// duty ID so duties stay distinct. Returns the original -> new mapping.
+    pub fn normalize_ids(&mut self, opts: NormalizeIdsOptions) -> IdMapping {
+        let mapping = normalize_ids(&mut self.rows, &opts);
+        self.invalidate_caches();
+        mapping
+    }
+
+    /// Chain revenue trips that have no block into generated blocks.
+    ///
+    /// A lightweight heuristic rather than a blocking optimizer: a trip
+    /// follows another on the same vehicle when it starts at the place the
+    /// other ends, within the configured layover range. Trips that chain
+    /// with no other trip keep no block, so they are still reported as
+    /// orphans by validation. Results are deterministic for the same rows.
+    ///
+    /// Generated IDs are written to each row's `block`, so derived blocks
+    /// and exports include them without a separate field. Only the raw
+    /// `block` field is read and written, even under a composite
+    /// [block key](Self::block_key): a trip with a `block` is never
+    /// reassigned, and one without keeps its other key fields.
+    pub fn assign_blocks_by_vehicle_continuity(
+        &mut self,
+        config: BlockingConfig,
+    ) -> BlockingReport {
+        let report = assign_blocks(&mut self.rows, &config);
+        self.invalidate_caches();
+        report
+    }
+
+    /// Indices of rows whose start or end time is missing or unparseable.
+    ///
+    /// These rows have no duration, so they are left out of time totals such
+    /// as block revenue/deadhead/layover time, duty driving and break time,
+    /// and route revenue time.
+    pub fn rows_missing_times(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                row.start_time_seconds().is_none() || row.end_time_seconds().is_none()
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Fill missing end times of revenue trips from GTFS `stop_times`.
+    ///
+    /// Each revenue row with a start time, a `trip_id` and no end time gets
+    /// the last arrival of that trip in the feed. Returns the number of rows
+    /// filled.
+    pub fn backfill_end_times(&mut self, gtfs: &GtfsFeed) -> usize {
+        let mut trip_ends: HashMap<&str, u32> = HashMap::new();
+        for stop_time in &gtfs.feed.stop_times {
+            if let Some(arrival) = stop_time.arrival_time.or(stop_time.departure_time) {
+                let end = trip_ends
+                    .entry(stop_time.trip_id.as_str())
+                    .or_insert(arrival);
+                *end = (*end).max(arrival);
+            }
+        }
+
+        let mut filled = 0;
+        for row in &mut self.rows {
+            if !row.is_revenue() || row.end_time.is_some() || row.start_time.is_none() {
+                continue;
+            }
+            let end = row
+                .trip_id
+                .as_deref()
+                .and_then(|trip_id| trip_ends.get(trip_id));
+            if let Some(&end) = end {
+                row.end_time = Some(seconds_to_time_string(end));
+                filled += 1;
+            }
+        }
+
+        if filled > 0 {
+            self.invalidate_caches();
         }
+        filled
     }
 
-    #[test]
-    fn test_schedule_summary() {
-        let schedule = Schedule::from_rows(vec![
-            sample_row("B1", Some("T1"), "08:00:00"),
-            sample_row("B1", Some("T2"), "09:00:00"),
-            sample_row("B1", None, "10:00:00"), // deadhead
-            sample_row("B2", Some("T3"), "08:30:00"),
-        ]);
+    /// Rewrite end times that wrap past midnight so they follow the start.
+    ///
+    /// A row ending before it starts (e.g. `23:40:00` to `00:20:00`) gets
+    /// 24 hours added to its end time (`24:20:00`), the GTFS convention, so
+    /// its duration is no longer dropped. Returns the number of rows changed.
+    pub fn normalize_overnight_times(&mut self) -> usize {
+        const DAY_SECONDS: u32 = 86_400;
 
-        let summary = schedule.summary();
-        assert_eq!(summary.total_rows, 4);
-        assert_eq!(summary.revenue_trips, 3);
-        assert_eq!(summary.deadheads, 1);
-        assert_eq!(summary.unique_blocks, 2);
+        let mut changed = 0;
+        for row in &mut self.rows {
+            if let (Some(start), Some(end)) = (row.start_time_seconds(), row.end_time_seconds()) {
+                if end < start {
+                    row.end_time = Some(seconds_to_time_string(end + DAY_SECONDS));
+                    changed += 1;
+                }
+            }
+        }
+
+        if changed > 0 {
+            self.invalidate_caches();
+        }
+        changed
     }
 
-    #[test]
-    fn test_derive_blocks() {
-        let mut schedule = Schedule::from_rows(vec![
-            sample_row("B1", Some("T1"), "08:00:00"),
-            sample_row("B1", Some("T2"), "09:00:00"),
-            sample_row("B2", Some("T3"), "08:30:00"),
-        ]);
+    /// Fill missing start/end coordinates from GTFS stops.
+    ///
+    /// Each row's `start_place` and `end_place` are looked up as stop IDs;
+    /// only coordinates that are missing are filled. Distances derived from
+    /// coordinates (e.g. [`ScheduleRow::distance_meters`]) become available
+    /// once both ends are known. Returns the number of rows changed.
+    pub fn backfill_coordinates(&mut self, gtfs: &GtfsFeed) -> usize {
+        let stop_coords: HashMap<&str, (f64, f64)> = gtfs
+            .feed
+            .stops
+            .iter()
+            .map(|s| (s.id.as_str(), (s.latitude, s.longitude)))
+            .collect();
+        let lookup =
+            |place: &Option<String>| place.as_deref().and_then(|p| stop_coords.get(p)).copied();
 
-        let blocks = schedule.blocks();
-        assert_eq!(blocks.len(), 2);
-        assert!(blocks.contains_key("B1"));
-        assert!(blocks.contains_key("B2"));
-        assert_eq!(blocks.get("B1").unwrap().rows.len(), 2);
+        let mut changed = 0;
+        for row in &mut self.rows {
+            let mut row_changed = false;
+            if row.start_lat.is_none() || row.start_lon.is_none() {
+                if let Some((lat, lon)) = lookup(&row.start_place) {
+                    row.start_lat = Some(lat);
+                    row.start_lon = Some(lon);
+                    row_changed = true;
+                }
+            }
+            if row.end_lat.is_none() || row.end_lon.is_none() {
+                if let Some((lat, lon)) = lookup(&row.end_place) {
+                    row.end_lat = Some(lat);
+                    row.end_lon = Some(lon);
+                    row_changed = true;
+                }
+            }
+            if row_changed {
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            self.invalidate_caches();
+        }
+        changed
+    }
+
+    /// Expand each revenue row into its trip's GTFS `stop_times`.
+    ///
+    /// Stops are returned in row order and, within a row, by stop sequence,
+    /// each carrying the row's block, run and duty. Revenue rows without a
+    /// `trip_id`, or whose trip has no stop times in the feed, are skipped
+    /// and counted in [`ExpandedStopTimes::rows_skipped`].
+    pub fn expand_stop_times(&self, gtfs: &GtfsFeed) -> ExpandedStopTimes {
+        let mut trip_stops: HashMap<&str, Vec<&StopTime>> = HashMap::new();
+        for stop_time in &gtfs.feed.stop_times {
+            trip_stops
+                .entry(stop_time.trip_id.as_str())
+                .or_default()
+                .push(stop_time);
+        }
+        for stops in trip_stops.values_mut() {
+            stops.sort_by_key(|st| st.stop_sequence);
+        }
+
+        let mut expanded = ExpandedStopTimes::default();
+        for (row_index, row) in self.rows.iter().enumerate() {
+            if row.row_type != RowType::Revenue {
+                continue;
+            }
+            let Some(trip_id) = row.trip_id.as_deref().filter(|_| row.has_valid_trip_id()) else {
+                expanded.rows_skipped += 1;
+                continue;
+            };
+            let Some(stops) = trip_stops.get(trip_id) else {
+                expanded.rows_skipped += 1;
+                continue;
+            };
+            expanded
+                .stop_times
+                .extend(stops.iter().map(|stop_time| ExpandedStopTime {
+                    row_index,
+                    trip_id: trip_id.to_string(),
+                    block: row.block.clone(),
+                    run_number: row.run_number.clone(),
+                    duty_id: row.duty_id.clone(),
+                    stop_id: stop_time.stop_id.clone(),
+                    stop_sequence: stop_time.stop_sequence,
+                    arrival_time_seconds: stop_time.arrival_time,
+                    departure_time_seconds: stop_time.departure_time,
+                }));
+        }
+        expanded
+    }
+
+    /// Indices of rows that repeat an earlier row's block, trip ID, start
+    /// and end time, row type, and service.
+    ///
+    /// This is the dry run for [`deduplicate_rows`](Self::deduplicate_rows):
+    /// the first occurrence is kept and later copies are reported.
+    pub fn duplicate_row_indices(&self) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                !seen.insert((
+                    row.block.as_deref(),
+                    row.trip_id.as_deref(),
+                    row.start_time.as_deref(),
+                    row.end_time.as_deref(),
+                    row.row_type,
+                    row.service_id.as_deref(),
+                ))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Remove exact duplicate rows, keeping the first occurrence.
+    ///
+    /// Returns the number of rows removed.
+    pub fn deduplicate_rows(&mut self) -> usize {
+        let duplicates: HashSet<usize> = self.duplicate_row_indices().into_iter().collect();
+        if duplicates.is_empty() {
+            return 0;
+        }
+
+        let mut idx = 0;
+        self.rows.retain(|_| {
+            let keep = !duplicates.contains(&idx);
+            idx += 1;
+            keep
+        });
+        self.invalidate_caches();
+        duplicates.len()
+    }
+
+    /// Apply conservative fixes for common data-entry problems.
+    ///
+    /// In order: trim whitespace around text values, move overnight end
+    /// times past midnight (as
+    /// [`normalize_overnight_times`](Self::normalize_overnight_times)),
+    /// remove duplicate rows (as [`deduplicate_rows`](Self::deduplicate_rows))
+    /// and sort by block and time. None of these change what the schedule
+    /// means; the report lists every change with the original values.
+    pub fn apply_safe_fixes(&mut self) -> FixReport {
+        apply_safe_fixes(self)
+    }
+
+    /// Apply [`apply_safe_fixes`](Self::apply_safe_fixes), then validate
+    /// the fixed schedule.
+    ///
+    /// Validates against `gtfs` if given, otherwise only structure and
+    /// business rules (see [`Validator::validate_structure`]).
+    pub fn validate_and_fix(
+        &mut self,
+        gtfs: Option<&GtfsFeed>,
+        config: ValidationConfig,
+    ) -> (FixReport, ValidationResult) {
+        let report = self.apply_safe_fixes();
+        let validator = Validator::new(config);
+        let result = match gtfs {
+            Some(gtfs) => validator.validate(self, gtfs),
+            None => validator.validate_structure(self),
+        };
+        (report, result)
+    }
+
+    /// Write each row's 1-based chronological position within its block
+    /// into `block_sequence`.
+    ///
+    /// Rows are ordered the same way `derive_blocks` orders them (a stable
+    /// sort by start time), so sequences match `Block::sequenced_rows`.
+    /// Rows without a block get `None`.
+    pub fn assign_block_sequences(&mut self) {
+        let mut by_block: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, row) in self.rows.iter().enumerate() {
+            if let Some(block_id) = self.block_key.key_for(row) {
+                by_block.entry(block_id).or_default().push(idx);
+            }
+        }
+
+        let mut sequences: Vec<Option<u32>> = vec![None; self.rows.len()];
+        for indices in by_block.values_mut() {
+            indices.sort_by_key(|&i| self.rows[i].start_time_seconds().unwrap_or(0));
+            for (pos, &idx) in indices.iter().enumerate() {
+                sequences[idx] = Some(pos as u32 + 1);
+            }
+        }
+
+        for (row, seq) in self.rows.iter_mut().zip(sequences) {
+            row.block_sequence = seq;
+        }
+        self.invalidate_caches();
+    }
+
+    /// Split a block into two at a time boundary, e.g. to break up a block
+    /// flagged as over-long by validation.
+    ///
+    /// Rows ending at or before `at_seconds` move to block `{id}a` and rows
+    /// starting at or after it move to `{id}b`; the two new IDs are returned.
+    /// Rows that carried a block sequence are renumbered within their new
+    /// block. Under a composite [block key](Self::block_key) only the `block`
+    /// field is suffixed, so `NORTH:1` splits into `NORTH:1a` and `NORTH:1b`.
+    ///
+    /// Nothing is changed if the split is invalid: the block must exist, no
+    /// row may span the boundary or lack a start time, both halves must be
+    /// non-empty, and neither new ID may already be in use. A block key
+    /// without the `block` field can't be split.
+    pub fn split_block(
+        &mut self,
+        block_id: &str,
+        at_seconds: u32,
+    ) -> Result<(String, String), ParseError> {
+        if !self.block_key.fields().iter().any(|f| f == "block") {
+            return Err(ParseError::InvalidData(format!(
+                "Cannot split block '{}': the block key has no block field",
+                block_id
+            )));
+        }
+        let members: Vec<usize> = (0..self.rows.len())
+            .filter(|&idx| self.block_key.key_for(&self.rows[idx]).as_deref() == Some(block_id))
+            .collect();
+        let Some(&first_member) = members.first() else {
+            return Err(ParseError::InvalidData(format!(
+                "Unknown block: {}",
+                block_id
+            )));
+        };
+
+        // Members share their block field, since it's part of the key
+        let block = self.rows[first_member].block.clone().unwrap_or_default();
+        let (first_block, second_block) = (format!("{}a", block), format!("{}b", block));
+        let renamed_key = |new_block: &str| {
+            let mut row = self.rows[first_member].clone();
+            row.block = Some(new_block.to_string());
+            self.block_key.key_for(&row).unwrap_or_default()
+        };
+        let (first_id, second_id) = (renamed_key(&first_block), renamed_key(&second_block));
+        if let Some(id) = self
+            .rows
+            .iter()
+            .filter_map(|r| self.block_key.key_for(r))
+            .find(|id| *id == first_id || *id == second_id)
+        {
+            return Err(ParseError::InvalidData(format!(
+                "Cannot split block '{}': block '{}' already exists",
+                block_id, id
+            )));
+        }
+
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        for idx in members {
+            let row = &self.rows[idx];
+            let start = row.start_time_seconds().ok_or_else(|| {
+                ParseError::InvalidData(format!(
+                    "Cannot split block '{}': row {} has no start time",
+                    block_id, idx
+                ))
+            })?;
+            let end = row.end_time_seconds().unwrap_or(start);
+            if start >= at_seconds {
+                second.push((start, idx));
+            } else if end <= at_seconds {
+                first.push((start, idx));
+            } else {
+                return Err(ParseError::InvalidData(format!(
+                    "Cannot split block '{}' at {}: row {} runs from {} to {}",
+                    block_id,
+                    seconds_to_time_string(at_seconds),
+                    idx,
+                    seconds_to_time_string(start),
+                    seconds_to_time_string(end)
+                )));
+            }
+        }
+
+        if first.is_empty() || second.is_empty() {
+            return Err(ParseError::InvalidData(format!(
+                "Cannot split block '{}' at {}: all rows fall on one side",
+                block_id,
+                seconds_to_time_string(at_seconds)
+            )));
+        }
+
+        for (indices, new_block) in [(&mut first, &first_block), (&mut second, &second_block)] {
+            // Stable sort, matching the order derived blocks use
+            indices.sort_by_key(|&(start, _)| start);
+            for (pos, &(_, idx)) in indices.iter().enumerate() {
+                let row = &mut self.rows[idx];
+                row.block = Some(new_block.clone());
+                if row.block_sequence.is_some() {
+                    row.block_sequence = Some(pos as u32 + 1);
+                }
+            }
+        }
+        self.invalidate_caches();
+
+        Ok((first_id, second_id))
+    }
+
+    /// Reassign a revenue trip to another block.
+    ///
+    /// Exactly one revenue row must carry `trip_id`; otherwise nothing is
+    /// changed. The row's block sequence is cleared, since it no longer
+    /// fits either block; call [`assign_block_sequences`](Self::assign_block_sequences)
+    /// to renumber.
+    ///
+    /// Under a composite [block key](Self::block_key) the row takes every
+    /// key field from the target block, which must already exist.
+    pub fn move_trip(&mut self, trip_id: &str, to_block: &str) -> Result<(), ParseError> {
+        let mut matches = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.is_revenue() && row.trip_id.as_deref() == Some(trip_id))
+            .map(|(idx, _)| idx);
+        let idx = matches
+            .next()
+            .ok_or_else(|| ParseError::InvalidData(format!("Unknown trip: {}", trip_id)))?;
+        let count = 1 + matches.count();
+        if count > 1 {
+            return Err(ParseError::InvalidData(format!(
+                "Cannot move trip '{}': it appears in {} revenue rows",
+                trip_id, count
+            )));
+        }
+
+        let target = self
+            .rows
+            .iter()
+            .find(|r| self.block_key.key_for(r).as_deref() == Some(to_block))
+            .cloned();
+        let row = match target {
+            Some(target) => {
+                let row = &mut self.rows[idx];
+                self.block_key.copy_fields(&target, row);
+                row
+            }
+            None if self.block_key == BlockKey::block() => {
+                let row = &mut self.rows[idx];
+                row.block = Some(to_block.to_string());
+                row
+            }
+            None => {
+                return Err(ParseError::InvalidData(format!(
+                    "Cannot move trip '{}': unknown block '{}' under a composite block key",
+                    trip_id, to_block
+                )))
+            }
+        };
+        row.block_sequence = None;
+        self.invalidate_caches();
+        Ok(())
+    }
+
+    /// Check the schedule for style and consistency issues.
+    ///
+    /// These checks need no GTFS feed or configuration and never make a
+    /// schedule invalid; they point at data worth normalizing before import.
+    /// All warnings are in the
+    /// [`BestPractice`](crate::validation::validator::WarningCategory::BestPractice)
+    /// category:
+    ///
+    /// - `W301`: times written in more than one format (`HH:MM:SS`, `HH:MM`,
+    ///   plain seconds, a one-digit hour, ...), one warning per format other
+    ///   than the most common, at the first row using it
+    /// - `W302`: block IDs that differ only in case, such as `B1` and `b1`
+    /// - `W303`: a start or end place with leading or trailing whitespace
+    /// - `W304`: depot names that differ only in case, spaces, underscores or
+    ///   hyphens, such as `North Depot` and `NORTH_DEPOT`
+    pub fn lint(&self) -> Vec<ValidationWarning> {
+        lint_rows(&self.rows)
+    }
+
+    /// Get summary statistics about the schedule.
+    pub fn summary(&self) -> ScheduleSummary {
+        let revenue_count = self.rows.iter().filter(|r| r.is_revenue()).count();
+        let deadhead_count = self.rows.iter().filter(|r| r.is_deadhead()).count();
+        let break_count = self.rows.iter().filter(|r| r.is_break_or_relief()).count();
+
+        ScheduleSummary {
+            total_rows: self.rows.len(),
+            revenue_trips: revenue_count,
+            deadheads: deadhead_count,
+            breaks_and_reliefs: break_count,
+            unique_blocks: self.block_ids().len(),
+            unique_runs: self.run_numbers().len(),
+            unique_depots: self.depots().len(),
+        }
+    }
+
+    /// Count deadhead rows and their time by type.
+    ///
+    /// `PullOut` and `PullIn` rows are pull-outs and pull-ins and `Deadhead`
+    /// rows are interlinings. Rows without a valid start and end time are
+    /// counted but add no time.
+    pub fn deadhead_stats(&self) -> DeadheadStats {
+        let mut stats = DeadheadStats::default();
+        for row in &self.rows {
+            let (count, seconds) = match row.row_type {
+                RowType::PullOut => (&mut stats.pull_outs, &mut stats.pull_out_seconds),
+                RowType::PullIn => (&mut stats.pull_ins, &mut stats.pull_in_seconds),
+                RowType::Deadhead => (&mut stats.interlinings, &mut stats.interlining_seconds),
+                _ => continue,
+            };
+            *count += 1;
+            *seconds += row.duration_seconds().unwrap_or(0);
+        }
+        stats
+    }
+
+    /// Places ranked by how many interlining deadheads start or end there,
+    /// as (place, count) pairs.
+    ///
+    /// Only `Deadhead` rows are counted, once for their start place and once
+    /// for their end place; busy places are candidates for through-routing.
+    /// Pull-outs and pull-ins are counted by
+    /// [`depot_deadhead_hotspots`](Self::depot_deadhead_hotspots). The most
+    /// frequent come first (ties by place name).
+    pub fn deadhead_hotspots(&self) -> Vec<(String, usize)> {
+        self.rank_deadhead_endpoints(|row_type| row_type == RowType::Deadhead)
+    }
+
+    /// Places ranked by how many pull-outs and pull-ins start or end there,
+    /// as (place, count) pairs.
+    ///
+    /// The counterpart of [`deadhead_hotspots`](Self::deadhead_hotspots) for
+    /// `PullOut` and `PullIn` rows, so depot endpoints don't crowd out
+    /// interlining ones.
+    pub fn depot_deadhead_hotspots(&self) -> Vec<(String, usize)> {
+        self.rank_deadhead_endpoints(|row_type| {
+            matches!(row_type, RowType::PullOut | RowType::PullIn)
+        })
+    }
+
+    /// Count the start and end places of rows whose type matches, ranked by
+    /// count then place name.
+    fn rank_deadhead_endpoints(&self, include: impl Fn(RowType) -> bool) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for row in self.rows.iter().filter(|row| include(row.row_type)) {
+            let start = row.start_place.as_deref();
+            let end = row.end_place.as_deref();
+            for place in start.into_iter().chain(end) {
+                *counts.entry(place).or_insert(0) += 1;
+            }
+        }
+
+        let mut ranked: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(place, count)| (place.to_string(), count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+    }
+
+    /// Summary statistics for each service, in [`split_by_service`](Self::split_by_service) order.
+    pub fn summary_by_service(&self) -> Vec<(Option<String>, ScheduleSummary)> {
+        self.split_by_service()
+            .into_iter()
+            .map(|(service_id, schedule)| (service_id, schedule.summary()))
+            .collect()
+    }
+
+    /// Time span of each block, from its earliest start to its latest end.
+    ///
+    /// Blocks without any row times are skipped.
+    fn block_spans(&self) -> Vec<(u32, u32)> {
+        let mut spans: HashMap<String, (Option<u32>, Option<u32>)> = HashMap::new();
+        for row in &self.rows {
+            if let Some(block_id) = self.block_key.key_for(row) {
+                let (start, end) = spans.entry(block_id).or_default();
+                if let Some(s) = row.start_time_seconds() {
+                    *start = Some(start.map_or(s, |v| v.min(s)));
+                }
+                if let Some(e) = row.end_time_seconds() {
+                    *end = Some(end.map_or(e, |v| v.max(e)));
+                }
+            }
+        }
+
+        spans
+            .into_values()
+            .filter_map(|span| match span {
+                (Some(start), Some(end)) if end >= start => Some((start, end)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Peak number of blocks in service at the same time.
+    ///
+    /// Returns `(peak_count, peak_time_seconds)`, where the time is the first
+    /// moment the peak is reached. Each block is in service from its first
+    /// start time up to (but not including) its last end time, so a block
+    /// ending exactly when another starts doesn't count twice, and a block
+    /// that starts and ends at the same time is never in service. An empty
+    /// schedule returns `(0, 0)`.
+    pub fn peak_vehicle_count(&self) -> (u32, u32) {
+        let mut events: Vec<(u32, i32)> = Vec::new();
+        for (start, end) in self.block_spans() {
+            if start == end {
+                continue;
+            }
+            events.push((start, 1));
+            events.push((end, -1));
+        }
+        // At equal times, process ends before starts
+        events.sort();
+
+        let mut current: i32 = 0;
+        let mut peak = (0, 0);
+        for (time, delta) in events {
+            current += delta;
+            if current > peak.0 {
+                peak = (current, time);
+            }
+        }
+        (peak.0 as u32, peak.1)
+    }
+
+    /// Number of blocks in service at the given time (seconds since midnight).
+    pub fn vehicles_in_service_at(&self, seconds: u32) -> usize {
+        self.block_spans()
+            .into_iter()
+            .filter(|&(start, end)| start <= seconds && seconds < end)
+            .count()
+    }
+
+    /// Rows running at the given time (seconds since midnight), optionally
+    /// only those starting or ending at `place`.
+    ///
+    /// A row matches when its start and end times both include `seconds`,
+    /// so rows that end exactly at `seconds` and rows that start then are
+    /// both returned. A row without an end time only matches at its start
+    /// time, and a row without a start time never matches. Rows come back in
+    /// schedule order.
+    ///
+    /// This scans every row, so repeated queries over a large schedule are
+    /// O(n) each.
+    pub fn rows_active_at(&self, place: Option<&str>, seconds: u32) -> Vec<&ScheduleRow> {
+        self.rows
+            .iter()
+            .filter(|row| {
+                let Some(start) = row.start_time_seconds() else {
+                    return false;
+                };
+                let end = row.end_time_seconds().unwrap_or(start);
+                (start..=end).contains(&seconds)
+            })
+            .filter(|row| match place {
+                Some(place) => {
+                    row.start_place.as_deref() == Some(place)
+                        || row.end_place.as_deref() == Some(place)
+                }
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Count revenue departures in fixed-width time buckets, optionally only
+    /// those of trips with the given `route_short_name`.
+    ///
+    /// Returns `(bucket_start_seconds, count)` in time order for each bucket
+    /// holding at least one departure; empty buckets are left out, so fill
+    /// them in when charting. Departures after midnight written past
+    /// `24:00:00` fall in buckets past 86400. Returns an empty vector when
+    /// there are no departures or `bucket_seconds` is 0.
+    pub fn departure_histogram(
+        &self,
+        bucket_seconds: u32,
+        route: Option<&str>,
+    ) -> Vec<(u32, usize)> {
+        if bucket_seconds == 0 {
+            return Vec::new();
+        }
+
+        let mut counts: BTreeMap<u32, usize> = BTreeMap::new();
+        for start in self
+            .revenue_trips()
+            .filter(|row| route.map_or(true, |r| row.route_short_name.as_deref() == Some(r)))
+            .filter_map(ScheduleRow::start_time_seconds)
+        {
+            *counts.entry(start - start % bucket_seconds).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// Earliest start and latest end across revenue rows.
+    ///
+    /// A row whose end time is earlier than its start time is taken to run
+    /// past midnight, so its end counts as the next day (e.g. `00:30:00`
+    /// after a `23:50:00` start becomes 88200). Rows without an end time
+    /// contribute their start. Returns `None` if no revenue row has a start
+    /// time.
+    pub fn service_span(&self) -> Option<(u32, u32)> {
+        self.revenue_trips()
+            .filter_map(|row| {
+                let start = row.start_time_seconds()?;
+                let end = match row.end_time_seconds() {
+                    Some(end) if end < start => end + 86400,
+                    Some(end) => end,
+                    None => start,
+                };
+                Some((start, end))
+            })
+            .reduce(|(first, last), (start, end)| (first.min(start), last.max(end)))
+    }
+
+    /// Extent of the schedule's coordinates, as
+    /// `(min_lat, min_lon, max_lat, max_lon)`.
+    ///
+    /// Start and end points of every row are included; a point missing its
+    /// latitude or longitude, or with a non-finite value, is skipped. Returns
+    /// `None` if no row has coordinates.
+    pub fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+        self.coordinates()
+            .map(|(lat, lon)| (lat, lon, lat, lon))
+            .reduce(|(min_lat, min_lon, max_lat, max_lon), (lat, lon, _, _)| {
+                (
+                    min_lat.min(lat),
+                    min_lon.min(lon),
+                    max_lat.max(lat),
+                    max_lon.max(lon),
+                )
+            })
+    }
+
+    /// Mean `(lat, lon)` of the points used by [`bounding_box`](Self::bounding_box).
+    ///
+    /// Each start and end point counts once, so busy places weigh more.
+    /// Returns `None` if no row has coordinates.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let (count, lat_sum, lon_sum) = self.coordinates().fold(
+            (0usize, 0.0, 0.0),
+            |(count, lat_sum, lon_sum), (lat, lon)| (count + 1, lat_sum + lat, lon_sum + lon),
+        );
+        (count > 0).then(|| (lat_sum / count as f64, lon_sum / count as f64))
+    }
+
+    /// Start and end points of every row that has both coordinates.
+    fn coordinates(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.rows
+            .iter()
+            .flat_map(|row| {
+                [
+                    row.start_lat.zip(row.start_lon),
+                    row.end_lat.zip(row.end_lon),
+                ]
+            })
+            .flatten()
+            .filter(|(lat, lon)| lat.is_finite() && lon.is_finite())
+    }
+
+    /// Find bands of a route's revenue trips that depart at a regular
+    /// headway, for GTFS `frequencies.txt` or service-pattern reports.
+    ///
+    /// Departures of trips with that `route_short_name` are taken in time
+    /// order. A band grows while each gap stays within
+    /// `headway_tolerance_seconds` of the band's first gap, and is kept if
+    /// it has at least `min_trips` trips (and at least two). Trips in no band
+    /// have irregular headways and are left to exact times. Both directions
+    /// of a route are mixed, so filter the schedule first if they run
+    /// different patterns.
+    pub fn detect_frequencies(
+        &self,
+        route: &str,
+        min_trips: usize,
+        headway_tolerance_seconds: u32,
+    ) -> Vec<FrequencyBand> {
+        let mut departures: Vec<u32> = self
+            .revenue_trips()
+            .filter(|row| row.route_short_name.as_deref() == Some(route))
+            .filter_map(ScheduleRow::start_time_seconds)
+            .collect();
+        departures.sort_unstable();
+
+        let min_trips = min_trips.max(2);
+        let mut bands = Vec::new();
+        let mut first = 0;
+        while first + 1 < departures.len() {
+            let headway = departures[first + 1] - departures[first];
+            let mut last = first + 1;
+            while last + 1 < departures.len()
+                && (departures[last + 1] - departures[last]).abs_diff(headway)
+                    <= headway_tolerance_seconds
+            {
+                last += 1;
+            }
+
+            let trip_count = last - first + 1;
+            if headway == 0 || trip_count < min_trips {
+                first += 1;
+                continue;
+            }
+            let span = departures[last] - departures[first];
+            let mean_headway = (span + (trip_count as u32 - 1) / 2) / (trip_count as u32 - 1);
+            bands.push(FrequencyBand {
+                start_time_seconds: departures[first],
+                end_time_seconds: departures[last] + mean_headway,
+                headway_seconds: mean_headway,
+                trip_count,
+            });
+            first = last + 1;
+        }
+        bands
+    }
+
+    /// Get summary statistics per route.
+    ///
+    /// Revenue rows are grouped by `route_short_name`; rows without a route
+    /// are not included. Blocks are identified by the
+    /// [block key](Self::block_key).
+    pub fn route_summaries(&self) -> HashMap<String, RouteSummary> {
+        let mut summaries: HashMap<String, RouteSummary> = HashMap::new();
+
+        for row in self.revenue_trips() {
+            let Some(route) = &row.route_short_name else {
+                continue;
+            };
+            let summary = summaries
+                .entry(route.clone())
+                .or_insert_with(|| RouteSummary {
+                    route_short_name: route.clone(),
+                    ..Default::default()
+                });
+
+            summary.trip_count += 1;
+            summary.revenue_time_seconds += row.duration_seconds().unwrap_or(0);
+            if let Some(start) = row.start_time_seconds() {
+                summary.first_departure_seconds = Some(
+                    summary
+                        .first_departure_seconds
+                        .map_or(start, |first| first.min(start)),
+                );
+            }
+            if let Some(end) = row.end_time_seconds() {
+                summary.last_arrival_seconds = Some(
+                    summary
+                        .last_arrival_seconds
+                        .map_or(end, |last| last.max(end)),
+                );
+            }
+            if let Some(block) = self.block_key.key_for(row) {
+                summary.blocks.push(block);
+            }
+        }
+
+        for summary in summaries.values_mut() {
+            summary.blocks.sort();
+            summary.blocks.dedup();
+        }
+
+        summaries
+    }
+}
+
+/// A row's (start, end) in seconds, with an end before the start moved to
+/// the next day.
+fn row_interval(row: &ScheduleRow) -> Option<(u32, u32)> {
+    let (start, end) = (row.start_time_seconds()?, row.end_time_seconds()?);
+    Some((start, if end < start { end + 86_400 } else { end }))
+}
+
+/// Sort intervals and merge the ones that overlap or touch.
+fn merge_intervals(mut intervals: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    intervals.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Parts of `interval` not covered by the sorted, merged `covered`.
+fn subtract_intervals(interval: (u32, u32), covered: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let (mut start, end) = interval;
+    let mut remaining = Vec::new();
+    for &(cover_start, cover_end) in covered {
+        if cover_end <= start || cover_start >= end {
+            continue;
+        }
+        if cover_start > start {
+            remaining.push((start, cover_start));
+        }
+        start = start.max(cover_end);
+    }
+    if start < end {
+        remaining.push((start, end));
+    }
+    remaining
+}
+
+/// Blocks whose rows name more than one depot, as (block ID, sorted depots),
+/// sorted by block ID.
+fn depot_conflicts(blocks: &HashMap<String, Block>) -> Vec<(String, Vec<String>)> {
+    let mut conflicts: Vec<(String, Vec<String>)> = blocks
+        .values()
+        .filter_map(|block| {
+            let depots: BTreeSet<&str> = block
+                .rows
+                .iter()
+                .filter_map(|r| r.depot.as_deref())
+                .collect();
+            (depots.len() > 1).then(|| {
+                (
+                    block.block_id.clone(),
+                    depots.into_iter().map(String::from).collect(),
+                )
+            })
+        })
+        .collect();
+    conflicts.sort();
+    conflicts
+}
+
+/// Order by block ID, rows without a block last, then by start time.
+/// Order rows by block ID, rows without one last, then by start time.
+fn block_and_time_order(
+    a_block: &Option<String>,
+    a: &ScheduleRow,
+    b_block: &Option<String>,
+    b: &ScheduleRow,
+) -> Ordering {
+    let block_order = match (a_block, b_block) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    block_order.then_with(|| {
+        let a_time = a.start_time_seconds().unwrap_or(0);
+        let b_time = b.start_time_seconds().unwrap_or(0);
+        a_time.cmp(&b_time)
+    })
+}
+
+/// Driver coverage of one block, from [`Schedule::block_coverage`].
+///
+/// Intervals are (start, end) in seconds since midnight, sorted and merged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockCoverage {
+    pub block_id: String,
+    /// Time on the block covered by some duty.
+    pub covered: Vec<(u32, u32)>,
+    /// Vehicle time with no duty assigned.
+    pub uncovered: Vec<(u32, u32)>,
+    /// Duties with rows on the block, sorted.
+    pub duty_ids: Vec<String>,
+}
+
+impl BlockCoverage {
+    /// Whether all vehicle time is covered by a duty.
+    pub fn is_fully_covered(&self) -> bool {
+        self.uncovered.is_empty()
+    }
+
+    /// Total uncovered vehicle time in seconds.
+    pub fn uncovered_seconds(&self) -> u32 {
+        self.uncovered.iter().map(|&(start, end)| end - start).sum()
+    }
+}
+
+/// Summary statistics for the revenue trips on one route.
+#[derive(Debug, Clone, Default)]
+pub struct RouteSummary {
+    pub route_short_name: String,
+    pub trip_count: usize,
+    pub first_departure_seconds: Option<u32>,
+    pub last_arrival_seconds: Option<u32>,
+    pub revenue_time_seconds: u32,
+    /// Distinct blocks serving the route, sorted.
+    pub blocks: Vec<String>,
+}
+
+/// Trips departing at a regular headway, from
+/// [`Schedule::detect_frequencies`].
+///
+/// Times follow GTFS `frequencies.txt`: the band runs from the first
+/// departure up to, but not including, `end_time_seconds`, one headway after
+/// the last departure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrequencyBand {
+    pub start_time_seconds: u32,
+    pub end_time_seconds: u32,
+    /// Mean gap between departures, rounded to the nearest second.
+    pub headway_seconds: u32,
+    pub trip_count: usize,
+}
+
+/// One GTFS stop time of a schedule row, from
+/// [`Schedule::expand_stop_times`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedStopTime {
+    /// Index of the schedule row this stop belongs to.
+    pub row_index: usize,
+    pub trip_id: String,
+    pub block: Option<String>,
+    pub run_number: Option<String>,
+    pub duty_id: Option<String>,
+    pub stop_id: String,
+    pub stop_sequence: u32,
+    pub arrival_time_seconds: Option<u32>,
+    pub departure_time_seconds: Option<u32>,
+}
+
+/// Result of [`Schedule::expand_stop_times`].
+#[derive(Debug, Clone, Default)]
+pub struct ExpandedStopTimes {
+    /// Stop times in row order, then stop sequence.
+    pub stop_times: Vec<ExpandedStopTime>,
+    /// Revenue rows without a trip ID or whose trip has no stop times in
+    /// the feed.
+    pub rows_skipped: usize,
+}
+
+/// A time total over a schedule's duties, from
+/// [`Schedule::total_driving_seconds`] and related methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DutyTotal {
+    /// Total time in seconds over the duties counted.
+    pub seconds: u32,
+    /// Number of duties counted.
+    pub duties_counted: usize,
+    /// Number of duties left out because of missing times.
+    pub duties_excluded: usize,
+}
+
+/// Deadhead counts and time by type, from [`Schedule::deadhead_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadheadStats {
+    pub pull_outs: usize,
+    pub pull_out_seconds: u32,
+    pub pull_ins: usize,
+    pub pull_in_seconds: u32,
+    pub interlinings: usize,
+    pub interlining_seconds: u32,
+}
+
+impl DeadheadStats {
+    /// Number of deadhead rows of any type.
+    pub fn total_count(&self) -> usize {
+        self.pull_outs + self.pull_ins + self.interlinings
+    }
+
+    /// Time spent on deadheads of any type, in seconds.
+    pub fn total_seconds(&self) -> u32 {
+        self.pull_out_seconds + self.pull_in_seconds + self.interlining_seconds
+    }
+}
+
+/// Summary statistics for a schedule.
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleSummary {
+    pub total_rows: usize,
+    pub revenue_trips: usize,
+    pub deadheads: usize,
+    pub breaks_and_reliefs: usize,
+    pub unique_blocks: usize,
+    pub unique_runs: usize,
+    pub unique_depots: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schedule_row::RowType;
+
+    fn sample_row(block: &str, trip_id: Option<&str>, start: &str) -> ScheduleRow {
+        ScheduleRow {
+            block: Some(block.to_string()),
+            trip_id: trip_id.map(|s| s.to_string()),
+            start_time: Some(start.to_string()),
+            row_type: if trip_id.is_some() {
+                RowType::Revenue
+            } else {
+                RowType::Deadhead
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deadhead_stats() {
+        let timed = |row_type: RowType, start: &str, end: &str| ScheduleRow {
+            row_type,
+            end_time: Some(end.to_string()),
+            ..sample_row("B1", None, start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            timed(RowType::PullOut, "05:40:00", "06:00:00"),
+            sample_row("B1", Some("T1"), "06:00:00"),
+            timed(RowType::Deadhead, "07:00:00", "07:10:00"),
+            timed(RowType::Deadhead, "09:00:00", "09:05:00"),
+            timed(RowType::PullIn, "10:00:00", "10:15:00"),
+            sample_row("B2", None, "11:00:00"),
+        ]);
+
+        let stats = schedule.deadhead_stats();
+        assert_eq!((stats.pull_outs, stats.pull_out_seconds), (1, 1200));
+        assert_eq!((stats.pull_ins, stats.pull_in_seconds), (1, 900));
+        assert_eq!((stats.interlinings, stats.interlining_seconds), (3, 900));
+        assert_eq!(stats.total_count(), 5);
+        assert_eq!(stats.total_seconds(), 3000);
+        assert_eq!(Schedule::new().deadhead_stats(), DeadheadStats::default());
+    }
+
+    #[test]
+    fn test_deadhead_hotspots() {
+        let moved = |row_type: RowType, from: &str, to: &str| ScheduleRow {
+            row_type,
+            start_place: Some(from.to_string()),
+            end_place: Some(to.to_string()),
+            ..Default::default()
+        };
+        let schedule = Schedule::from_rows(vec![
+            moved(RowType::PullOut, "DEPOT", "CENTRAL"),
+            moved(RowType::Deadhead, "CENTRAL", "MARKET"),
+            moved(RowType::Deadhead, "PARK", "CENTRAL"),
+            moved(RowType::Revenue, "CENTRAL", "PARK"),
+            moved(RowType::PullIn, "MARKET", "DEPOT"),
+        ]);
+
+        assert_eq!(
+            schedule.deadhead_hotspots(),
+            vec![
+                ("CENTRAL".to_string(), 2),
+                ("MARKET".to_string(), 1),
+                ("PARK".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            schedule.depot_deadhead_hotspots(),
+            vec![
+                ("DEPOT".to_string(), 2),
+                ("CENTRAL".to_string(), 1),
+                ("MARKET".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duty_totals() {
+        let row = |duty: &str, row_type: RowType, start: &str, end: Option<&str>| ScheduleRow {
+            duty_id: Some(duty.to_string()),
+            row_type,
+            end_time: end.map(String::from),
+            ..sample_row("B1", Some("T1"), start)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("D1", RowType::Revenue, "08:00:00", Some("10:00:00")),
+            row("D1", RowType::Break, "10:00:00", Some("10:30:00")),
+            row("D1", RowType::Revenue, "10:30:00", Some("12:00:00")),
+            row("D2", RowType::Revenue, "09:00:00", Some("10:00:00")),
+            row("D3", RowType::Revenue, "09:00:00", None),
+        ]);
+
+        let excluded = |seconds: u32, duties_counted: usize| DutyTotal {
+            seconds,
+            duties_counted,
+            duties_excluded: 1,
+        };
+        assert_eq!(schedule.total_driving_seconds(), excluded(16200, 2));
+        assert_eq!(schedule.total_break_seconds(), excluded(1800, 2));
+        assert_eq!(
+            schedule.total_paid_seconds(&PayRules::default()),
+            excluded(16200, 2)
+        );
+        let rules = PayRules {
+            paid_breaks: true,
+            minimum_paid_seconds: 4 * 3600,
+        };
+        assert_eq!(schedule.total_paid_seconds(&rules), excluded(28800, 2));
+    }
+
+    #[test]
+    fn test_schedule_summary() {
+        let schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B1", None, "10:00:00"), // deadhead
+            sample_row("B2", Some("T3"), "08:30:00"),
+        ]);
+
+        let summary = schedule.summary();
+        assert_eq!(summary.total_rows, 4);
+        assert_eq!(summary.revenue_trips, 3);
+        assert_eq!(summary.deadheads, 1);
+        assert_eq!(summary.unique_blocks, 2);
+    }
+
+    #[test]
+    fn test_detect_frequencies() {
+        let departure = |route: &str, start: &str| ScheduleRow {
+            route_short_name: Some(route.to_string()),
+            ..sample_row("B1", Some("T"), start)
+        };
+        // A clean 10-minute band, one trip a minute late, then an irregular tail
+        let mut rows: Vec<ScheduleRow> = [
+            "08:00:00", "08:10:00", "08:21:00", "08:30:00", "08:40:00", "08:50:00", "09:00:00",
+            "09:13:00", "09:31:00", "09:38:00",
+        ]
+        .into_iter()
+        .rev()
+        .map(|start| departure("10", start))
+        .collect();
+        rows.push(departure("20", "08:05:00"));
+        let schedule = Schedule::from_rows(rows);
+
+        let bands = schedule.detect_frequencies("10", 3, 60);
+        assert_eq!(
+            bands,
+            vec![FrequencyBand {
+                start_time_seconds: 8 * 3600,
+                end_time_seconds: 9 * 3600 + 600,
+                headway_seconds: 600,
+                trip_count: 7,
+            }]
+        );
+
+        // Without tolerance the late trip splits the band
+        let bands = schedule.detect_frequencies("10", 3, 0);
+        assert_eq!(bands.len(), 1);
+        assert_eq!(bands[0].start_time_seconds, 8 * 3600 + 30 * 60);
+        assert_eq!(bands[0].trip_count, 4);
+
+        assert!(schedule.detect_frequencies("10", 8, 60).is_empty());
+        assert!(schedule.detect_frequencies("20", 1, 60).is_empty());
+    }
+
+    #[test]
+    fn test_equivalent_to() {
+        let rows = vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B2", None, "07:00:00"),
+        ];
+        let schedule = Schedule::from_rows(rows.clone());
+
+        let mut shuffled = Schedule::from_rows(rows.into_iter().rev().collect());
+        shuffled.metadata.name = Some("copy".to_string());
+        assert!(schedule.equivalent_to(&shuffled));
+        shuffled.sort_by_block_and_time();
+        assert!(shuffled.equivalent_to(&schedule));
+
+        // Same length, but the duplicate is of a different row
+        let mut other = schedule.clone();
+        other.rows[2] = sample_row("B1", Some("T1"), "08:00:00");
+        assert!(!schedule.equivalent_to(&other));
+        other.rows.pop();
+        assert!(!schedule.equivalent_to(&other));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+        ]);
+        let hash = schedule.content_hash();
+        assert_eq!(hash, schedule.clone().content_hash());
+
+        let mut edited = schedule.clone();
+        edited.rows[1].start_lat = Some(51.5);
+        assert_ne!(edited.content_hash(), hash);
+
+        // Order matters until the rows are sorted
+        let mut reordered = schedule.clone();
+        reordered.rows.reverse();
+        assert_ne!(reordered.content_hash(), hash);
+        reordered.sort_by_block_and_time();
+        assert_eq!(reordered.content_hash(), hash);
+
+        assert_ne!(Schedule::new().content_hash(), hash);
+    }
+
+    #[test]
+    fn test_extend_rows() {
+        let mut schedule = Schedule::from_rows(vec![sample_row("B1", Some("T1"), "08:00:00")]);
+        assert_eq!(schedule.blocks().len(), 1);
+
+        schedule.extend_rows(vec![
+            sample_row("B2", Some("T2"), "09:00:00"),
+            sample_row("B3", Some("T3"), "10:00:00"),
+        ]);
+
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(schedule.rows[2].trip_id.as_deref(), Some("T3"));
+        assert_eq!(schedule.blocks().len(), 3);
+    }
+
+    #[test]
+    fn test_service_segmentation() {
+        let with_service = |block: &str, trip: &str, service: Option<&str>| ScheduleRow {
+            service_id: service.map(|s| s.to_string()),
+            ..sample_row(block, Some(trip), "08:00:00")
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            with_service("B1", "T1", Some("WKDY")),
+            with_service("B1", "T1", Some("SAT")),
+            with_service("B2", "T2", Some("WKDY")),
+            with_service("B3", "T3", None),
+        ]);
+
+        assert_eq!(schedule.service_ids(), vec!["SAT", "WKDY"]);
+
+        let weekday = schedule.filter_service("WKDY");
+        assert_eq!(weekday.len(), 2);
+        assert_eq!(weekday.block_ids(), vec!["B1", "B2"]);
+        assert!(schedule.filter_service("SUN").is_empty());
+
+        let segments: Vec<(Option<String>, usize)> = schedule
+            .summary_by_service()
+            .into_iter()
+            .map(|(service, summary)| (service, summary.total_rows))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                (None, 1),
+                (Some("SAT".to_string()), 1),
+                (Some("WKDY".to_string()), 2)
+            ]
+        );
+
+        // Same block and time on different service days is not a duplicate
+        assert_eq!(schedule.deduplicate_rows(), 0);
+    }
+
+    #[test]
+    fn test_backfill_end_times() {
+        let mut gtfs = GtfsFeed::new();
+        for (seq, arrival) in [(0, 28800), (1, 30600), (2, 32400)] {
+            let mut stop_time = StopTime::new("T1", format!("S{}", seq), seq);
+            stop_time.arrival_time = Some(arrival);
+            gtfs.feed.stop_times.push(stop_time);
+        }
+
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"), // not in the feed
+            sample_row("B1", None, "10:00:00"),       // deadhead
+        ]);
+        assert_eq!(schedule.rows_missing_times(), vec![0, 1, 2]);
+
+        assert_eq!(schedule.backfill_end_times(&gtfs), 1);
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("09:00:00"));
+        assert_eq!(schedule.rows_missing_times(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_expand_stop_times() {
+        let mut gtfs = GtfsFeed::new();
+        // Out of sequence order in the feed
+        for (trip, seq, time) in [("T1", 2, 29400), ("T1", 1, 28800), ("T3", 1, 36000)] {
+            let mut stop_time = StopTime::new(trip, format!("S{}", seq), seq);
+            stop_time.arrival_time = Some(time);
+            stop_time.departure_time = Some(time);
+            gtfs.feed.stop_times.push(stop_time);
+        }
+
+        let mut t1 = sample_row("B1", Some("T1"), "08:00:00");
+        t1.run_number = Some("R1".to_string());
+        let schedule = Schedule::from_rows(vec![
+            sample_row("B1", None, "07:50:00"), // deadhead
+            t1,
+            sample_row("B1", Some("T2"), "09:00:00"), // not in the feed
+            sample_row("B2", Some("T3"), "10:00:00"),
+            ScheduleRow {
+                row_type: RowType::Revenue,
+                ..sample_row("B2", None, "11:00:00") // no trip ID
+            },
+        ]);
+
+        let expanded = schedule.expand_stop_times(&gtfs);
+        assert_eq!(expanded.rows_skipped, 2);
+        let stops: Vec<(usize, &str, u32, Option<&str>)> = expanded
+            .stop_times
+            .iter()
+            .map(|st| {
+                (
+                    st.row_index,
+                    st.stop_id.as_str(),
+                    st.stop_sequence,
+                    st.block.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            stops,
+            vec![
+                (1, "S1", 1, Some("B1")),
+                (1, "S2", 2, Some("B1")),
+                (3, "S1", 1, Some("B2")),
+            ]
+        );
+        assert_eq!(expanded.stop_times[0].run_number.as_deref(), Some("R1"));
+        assert_eq!(expanded.stop_times[1].arrival_time_seconds, Some(29400));
+    }
+
+    #[test]
+    fn test_interlining_edges() {
+        let mut t2 = sample_row("B1", Some("T2"), "09:00:00");
+        t2.end_time = Some("09:45:00".to_string());
+        let mut deadhead = sample_row("B1", None, "08:50:00");
+        deadhead.end_time = Some("08:58:00".to_string());
+        deadhead.row_type = RowType::Deadhead;
+        let mut t1 = sample_row("B1", Some("T1"), "08:00:00");
+        t1.end_time = Some("08:45:00".to_string());
+        let mut unnamed = sample_row("B1", Some("T3"), "10:00:00");
+        unnamed.trip_id = None;
+        let mut schedule = Schedule::from_rows(vec![
+            t2,
+            deadhead,
+            t1,
+            unnamed,
+            sample_row("B0", Some("T9"), "07:00:00"),
+        ]);
+
+        let edges = schedule.interlining_edges();
+        assert_eq!(
+            edges,
+            vec![
+                ("T1".to_string(), "T2".to_string(), Some(900), true),
+                ("T2".to_string(), "B1:3".to_string(), Some(900), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_likely_depots() {
+        let trip = |block: &str, trip_id: &str, start: &str, from: &str, to: &str| ScheduleRow {
+            start_place: Some(from.to_string()),
+            end_place: Some(to.to_string()),
+            ..sample_row(block, Some(trip_id), start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            trip("B1", "T1", "06:00:00", "GARAGE", "A"),
+            trip("B1", "T2", "07:00:00", "A", "GARAGE"),
+            trip("B2", "T3", "06:30:00", "GARAGE", "B"),
+            trip("B2", "T4", "07:30:00", "B", "C"),
+            trip("B3", "T5", "08:00:00", "C", "GARAGE"),
+        ]);
+
+        assert_eq!(
+            schedule.infer_likely_depots(),
+            vec![("GARAGE".to_string(), 4), ("C".to_string(), 2)]
+        );
+        assert!(Schedule::new().infer_likely_depots().is_empty());
+    }
+
+    #[test]
+    fn test_block_coverage() {
+        let row = |block: &str, duty: Option<&str>, start: &str, end: &str, row_type| ScheduleRow {
+            block: Some(block.to_string()),
+            duty_id: duty.map(String::from),
+            start_time: Some(start.to_string()),
+            end_time: Some(end.to_string()),
+            row_type,
+            ..Default::default()
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("B1", Some("D1"), "06:00:00", "08:00:00", RowType::Revenue),
+            row("B1", Some("D1"), "08:00:00", "08:10:00", RowType::Relief),
+            row("B1", Some("D2"), "08:10:00", "10:00:00", RowType::Revenue),
+            row("B1", None, "10:00:00", "10:30:00", RowType::PullIn),
+            row("B2", Some("D2"), "11:00:00", "12:00:00", RowType::Revenue),
+        ]);
+
+        let coverage = schedule.block_coverage();
+        assert_eq!(coverage.len(), 2);
+        assert_eq!(coverage[0].block_id, "B1");
+        assert_eq!(coverage[0].covered, vec![(21600, 36000)]);
+        assert_eq!(coverage[0].uncovered, vec![(36000, 37800)]);
+        assert_eq!(coverage[0].uncovered_seconds(), 1800);
+        assert_eq!(coverage[0].duty_ids, vec!["D1", "D2"]);
+        assert!(coverage[1].is_fully_covered());
+    }
+
+    #[test]
+    fn test_normalize_overnight_times() {
+        let mut late = sample_row("B1", Some("T1"), "23:40:00");
+        late.end_time = Some("00:20:00".to_string());
+        let mut day = sample_row("B1", Some("T2"), "08:00:00");
+        day.end_time = Some("09:00:00".to_string());
+        let mut schedule = Schedule::from_rows(vec![late, day]);
+
+        assert_eq!(schedule.normalize_overnight_times(), 1);
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("24:20:00"));
+        assert_eq!(schedule.rows[0].duration_seconds(), Some(2400));
+        assert_eq!(schedule.rows[1].end_time.as_deref(), Some("09:00:00"));
+        assert_eq!(schedule.normalize_overnight_times(), 0);
+    }
+
+    #[test]
+    fn test_derive_blocks() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B2", Some("T3"), "08:30:00"),
+        ]);
+
+        let blocks = schedule.blocks();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.contains_key("B1"));
+        assert!(blocks.contains_key("B2"));
+        assert_eq!(blocks.get("B1").unwrap().rows.len(), 2);
+    }
+
+    #[test]
+    fn test_derive_blocks_by_composite_key() {
+        let row = |depot: Option<&str>, trip_id: &str| ScheduleRow {
+            depot: depot.map(String::from),
+            ..sample_row("1", Some(trip_id), "08:00:00")
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row(Some("NORTH"), "T1"),
+            row(Some("SOUTH"), "T2"),
+            row(Some("NORTH"), "T3"),
+            row(None, "T4"),
+        ]);
+        assert_eq!(schedule.block_ids(), vec!["1"]);
+
+        schedule.derive_blocks_by(BlockKey::depot_block());
+        assert_eq!(schedule.block_ids(), vec!["NORTH:1", "SOUTH:1"]);
+        assert_eq!(schedule.blocks().len(), 2);
+        assert_eq!(schedule.get_block("NORTH:1").unwrap().rows.len(), 2);
+        assert_eq!(schedule.rows_for_block("SOUTH:1").len(), 1);
+        // The key survives cache invalidation and service splits
+        schedule.add_row(row(Some("SOUTH"), "T5"));
+        assert_eq!(schedule.get_block("SOUTH:1").unwrap().rows.len(), 2);
+        let (_, split) = schedule.split_by_service().remove(0);
+        assert_eq!(split.block_key(), &BlockKey::depot_block());
+
+        schedule.derive_blocks_by(BlockKey::block());
+        assert_eq!(schedule.get_block("1").unwrap().rows.len(), 5);
+    }
+
+    #[test]
+    fn test_block_depot_conflicts() {
+        let row = |block: &str, depot: Option<&str>, trip_id: &str| ScheduleRow {
+            depot: depot.map(String::from),
+            ..sample_row(block, Some(trip_id), "08:00:00")
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("B1", Some("D1"), "T1"),
+            row("B1", Some("D2"), "T2"),
+            row("B1", None, "T3"),
+            row("B2", Some("D1"), "T4"),
+        ]);
+
+        schedule.derive_blocks();
+        assert_eq!(
+            schedule.metadata.block_depot_conflicts,
+            vec![("B1".to_string(), vec!["D1".to_string(), "D2".to_string()])]
+        );
+
+        assert_eq!(schedule.namespace_blocks_by_depot(), 1);
+        assert_eq!(schedule.block_ids(), vec!["B1", "B2", "D1:B1", "D2:B1"]);
+        schedule.derive_blocks();
+        assert!(schedule.metadata.block_depot_conflicts.is_empty());
+        assert_eq!(schedule.namespace_blocks_by_depot(), 0);
+    }
+
+    #[test]
+    fn test_blocks_and_duties_sorted() {
+        let row = |block: &str, duty: &str| ScheduleRow {
+            duty_id: Some(duty.to_string()),
+            ..sample_row(block, Some("T1"), "08:00:00")
+        };
+        let mut schedule =
+            Schedule::from_rows(vec![row("B3", "D2"), row("B1", "D3"), row("B2", "D1")]);
+
+        let block_ids: Vec<&String> = schedule
+            .blocks_sorted()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(block_ids, ["B1", "B2", "B3"]);
+        let duties = schedule.duties_sorted();
+        let duty_ids: Vec<&str> = duties.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(duty_ids, ["D1", "D2", "D3"]);
+        assert_eq!(duties[0].1.rows[0].block.as_deref(), Some("B2"));
+    }
+
+    #[test]
+    fn test_extract_block_and_duty() {
+        let row = |block: &str, duty: &str, start: &str| ScheduleRow {
+            duty_id: Some(duty.to_string()),
+            ..sample_row(block, Some("T1"), start)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("B1", "D1", "08:00:00"),
+            row("B2", "D1", "09:00:00"),
+            row("B1", "D2", "10:00:00"),
+        ]);
+        schedule.metadata.name = Some("Weekday".to_string());
+
+        let block = schedule.extract_block("B1").unwrap();
+        assert_eq!(block.len(), 2);
+        assert_eq!(block.block_ids(), ["B1"]);
+        assert_eq!(block.metadata.name.as_deref(), Some("Weekday"));
+        assert_eq!(block.metadata.extracted.as_deref(), Some("block B1"));
+
+        let mut duty = schedule.extract_duty("D1").unwrap();
+        assert_eq!(duty.block_ids(), ["B1", "B2"]);
+        assert_eq!(duty.duties().len(), 1);
+        assert_eq!(duty.metadata.extracted.as_deref(), Some("duty D1"));
+
+        assert!(schedule.extract_block("B9").is_none());
+        assert!(schedule.extract_duty("D9").is_none());
+        assert_eq!(schedule.metadata.extracted, None);
+    }
+
+    #[test]
+    fn test_assign_block_sequences() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B2", Some("T3"), "08:30:00"),
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T3"), "10:00:00"),
+        ]);
+        schedule.rows.push(ScheduleRow::default());
+
+        schedule.assign_block_sequences();
+
+        let seqs: Vec<Option<u32>> = schedule.rows.iter().map(|r| r.block_sequence).collect();
+        assert_eq!(seqs, vec![Some(2), Some(1), Some(1), Some(3), None]);
+
+        // Sequences agree with the derived block order
+        let block = schedule.get_block("B1").unwrap();
+        for (seq, row) in block.sequenced_rows() {
+            assert_eq!(row.block_sequence, Some(seq));
+        }
+    }
+
+    #[test]
+    fn test_route_summaries() {
+        let mut rows = vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B2", Some("T2"), "07:30:00"),
+            sample_row("B1", Some("T3"), "09:00:00"),
+            sample_row("B1", None, "10:00:00"),
+            sample_row("B3", Some("T4"), "08:15:00"),
+        ];
+        for (row, end) in rows
+            .iter_mut()
+            .zip(["08:40:00", "08:00:00", "09:30:00", "10:20:00", "09:00:00"])
+        {
+            row.end_time = Some(end.to_string());
+        }
+        for row in &mut rows[..4] {
+            row.route_short_name = Some("10".to_string());
+        }
+        rows[4].route_short_name = Some("20".to_string());
+
+        let summaries = Schedule::from_rows(rows).route_summaries();
+        assert_eq!(summaries.len(), 2);
+
+        let route = &summaries["10"];
+        assert_eq!(route.trip_count, 3);
+        assert_eq!(route.first_departure_seconds, Some(7 * 3600 + 1800));
+        assert_eq!(route.last_arrival_seconds, Some(9 * 3600 + 1800));
+        assert_eq!(route.revenue_time_seconds, 40 * 60 + 30 * 60 + 30 * 60);
+        assert_eq!(route.blocks, vec!["B1".to_string(), "B2".to_string()]);
+
+        assert_eq!(summaries["20"].trip_count, 1);
+    }
+
+    #[test]
+    fn test_deduplicate_rows() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B2", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T1"), "08:00:00"),
+        ]);
+        let mut layover = sample_row("B1", Some("T1"), "08:00:00");
+        layover.row_type = RowType::Layover;
+        schedule.rows.push(layover);
+
+        assert_eq!(schedule.duplicate_row_indices(), vec![2, 4]);
+        assert_eq!(schedule.len(), 6);
+
+        assert_eq!(schedule.deduplicate_rows(), 2);
+        assert_eq!(schedule.len(), 4);
+        assert!(schedule.duplicate_row_indices().is_empty());
+        assert_eq!(schedule.deduplicate_rows(), 0);
+    }
+
+    #[test]
+    fn test_sorting() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B2", Some("T4"), "07:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B2", Some("T3"), "06:00:00"),
+        ]);
+        let mut unassigned = sample_row("B1", Some("T0"), "05:00:00");
+        unassigned.block = None;
+        schedule.rows.insert(0, unassigned);
+
+        // Populate caches so we can check they are rebuilt
+        assert_eq!(schedule.blocks().len(), 2);
+
+        schedule.sort_by_block_and_time();
+        let trips: Vec<&str> = schedule
+            .rows
+            .iter()
+            .map(|r| r.trip_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(trips, vec!["T1", "T2", "T3", "T4", "T0"]);
+
+        schedule.sort_by_time();
+        let trips: Vec<&str> = schedule
+            .rows
+            .iter()
+            .map(|r| r.trip_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(trips, vec!["T0", "T3", "T4", "T1", "T2"]);
+
+        schedule.rows.clear();
+        schedule.sort_by_time();
+        assert!(schedule.blocks().is_empty());
+    }
+
+    #[test]
+    fn test_index_by_trip_id() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", None, "09:00:00"),
+            sample_row("B2", Some("T2"), "08:30:00"),
+            sample_row("B3", Some("T1"), "10:00:00"),
+            sample_row("B3", Some(""), "11:00:00"),
+        ]);
+
+        let index = schedule.index_by_trip_id();
+        assert_eq!(index.len(), 2);
+        // Duplicates point at the first occurrence
+        assert_eq!(index["T1"], 0);
+        assert_eq!(index["T2"], 2);
+        assert_eq!(
+            schedule.row_for_trip("T2").unwrap().block.as_deref(),
+            Some("B2")
+        );
+        assert!(schedule.row_for_trip("T9").is_none());
+
+        // The index is rebuilt after a mutation
+        schedule.sort_by_time();
+        assert_eq!(schedule.index_by_trip_id()["T2"], 1);
+        schedule.add_row(sample_row("B4", Some("T9"), "12:00:00"));
+        assert_eq!(
+            schedule.row_for_trip("T9").unwrap().block.as_deref(),
+            Some("B4")
+        );
+    }
+
+    #[test]
+    fn test_move_trip() {
+        let mut schedule = Schedule::from_rows(vec![
+            sample_row("B1", Some("T1"), "08:00:00"),
+            sample_row("B1", Some("T2"), "09:00:00"),
+            sample_row("B2", Some("T3"), "08:30:00"),
+            sample_row("B2", Some("T3"), "10:30:00"),
+        ]);
+        schedule.assign_block_sequences();
+        assert_eq!(schedule.blocks()["B1"].revenue_trip_count(), 2);
+
+        schedule.move_trip("T2", "B2").unwrap();
+        assert_eq!(schedule.blocks()["B1"].revenue_trip_count(), 1);
+        assert_eq!(schedule.blocks()["B2"].revenue_trip_count(), 3);
+        assert_eq!(schedule.rows[1].block_sequence, None);
+
+        schedule.move_trip("T1", "B9").unwrap();
+        assert!(!schedule.blocks().contains_key("B1"));
+        assert!(schedule.blocks().contains_key("B9"));
+
+        assert!(schedule.move_trip("T8", "B1").is_err());
+        let err = schedule.move_trip("T3", "B1").unwrap_err();
+        assert!(err.to_string().contains("2 revenue rows"));
+        assert_eq!(schedule.rows_for_block("B2").len(), 3);
+    }
+
+    #[test]
+    fn test_split_block() {
+        let timed = |trip: &str, start: &str, end: &str| ScheduleRow {
+            end_time: Some(end.to_string()),
+            ..sample_row("B1", Some(trip), start)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            timed("T2", "09:00:00", "10:00:00"),
+            timed("T1", "08:00:00", "09:00:00"),
+            timed("T3", "10:30:00", "11:30:00"),
+            sample_row("B2", Some("T4"), "08:00:00"),
+        ]);
+        schedule.assign_block_sequences();
+        assert_eq!(schedule.blocks().len(), 2);
+
+        // Mid-trip boundaries and one-sided splits leave the schedule alone
+        assert!(schedule.split_block("B1", 9 * 3600 + 1800).is_err());
+        assert!(schedule.split_block("B1", 12 * 3600).is_err());
+        assert!(schedule.split_block("B9", 9 * 3600).is_err());
+        assert_eq!(schedule.rows_for_block("B1").len(), 3);
+
+        let (first, second) = schedule.split_block("B1", 10 * 3600).unwrap();
+        assert_eq!((first.as_str(), second.as_str()), ("B1a", "B1b"));
+        assert!(schedule.rows_for_block("B1").is_empty());
+
+        let blocks = schedule.blocks();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks["B1a"].revenue_trip_count(), 2);
+        assert_eq!(blocks["B1b"].revenue_trip_count(), 1);
+
+        let sequences: Vec<(Option<&str>, Option<u32>)> = schedule
+            .rows
+            .iter()
+            .map(|r| (r.trip_id.as_deref(), r.block_sequence))
+            .collect();
+        assert_eq!(
+            sequences,
+            vec![
+                (Some("T2"), Some(2)),
+                (Some("T1"), Some(1)),
+                (Some("T3"), Some(1)),
+                (Some("T4"), Some(1)),
+            ]
+        );
+
+        // A block already using one of the new IDs prevents the split
+        schedule.rows[3].block = Some("B2a".to_string());
+        schedule.rows.push(sample_row("B2", Some("T5"), "09:00:00"));
+        assert!(schedule.split_block("B2", 8 * 3600 + 1800).is_err());
+    }
+
+    #[test]
+    fn test_split_block_and_move_trip_composite_key() {
+        let row = |depot: &str, trip: &str, start: &str| ScheduleRow {
+            depot: Some(depot.to_string()),
+            ..sample_row("1", Some(trip), start)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("NORTH", "T1", "08:00:00"),
+            row("NORTH", "T2", "10:00:00"),
+            row("SOUTH", "T3", "08:00:00"),
+        ]);
+        schedule.derive_blocks_by(BlockKey::depot_block());
+
+        let (first, second) = schedule.split_block("NORTH:1", 9 * 3600).unwrap();
+        assert_eq!((first.as_str(), second.as_str()), ("NORTH:1a", "NORTH:1b"));
+        assert_eq!(schedule.block_ids(), ["NORTH:1a", "NORTH:1b", "SOUTH:1"]);
+        assert_eq!(schedule.rows[2].block.as_deref(), Some("1"));
+
+        // The moved row takes the target's depot as well as its block
+        schedule.move_trip("T2", "SOUTH:1").unwrap();
+        assert_eq!(schedule.rows[1].depot.as_deref(), Some("SOUTH"));
+        assert_eq!(schedule.rows_for_block("SOUTH:1").len(), 2);
+        assert!(schedule.move_trip("T1", "EAST:1").is_err());
+
+        schedule.derive_blocks_by(BlockKey::from_fields(["depot"]).unwrap());
+        assert!(schedule.split_block("SOUTH", 9 * 3600).is_err());
+    }
+
+    #[test]
+    fn test_sort_and_route_summaries_composite_key() {
+        let row = |depot: Option<&str>, trip: &str, start: &str| ScheduleRow {
+            depot: depot.map(String::from),
+            route_short_name: Some("10".to_string()),
+            ..sample_row("1", Some(trip), start)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row(Some("SOUTH"), "T1", "08:00:00"),
+            row(None, "T2", "07:00:00"),
+            row(Some("NORTH"), "T3", "09:00:00"),
+            row(Some("SOUTH"), "T4", "06:00:00"),
+        ]);
+        schedule.derive_blocks_by(BlockKey::depot_block());
+        assert_eq!(schedule.rows_moved_by_sort(), 4);
+
+        // A row without a depot has no block under the key and goes last
+        schedule.sort_by_block_and_time();
+        let trips: Vec<_> = schedule
+            .rows
+            .iter()
+            .map(|r| r.trip_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(trips, ["T3", "T4", "T1", "T2"]);
+        assert_eq!(schedule.rows_moved_by_sort(), 0);
+
+        let summaries = schedule.route_summaries();
+        assert_eq!(summaries["10"].blocks, ["NORTH:1", "SOUTH:1"]);
+    }
+
+    #[test]
+    fn test_peak_vehicle_count() {
+        let row = |block: &str, start: &str, end: &str| ScheduleRow {
+            end_time: Some(end.to_string()),
+            ..sample_row(block, Some("T"), start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            row("B1", "06:00:00", "08:00:00"),
+            row("B1", "08:00:00", "12:00:00"),
+            row("B2", "07:00:00", "09:00:00"),
+            row("B3", "09:00:00", "10:00:00"),
+            row("B4", "07:30:00", "07:45:00"),
+        ]);
+
+        // B1, B2 and B4 overlap from 07:30; B3 starts as B2 ends
+        assert_eq!(schedule.peak_vehicle_count(), (3, 7 * 3600 + 1800));
+
+        assert_eq!(schedule.vehicles_in_service_at(5 * 3600), 0);
+        assert_eq!(schedule.vehicles_in_service_at(7 * 3600 + 1800), 3);
+        assert_eq!(schedule.vehicles_in_service_at(9 * 3600), 2);
+        assert_eq!(schedule.vehicles_in_service_at(12 * 3600), 0);
+
+        assert_eq!(Schedule::new().peak_vehicle_count(), (0, 0));
+    }
+
+    #[test]
+    fn test_peak_vehicle_count_zero_length_block() {
+        let row = |block: &str, start: &str, end: &str| ScheduleRow {
+            end_time: Some(end.to_string()),
+            ..sample_row(block, Some("T"), start)
+        };
+        let schedule = Schedule::from_rows(vec![row("B1", "08:00:00", "08:00:00")]);
+        assert_eq!(schedule.peak_vehicle_count(), (0, 0));
+
+        let schedule = Schedule::from_rows(vec![
+            row("B1", "08:00:00", "08:00:00"),
+            row("B2", "07:00:00", "09:00:00"),
+        ]);
+        assert_eq!(schedule.peak_vehicle_count(), (1, 7 * 3600));
+    }
+
+    #[test]
+    fn test_rows_active_at() {
+        let row = |trip: &str, from: &str, to: &str, start: &str, end: Option<&str>| ScheduleRow {
+            start_place: Some(from.to_string()),
+            end_place: Some(to.to_string()),
+            end_time: end.map(String::from),
+            ..sample_row("B1", Some(trip), start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            row("T1", "CEN", "NTH", "08:00:00", Some("08:15:00")),
+            row("T2", "NTH", "CEN", "08:15:00", Some("08:40:00")),
+            row("T3", "STH", "EST", "08:00:00", Some("09:00:00")),
+            row("T4", "CEN", "STH", "08:15:00", None),
+            row("T5", "CEN", "STH", "08:20:00", None),
+        ]);
+        let trips = |rows: Vec<&ScheduleRow>| -> Vec<String> {
+            rows.iter().filter_map(|r| r.trip_id.clone()).collect()
+        };
+
+        let at = 8 * 3600 + 15 * 60;
+        assert_eq!(
+            trips(schedule.rows_active_at(None, at)),
+            vec!["T1", "T2", "T3", "T4"]
+        );
+        assert_eq!(
+            trips(schedule.rows_active_at(Some("CEN"), at)),
+            vec!["T1", "T2", "T4"]
+        );
+        assert!(schedule.rows_active_at(Some("XYZ"), at).is_empty());
+        assert!(schedule.rows_active_at(None, 7 * 3600).is_empty());
+    }
+
+    #[test]
+    fn test_bounding_box_and_centroid() {
+        assert_eq!(Schedule::new().bounding_box(), None);
+        assert_eq!(Schedule::new().centroid(), None);
+
+        let mut partial = sample_row("B1", Some("T1"), "08:00:00");
+        partial.start_lat = Some(51.0);
+        partial.end_lat = Some(52.0);
+        partial.end_lon = Some(-1.0);
+        let mut full = sample_row("B1", Some("T2"), "09:00:00");
+        full.start_lat = Some(50.0);
+        full.start_lon = Some(0.5);
+        full.end_lat = Some(f64::NAN);
+        full.end_lon = Some(0.0);
+        let schedule = Schedule::from_rows(vec![
+            partial,
+            sample_row("B1", Some("T3"), "10:00:00"),
+            full,
+        ]);
+
+        // The start of T1 lacks a longitude and the end of T2 isn't finite
+        assert_eq!(schedule.bounding_box(), Some((50.0, -1.0, 52.0, 0.5)));
+        assert_eq!(schedule.centroid(), Some((51.0, -0.25)));
+    }
+
+    #[test]
+    fn test_departure_histogram_and_service_span() {
+        let row = |trip: &str, start: &str, end: &str| ScheduleRow {
+            end_time: Some(end.to_string()),
+            ..sample_row("B1", Some(trip), start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            row("T1", "06:10:00", "06:50:00"),
+            row("T2", "06:40:00", "07:20:00"),
+            row("T3", "08:05:00", "08:45:00"),
+            row("T4", "23:50:00", "00:30:00"), // runs past midnight
+            sample_row("B1", None, "05:00:00"), // deadhead, not counted
+        ]);
+
+        let histogram = schedule.departure_histogram(3600, None);
+        assert_eq!(
+            histogram,
+            vec![(6 * 3600, 2), (8 * 3600, 1), (23 * 3600, 1)]
+        );
+
+        // One-second buckets stay sparse however wide the span.
+        let histogram = schedule.departure_histogram(1, None);
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[3], (23 * 3600 + 50 * 60, 1));
+
+        assert_eq!(
+            schedule.service_span(),
+            Some((6 * 3600 + 600, 24 * 3600 + 1800))
+        );
+
+        assert!(Schedule::new().departure_histogram(3600, None).is_empty());
+        assert!(schedule.departure_histogram(0, None).is_empty());
+        assert_eq!(Schedule::new().service_span(), None);
+    }
+
+    #[test]
+    fn test_departure_histogram_by_route() {
+        let row = |trip: &str, route: &str, start: &str| ScheduleRow {
+            route_short_name: Some(route.to_string()),
+            ..sample_row("B1", Some(trip), start)
+        };
+        let schedule = Schedule::from_rows(vec![
+            row("T1", "10", "06:10:00"),
+            row("T2", "20", "06:40:00"),
+            row("T3", "10", "08:05:00"),
+            sample_row("B2", Some("T4"), "07:00:00"), // no route
+        ]);
+
+        assert_eq!(
+            schedule.departure_histogram(3600, Some("10")),
+            vec![(6 * 3600, 1), (8 * 3600, 1)]
+        );
+        assert_eq!(
+            schedule.departure_histogram(3600, Some("20")),
+            vec![(6 * 3600, 1)]
+        );
+        assert!(schedule.departure_histogram(3600, Some("99")).is_empty());
+        assert_eq!(
+            schedule.departure_histogram(3600, None),
+            vec![(6 * 3600, 2), (7 * 3600, 1), (8 * 3600, 1)]
+        );
     }
 }
@@ -1,9 +1,13 @@
 //! Schedule row model - the primary artifact of a schedule.
 
+use super::deadhead::haversine_distance;
+use crate::time::parse_time_to_seconds;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use transit_core::ParseError;
 
 /// Type of schedule row indicating what kind of movement it represents.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum RowType {
     /// Revenue service trip (has a trip_id).
@@ -23,6 +27,24 @@ pub enum RowType {
     Layover,
 }
 
+impl FromStr for RowType {
+    type Err = ParseError;
+
+    /// Parse a row type from its name or a common alias (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "revenue" | "trip" | "service" => Ok(RowType::Revenue),
+            "pull_out" | "pullout" | "pull-out" | "po" => Ok(RowType::PullOut),
+            "pull_in" | "pullin" | "pull-in" | "pi" => Ok(RowType::PullIn),
+            "deadhead" | "dh" | "dead" | "non_revenue" => Ok(RowType::Deadhead),
+            "break" | "brk" | "meal" => Ok(RowType::Break),
+            "relief" | "changeover" | "swap" => Ok(RowType::Relief),
+            "layover" | "wait" | "dwell" => Ok(RowType::Layover),
+            _ => Err(ParseError::InvalidData(format!("Unknown row type: {}", s))),
+        }
+    }
+}
+
 /// A single row in a schedule file.
 ///
 /// This represents one movement or activity in the schedule, which could be:
@@ -36,7 +58,7 @@ pub enum RowType {
 /// trip_id, depot, vehicle_class, vehicle_type,
 /// start_lat, start_lon, end_lat, end_lon, route_shape_id
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ScheduleRow {
     /// Run number (driver assignment identifier).
     pub run_number: Option<String>,
@@ -98,6 +120,13 @@ pub struct ScheduleRow {
 
     /// Headsign/destination.
     pub headsign: Option<String>,
+
+    /// Service (calendar) identifier, e.g. a weekday or Saturday variant.
+    /// `None` for single-day schedules.
+    pub service_id: Option<String>,
+
+    /// 1-based chronological position of this row within its block.
+    pub block_sequence: Option<u32>,
 }
 
 impl ScheduleRow {
@@ -107,8 +136,20 @@ impl ScheduleRow {
     }
 
     /// Check if this is a revenue (passenger-carrying) trip.
+    ///
+    /// Revenue rows need a [valid trip ID](Self::has_valid_trip_id).
     pub fn is_revenue(&self) -> bool {
-        self.row_type == RowType::Revenue && self.trip_id.is_some()
+        self.row_type == RowType::Revenue && self.has_valid_trip_id()
+    }
+
+    /// Check that the trip ID is set and not blank.
+    ///
+    /// The reader trims values, but rows built in code may carry an empty or
+    /// whitespace-only ID.
+    pub fn has_valid_trip_id(&self) -> bool {
+        self.trip_id
+            .as_deref()
+            .is_some_and(|trip_id| !trip_id.trim().is_empty())
     }
 
     /// Check if this is any type of deadhead movement.
@@ -124,6 +165,21 @@ impl ScheduleRow {
         matches!(self.row_type, RowType::Break | RowType::Relief)
     }
 
+    /// Check if this is a layover (the vehicle waiting in place).
+    pub fn is_layover(&self) -> bool {
+        self.row_type == RowType::Layover
+    }
+
+    /// Straight-line distance between start and end coordinates in meters.
+    pub fn distance_meters(&self) -> Option<f64> {
+        match (self.start_lat, self.start_lon, self.end_lat, self.end_lon) {
+            (Some(lat1), Some(lon1), Some(lat2), Some(lon2)) => {
+                Some(haversine_distance(lat1, lon1, lat2, lon2))
+            }
+            _ => None,
+        }
+    }
+
     /// Parse start_time as seconds since midnight.
     pub fn start_time_seconds(&self) -> Option<u32> {
         self.start_time
@@ -147,72 +203,33 @@ impl ScheduleRow {
     }
 }
 
-/// Parse a time string to seconds since midnight.
-///
-/// Supports formats:
-/// - HH:MM:SS (e.g., "14:30:00")
-/// - HH:MM (e.g., "14:30")
-/// - Seconds as integer (e.g., "52200")
-fn parse_time_to_seconds(time: &str) -> Option<u32> {
-    // Try parsing as plain seconds first
-    if let Ok(secs) = time.parse::<u32>() {
-        return Some(secs);
-    }
-
-    // Try HH:MM:SS or HH:MM format
-    let parts: Vec<&str> = time.split(':').collect();
-    match parts.len() {
-        3 => {
-            let hours: u32 = parts[0].parse().ok()?;
-            let minutes: u32 = parts[1].parse().ok()?;
-            let seconds: u32 = parts[2].parse().ok()?;
-            Some(hours * 3600 + minutes * 60 + seconds)
-        }
-        2 => {
-            let hours: u32 = parts[0].parse().ok()?;
-            let minutes: u32 = parts[1].parse().ok()?;
-            Some(hours * 3600 + minutes * 60)
-        }
-        _ => None,
-    }
-}
-
-/// Convert seconds since midnight to HH:MM:SS format.
-pub fn seconds_to_time_string(seconds: u32) -> String {
-    let hours = seconds / 3600;
-    let minutes = (seconds % 3600) / 60;
-    let secs = seconds % 60;
-    format!("{:02}:{:02}:{:02}", hours, minutes, secs)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_time_hhmmss() {
-        assert_eq!(parse_time_to_seconds("14:30:00"), Some(52200));
-        assert_eq!(parse_time_to_seconds("00:00:00"), Some(0));
-        assert_eq!(parse_time_to_seconds("25:00:00"), Some(90000)); // Next day
+    fn test_row_type_from_str() {
+        assert_eq!("pull_out".parse::<RowType>().unwrap(), RowType::PullOut);
+        assert_eq!("PullIn".parse::<RowType>().unwrap(), RowType::PullIn);
+        assert_eq!("meal".parse::<RowType>().unwrap(), RowType::Break);
+        assert!("bogus".parse::<RowType>().is_err());
     }
 
     #[test]
-    fn test_parse_time_hhmm() {
-        assert_eq!(parse_time_to_seconds("14:30"), Some(52200));
-        assert_eq!(parse_time_to_seconds("00:00"), Some(0));
-    }
-
-    #[test]
-    fn test_parse_time_seconds() {
-        assert_eq!(parse_time_to_seconds("52200"), Some(52200));
-        assert_eq!(parse_time_to_seconds("0"), Some(0));
-    }
+    fn test_blank_trip_id_is_not_revenue() {
+        let row = |trip_id: Option<&str>| ScheduleRow {
+            trip_id: trip_id.map(String::from),
+            ..Default::default()
+        };
+        assert!(row(Some("T1")).is_revenue());
+        for trip_id in [None, Some(""), Some("   ")] {
+            assert!(!row(trip_id).has_valid_trip_id());
+            assert!(!row(trip_id).is_revenue());
+        }
 
-    #[test]
-    fn test_seconds_to_time_string() {
-        assert_eq!(seconds_to_time_string(52200), "14:30:00");
-        assert_eq!(seconds_to_time_string(0), "00:00:00");
-        assert_eq!(seconds_to_time_string(90000), "25:00:00");
+        let schedule = crate::Schedule::from_rows(vec![row(Some("T1")), row(Some(""))]);
+        assert_eq!(schedule.trip_ids(), vec!["T1"]);
+        assert_eq!(schedule.summary().revenue_trips, 1);
     }
 
     #[test]
@@ -0,0 +1,240 @@
+//! Renumbering of block IDs and run numbers into one consistent scheme.
+
+use crate::models::ScheduleRow;
+use std::collections::{HashMap, HashSet};
+
+/// Options for [`Schedule::normalize_ids`](crate::Schedule::normalize_ids).
+#[derive(Debug, Clone)]
+pub struct NormalizeIdsOptions {
+    /// Renumber block IDs (default: true).
+    pub normalize_blocks: bool,
+    /// Renumber run numbers (default: true).
+    pub normalize_runs: bool,
+    /// Prefix of new block IDs (default: `"B"`).
+    pub block_prefix: String,
+    /// Prefix of new run numbers (default: `"R"`).
+    pub run_prefix: String,
+    /// Digits the number is zero-padded to (default: 4, giving `B0001`).
+    /// Numbers that need more digits are written in full.
+    pub width: usize,
+}
+
+impl Default for NormalizeIdsOptions {
+    fn default() -> Self {
+        Self {
+            normalize_blocks: true,
+            normalize_runs: true,
+            block_prefix: "B".to_string(),
+            run_prefix: "R".to_string(),
+            width: 4,
+        }
+    }
+}
+
+impl NormalizeIdsOptions {
+    /// Create default options (both fields, `B0001` and `R0001`).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether block IDs are renumbered.
+    pub fn with_normalize_blocks(mut self, normalize: bool) -> Self {
+        self.normalize_blocks = normalize;
+        self
+    }
+
+    /// Set whether run numbers are renumbered.
+    pub fn with_normalize_runs(mut self, normalize: bool) -> Self {
+        self.normalize_runs = normalize;
+        self
+    }
+
+    /// Set the prefix of new block IDs.
+    pub fn with_block_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.block_prefix = prefix.into();
+        self
+    }
+
+    /// Set the prefix of new run numbers.
+    pub fn with_run_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.run_prefix = prefix.into();
+        self
+    }
+
+    /// Set the zero-padded width of the number.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+/// What [`Schedule::normalize_ids`](crate::Schedule::normalize_ids) renamed,
+/// as original -> new value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdMapping {
+    /// Original block ID -> new block ID (empty unless blocks were renumbered).
+    pub blocks: HashMap<String, String>,
+    /// Original run number -> new run number (empty unless runs were
+    /// renumbered). Duty IDs equal to an original run number were renamed
+    /// the same way.
+    pub run_numbers: HashMap<String, String>,
+}
+
+/// Numbers distinct values in order of first appearance, skipping any
+/// candidate that is reserved.
+struct Renumberer<'a> {
+    prefix: &'a str,
+    width: usize,
+    reserved: HashSet<String>,
+    next_number: usize,
+    mapping: HashMap<String, String>,
+}
+
+impl<'a> Renumberer<'a> {
+    fn new(prefix: &'a str, width: usize, reserved: HashSet<String>) -> Self {
+        Self {
+            prefix,
+            width,
+            reserved,
+            next_number: 1,
+            mapping: HashMap::new(),
+        }
+    }
+
+    fn assign(&mut self, original: &str) {
+        if self.mapping.contains_key(original) {
+            return;
+        }
+        let new_id = loop {
+            let candidate = format!(
+                "{}{:0width$}",
+                self.prefix,
+                self.next_number,
+                width = self.width
+            );
+            self.next_number += 1;
+            if !self.reserved.contains(&candidate) {
+                break candidate;
+            }
+        };
+        self.mapping.insert(original.to_string(), new_id);
+    }
+}
+
+/// Rename `value` through `mapping`, leaving unmapped values alone.
+fn rename(value: &mut Option<String>, mapping: &HashMap<String, String>) {
+    if let Some(new_id) = value.as_deref().and_then(|v| mapping.get(v)) {
+        *value = Some(new_id.clone());
+    }
+}
+
+/// Renumber block IDs and run numbers in place, returning what changed.
+pub(crate) fn normalize_ids(rows: &mut [ScheduleRow], options: &NormalizeIdsOptions) -> IdMapping {
+    let mut mapping = IdMapping::default();
+
+    if options.normalize_blocks {
+        let mut blocks = Renumberer::new(&options.block_prefix, options.width, HashSet::new());
+        for block in rows.iter().filter_map(|r| r.block.as_deref()) {
+            blocks.assign(block);
+        }
+        mapping.blocks = blocks.mapping;
+    }
+
+    if options.normalize_runs {
+        let runs: HashSet<&str> = rows
+            .iter()
+            .filter_map(|r| r.run_number.as_deref())
+            .collect();
+        // Duties keyed by something other than a run number keep their ID,
+        // so new run numbers must not collide with them
+        let reserved: HashSet<String> = rows
+            .iter()
+            .filter_map(|r| r.duty_id.as_deref())
+            .filter(|duty| !runs.contains(duty))
+            .map(String::from)
+            .collect();
+        let mut renumberer = Renumberer::new(&options.run_prefix, options.width, reserved);
+        for run in rows.iter().filter_map(|r| r.run_number.as_deref()) {
+            renumberer.assign(run);
+        }
+        mapping.run_numbers = renumberer.mapping;
+    }
+
+    for row in rows.iter_mut() {
+        rename(&mut row.block, &mapping.blocks);
+        rename(&mut row.run_number, &mapping.run_numbers);
+        rename(&mut row.duty_id, &mapping.run_numbers);
+    }
+
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_row(block: &str, run: Option<&str>, duty: Option<&str>) -> ScheduleRow {
+        ScheduleRow {
+            block: Some(block.to_string()),
+            run_number: run.map(String::from),
+            duty_id: duty.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_ids() {
+        let mut rows = vec![
+            make_row("block-0007", Some("12"), Some("12")),
+            make_row("VEH_12", Some("12"), Some("12")),
+            make_row("block-0007", Some("run 3"), Some("R0001")),
+            make_row("B1", None, None),
+        ];
+
+        let mapping = normalize_ids(&mut rows, &NormalizeIdsOptions::new());
+        assert_eq!(mapping.blocks.len(), 3);
+        assert_eq!(mapping.blocks["block-0007"], "B0001");
+        assert_eq!(mapping.blocks["VEH_12"], "B0002");
+        assert_eq!(mapping.blocks["B1"], "B0003");
+        // R0001 is taken by a duty that isn't a run number
+        assert_eq!(mapping.run_numbers["12"], "R0002");
+        assert_eq!(mapping.run_numbers["run 3"], "R0003");
+
+        let ids: Vec<(Option<&str>, Option<&str>, Option<&str>)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.block.as_deref(),
+                    r.run_number.as_deref(),
+                    r.duty_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (Some("B0001"), Some("R0002"), Some("R0002")),
+                (Some("B0002"), Some("R0002"), Some("R0002")),
+                (Some("B0001"), Some("R0003"), Some("R0001")),
+                (Some("B0003"), None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_options() {
+        let mut rows = vec![
+            make_row("X", Some("1"), None),
+            make_row("Y", Some("2"), None),
+        ];
+        let options = NormalizeIdsOptions::new()
+            .with_normalize_runs(false)
+            .with_block_prefix("BLK-")
+            .with_width(0);
+
+        let mapping = normalize_ids(&mut rows, &options);
+        assert!(mapping.run_numbers.is_empty());
+        assert_eq!(rows[1].block.as_deref(), Some("BLK-2"));
+        assert_eq!(rows[1].run_number.as_deref(), Some("2"));
+    }
+}
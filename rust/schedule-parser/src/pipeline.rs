@@ -0,0 +1,282 @@
+//! One-call cleanup of a schedule using the standard transformation order.
+
+use crate::deadhead::{inferrer::InferenceConfig, DeadheadInferrer};
+use crate::models::{DeadheadInferenceResult, Schedule};
+use gtfs_parser::GtfsFeed;
+
+/// A transformation step run by [`SchedulePipeline`], in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStep {
+    /// [`Schedule::normalize_overnight_times`].
+    NormalizeOvernight,
+    /// [`Schedule::sort_by_block_and_time`].
+    Sort,
+    /// [`DeadheadInferrer::infer_and_apply`].
+    InferDeadheads,
+    /// [`Schedule::backfill_coordinates`] (needs GTFS).
+    BackfillCoordinates,
+    /// [`Schedule::assign_block_sequences`].
+    AssignSequences,
+}
+
+impl PipelineStep {
+    /// Snake-case name of the step.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStep::NormalizeOvernight => "normalize_overnight",
+            PipelineStep::Sort => "sort",
+            PipelineStep::InferDeadheads => "infer_deadheads",
+            PipelineStep::BackfillCoordinates => "backfill_coordinates",
+            PipelineStep::AssignSequences => "assign_sequences",
+        }
+    }
+}
+
+/// What a [`SchedulePipeline`] run changed.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    /// Steps that ran, in order.
+    pub steps_run: Vec<PipelineStep>,
+    /// Rows whose end time was moved past midnight.
+    pub overnight_rows_normalized: usize,
+    /// Deadhead rows added by inference.
+    pub deadheads_added: usize,
+    /// Rows that had coordinates filled from GTFS stops.
+    pub coordinates_backfilled: usize,
+    /// Rows given a block sequence number.
+    pub sequences_assigned: usize,
+    /// Full inference result, if inference ran.
+    pub inference: Option<DeadheadInferenceResult>,
+}
+
+/// Chains the standard schedule transformations and runs them in order.
+///
+/// The order is fixed because later steps depend on earlier ones:
+///
+/// 1. Normalize overnight times, so durations and ordering are correct
+///    across midnight before anything reads them.
+/// 2. Sort by block and time.
+/// 3. Infer deadheads (pull-outs, pull-ins, interlinings) and add them as
+///    rows. The schedule is re-sorted afterwards if sorting is enabled.
+/// 4. Backfill missing coordinates from GTFS stops, which also makes row
+///    distances available. Skipped without a GTFS feed.
+/// 5. Assign block sequence numbers over the final rows.
+///
+/// Every step is enabled by default and can be turned off individually.
+///
+/// ```rust,ignore
+/// let report = SchedulePipeline::new()
+///     .with_gtfs(&gtfs)
+///     .with_inference_config(InferenceConfig::new().with_default_depot("DEPOT"))
+///     .run(&mut schedule);
+/// println!("added {} deadheads", report.deadheads_added);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchedulePipeline<'a> {
+    normalize_overnight: bool,
+    sort: bool,
+    infer_deadheads: bool,
+    backfill_coordinates: bool,
+    assign_sequences: bool,
+    inference_config: InferenceConfig,
+    gtfs: Option<&'a GtfsFeed>,
+}
+
+impl Default for SchedulePipeline<'_> {
+    fn default() -> Self {
+        Self {
+            normalize_overnight: true,
+            sort: true,
+            infer_deadheads: true,
+            backfill_coordinates: true,
+            assign_sequences: true,
+            inference_config: InferenceConfig::default(),
+            gtfs: None,
+        }
+    }
+}
+
+impl<'a> SchedulePipeline<'a> {
+    /// Create a pipeline with every step enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether overnight end times are normalized.
+    pub fn with_normalize_overnight(mut self, enabled: bool) -> Self {
+        self.normalize_overnight = enabled;
+        self
+    }
+
+    /// Set whether rows are sorted by block and time.
+    pub fn with_sort(mut self, enabled: bool) -> Self {
+        self.sort = enabled;
+        self
+    }
+
+    /// Set whether missing deadheads are inferred and added.
+    pub fn with_infer_deadheads(mut self, enabled: bool) -> Self {
+        self.infer_deadheads = enabled;
+        self
+    }
+
+    /// Set whether missing coordinates are filled from GTFS stops.
+    pub fn with_backfill_coordinates(mut self, enabled: bool) -> Self {
+        self.backfill_coordinates = enabled;
+        self
+    }
+
+    /// Set whether block sequence numbers are assigned.
+    pub fn with_assign_sequences(mut self, enabled: bool) -> Self {
+        self.assign_sequences = enabled;
+        self
+    }
+
+    /// Set the configuration used for deadhead inference.
+    pub fn with_inference_config(mut self, config: InferenceConfig) -> Self {
+        self.inference_config = config;
+        self
+    }
+
+    /// Use a GTFS feed for stop coordinates during inference and backfill.
+    pub fn with_gtfs(mut self, gtfs: &'a GtfsFeed) -> Self {
+        self.gtfs = Some(gtfs);
+        self
+    }
+
+    /// Run the enabled steps in order on `schedule`.
+    pub fn run(&self, schedule: &mut Schedule) -> PipelineReport {
+        let mut report = PipelineReport::default();
+
+        if self.normalize_overnight {
+            report.overnight_rows_normalized = schedule.normalize_overnight_times();
+            report.steps_run.push(PipelineStep::NormalizeOvernight);
+        }
+
+        if self.sort {
+            schedule.sort_by_block_and_time();
+            report.steps_run.push(PipelineStep::Sort);
+        }
+
+        if self.infer_deadheads {
+            let config = self.inference_config.clone();
+            let inferrer = match self.gtfs {
+                Some(gtfs) => DeadheadInferrer::with_gtfs(config, gtfs),
+                None => DeadheadInferrer::new(config),
+            };
+            let result = inferrer.infer_and_apply(schedule);
+            report.deadheads_added = result.total_count();
+            report.inference = Some(result);
+            report.steps_run.push(PipelineStep::InferDeadheads);
+
+            if self.sort && report.deadheads_added > 0 {
+                schedule.sort_by_block_and_time();
+            }
+        }
+
+        if self.backfill_coordinates {
+            if let Some(gtfs) = self.gtfs {
+                report.coordinates_backfilled = schedule.backfill_coordinates(gtfs);
+                report.steps_run.push(PipelineStep::BackfillCoordinates);
+            }
+        }
+
+        if self.assign_sequences {
+            schedule.assign_block_sequences();
+            report.sequences_assigned = schedule
+                .rows
+                .iter()
+                .filter(|row| row.block_sequence.is_some())
+                .count();
+            report.steps_run.push(PipelineStep::AssignSequences);
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RowType, ScheduleRow};
+    use transit_core::Stop;
+
+    fn make_trip(
+        trip_id: &str,
+        start_place: &str,
+        end_place: &str,
+        start: &str,
+        end: &str,
+    ) -> ScheduleRow {
+        ScheduleRow {
+            trip_id: Some(trip_id.to_string()),
+            block: Some("B1".to_string()),
+            start_place: Some(start_place.to_string()),
+            end_place: Some(end_place.to_string()),
+            start_time: Some(start.to_string()),
+            end_time: Some(end.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_full_pipeline() {
+        let mut gtfs = GtfsFeed::new();
+        gtfs.feed.stops.push(Stop::new("A", "Stop A", 51.50, -0.12));
+        gtfs.feed.stops.push(Stop::new("B", "Stop B", 51.51, -0.10));
+
+        let mut schedule = Schedule::from_rows(vec![
+            make_trip("T2", "B", "A", "23:30:00", "00:15:00"),
+            make_trip("T1", "A", "B", "22:30:00", "23:15:00"),
+        ]);
+
+        let report = SchedulePipeline::new()
+            .with_gtfs(&gtfs)
+            .with_inference_config(InferenceConfig::new().with_default_depot("DEPOT"))
+            .run(&mut schedule);
+
+        assert_eq!(report.steps_run.len(), 5);
+        assert_eq!(report.overnight_rows_normalized, 1);
+        assert_eq!(report.deadheads_added, 2);
+        assert_eq!(report.coordinates_backfilled, 2);
+        assert_eq!(report.sequences_assigned, 4);
+
+        let types: Vec<RowType> = schedule.rows.iter().map(|r| r.row_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                RowType::PullOut,
+                RowType::Revenue,
+                RowType::Revenue,
+                RowType::PullIn
+            ]
+        );
+        assert_eq!(schedule.rows[2].end_time.as_deref(), Some("24:15:00"));
+        assert_eq!(schedule.rows[3].start_time.as_deref(), Some("24:15:00"));
+        assert!(schedule.rows[1].distance_meters().is_some());
+        assert_eq!(schedule.rows[3].block_sequence, Some(4));
+    }
+
+    #[test]
+    fn test_steps_can_be_disabled() {
+        let mut schedule = Schedule::from_rows(vec![
+            make_trip("T2", "B", "A", "23:30:00", "00:15:00"),
+            make_trip("T1", "A", "B", "22:30:00", "23:15:00"),
+        ]);
+
+        let report = SchedulePipeline::new()
+            .with_normalize_overnight(false)
+            .with_infer_deadheads(false)
+            .run(&mut schedule);
+
+        // Backfill needs GTFS, so only sort and sequencing run
+        assert_eq!(
+            report.steps_run,
+            vec![PipelineStep::Sort, PipelineStep::AssignSequences]
+        );
+        assert!(report.inference.is_none());
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("T1"));
+        assert_eq!(schedule.rows[1].end_time.as_deref(), Some("00:15:00"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Progress reporting for long-running reads and validations.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Shared counter that can be polled while a read or validation runs.
+///
+/// Clones share the same counts: keep one clone and hand another to
+/// [`ReadOptions`](crate::ReadOptions) or [`Validator`](crate::Validator).
+/// The working thread updates the counts with relaxed atomics, and any other
+/// thread may read them at any time. The two counts are not updated together,
+/// so treat `processed / total` as an estimate for display only.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressCounter {
+    processed: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl ProgressCounter {
+    /// Create a new counter at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Units of work processed so far.
+    pub fn processed(&self) -> usize {
+        self.processed.load(Ordering::Relaxed)
+    }
+
+    /// Total units of work expected, or 0 if unknown (e.g. while reading).
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Reset both counts to zero.
+    pub fn reset(&self) {
+        self.start(0);
+    }
+
+    /// Reset the processed count and set the expected total.
+    pub(crate) fn start(&self, total: usize) {
+        self.processed.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Record one more unit of work.
+    pub(crate) fn increment(&self) {
+        self.processed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_counts() {
+        let counter = ProgressCounter::new();
+        let worker = counter.clone();
+
+        worker.start(3);
+        worker.increment();
+        worker.increment();
+        assert_eq!(counter.processed(), 2);
+        assert_eq!(counter.total(), 3);
+
+        counter.reset();
+        assert_eq!(worker.processed(), 0);
+        assert_eq!(worker.total(), 0);
+    }
+}
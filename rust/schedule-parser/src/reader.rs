@@ -1,20 +1,63 @@
 //! CSV reader for schedule files with flexible column mapping.
 
-use crate::models::{RowType, Schedule, ScheduleRow};
+use crate::models::{
+    seconds_to_time_string, ParseWarning, RowType, Schedule, ScheduleMetadata, ScheduleRow,
+};
+use crate::progress::ProgressCounter;
+use chrono::{NaiveDate, NaiveTime, Timelike};
 use csv::StringRecord;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
 use transit_core::ParseError;
 
 /// Column mapping for schedule CSV files.
 ///
-/// Maps standard field names to actual column names in the CSV.
-#[derive(Debug, Clone, Default)]
+/// Maps standard field names to actual column names in the CSV. Column names
+/// are matched ignoring case, surrounding whitespace, and the difference
+/// between spaces and underscores, so `Start_Time` matches a `start time`
+/// header.
+///
+/// Exports that split a timestamp across a date and a clock column can map
+/// `start_time_date` + `start_time_clock` (and `end_time_date` +
+/// `end_time_clock`) instead of `start_time`/`end_time`. Dates are read as
+/// `YYYY-MM-DD` and clocks as `HH:MM:SS` or `HH:MM`; the pair is converted to
+/// a time relative to the service date (see [`ReadOptions::service_date`]),
+/// so a clock of `00:30` on the following day becomes `24:30:00`.
+///
+/// Mappings can be stored in a JSON or TOML file and shared between imports.
+/// Both tables are optional:
+///
+/// ```toml
+/// [columns]          # standard field name -> CSV column name
+/// block = "VehicleBlock"
+/// start_time = "Dep"
+///
+/// [constants]        # standard field name -> value used for every row
+/// depot = "NORTH"
+/// ```
+///
+/// The JSON form uses the same shape:
+/// `{"columns": {"block": "VehicleBlock"}, "constants": {"depot": "NORTH"}}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ColumnMapping {
     /// Map of standard field name -> CSV column name.
+    #[serde(rename = "columns", default, serialize_with = "serialize_sorted")]
     mappings: HashMap<String, String>,
+    /// Map of standard field name -> literal value used for every row.
+    #[serde(default, serialize_with = "serialize_sorted")]
+    constants: HashMap<String, String>,
+}
+
+/// Serialize a map with its keys in order, so saved mappings diff cleanly.
+fn serialize_sorted<S: Serializer>(
+    map: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
 }
 
 impl ColumnMapping {
@@ -25,7 +68,10 @@ impl ColumnMapping {
 
     /// Create mapping from a HashMap.
     pub fn from_map(mappings: HashMap<String, String>) -> Self {
-        Self { mappings }
+        Self {
+            mappings,
+            constants: HashMap::new(),
+        }
     }
 
     /// Add a mapping.
@@ -38,6 +84,50 @@ impl ColumnMapping {
         self.mappings.get(field).map(|s| s.as_str())
     }
 
+    /// Give a field the same literal value on every row.
+    ///
+    /// Useful when a value such as the depot is implied by the file rather
+    /// than stored in a column. A mapped column still wins for rows where it
+    /// has a value.
+    pub fn add_constant(&mut self, field: impl Into<String>, value: impl Into<String>) {
+        self.constants.insert(field.into(), value.into());
+    }
+
+    /// Get the constant value for a standard field.
+    pub fn get_constant(&self, field: &str) -> Option<&str> {
+        self.constants.get(field).map(|s| s.as_str())
+    }
+
+    /// Parse a mapping from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, ParseError> {
+        serde_json::from_str(json)
+            .map_err(|e| ParseError::InvalidData(format!("Invalid column mapping: {}", e)))
+    }
+
+    /// Parse a mapping from a TOML string.
+    pub fn from_toml(toml: &str) -> Result<Self, ParseError> {
+        toml::from_str(toml)
+            .map_err(|e| ParseError::InvalidData(format!("Invalid column mapping: {}", e)))
+    }
+
+    /// Load a mapping from a JSON file.
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, ParseError> {
+        Self::from_json(&std::fs::read_to_string(path).map_err(ParseError::Io)?)
+    }
+
+    /// Load a mapping from a TOML file.
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self, ParseError> {
+        Self::from_toml(&std::fs::read_to_string(path).map_err(ParseError::Io)?)
+    }
+
+    /// Serialize the mapping as pretty-printed JSON.
+    ///
+    /// Pair with [`auto_detect`](Self::auto_detect) to save a detected mapping
+    /// and reuse it with [`from_json_file`](Self::from_json_file).
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("string maps always serialize")
+    }
+
     /// Create a default mapping with standard column names.
     pub fn default_mapping() -> Self {
         let mut m = Self::new();
@@ -61,6 +151,7 @@ impl ColumnMapping {
         m.add("shift_id", "shift_id");
         m.add("route_short_name", "route_short_name");
         m.add("headsign", "headsign");
+        m.add("service_id", "service_id");
         m
     }
 
@@ -121,11 +212,15 @@ impl ColumnMapping {
             ("shift_id", &["shift_id", "shift"]),
             ("route_short_name", &["route_short_name", "route", "line"]),
             ("headsign", &["headsign", "destination", "direction"]),
+            (
+                "service_id",
+                &["service_id", "calendar_id", "day_type", "service_key"],
+            ),
         ];
 
         for (field, possible_names) in patterns {
             for header in headers {
-                let header_lower = header.to_lowercase();
+                let header_lower = normalize_column_name(header);
                 for &name in *possible_names {
                     if header_lower == name || header_lower.contains(name) {
                         mapping.add(*field, header.clone());
@@ -159,6 +254,98 @@ pub struct ReadOptions {
 
     /// Skip rows where all fields are empty.
     pub skip_empty_rows: bool,
+
+    /// Service date that composite date + clock times are relative to.
+    ///
+    /// When unset, each row's own start date is used as its service date.
+    pub service_date: Option<NaiveDate>,
+
+    /// Counter advanced once per CSV record read, for polling from another
+    /// thread. The total stays 0 because the record count isn't known upfront.
+    pub progress: Option<ProgressCounter>,
+
+    /// Treat a missing end time as a zero-duration event at the start time.
+    ///
+    /// Rows with only a start time (e.g. relief points, or trips where only
+    /// the departure is known) otherwise have no duration and are left out of
+    /// block and duty time totals.
+    pub fill_missing_end_times: bool,
+
+    /// Sentinel values read as empty, such as `"NULL"`, `"-"` or `"N/A"`.
+    ///
+    /// Matched case-insensitively against the trimmed field value.
+    pub null_tokens: Vec<String>,
+
+    /// Fields whose values keep their surrounding whitespace.
+    ///
+    /// Every other field is trimmed. A value that is only whitespace is
+    /// still read as empty.
+    pub untrimmed_fields: HashSet<String>,
+
+    /// Maximum number of rows to read before failing.
+    ///
+    /// Rows skipped as empty don't count. Reading stops with
+    /// [`ParseError::InvalidData`] at the first row past the limit, so a huge
+    /// input is never collected in full.
+    pub max_total_rows: Option<usize>,
+
+    /// Maximum input size in bytes before failing.
+    ///
+    /// [`ScheduleReader::read_path`] checks the file size before reading;
+    /// other inputs fail with [`ParseError::InvalidData`] once more than
+    /// this many bytes have been read.
+    pub max_file_bytes: Option<u64>,
+
+    /// Extra row type labels, such as `"garage run"` for a pull-out.
+    ///
+    /// Keys are trimmed, lowercase labels (as stored by
+    /// [`with_row_type_alias`](Self::with_row_type_alias)), matched against
+    /// the trimmed, lowercased value and checked before the built-in aliases,
+    /// so they can also override them. Values matching neither are read as
+    /// revenue rows.
+    pub row_type_aliases: HashMap<String, RowType>,
+
+    /// Number of lines before the header (or first record) to skip, such as
+    /// agency or export date lines some vendors write above the table.
+    pub skip_preamble_lines: usize,
+
+    /// Keep the skipped preamble lines in [`ScheduleMetadata::preamble`].
+    ///
+    /// `key: value` lines with a recognized key also fill in the metadata:
+    /// `name`/`schedule` set the name, `operator`/`agency`/`company` the
+    /// operator, and `start date`/`valid from` or `end date`/`valid to` the
+    /// service date range. Keys are matched like column names.
+    ///
+    /// [`ScheduleMetadata::preamble`]: crate::ScheduleMetadata::preamble
+    pub capture_preamble: bool,
+
+    /// Column holding each trip's stops in one cell, such as
+    /// `A@08:00;B@08:20;C@08:45`.
+    ///
+    /// The first stop gives the start place and time and the last stop the
+    /// end place and time, wherever mapped columns left them empty. A stop
+    /// may omit its time. Only used by the CSV reader; a column missing from
+    /// the header fails with [`ParseError::InvalidData`].
+    pub stop_list_column: Option<String>,
+
+    /// Separator between the stops of a stop list (default `;`).
+    pub stop_list_delimiter: Option<char>,
+
+    /// Separator between a stop's place and time in a stop list (default
+    /// `@`).
+    pub stop_time_separator: Option<char>,
+
+    /// Record each coordinate value that isn't a number in
+    /// [`ScheduleMetadata::parse_warnings`] instead of silently leaving the
+    /// coordinate empty.
+    ///
+    /// Null tokens are still read as empty without a warning.
+    ///
+    /// [`ScheduleMetadata::parse_warnings`]: crate::ScheduleMetadata::parse_warnings
+    pub strict_coordinates: bool,
+
+    /// Read a `,` in coordinates as the decimal separator, as in `51,5074`.
+    pub comma_decimal_coordinates: bool,
 }
 
 impl ReadOptions {
@@ -170,6 +357,21 @@ impl ReadOptions {
             delimiter: None,
             has_headers: true,
             skip_empty_rows: true,
+            service_date: None,
+            progress: None,
+            fill_missing_end_times: false,
+            null_tokens: Vec::new(),
+            untrimmed_fields: HashSet::new(),
+            max_total_rows: None,
+            max_file_bytes: None,
+            row_type_aliases: HashMap::new(),
+            skip_preamble_lines: 0,
+            capture_preamble: false,
+            stop_list_column: None,
+            stop_list_delimiter: None,
+            stop_time_separator: None,
+            strict_coordinates: false,
+            comma_decimal_coordinates: false,
         }
     }
 
@@ -184,6 +386,104 @@ impl ReadOptions {
         self.delimiter = Some(delimiter);
         self
     }
+
+    /// Set the service date for composite date + clock times.
+    pub fn with_service_date(mut self, date: NaiveDate) -> Self {
+        self.service_date = Some(date);
+        self
+    }
+
+    /// Set whether a missing end time is read as the start time.
+    pub fn with_fill_missing_end_times(mut self, fill: bool) -> Self {
+        self.fill_missing_end_times = fill;
+        self
+    }
+
+    /// Set the sentinel values read as empty.
+    pub fn with_null_tokens<I, S>(mut self, tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.null_tokens = tokens.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set whether a field's values are trimmed (the default) or kept as is.
+    pub fn with_field_trim(mut self, field: impl Into<String>, trim: bool) -> Self {
+        let field = field.into();
+        if trim {
+            self.untrimmed_fields.remove(&field);
+        } else {
+            self.untrimmed_fields.insert(field);
+        }
+        self
+    }
+
+    /// Set the maximum number of rows to read.
+    pub fn with_max_total_rows(mut self, max_rows: usize) -> Self {
+        self.max_total_rows = Some(max_rows);
+        self
+    }
+
+    /// Set the maximum input size in bytes.
+    pub fn with_max_file_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_file_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Read `alias` in the row type column as `row_type`, ignoring case and
+    /// surrounding whitespace. A later alias differing only in case replaces
+    /// an earlier one.
+    pub fn with_row_type_alias(mut self, alias: impl AsRef<str>, row_type: RowType) -> Self {
+        self.row_type_aliases
+            .insert(alias.as_ref().trim().to_lowercase(), row_type);
+        self
+    }
+
+    /// Set the number of preamble lines skipped before the header.
+    pub fn with_skip_preamble_lines(mut self, lines: usize) -> Self {
+        self.skip_preamble_lines = lines;
+        self
+    }
+
+    /// Set whether skipped preamble lines are kept in the schedule metadata.
+    pub fn with_capture_preamble(mut self, capture: bool) -> Self {
+        self.capture_preamble = capture;
+        self
+    }
+
+    /// Read start and end places and times from a stop list column.
+    pub fn with_stop_list_column(mut self, column: impl Into<String>) -> Self {
+        self.stop_list_column = Some(column.into());
+        self
+    }
+
+    /// Set the separators of a stop list: between stops, and between a
+    /// stop's place and time.
+    pub fn with_stop_list_separators(mut self, delimiter: char, time_separator: char) -> Self {
+        self.stop_list_delimiter = Some(delimiter);
+        self.stop_time_separator = Some(time_separator);
+        self
+    }
+
+    /// Set whether unparseable coordinates are recorded as parse warnings.
+    pub fn with_strict_coordinates(mut self, strict: bool) -> Self {
+        self.strict_coordinates = strict;
+        self
+    }
+
+    /// Set whether `,` is read as the decimal separator in coordinates.
+    pub fn with_comma_decimal_coordinates(mut self, comma_decimal: bool) -> Self {
+        self.comma_decimal_coordinates = comma_decimal;
+        self
+    }
+
+    /// Report reading progress through a shared counter.
+    pub fn with_progress(mut self, progress: ProgressCounter) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 }
 
 /// Schedule CSV reader.
@@ -193,8 +493,7 @@ impl ScheduleReader {
     /// Read a schedule from a file path.
     pub fn read_path(path: impl AsRef<Path>, options: ReadOptions) -> Result<Schedule, ParseError> {
         let path = path.as_ref();
-        let file = File::open(path).map_err(ParseError::Io)?;
-        let reader = BufReader::new(file);
+        let reader = open_file(path, &options)?;
 
         let mut schedule = Self::read_reader(reader, options)?;
         schedule.metadata.source_file = path.to_string_lossy().into_owned().into();
@@ -220,13 +519,28 @@ impl ScheduleReader {
             csv_builder.delimiter(delim);
         }
 
+        let mut reader = BufReader::new(ByteLimit::new(reader, options.max_file_bytes));
+        let mut preamble = Vec::new();
+        // Preamble lines are read as bytes, so one that isn't UTF-8 is
+        // skipped rather than failing the read
+        for _ in 0..options.skip_preamble_lines {
+            let mut line = Vec::new();
+            if reader.read_until(b'\n', &mut line).map_err(io_error)? == 0 {
+                break;
+            }
+            if options.capture_preamble {
+                let line = String::from_utf8_lossy(&line);
+                preamble.push(line.trim_end_matches(['\r', '\n']).to_string());
+            }
+        }
+
         let mut csv_reader = csv_builder.from_reader(reader);
 
         // Get headers
         let headers: Vec<String> = if options.has_headers {
             csv_reader
                 .headers()
-                .map_err(|e| ParseError::Csv(e.to_string()))?
+                .map_err(csv_error)?
                 .iter()
                 .map(String::from)
                 .collect()
@@ -234,36 +548,157 @@ impl ScheduleReader {
             Vec::new()
         };
 
-        // Determine column mapping
-        let mapping = match options.column_mapping {
-            Some(m) => m,
-            None if options.auto_detect_columns && !headers.is_empty() => {
-                ColumnMapping::auto_detect(&headers)
+        // Determine column mapping; a mapping holding only constants still
+        // gets its columns detected
+        let mapping = match options.column_mapping.clone() {
+            Some(m) if !m.mappings.is_empty() => m,
+            provided => {
+                let mut m = if options.auto_detect_columns && !headers.is_empty() {
+                    ColumnMapping::auto_detect(&headers)
+                } else {
+                    ColumnMapping::default_mapping()
+                };
+                if let Some(provided) = provided {
+                    m.constants = provided.constants;
+                }
+                m
             }
-            None => ColumnMapping::default_mapping(),
         };
 
-        // Create header index map
-        let header_index: HashMap<String, usize> = headers
+        // Resolve each mapped field to its column index once. Names are
+        // compared normalized so "Start Time" matches a "start_time" mapping;
+        // the first of any headers that normalize the same wins.
+        let mut header_index: HashMap<String, usize> = HashMap::new();
+        for (i, header) in headers.iter().enumerate() {
+            header_index
+                .entry(normalize_column_name(header))
+                .or_insert(i);
+        }
+        let field_index: HashMap<String, usize> = mapping
+            .mappings
             .iter()
-            .enumerate()
-            .map(|(i, h)| (h.clone(), i))
+            .filter_map(|(field, column)| {
+                header_index
+                    .get(&normalize_column_name(column))
+                    .map(|&i| (field.clone(), i))
+            })
             .collect();
 
+        let stop_list_index = match &options.stop_list_column {
+            Some(column) => Some(
+                *header_index
+                    .get(&normalize_column_name(column))
+                    .ok_or_else(|| {
+                        ParseError::InvalidData(format!("Stop list column '{}' not found", column))
+                    })?,
+            ),
+            None => None,
+        };
+
+        if let Some(progress) = &options.progress {
+            progress.reset();
+        }
+
         // Parse rows
         let mut rows = Vec::new();
+        let mut warnings = Vec::new();
         for result in csv_reader.records() {
-            let record = result.map_err(|e| ParseError::Csv(e.to_string()))?;
+            let record = result.map_err(csv_error)?;
+            Self::push_record(
+                &mut rows,
+                &mut warnings,
+                &record,
+                &mapping,
+                &field_index,
+                stop_list_index,
+                &options,
+            )?;
+        }
 
-            if options.skip_empty_rows && record.iter().all(|f| f.trim().is_empty()) {
-                continue;
-            }
+        let mut schedule = Schedule::from_rows(rows);
+        schedule.metadata.parse_warnings = warnings;
+        schedule.metadata.column_mapping = Some(
+            mapping
+                .mappings
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        );
+        apply_preamble(
+            &mut schedule.metadata,
+            preamble,
+            options.delimiter.unwrap_or(b','),
+        );
 
-            let row = Self::parse_row(&record, &mapping, &header_index)?;
-            rows.push(row);
+        Ok(schedule)
+    }
+
+    /// Read a schedule from fixed-width text.
+    ///
+    /// Each spec is `(field, start_col, end_col)`: a standard field name
+    /// (as used in [`ColumnMapping`]) and a 0-based, end-exclusive character
+    /// range. Lines shorter than a range give a shorter or empty field, and
+    /// values are trimmed like CSV fields. The first line is skipped when
+    /// `options.has_headers` is set; constants from `options.column_mapping`
+    /// still apply, but its columns, the delimiter and auto-detection are
+    /// ignored.
+    pub fn read_fixed_width<R: Read>(
+        reader: R,
+        field_specs: &[(String, usize, usize)],
+        options: ReadOptions,
+    ) -> Result<Schedule, ParseError> {
+        if let Some((field, start, end)) = field_specs.iter().find(|(_, start, end)| start >= end) {
+            return Err(ParseError::InvalidData(format!(
+                "Invalid column range {}..{} for field {}",
+                start, end, field
+            )));
+        }
+
+        let mut mapping = ColumnMapping::new();
+        for (field, start, end) in field_specs {
+            mapping.add(field.clone(), format!("{}..{}", start, end));
+        }
+        if let Some(provided) = &options.column_mapping {
+            mapping.constants = provided.constants.clone();
+        }
+        let field_index: HashMap<String, usize> = field_specs
+            .iter()
+            .enumerate()
+            .map(|(i, (field, _, _))| (field.clone(), i))
+            .collect();
+
+        if let Some(progress) = &options.progress {
+            progress.reset();
+        }
+
+        let mut rows = Vec::new();
+        let mut warnings = Vec::new();
+        let lines = BufReader::new(ByteLimit::new(reader, options.max_file_bytes)).lines();
+        for line in lines.skip(usize::from(options.has_headers)) {
+            let line = line.map_err(io_error)?;
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            let record: StringRecord = field_specs
+                .iter()
+                .map(|&(_, start, end)| {
+                    line.chars()
+                        .skip(start)
+                        .take(end - start)
+                        .collect::<String>()
+                })
+                .collect();
+            Self::push_record(
+                &mut rows,
+                &mut warnings,
+                &record,
+                &mapping,
+                &field_index,
+                None,
+                &options,
+            )?;
         }
 
         let mut schedule = Schedule::from_rows(rows);
+        schedule.metadata.parse_warnings = warnings;
         schedule.metadata.column_mapping = Some(
             mapping
                 .mappings
@@ -275,37 +710,168 @@ impl ScheduleReader {
         Ok(schedule)
     }
 
+    /// Read a schedule from JSON Lines (NDJSON), one serialized
+    /// [`ScheduleRow`] per line, as written by
+    /// [`JsonExporter::export_ndjson`](crate::formats::JsonExporter::export_ndjson).
+    ///
+    /// Missing fields are read as empty and a missing `row_type` as revenue.
+    /// Blank lines are skipped. A line that isn't a valid row fails with
+    /// [`ParseError::Json`] naming its 1-based line number. Of the read
+    /// options only the progress counter, `fill_missing_end_times`,
+    /// `max_total_rows` and `max_file_bytes` apply.
+    pub fn read_ndjson<R: Read>(reader: R, options: ReadOptions) -> Result<Schedule, ParseError> {
+        if let Some(progress) = &options.progress {
+            progress.reset();
+        }
+
+        let mut rows = Vec::new();
+        let lines = BufReader::new(ByteLimit::new(reader, options.max_file_bytes)).lines();
+        for (idx, line) in lines.enumerate() {
+            let line = line.map_err(io_error)?;
+            if let Some(progress) = &options.progress {
+                progress.increment();
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(limit) = options.max_total_rows {
+                if rows.len() >= limit {
+                    return Err(ParseError::InvalidData(format!(
+                        "Input exceeds the limit of {} rows",
+                        limit
+                    )));
+                }
+            }
+
+            let mut row: ScheduleRow = serde_json::from_str(&line)
+                .map_err(|e| ParseError::Json(format!("line {}: {}", idx + 1, e)))?;
+            if options.fill_missing_end_times && row.end_time.is_none() {
+                row.end_time = row.start_time.clone();
+            }
+            rows.push(row);
+        }
+
+        Ok(Schedule::from_rows(rows))
+    }
+
+    /// Parse one record and append it to `rows`, applying the per-row read
+    /// options. `stop_list` is the index of the stop list column, if any.
+    fn push_record(
+        rows: &mut Vec<ScheduleRow>,
+        warnings: &mut Vec<ParseWarning>,
+        record: &StringRecord,
+        mapping: &ColumnMapping,
+        field_index: &HashMap<String, usize>,
+        stop_list: Option<usize>,
+        options: &ReadOptions,
+    ) -> Result<(), ParseError> {
+        if let Some(progress) = &options.progress {
+            progress.increment();
+        }
+
+        if options.skip_empty_rows && record.iter().all(|f| f.trim().is_empty()) {
+            return Ok(());
+        }
+
+        if let Some(limit) = options.max_total_rows {
+            if rows.len() >= limit {
+                return Err(ParseError::InvalidData(format!(
+                    "Input exceeds the limit of {} rows",
+                    limit
+                )));
+            }
+        }
+
+        let mut row = Self::parse_row(record, mapping, field_index, options)?;
+        if options.strict_coordinates {
+            warnings.extend(coordinate_warnings(record, field_index, &row, options).map(
+                |(field, value)| ParseWarning {
+                    row_index: rows.len(),
+                    field: field.to_string(),
+                    value,
+                },
+            ));
+        }
+        if let Some(cell) = stop_list.and_then(|idx| record.get(idx)) {
+            apply_stop_list(&mut row, cell, options);
+        }
+        if options.fill_missing_end_times && row.end_time.is_none() {
+            row.end_time = row.start_time.clone();
+        }
+        rows.push(row);
+        Ok(())
+    }
+
     /// Parse a single record into a ScheduleRow.
     fn parse_row(
         record: &StringRecord,
         mapping: &ColumnMapping,
-        header_index: &HashMap<String, usize>,
+        field_index: &HashMap<String, usize>,
+        options: &ReadOptions,
     ) -> Result<ScheduleRow, ParseError> {
+        let service_date = options.service_date;
         let get_field = |field: &str| -> Option<String> {
-            let column = mapping.get_column(field)?;
-            let idx = *header_index.get(column)?;
-            let value = record.get(idx)?.trim();
-            if value.is_empty() {
-                None
-            } else {
-                Some(value.to_string())
-            }
+            let value = field_index
+                .get(field)
+                .and_then(|&idx| record.get(idx))
+                .filter(|value| {
+                    let trimmed = value.trim();
+                    !trimmed.is_empty()
+                        && !options
+                            .null_tokens
+                            .iter()
+                            .any(|token| token.eq_ignore_ascii_case(trimmed))
+                })
+                .map(|value| {
+                    if options.untrimmed_fields.contains(field) {
+                        value
+                    } else {
+                        value.trim()
+                    }
+                });
+            value
+                .or_else(|| mapping.get_constant(field))
+                .map(String::from)
         };
 
-        let get_f64 =
-            |field: &str| -> Option<f64> { get_field(field).and_then(|s| s.parse().ok()) };
+        let get_f64 = |field: &str| -> Option<f64> {
+            get_field(field).and_then(|s| parse_coordinate(&s, options))
+        };
 
         let row_type = get_field("row_type")
-            .map(|s| parse_row_type(&s))
+            .map(|s| parse_row_type(&s, &options.row_type_aliases))
             .unwrap_or(RowType::Revenue);
 
+        // Split date + clock columns, resolved against the service date
+        let start_date = get_field("start_time_date")
+            .map(|s| parse_date(&s))
+            .transpose()?;
+        let end_date = get_field("end_time_date")
+            .map(|s| parse_date(&s))
+            .transpose()?;
+        let service_date = service_date.or(start_date).or(end_date);
+
+        let start_time = match get_field("start_time") {
+            Some(time) => Some(time),
+            None => get_field("start_time_clock")
+                .map(|clock| combine_date_time(start_date, &clock, service_date))
+                .transpose()?,
+        };
+        let end_time = match get_field("end_time") {
+            Some(time) => Some(time),
+            None => get_field("end_time_clock")
+                .map(|clock| combine_date_time(end_date, &clock, service_date))
+                .transpose()?,
+        };
+
         Ok(ScheduleRow {
             run_number: get_field("run_number"),
             block: get_field("block"),
             start_place: get_field("start_place"),
             end_place: get_field("end_place"),
-            start_time: get_field("start_time"),
-            end_time: get_field("end_time"),
+            start_time,
+            end_time,
             trip_id: get_field("trip_id"),
             depot: get_field("depot"),
             vehicle_class: get_field("vehicle_class"),
@@ -320,24 +886,255 @@ impl ScheduleReader {
             shift_id: get_field("shift_id"),
             route_short_name: get_field("route_short_name"),
             headsign: get_field("headsign"),
+            service_id: get_field("service_id"),
+            block_sequence: None,
         })
     }
 }
 
-/// Parse a row type string to RowType enum.
-fn parse_row_type(s: &str) -> RowType {
-    match s.to_lowercase().as_str() {
-        "revenue" | "trip" | "service" => RowType::Revenue,
-        "pull_out" | "pullout" | "pull-out" | "po" => RowType::PullOut,
-        "pull_in" | "pullin" | "pull-in" | "pi" => RowType::PullIn,
-        "deadhead" | "dh" | "dead" | "non_revenue" => RowType::Deadhead,
-        "break" | "brk" | "meal" => RowType::Break,
-        "relief" | "changeover" | "swap" => RowType::Relief,
-        "layover" | "wait" | "dwell" => RowType::Layover,
-        _ => RowType::Revenue, // Default to revenue
+/// Parse a row type string to RowType enum, trying `aliases` before the
+/// built-in names.
+///
+/// Unknown values default to `RowType::Revenue`.
+fn parse_row_type(s: &str, aliases: &HashMap<String, RowType>) -> RowType {
+    aliases
+        .get(&s.trim().to_lowercase())
+        .copied()
+        .unwrap_or_else(|| s.parse().unwrap_or(RowType::Revenue))
+}
+
+/// Standard field names of the coordinate columns.
+const COORDINATE_FIELDS: [&str; 4] = ["start_lat", "start_lon", "end_lat", "end_lon"];
+
+/// Parse a coordinate, reading `,` as the decimal separator if configured.
+fn parse_coordinate(value: &str, options: &ReadOptions) -> Option<f64> {
+    if options.comma_decimal_coordinates {
+        value.replace(',', ".").parse().ok()
+    } else {
+        value.parse().ok()
     }
 }
 
+/// Coordinate cells of a record that had a value but were left empty on the
+/// parsed row, as (field, raw value) pairs.
+fn coordinate_warnings<'a>(
+    record: &'a StringRecord,
+    field_index: &'a HashMap<String, usize>,
+    row: &'a ScheduleRow,
+    options: &'a ReadOptions,
+) -> impl Iterator<Item = (&'static str, String)> + 'a {
+    let coordinates = [row.start_lat, row.start_lon, row.end_lat, row.end_lon];
+    COORDINATE_FIELDS
+        .into_iter()
+        .zip(coordinates)
+        .filter(|(_, coordinate)| coordinate.is_none())
+        .filter_map(move |(field, _)| {
+            let value = record.get(*field_index.get(field)?)?.trim();
+            let is_null = value.is_empty()
+                || options
+                    .null_tokens
+                    .iter()
+                    .any(|token| token.eq_ignore_ascii_case(value));
+            (!is_null).then(|| (field, value.to_string()))
+        })
+}
+
+/// Fill a row's empty start and end place and time from a stop list cell.
+fn apply_stop_list(row: &mut ScheduleRow, cell: &str, options: &ReadOptions) {
+    let delimiter = options.stop_list_delimiter.unwrap_or(';');
+    let separator = options.stop_time_separator.unwrap_or('@');
+    let stop = |entry: &str| {
+        let (place, time) = entry.split_once(separator).unwrap_or((entry, ""));
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        (non_empty(place), non_empty(time))
+    };
+
+    let mut stops = cell
+        .split(delimiter)
+        .filter(|entry| !entry.trim().is_empty());
+    let Some(first) = stops.next() else {
+        return;
+    };
+    let (first_place, first_time) = stop(first);
+    let (last_place, last_time) = stop(stops.next_back().unwrap_or(first));
+
+    row.start_place = row.start_place.take().or(first_place);
+    row.start_time = row.start_time.take().or(first_time);
+    row.end_place = row.end_place.take().or(last_place);
+    row.end_time = row.end_time.take().or(last_time);
+}
+
+/// Normalize a column name for matching: trim, lowercase, and collapse
+/// runs of whitespace and underscores into a single `_`.
+fn normalize_column_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut pending_separator = false;
+    for c in name.trim().chars() {
+        if c.is_whitespace() || c == '_' {
+            pending_separator = true;
+            continue;
+        }
+        if pending_separator && !normalized.is_empty() {
+            normalized.push('_');
+        }
+        pending_separator = false;
+        normalized.extend(c.to_lowercase());
+    }
+    normalized
+}
+
+/// Parse a `YYYY-MM-DD` date.
+fn parse_date(s: &str) -> Result<NaiveDate, ParseError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| ParseError::InvalidData(format!("Invalid date: {}", s)))
+}
+
+/// Combine a date and a clock time into an `HH:MM:SS` time relative to the
+/// service date, rolling past 24:00:00 for later days.
+fn combine_date_time(
+    date: Option<NaiveDate>,
+    clock: &str,
+    service_date: Option<NaiveDate>,
+) -> Result<String, ParseError> {
+    let time = NaiveTime::parse_from_str(clock, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(clock, "%H:%M"))
+        .map_err(|_| ParseError::InvalidData(format!("Invalid time: {}", clock)))?;
+
+    let day_offset = match (date, service_date) {
+        (Some(date), Some(service_date)) => (date - service_date).num_days(),
+        _ => 0,
+    };
+    if day_offset < 0 {
+        return Err(ParseError::InvalidData(format!(
+            "Date {} is before the service date",
+            date.map(|d| d.to_string()).unwrap_or_default()
+        )));
+    }
+
+    let seconds = u32::try_from(day_offset)
+        .ok()
+        .and_then(|days| days.checked_mul(86_400))
+        .and_then(|secs| secs.checked_add(time.num_seconds_from_midnight()))
+        .ok_or_else(|| {
+            ParseError::InvalidData(format!(
+                "Date {} is too far after the service date",
+                date.map(|d| d.to_string()).unwrap_or_default()
+            ))
+        })?;
+    Ok(seconds_to_time_string(seconds))
+}
+
+/// Store preamble lines in the metadata, filling in recognized
+/// `key: value` fields. Trailing delimiters, as left by spreadsheet exports,
+/// are ignored.
+fn apply_preamble(metadata: &mut ScheduleMetadata, preamble: Vec<String>, delimiter: u8) {
+    for line in &preamble {
+        let line = line.trim_end_matches(|c: char| c == delimiter as char || c.is_whitespace());
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let field = match normalize_column_name(key).as_str() {
+            "name" | "schedule" | "schedule_name" => &mut metadata.name,
+            "operator" | "agency" | "company" => &mut metadata.operator,
+            "start_date" | "valid_from" => &mut metadata.start_date,
+            "end_date" | "valid_to" => &mut metadata.end_date,
+            _ => continue,
+        };
+        *field = Some(value.to_string());
+    }
+    metadata.preamble = preamble;
+}
+
+/// Open a file for reading, failing early if it is larger than
+/// `options.max_file_bytes`.
+pub(crate) fn open_file(path: &Path, options: &ReadOptions) -> Result<BufReader<File>, ParseError> {
+    let file = File::open(path).map_err(ParseError::Io)?;
+    if let Some(limit) = options.max_file_bytes {
+        let size = file.metadata().map_err(ParseError::Io)?.len();
+        if size > limit {
+            return Err(FileSizeExceeded { limit }.into());
+        }
+    }
+    Ok(BufReader::new(file))
+}
+
+/// Error raised inside a [`ByteLimit`] read, carried through the CSV
+/// reader as an I/O error.
+#[derive(Debug)]
+struct FileSizeExceeded {
+    limit: u64,
+}
+
+impl fmt::Display for FileSizeExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Input exceeds the limit of {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for FileSizeExceeded {}
+
+impl From<FileSizeExceeded> for ParseError {
+    fn from(err: FileSizeExceeded) -> Self {
+        ParseError::InvalidData(err.to_string())
+    }
+}
+
+/// Reader that fails once more than `limit` bytes have been read.
+struct ByteLimit<R> {
+    inner: R,
+    limit: Option<u64>,
+    read: u64,
+}
+
+impl<R> ByteLimit<R> {
+    fn new(inner: R, limit: Option<u64>) -> Self {
+        Self {
+            inner,
+            limit,
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for ByteLimit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        match self.limit {
+            Some(limit) if self.read > limit => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                FileSizeExceeded { limit },
+            )),
+            _ => Ok(n),
+        }
+    }
+}
+
+/// The size limit error carried by an I/O error, if any.
+fn size_limit_error(err: &io::Error) -> Option<ParseError> {
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<FileSizeExceeded>())
+        .map(|exceeded| ParseError::InvalidData(exceeded.to_string()))
+}
+
+/// Convert an I/O error, surfacing a size limit as invalid data.
+fn io_error(err: io::Error) -> ParseError {
+    size_limit_error(&err).unwrap_or(ParseError::Io(err))
+}
+
+/// Convert a CSV error, surfacing a size limit as invalid data.
+fn csv_error(err: csv::Error) -> ParseError {
+    match err.kind() {
+        csv::ErrorKind::Io(io_err) => size_limit_error(io_err),
+        _ => None,
+    }
+    .unwrap_or_else(|| ParseError::Csv(err.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,6 +1182,33 @@ R1,B1,09:30:00,10:00:00,,pull_in
         assert_eq!(schedule.rows[4].row_type, RowType::PullIn);
     }
 
+    #[test]
+    fn test_row_type_aliases() {
+        let csv = r#"block,start_time,end_time,row_type
+B1,06:00:00,06:30:00,Garage Run
+B1,06:30:00,08:00:00,spare
+B1,08:00:00,08:15:00,dh
+B1,08:15:00,09:00:00,shunt
+"#;
+
+        let options = ReadOptions::new()
+            .with_row_type_alias("garage run", RowType::PullOut)
+            .with_row_type_alias("SPARE", RowType::Layover)
+            .with_row_type_alias("DH", RowType::Break)
+            .with_row_type_alias(" dh ", RowType::PullIn);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        let types: Vec<RowType> = schedule.rows.iter().map(|r| r.row_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                RowType::PullOut,
+                RowType::Layover,
+                RowType::PullIn,
+                RowType::Revenue
+            ]
+        );
+    }
+
     #[test]
     fn test_custom_mapping() {
         let csv = r#"driver,bus_block,origin,destination,depart,arrive,trip
@@ -408,6 +1232,202 @@ D1,V1,A,B,08:00:00,09:00:00,T1
         assert_eq!(schedule.rows[0].trip_id, Some("T1".to_string()));
     }
 
+    #[test]
+    fn test_constant_fields() {
+        let csv = r#"block,trip_id,depot
+B1,T1,
+B1,T2,NORTH
+"#;
+
+        let mut mapping = ColumnMapping::new();
+        mapping.add("block", "block");
+        mapping.add("trip_id", "trip_id");
+        mapping.add("depot", "depot");
+        mapping.add_constant("depot", "SOUTH");
+        mapping.add_constant("vehicle_class", "DD");
+
+        let options = ReadOptions::new().with_mapping(mapping);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+
+        assert_eq!(schedule.rows[0].depot, Some("SOUTH".to_string()));
+        assert_eq!(schedule.rows[1].depot, Some("NORTH".to_string()));
+        assert!(schedule
+            .rows
+            .iter()
+            .all(|r| r.vehicle_class.as_deref() == Some("DD")));
+
+        // Constants alone keep column auto-detection
+        let mut mapping = ColumnMapping::new();
+        mapping.add_constant("depot", "SOUTH");
+        let options = ReadOptions::new().with_mapping(mapping);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+
+        assert_eq!(schedule.rows[0].trip_id, Some("T1".to_string()));
+        assert_eq!(schedule.rows[0].depot, Some("SOUTH".to_string()));
+        assert_eq!(schedule.rows[1].depot, Some("NORTH".to_string()));
+    }
+
+    #[test]
+    fn test_mapping_from_toml_and_json() {
+        let toml = r#"
+[columns]
+block = "VehicleBlock"
+start_time = "Dep"
+
+[constants]
+depot = "NORTH"
+"#;
+        let mapping = ColumnMapping::from_toml(toml).unwrap();
+        assert_eq!(mapping.get_column("block"), Some("VehicleBlock"));
+        assert_eq!(mapping.get_column("start_time"), Some("Dep"));
+        assert_eq!(mapping.get_constant("depot"), Some("NORTH"));
+
+        let reloaded = ColumnMapping::from_json(&mapping.to_json()).unwrap();
+        assert_eq!(reloaded.get_column("block"), Some("VehicleBlock"));
+        assert_eq!(reloaded.get_constant("depot"), Some("NORTH"));
+
+        let constants_only = ColumnMapping::from_json(r#"{"constants": {"depot": "X"}}"#).unwrap();
+        assert_eq!(constants_only.get_column("block"), None);
+        assert_eq!(constants_only.get_constant("depot"), Some("X"));
+
+        assert!(ColumnMapping::from_json("{\"columns\": 5}").is_err());
+    }
+
+    #[test]
+    fn test_mapping_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mapping.json");
+        let headers = vec!["Block".to_string(), "Start".to_string()];
+        std::fs::write(&path, ColumnMapping::auto_detect(&headers).to_json()).unwrap();
+
+        let mapping = ColumnMapping::from_json_file(&path).unwrap();
+        assert_eq!(mapping.get_column("block"), Some("Block"));
+        assert!(ColumnMapping::from_toml_file(dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_fill_missing_end_times() {
+        let csv = r#"block,start_time,end_time,row_type
+B1,08:00:00,,relief
+B1,08:00:00,09:00:00,revenue
+"#;
+        let schedule = ScheduleReader::read_str(csv, ReadOptions::new()).unwrap();
+        assert_eq!(schedule.rows[0].end_time, None);
+        assert_eq!(schedule.rows_missing_times(), vec![0]);
+
+        let options = ReadOptions::new().with_fill_missing_end_times(true);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("08:00:00"));
+        assert_eq!(schedule.rows[0].duration_seconds(), Some(0));
+        assert!(schedule.rows_missing_times().is_empty());
+    }
+
+    #[test]
+    fn test_null_tokens_and_trimming() {
+        let csv = "block,depot,trip_id,start_place\nNULL,-,T1,  Main St\nB2,n/a,T2, Main St\n";
+
+        let schedule = ScheduleReader::read_str(csv, ReadOptions::new()).unwrap();
+        assert_eq!(schedule.block_ids(), vec!["B2", "NULL"]);
+        assert_eq!(schedule.rows[0].start_place.as_deref(), Some("Main St"));
+
+        let options = ReadOptions::new()
+            .with_null_tokens(["NULL", "-", "N/A"])
+            .with_field_trim("start_place", false);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(schedule.block_ids(), vec!["B2"]);
+        assert!(schedule.depots().is_empty());
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("T1"));
+        assert_eq!(schedule.rows[0].start_place.as_deref(), Some("  Main St"));
+        assert_eq!(schedule.rows[1].start_place.as_deref(), Some(" Main St"));
+    }
+
+    #[test]
+    fn test_column_matching_ignores_case_and_spacing() {
+        let csv = "Block ID, Start  Time ,END_TIME,Trip__Id\nB1,08:00:00,09:00:00,T1\n";
+
+        // Auto-detected
+        let schedule = ScheduleReader::read_str(csv, ReadOptions::new()).unwrap();
+        assert_eq!(schedule.rows[0].block.as_deref(), Some("B1"));
+        assert_eq!(schedule.rows[0].start_time.as_deref(), Some("08:00:00"));
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("09:00:00"));
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("T1"));
+
+        // Explicit mapping with different spelling than the headers
+        let mut mapping = ColumnMapping::new();
+        mapping.add("block", "block_id");
+        mapping.add("start_time", "Start_Time");
+        mapping.add("end_time", "end time");
+        let options = ReadOptions::new().with_mapping(mapping);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(schedule.rows[0].block.as_deref(), Some("B1"));
+        assert_eq!(schedule.rows[0].start_time.as_deref(), Some("08:00:00"));
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("09:00:00"));
+
+        // The original header names are kept
+        let used = schedule.metadata.column_mapping.unwrap();
+        assert_eq!(used["start_time"], "Start_Time");
+    }
+
+    #[test]
+    fn test_read_fixed_width() {
+        let text = "BLOCK START END   TRIP\r\n\
+                    B1    08:00 09:00 TRIP1\r\n\
+                    \r\n\
+                    B2    09:15 10:00\r\n";
+        let specs: Vec<(String, usize, usize)> = [
+            ("block", 0, 6),
+            ("start_time", 6, 11),
+            ("end_time", 12, 17),
+            ("trip_id", 18, 24),
+        ]
+        .into_iter()
+        .map(|(field, start, end)| (field.to_string(), start, end))
+        .collect();
+        let mut constants = ColumnMapping::new();
+        constants.add_constant("depot", "NORTH");
+
+        let schedule = ScheduleReader::read_fixed_width(
+            text.as_bytes(),
+            &specs,
+            ReadOptions::new().with_mapping(constants),
+        )
+        .unwrap();
+
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule.rows[0].block.as_deref(), Some("B1"));
+        assert_eq!(schedule.rows[0].start_time_seconds(), Some(28800));
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("TRIP1"));
+        assert_eq!(schedule.rows[1].end_time.as_deref(), Some("10:00"));
+        assert_eq!(schedule.rows[1].trip_id, None);
+        assert_eq!(schedule.rows[1].depot.as_deref(), Some("NORTH"));
+
+        let bad = vec![("block".to_string(), 5, 5)];
+        assert!(
+            ScheduleReader::read_fixed_width(text.as_bytes(), &bad, ReadOptions::new()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_normalize_column_name() {
+        assert_eq!(normalize_column_name("  Start Time "), "start_time");
+        assert_eq!(normalize_column_name("start__time"), "start_time");
+        assert_eq!(normalize_column_name("Start \t_ Time"), "start_time");
+        assert_eq!(normalize_column_name("_block"), "block");
+    }
+
+    #[test]
+    fn test_read_progress() {
+        let csv = "block,trip_id\nB1,T1\n,\nB1,T2\n";
+
+        let progress = ProgressCounter::new();
+        let options = ReadOptions::new().with_progress(progress.clone());
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(progress.processed(), 3);
+        assert_eq!(progress.total(), 0);
+    }
+
     #[test]
     fn test_skip_empty_rows() {
         let csv = r#"run_number,block,start_time,trip_id
@@ -419,4 +1439,204 @@ R1,B1,09:00:00,T2
         let schedule = ScheduleReader::read_str(csv, ReadOptions::new()).unwrap();
         assert_eq!(schedule.len(), 2); // Empty row skipped
     }
+
+    #[test]
+    fn test_preamble_lines() {
+        let csv = "Agency: Metro Bus,,\r\nValid From: 2024-03-01,,\r\nblock,start_time,trip_id\r\nB1,08:00:00,T1\r\n";
+
+        let schedule =
+            ScheduleReader::read_str(csv, ReadOptions::new().with_skip_preamble_lines(2)).unwrap();
+        assert_eq!(schedule.len(), 1);
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("T1"));
+        assert!(schedule.metadata.preamble.is_empty());
+        assert_eq!(schedule.metadata.operator, None);
+
+        let options = ReadOptions::new()
+            .with_skip_preamble_lines(2)
+            .with_capture_preamble(true);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(
+            schedule.metadata.preamble,
+            vec!["Agency: Metro Bus,,", "Valid From: 2024-03-01,,"]
+        );
+        assert_eq!(schedule.metadata.operator.as_deref(), Some("Metro Bus"));
+        assert_eq!(schedule.metadata.start_date.as_deref(), Some("2024-03-01"));
+        assert_eq!(schedule.metadata.name, None);
+
+        // A preamble line that isn't UTF-8 doesn't fail the read
+        let latin1 = b"Agency: M\xe9tro\nblock,trip_id\nB1,T1\n";
+        let options = ReadOptions::new().with_skip_preamble_lines(1);
+        let schedule = ScheduleReader::read_bytes(latin1, options.clone()).unwrap();
+        assert_eq!(schedule.rows[0].trip_id.as_deref(), Some("T1"));
+        let schedule =
+            ScheduleReader::read_bytes(latin1, options.with_capture_preamble(true)).unwrap();
+        assert_eq!(schedule.metadata.preamble, vec!["Agency: M\u{fffd}tro"]);
+    }
+
+    #[test]
+    fn test_stop_list_column() {
+        let csv = "block,trip_id,end_time,stops\n\
+                   B1,T1,,A@08:00; B@08:20 ;C@08:45\n\
+                   B1,T2,09:40,C@09:00;D\n\
+                   B1,T3,,E@10:00\n\
+                   B1,T4,,\n";
+
+        let options = ReadOptions::new().with_stop_list_column("Stops");
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        let places_and_times: Vec<[Option<&str>; 4]> = schedule
+            .rows
+            .iter()
+            .map(|r| {
+                [
+                    r.start_place.as_deref(),
+                    r.start_time.as_deref(),
+                    r.end_place.as_deref(),
+                    r.end_time.as_deref(),
+                ]
+            })
+            .collect();
+        assert_eq!(
+            places_and_times,
+            vec![
+                [Some("A"), Some("08:00"), Some("C"), Some("08:45")],
+                // The mapped end time column wins over the stop list
+                [Some("C"), Some("09:00"), Some("D"), Some("09:40")],
+                [Some("E"), Some("10:00"), Some("E"), Some("10:00")],
+                [None, None, None, None],
+            ]
+        );
+
+        let options = ReadOptions::new()
+            .with_stop_list_column("stops")
+            .with_stop_list_separators('|', '=');
+        let schedule =
+            ScheduleReader::read_str("trip_id,stops\nT1,A=08:00|B=08:30\n", options).unwrap();
+        assert_eq!(schedule.rows[0].end_place.as_deref(), Some("B"));
+        assert_eq!(schedule.rows[0].end_time.as_deref(), Some("08:30"));
+
+        let options = ReadOptions::new().with_stop_list_column("via");
+        assert!(ScheduleReader::read_str(csv, options).is_err());
+    }
+
+    #[test]
+    fn test_strict_coordinates() {
+        let csv = "trip_id;start_lat;start_lon\n\
+                   T1;51,5074;-0,1278\n\
+                   T2;N/A;-\n\
+                   T3;;\n";
+
+        // Without either option the bad values are dropped silently
+        let schedule =
+            ScheduleReader::read_str(csv, ReadOptions::new().with_delimiter(b';')).unwrap();
+        assert_eq!(schedule.rows[0].start_lat, None);
+        assert!(schedule.metadata.parse_warnings.is_empty());
+
+        let options = ReadOptions::new()
+            .with_delimiter(b';')
+            .with_null_tokens(["-"])
+            .with_strict_coordinates(true);
+        let schedule = ScheduleReader::read_str(csv, options.clone()).unwrap();
+        let warnings: Vec<(usize, &str, &str)> = schedule
+            .metadata
+            .parse_warnings
+            .iter()
+            .map(|w| (w.row_index, w.field.as_str(), w.value.as_str()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (0, "start_lat", "51,5074"),
+                (0, "start_lon", "-0,1278"),
+                (1, "start_lat", "N/A"),
+            ]
+        );
+
+        let options = options.with_comma_decimal_coordinates(true);
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(schedule.rows[0].start_lat, Some(51.5074));
+        assert_eq!(schedule.rows[0].start_lon, Some(-0.1278));
+        assert_eq!(schedule.metadata.parse_warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_composite_date_time_columns() {
+        let csv = r#"block,dep_date,dep_time,arr_date,arr_time,trip
+B1,2024-03-01,23:30:00,2024-03-02,00:30:00,T1
+B1,2024-03-02,01:00,2024-03-02,01:45,T2
+"#;
+
+        let mut mapping = ColumnMapping::new();
+        mapping.add("block", "block");
+        mapping.add("start_time_date", "dep_date");
+        mapping.add("start_time_clock", "dep_time");
+        mapping.add("end_time_date", "arr_date");
+        mapping.add("end_time_clock", "arr_time");
+        mapping.add("trip_id", "trip");
+
+        let options = ReadOptions::new()
+            .with_mapping(mapping)
+            .with_service_date(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+
+        assert_eq!(schedule.rows[0].start_time, Some("23:30:00".to_string()));
+        assert_eq!(schedule.rows[0].end_time, Some("24:30:00".to_string()));
+        assert_eq!(schedule.rows[1].start_time, Some("25:00:00".to_string()));
+        assert_eq!(schedule.rows[1].end_time, Some("25:45:00".to_string()));
+    }
+
+    #[test]
+    fn test_composite_time_defaults_to_row_start_date() {
+        let csv = r#"dep_date,dep_time,arr_date,arr_time
+2024-03-01,23:30:00,2024-03-02,00:30:00
+"#;
+
+        let mut mapping = ColumnMapping::new();
+        mapping.add("start_time_date", "dep_date");
+        mapping.add("start_time_clock", "dep_time");
+        mapping.add("end_time_date", "arr_date");
+        mapping.add("end_time_clock", "arr_time");
+
+        let options = ReadOptions::new().with_mapping(mapping.clone());
+        let schedule = ScheduleReader::read_str(csv, options).unwrap();
+        assert_eq!(schedule.rows[0].start_time, Some("23:30:00".to_string()));
+        assert_eq!(schedule.rows[0].end_time, Some("24:30:00".to_string()));
+
+        let bad = "dep_date,dep_time\n01/03/2024,08:00:00\n";
+        let options = ReadOptions::new().with_mapping(mapping.clone());
+        assert!(ScheduleReader::read_str(bad, options).is_err());
+
+        // A mistyped year is an error, not an overflow
+        let far = "dep_date,dep_time,arr_date,arr_time\n2024-03-01,08:00:00,2224-03-01,09:00:00\n";
+        let options = ReadOptions::new().with_mapping(mapping);
+        assert!(ScheduleReader::read_str(far, options).is_err());
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let csv = "block,trip_id\nB1,T1\nB1,T2\nB1,T3\n";
+
+        let options = ReadOptions::new().with_max_total_rows(3);
+        assert_eq!(
+            ScheduleReader::read_str(csv, options).unwrap().rows.len(),
+            3
+        );
+
+        let options = ReadOptions::new().with_max_total_rows(2);
+        let err = ScheduleReader::read_str(csv, options).unwrap_err();
+        assert!(matches!(&err, ParseError::InvalidData(msg) if msg.contains("2 rows")));
+
+        let options = ReadOptions::new().with_max_file_bytes(csv.len() as u64);
+        assert!(ScheduleReader::read_str(csv, options).is_ok());
+
+        let options = ReadOptions::new().with_max_file_bytes(20);
+        let err = ScheduleReader::read_str(csv, options).unwrap_err();
+        assert!(matches!(&err, ParseError::InvalidData(msg) if msg.contains("20 bytes")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedule.csv");
+        std::fs::write(&path, csv).unwrap();
+        let options = ReadOptions::new().with_max_file_bytes(20);
+        let err = ScheduleReader::read_path(&path, options).unwrap_err();
+        assert!(matches!(err, ParseError::InvalidData(_)));
+    }
 }
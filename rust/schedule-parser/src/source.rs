@@ -0,0 +1,219 @@
+//! A common interface over the schedule input formats.
+//!
+//! Each source describes where a schedule comes from and in which format;
+//! [`ScheduleSource::load`] reads it with the given options. Code that only
+//! needs a [`Schedule`] can take any source, so new formats can be added
+//! without changing the callers:
+//!
+//! ```rust,ignore
+//! use schedule_parser::source::{CsvSource, NdjsonSource, ScheduleSource};
+//! use schedule_parser::ReadOptions;
+//!
+//! let sources: Vec<Box<dyn ScheduleSource>> = vec![
+//!     Box::new(CsvSource::path("north.csv")),
+//!     Box::new(NdjsonSource::path("south.ndjson")),
+//! ];
+//! for source in &sources {
+//!     let schedule = source.load(&ReadOptions::new())?;
+//!     println!("{} rows", schedule.len());
+//! }
+//! ```
+
+use crate::models::Schedule;
+use crate::reader::{open_file, ReadOptions, ScheduleReader};
+use std::path::PathBuf;
+use transit_core::ParseError;
+
+/// Something a schedule can be loaded from.
+pub trait ScheduleSource {
+    /// Read the schedule.
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError>;
+}
+
+impl<S: ScheduleSource + ?Sized> ScheduleSource for &S {
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError> {
+        (**self).load(options)
+    }
+}
+
+impl<S: ScheduleSource + ?Sized> ScheduleSource for Box<S> {
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError> {
+        (**self).load(options)
+    }
+}
+
+/// Where a source's data lives.
+#[derive(Debug, Clone)]
+enum Input {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+impl Input {
+    /// Run `read` over the input. For a file, the size limit is checked
+    /// first and the path is recorded as the schedule's source file.
+    fn read<F>(&self, options: &ReadOptions, read: F) -> Result<Schedule, ParseError>
+    where
+        F: FnOnce(&mut dyn std::io::Read) -> Result<Schedule, ParseError>,
+    {
+        match self {
+            Input::Path(path) => {
+                let mut reader = open_file(path, options)?;
+                let mut schedule = read(&mut reader)?;
+                schedule.metadata.source_file = path.to_string_lossy().into_owned().into();
+                Ok(schedule)
+            }
+            Input::Bytes(bytes) => read(&mut bytes.as_slice()),
+        }
+    }
+}
+
+/// A delimited text (CSV) schedule, read with [`ScheduleReader`].
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    input: Input,
+}
+
+impl CsvSource {
+    /// A CSV file.
+    pub fn path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            input: Input::Path(path.into()),
+        }
+    }
+
+    /// CSV data already in memory.
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            input: Input::Bytes(bytes.into()),
+        }
+    }
+}
+
+impl ScheduleSource for CsvSource {
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError> {
+        match &self.input {
+            Input::Path(path) => ScheduleReader::read_path(path, options.clone()),
+            Input::Bytes(bytes) => ScheduleReader::read_bytes(bytes, options.clone()),
+        }
+    }
+}
+
+/// A JSON Lines schedule, read with [`ScheduleReader::read_ndjson`].
+#[derive(Debug, Clone)]
+pub struct NdjsonSource {
+    input: Input,
+}
+
+impl NdjsonSource {
+    /// An NDJSON file.
+    pub fn path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            input: Input::Path(path.into()),
+        }
+    }
+
+    /// NDJSON data already in memory.
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            input: Input::Bytes(bytes.into()),
+        }
+    }
+}
+
+impl ScheduleSource for NdjsonSource {
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError> {
+        self.input.read(options, |reader| {
+            ScheduleReader::read_ndjson(reader, options.clone())
+        })
+    }
+}
+
+/// A fixed-width text schedule, read with
+/// [`ScheduleReader::read_fixed_width`].
+#[derive(Debug, Clone)]
+pub struct FixedWidthSource {
+    input: Input,
+    field_specs: Vec<(String, usize, usize)>,
+}
+
+impl FixedWidthSource {
+    /// A fixed-width file, with `(field, start_col, end_col)` column specs.
+    pub fn path(path: impl Into<PathBuf>, field_specs: Vec<(String, usize, usize)>) -> Self {
+        Self {
+            input: Input::Path(path.into()),
+            field_specs,
+        }
+    }
+
+    /// Fixed-width data already in memory, with `(field, start_col,
+    /// end_col)` column specs.
+    pub fn bytes(bytes: impl Into<Vec<u8>>, field_specs: Vec<(String, usize, usize)>) -> Self {
+        Self {
+            input: Input::Bytes(bytes.into()),
+            field_specs,
+        }
+    }
+}
+
+impl ScheduleSource for FixedWidthSource {
+    fn load(&self, options: &ReadOptions) -> Result<Schedule, ParseError> {
+        self.input.read(options, |reader| {
+            ScheduleReader::read_fixed_width(reader, &self.field_specs, options.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trip_ids<S: ScheduleSource>(source: S) -> Vec<String> {
+        source
+            .load(&ReadOptions::new())
+            .unwrap()
+            .rows
+            .into_iter()
+            .filter_map(|row| row.trip_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_sources_load_the_same_schedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("schedule.csv");
+        std::fs::write(&csv_path, "block,trip_id\nB1,T1\nB1,T2\n").unwrap();
+        let specs = vec![("block".to_string(), 0, 3), ("trip_id".to_string(), 3, 6)];
+
+        let sources: Vec<Box<dyn ScheduleSource>> = vec![
+            Box::new(CsvSource::path(&csv_path)),
+            Box::new(CsvSource::bytes("block,trip_id\nB1,T1\nB1,T2\n")),
+            Box::new(NdjsonSource::bytes(
+                "{\"block\": \"B1\", \"trip_id\": \"T1\"}\n{\"block\": \"B1\", \"trip_id\": \"T2\"}\n",
+            )),
+            Box::new(FixedWidthSource::bytes("BL TR\nB1 T1\nB1 T2\n", specs.clone())),
+        ];
+        for source in &sources {
+            assert_eq!(trip_ids(source), vec!["T1", "T2"]);
+        }
+
+        let fixed_path = dir.path().join("schedule.txt");
+        std::fs::write(&fixed_path, "BL TR\nB1 T1\n").unwrap();
+        let schedule = FixedWidthSource::path(&fixed_path, specs)
+            .load(&ReadOptions::new())
+            .unwrap();
+        assert_eq!(
+            schedule.metadata.source_file.as_deref(),
+            Some(fixed_path.to_string_lossy().as_ref())
+        );
+
+        let too_small = ReadOptions {
+            max_file_bytes: Some(4),
+            ..ReadOptions::new()
+        };
+        assert!(NdjsonSource::path(&fixed_path).load(&too_small).is_err());
+        assert!(NdjsonSource::path(dir.path().join("missing.ndjson"))
+            .load(&ReadOptions::new())
+            .is_err());
+    }
+}
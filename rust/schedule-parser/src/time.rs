@@ -0,0 +1,142 @@
+//! Conversion between schedule time strings and seconds since midnight.
+//!
+//! Schedule times count from midnight of the service day and may run past
+//! 24 hours for trips that finish after midnight (e.g. `"25:30:00"`), so
+//! both directions work on plain second counts rather than wall-clock times.
+
+/// Parse a time string to seconds since midnight.
+///
+/// Supports formats:
+/// - HH:MM:SS (e.g., "14:30:00", "25:30:00", "100:00:00")
+/// - HH:MM (e.g., "14:30")
+/// - Seconds as integer (e.g., "52200")
+///
+/// Surrounding whitespace is ignored. A fractional part on the seconds
+/// field (e.g. "14:30:00.750") is truncated to whole seconds.
+///
+/// Malformed input returns `None` rather than a partial value: empty or
+/// non-numeric fields, a wrong number of fields, minutes or seconds of 60
+/// or more, and values that overflow `u32` are all rejected.
+pub fn parse_time_to_seconds(time: &str) -> Option<u32> {
+    let time = time.trim();
+
+    // Try parsing as plain seconds first
+    if let Ok(secs) = time.parse::<u32>() {
+        return Some(secs);
+    }
+
+    // Try HH:MM:SS or HH:MM format
+    let parts: Vec<&str> = time.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (parse_field(h)?, parse_field(m)?, parse_seconds_field(s)?),
+        [h, m] => (parse_field(h)?, parse_field(m)?, 0),
+        _ => return None,
+    };
+
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    hours.checked_mul(3600)?.checked_add(minutes * 60 + seconds)
+}
+
+/// Convert seconds since midnight to HH:MM:SS format.
+///
+/// Hours are not wrapped at 24 and widen past two digits as needed
+/// (e.g. `360000` is `"100:00:00"`), so the output always parses back to
+/// the same value with [`parse_time_to_seconds`].
+pub fn seconds_to_time_string(seconds: u32) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+    format!("{:02}:{:02}:{:02}", hours, minutes, secs)
+}
+
+/// Parse a whole-number field, rejecting signs and empty strings.
+fn parse_field(field: &str) -> Option<u32> {
+    if field.is_empty() || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    field.parse().ok()
+}
+
+/// Parse the seconds field, truncating any fractional part.
+fn parse_seconds_field(field: &str) -> Option<u32> {
+    match field.split_once('.') {
+        Some((whole, frac)) if frac.bytes().all(|b| b.is_ascii_digit()) => parse_field(whole),
+        Some(_) => None,
+        None => parse_field(field),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_hhmmss() {
+        assert_eq!(parse_time_to_seconds("14:30:00"), Some(52200));
+        assert_eq!(parse_time_to_seconds("00:00:00"), Some(0));
+        assert_eq!(parse_time_to_seconds("25:00:00"), Some(90000)); // Next day
+        assert_eq!(parse_time_to_seconds("100:00:00"), Some(360000));
+        assert_eq!(parse_time_to_seconds("8:05:00"), Some(29100));
+    }
+
+    #[test]
+    fn test_parse_time_hhmm() {
+        assert_eq!(parse_time_to_seconds("14:30"), Some(52200));
+        assert_eq!(parse_time_to_seconds("00:00"), Some(0));
+    }
+
+    #[test]
+    fn test_parse_time_seconds() {
+        assert_eq!(parse_time_to_seconds("52200"), Some(52200));
+        assert_eq!(parse_time_to_seconds("0"), Some(0));
+    }
+
+    #[test]
+    fn test_parse_time_sub_second() {
+        assert_eq!(parse_time_to_seconds("14:30:00.750"), Some(52200));
+        assert_eq!(parse_time_to_seconds("14:30:59.999"), Some(52259));
+        assert_eq!(parse_time_to_seconds("14:30:00."), Some(52200));
+        assert_eq!(parse_time_to_seconds("14:30:00.5x"), None);
+    }
+
+    #[test]
+    fn test_parse_time_malformed() {
+        assert_eq!(parse_time_to_seconds(""), None);
+        assert_eq!(parse_time_to_seconds("   "), None);
+        assert_eq!(parse_time_to_seconds("14"), Some(14)); // plain seconds
+        assert_eq!(parse_time_to_seconds("14:"), None);
+        assert_eq!(parse_time_to_seconds(":30"), None);
+        assert_eq!(parse_time_to_seconds("14:30:00:00"), None);
+        assert_eq!(parse_time_to_seconds("14:60:00"), None);
+        assert_eq!(parse_time_to_seconds("14:30:60"), None);
+        assert_eq!(parse_time_to_seconds("-1:30:00"), None);
+        assert_eq!(parse_time_to_seconds("+1:30:00"), None);
+        assert_eq!(parse_time_to_seconds("ab:cd"), None);
+        assert_eq!(parse_time_to_seconds("2000000:00:00"), None); // overflow
+        assert_eq!(parse_time_to_seconds(" 14:30:00 "), Some(52200));
+    }
+
+    #[test]
+    fn test_seconds_to_time_string() {
+        assert_eq!(seconds_to_time_string(52200), "14:30:00");
+        assert_eq!(seconds_to_time_string(0), "00:00:00");
+        assert_eq!(seconds_to_time_string(90000), "25:00:00");
+        assert_eq!(seconds_to_time_string(360000), "100:00:00");
+        assert_eq!(seconds_to_time_string(u32::MAX), "1193046:28:15");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in ["00:00:00", "08:05:09", "23:59:59", "24:00:00", "100:00:00"] {
+            let secs = parse_time_to_seconds(input).unwrap();
+            assert_eq!(seconds_to_time_string(secs), input);
+        }
+        for secs in [0, 1, 59, 3599, 86399, 86400, 360000, u32::MAX] {
+            let text = seconds_to_time_string(secs);
+            assert_eq!(parse_time_to_seconds(&text), Some(secs));
+        }
+    }
+}
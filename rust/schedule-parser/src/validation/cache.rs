@@ -0,0 +1,146 @@
+//! Memoized validation for watch-mode tools.
+
+use crate::hash::Fnv1a;
+use crate::models::Schedule;
+use crate::validation::validator::{ValidationResult, Validator};
+use gtfs_parser::GtfsFeed;
+
+/// Wraps a [`Validator`] and remembers the last result, so validating an
+/// unchanged schedule against an unchanged feed returns it without running
+/// the checks again.
+///
+/// The schedule is identified by [`Schedule::content_hash`] together with
+/// its [block key](Schedule::block_key), which block-level checks group rows
+/// by. Hashing a whole
+/// GTFS feed is expensive and callers usually know when it changed (e.g.
+/// from a file modification time), so the feed's hash is supplied by the
+/// caller. Only the most recent result is kept.
+///
+/// ```rust,ignore
+/// let mut validator = CachingValidator::new(Validator::default_config());
+/// loop {
+///     let result = validator.validate(&mut schedule, &gtfs, gtfs_mtime);
+///     // ...wait for a change...
+/// }
+/// ```
+pub struct CachingValidator {
+    validator: Validator,
+    last: Option<(u64, u64, ValidationResult)>,
+    hits: usize,
+}
+
+impl CachingValidator {
+    /// Wrap a validator with an empty cache.
+    pub fn new(validator: Validator) -> Self {
+        Self {
+            validator,
+            last: None,
+            hits: 0,
+        }
+    }
+
+    /// The wrapped validator.
+    pub fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    /// Validate a schedule, reusing the last result if neither the
+    /// schedule's content hash, its block key nor `gtfs_hash` has changed
+    /// since.
+    pub fn validate(
+        &mut self,
+        schedule: &mut Schedule,
+        gtfs: &GtfsFeed,
+        gtfs_hash: u64,
+    ) -> ValidationResult {
+        let schedule_hash = schedule_key(schedule);
+        if let Some((cached_schedule, cached_gtfs, result)) = &self.last {
+            if *cached_schedule == schedule_hash && *cached_gtfs == gtfs_hash {
+                self.hits += 1;
+                return result.clone();
+            }
+        }
+
+        let result = self.validator.validate(schedule, gtfs);
+        self.last = Some((schedule_hash, gtfs_hash, result.clone()));
+        result
+    }
+
+    /// Number of calls to [`validate`](Self::validate) answered from the cache.
+    pub fn cache_hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Forget the cached result.
+    pub fn clear(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Content hash of the schedule mixed with its block key fields.
+fn schedule_key(schedule: &Schedule) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.update(&schedule.content_hash().to_le_bytes());
+    for field in schedule.block_key().fields() {
+        hasher.update(b"\n");
+        hasher.update(field.as_bytes());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BlockKey, ScheduleRow};
+
+    fn make_row(trip_id: &str) -> ScheduleRow {
+        ScheduleRow {
+            trip_id: Some(trip_id.to_string()),
+            block: Some("B1".to_string()),
+            start_time: Some("08:00:00".to_string()),
+            end_time: Some("09:00:00".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cached_result_reused() {
+        let gtfs = GtfsFeed::new();
+        let mut schedule = Schedule::from_rows(vec![make_row("T1")]);
+        let mut validator = CachingValidator::new(Validator::default_config());
+
+        let first = validator.validate(&mut schedule, &gtfs, 1);
+        let second = validator.validate(&mut schedule, &gtfs, 1);
+        assert_eq!(validator.cache_hits(), 1);
+        assert_eq!(first.error_count(), second.error_count());
+
+        // A new feed hash or an edited schedule runs the checks again
+        validator.validate(&mut schedule, &gtfs, 2);
+        schedule.rows[0].trip_id = Some("T2".to_string());
+        validator.validate(&mut schedule, &gtfs, 2);
+        assert_eq!(validator.cache_hits(), 1);
+
+        validator.clear();
+        validator.validate(&mut schedule, &gtfs, 2);
+        assert_eq!(validator.cache_hits(), 1);
+    }
+
+    #[test]
+    fn test_block_key_change_invalidates_cache() {
+        let gtfs = GtfsFeed::new();
+        let mut schedule = Schedule::from_rows(vec![make_row("T1")]);
+        let mut validator = CachingValidator::new(Validator::default_config());
+
+        validator.validate(&mut schedule, &gtfs, 1);
+        schedule.derive_blocks_by(BlockKey::depot_block());
+        validator.validate(&mut schedule, &gtfs, 1);
+        assert_eq!(validator.cache_hits(), 0);
+
+        validator.validate(&mut schedule, &gtfs, 1);
+        assert_eq!(validator.cache_hits(), 1);
+
+        schedule.derive_blocks_by(BlockKey::default());
+        validator.validate(&mut schedule, &gtfs, 1);
+        assert_eq!(validator.cache_hits(), 1);
+    }
+}
@@ -1,6 +1,9 @@
 //! Validation configuration and business rules.
 
+use crate::validation::validator::ErrorCategory;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use transit_core::RouteType;
 
 /// GTFS compliance level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -14,6 +17,20 @@ pub enum GtfsComplianceLevel {
     Lenient,
 }
 
+/// What resets the continuous-driving clock within a duty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ContinuousDrivingReset {
+    /// Any break or relief row resets the clock, however short; layovers,
+    /// deadheads and gaps between rows count as driving. Each stretch is one
+    /// of the duty's pieces of work.
+    #[default]
+    AnyBreak,
+    /// Only time off of at least `min_break_duration_seconds` resets the
+    /// clock, counting break and relief rows and gaps between rows as time
+    /// off; shorter breaks and all layovers count as driving.
+    QualifyingBreakOnly,
+}
+
 /// Business rules for schedule validation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BusinessRules {
@@ -49,6 +66,56 @@ pub struct BusinessRules {
 
     /// Flag unusual headways on same route (deviation from mean).
     pub headway_deviation_threshold: Option<f64>,
+
+    /// Maximum layover between consecutive revenue trips of a block at the
+    /// same place before the vehicle is flagged as idle (default: 7200 = 2 hours).
+    #[serde(default = "default_max_layover_seconds")]
+    pub max_layover_seconds: Option<u32>,
+
+    /// Maximum plausible deadhead speed in meters per second (default: 30 ≈ 108 km/h).
+    #[serde(default = "default_max_deadhead_speed_mps")]
+    pub max_deadhead_speed_mps: f64,
+
+    /// Minimum plausible duration for a deadhead that covers any distance
+    /// (default: 60 = 1 min).
+    #[serde(default = "default_min_deadhead_duration_seconds")]
+    pub min_deadhead_duration_seconds: u32,
+
+    /// Maximum plausible in-service speed of a revenue trip with start and
+    /// end coordinates, in meters per second (default: None, which disables
+    /// the check; 25 = 90 km/h is a reasonable bus limit). The speed is the
+    /// straight-line distance over the trip's duration, so it underestimates
+    /// the real speed.
+    #[serde(default)]
+    pub max_revenue_speed_mps: Option<f64>,
+
+    /// Overlap between consecutive rows in a block tolerated before it is
+    /// reported as a chronology error (default: 0 = none).
+    #[serde(default)]
+    pub chronology_tolerance_seconds: u32,
+
+    /// GTFS route types whose trips skip layover checks, e.g. ferries that
+    /// turn around without a scheduled layover (default: none). The route
+    /// type is looked up from each row's trip in the GTFS feed.
+    #[serde(default)]
+    pub layover_exempt_route_types: Vec<RouteType>,
+
+    /// What ends a stretch of continuous driving (default: any break or
+    /// relief).
+    #[serde(default)]
+    pub continuous_driving_reset: ContinuousDrivingReset,
+}
+
+fn default_max_layover_seconds() -> Option<u32> {
+    Some(7200) // 2 hours
+}
+
+fn default_max_deadhead_speed_mps() -> f64 {
+    30.0 // ~108 km/h
+}
+
+fn default_min_deadhead_duration_seconds() -> u32 {
+    60 // 1 minute
 }
 
 impl Default for BusinessRules {
@@ -65,6 +132,13 @@ impl Default for BusinessRules {
             flag_orphan_trips: true,
             flag_missing_coordinates: false,
             headway_deviation_threshold: Some(2.0), // 2x standard deviation
+            max_layover_seconds: default_max_layover_seconds(),
+            max_deadhead_speed_mps: default_max_deadhead_speed_mps(),
+            min_deadhead_duration_seconds: default_min_deadhead_duration_seconds(),
+            max_revenue_speed_mps: None,
+            chronology_tolerance_seconds: 0,
+            layover_exempt_route_types: Vec::new(),
+            continuous_driving_reset: ContinuousDrivingReset::AnyBreak,
         }
     }
 }
@@ -84,6 +158,13 @@ impl BusinessRules {
             flag_orphan_trips: true,
             flag_missing_coordinates: true,
             headway_deviation_threshold: Some(1.5),
+            max_layover_seconds: Some(3600),   // 1 hour
+            max_deadhead_speed_mps: 25.0,      // 90 km/h
+            min_deadhead_duration_seconds: 60, // 1 minute
+            max_revenue_speed_mps: Some(20.0), // 72 km/h
+            chronology_tolerance_seconds: 0,
+            layover_exempt_route_types: Vec::new(),
+            continuous_driving_reset: ContinuousDrivingReset::AnyBreak,
         }
     }
 
@@ -101,6 +182,13 @@ impl BusinessRules {
             flag_orphan_trips: false,
             flag_missing_coordinates: false,
             headway_deviation_threshold: None,
+            max_layover_seconds: None,
+            max_deadhead_speed_mps: 40.0, // 144 km/h
+            min_deadhead_duration_seconds: 0,
+            max_revenue_speed_mps: None,
+            chronology_tolerance_seconds: 0,
+            layover_exempt_route_types: Vec::new(),
+            continuous_driving_reset: ContinuousDrivingReset::AnyBreak,
         }
     }
 }
@@ -125,6 +213,26 @@ pub struct ValidationConfig {
 
     /// Maximum number of errors to collect before stopping.
     pub max_errors: Option<usize>,
+
+    /// Maximum number of errors kept per category. Further errors in a
+    /// category at its limit are dropped and the category is listed in
+    /// `ValidationResult::truncated_categories`, while validation carries on
+    /// for the others. Categories without an entry are unlimited.
+    #[serde(default)]
+    pub max_errors_per_category: HashMap<ErrorCategory, usize>,
+
+    /// Fail validation when any warning is reported, for CI runs that
+    /// require a clean feed. Warnings stay in the result's warning list;
+    /// only `ValidationResult::is_valid` changes. Has no effect unless
+    /// `generate_warnings` is set.
+    #[serde(default)]
+    pub warnings_as_errors: bool,
+
+    /// Report a location discontinuity between two block rows with no
+    /// deadhead between them as an error instead of warning `W101`. Always
+    /// on with [`GtfsComplianceLevel::Strict`].
+    #[serde(default)]
+    pub discontinuity_as_error: bool,
 }
 
 impl ValidationConfig {
@@ -137,6 +245,9 @@ impl ValidationConfig {
             validate_duty_constraints: true,
             generate_warnings: true,
             max_errors: None,
+            max_errors_per_category: HashMap::new(),
+            warnings_as_errors: false,
+            discontinuity_as_error: false,
         }
     }
 
@@ -149,6 +260,9 @@ impl ValidationConfig {
             validate_duty_constraints: true,
             generate_warnings: true,
             max_errors: None,
+            max_errors_per_category: HashMap::new(),
+            warnings_as_errors: false,
+            discontinuity_as_error: false,
         }
     }
 
@@ -161,6 +275,9 @@ impl ValidationConfig {
             validate_duty_constraints: false,
             generate_warnings: false,
             max_errors: None,
+            max_errors_per_category: HashMap::new(),
+            warnings_as_errors: false,
+            discontinuity_as_error: false,
         }
     }
 
@@ -181,6 +298,30 @@ impl ValidationConfig {
         self.max_errors = Some(max);
         self
     }
+
+    /// Set the maximum number of errors kept for one category.
+    pub fn with_max_errors_for(mut self, category: ErrorCategory, max: usize) -> Self {
+        self.max_errors_per_category.insert(category, max);
+        self
+    }
+
+    /// Set whether any warning fails validation.
+    pub fn with_warnings_as_errors(mut self, enabled: bool) -> Self {
+        self.warnings_as_errors = enabled;
+        self
+    }
+
+    /// Set whether unbridged location discontinuities are errors.
+    pub fn with_discontinuity_as_error(mut self, enabled: bool) -> Self {
+        self.discontinuity_as_error = enabled;
+        self
+    }
+
+    /// Whether unbridged location discontinuities are errors, either
+    /// explicitly or because of strict GTFS compliance.
+    pub fn discontinuity_is_error(&self) -> bool {
+        self.discontinuity_as_error || self.gtfs_compliance == GtfsComplianceLevel::Strict
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +349,24 @@ mod tests {
         assert!(rules.min_layover_seconds < BusinessRules::default().min_layover_seconds);
         assert!(rules.max_duty_length_seconds > BusinessRules::default().max_duty_length_seconds);
     }
+
+    #[test]
+    fn test_business_rules_deserialize_without_newer_fields() {
+        let mut value = serde_json::to_value(BusinessRules::default()).unwrap();
+        let fields = value.as_object_mut().unwrap();
+        for field in [
+            "max_layover_seconds",
+            "max_deadhead_speed_mps",
+            "min_deadhead_duration_seconds",
+            "chronology_tolerance_seconds",
+        ] {
+            fields.remove(field);
+        }
+
+        let rules: BusinessRules = serde_json::from_value(value).unwrap();
+        assert_eq!(rules.max_layover_seconds, Some(7200));
+        assert_eq!(rules.max_deadhead_speed_mps, 30.0);
+        assert_eq!(rules.min_deadhead_duration_seconds, 60);
+        assert_eq!(rules.chronology_tolerance_seconds, 0);
+    }
 }
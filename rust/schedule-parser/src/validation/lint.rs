@@ -0,0 +1,222 @@
+//! Style and consistency checks that need neither GTFS nor configuration.
+//!
+//! Unlike [`Validator`](super::Validator), these checks don't judge whether a
+//! schedule is correct, only whether it is written consistently. See
+//! [`Schedule::lint`](crate::Schedule::lint) for the warning codes.
+
+use super::validator::{ValidationWarning, WarningCategory};
+use crate::models::ScheduleRow;
+use std::collections::HashMap;
+
+/// Run every lint check over `rows`, in the order of the codes.
+pub(crate) fn lint_rows(rows: &[ScheduleRow]) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+    check_time_formats(rows, &mut warnings);
+    check_block_id_case(rows, &mut warnings);
+    check_place_whitespace(rows, &mut warnings);
+    check_depot_names(rows, &mut warnings);
+    warnings
+}
+
+fn warning(code: &str, message: String, context: String) -> ValidationWarning {
+    ValidationWarning {
+        code: code.to_string(),
+        category: WarningCategory::BestPractice,
+        message,
+        context: Some(context),
+    }
+}
+
+/// Describe how a time is written, or `None` if it isn't a time at all
+/// (which validation reports instead).
+fn time_format(time: &str) -> Option<&'static str> {
+    let time = time.trim();
+    if !time.is_empty() && time.bytes().all(|b| b.is_ascii_digit()) {
+        return Some("seconds");
+    }
+    let parts: Vec<&str> = time.split(':').collect();
+    let short_hour = parts[0].len() == 1;
+    match (parts.as_slice(), short_hour) {
+        ([_, _, s], false) if s.contains('.') => Some("HH:MM:SS.sss"),
+        ([_, _, s], true) if s.contains('.') => Some("H:MM:SS.sss"),
+        ([_, _, _], false) => Some("HH:MM:SS"),
+        ([_, _, _], true) => Some("H:MM:SS"),
+        ([_, _], false) => Some("HH:MM"),
+        ([_, _], true) => Some("H:MM"),
+        _ => None,
+    }
+}
+
+/// `W301`: one warning per time format other than the most common one.
+fn check_time_formats(rows: &[ScheduleRow], warnings: &mut Vec<ValidationWarning>) {
+    // format -> (count, first row), in order of first use
+    let mut formats: Vec<(&str, usize, usize)> = Vec::new();
+    let mut total = 0;
+    for (idx, row) in rows.iter().enumerate() {
+        let times = [row.start_time.as_deref(), row.end_time.as_deref()];
+        for format in times.into_iter().flatten().filter_map(time_format) {
+            total += 1;
+            match formats.iter_mut().find(|(f, _, _)| *f == format) {
+                Some(entry) => entry.1 += 1,
+                None => formats.push((format, 1, idx)),
+            }
+        }
+    }
+
+    // Ties go to the format used first
+    let Some(&(main, _, _)) = formats.iter().rev().max_by_key(|&&(_, count, _)| count) else {
+        return;
+    };
+    for &(format, count, first_row) in &formats {
+        if format != main {
+            warnings.push(warning(
+                "W301",
+                format!(
+                    "{} of {} times are written as {}, but most are {}",
+                    count, total, format, main
+                ),
+                format!("row {}", first_row),
+            ));
+        }
+    }
+}
+
+/// Group values by a normalized key, keeping each group's spellings and the
+/// groups themselves in order of first appearance.
+fn spelling_groups<'a>(
+    values: impl Iterator<Item = &'a str>,
+    normalize: impl Fn(&str) -> String,
+) -> Vec<Vec<&'a str>> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<Vec<&str>> = Vec::new();
+    for value in values {
+        let pos = *index.entry(normalize(value)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        if !groups[pos].contains(&value) {
+            groups[pos].push(value);
+        }
+    }
+    groups.retain(|spellings| spellings.len() > 1);
+    groups
+}
+
+/// `W302`: block IDs that are the same apart from case.
+fn check_block_id_case(rows: &[ScheduleRow], warnings: &mut Vec<ValidationWarning>) {
+    let blocks = rows.iter().filter_map(|r| r.block.as_deref());
+    for spellings in spelling_groups(blocks, str::to_lowercase) {
+        warnings.push(warning(
+            "W302",
+            format!("Block IDs differ only in case: {}", spellings.join(", ")),
+            format!("block {}", spellings[0]),
+        ));
+    }
+}
+
+/// `W303`: place names with surrounding whitespace.
+fn check_place_whitespace(rows: &[ScheduleRow], warnings: &mut Vec<ValidationWarning>) {
+    for (idx, row) in rows.iter().enumerate() {
+        for (field, place) in [
+            ("start_place", &row.start_place),
+            ("end_place", &row.end_place),
+        ] {
+            if let Some(place) = place {
+                if place.trim() != place {
+                    warnings.push(warning(
+                        "W303",
+                        format!("{} {:?} has leading or trailing whitespace", field, place),
+                        format!("row {}", idx),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// `W304`: depot names that are the same apart from case, spaces,
+/// underscores and hyphens.
+fn check_depot_names(rows: &[ScheduleRow], warnings: &mut Vec<ValidationWarning>) {
+    let depots = rows.iter().filter_map(|r| r.depot.as_deref());
+    let normalize = |depot: &str| -> String {
+        depot
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    for spellings in spelling_groups(depots, normalize) {
+        warnings.push(warning(
+            "W304",
+            format!("Depot is named inconsistently: {}", spellings.join(", ")),
+            format!("depot {}", spellings[0]),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(block: &str, start: &str, end: &str) -> ScheduleRow {
+        ScheduleRow {
+            block: Some(block.to_string()),
+            start_time: Some(start.to_string()),
+            end_time: Some(end.to_string()),
+            start_place: Some("A".to_string()),
+            end_place: Some("B".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clean_rows() {
+        let rows = vec![
+            row("B1", "08:00:00", "09:00:00"),
+            row("B2", "09:00:00", "25:00:00"),
+        ];
+        assert!(lint_rows(&rows).is_empty());
+        assert!(lint_rows(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_lint_rows() {
+        let mut rows = vec![
+            row("B1", "08:00:00", "09:00:00"),
+            row("b1", "09:00:00", "09:30"),
+            row("B2", "8:00:00", "09:00:00"),
+            row("B2", "not a time", "10:00:00"),
+        ];
+        rows[0].depot = Some("North Depot".to_string());
+        rows[1].depot = Some("NORTH_DEPOT".to_string());
+        rows[2].depot = Some("north depot".to_string());
+        rows[3].depot = Some("South".to_string());
+        rows[2].end_place = Some("CENTRAL ".to_string());
+
+        let warnings = lint_rows(&rows);
+        let found: Vec<(&str, &str)> = warnings
+            .iter()
+            .map(|w| (w.code.as_str(), w.context.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("W301", "row 1"),
+                ("W301", "row 2"),
+                ("W302", "block B1"),
+                ("W303", "row 2"),
+                ("W304", "depot North Depot"),
+            ]
+        );
+        assert!(warnings
+            .iter()
+            .all(|w| w.category == WarningCategory::BestPractice));
+        assert_eq!(
+            warnings[0].message,
+            "1 of 7 times are written as HH:MM, but most are HH:MM:SS"
+        );
+        assert!(warnings[4]
+            .message
+            .ends_with("North Depot, NORTH_DEPOT, north depot"));
+    }
+}
@@ -1,8 +1,11 @@
 //! Schedule validation.
 
+pub mod cache;
 pub mod config;
+pub(crate) mod lint;
 pub mod rules;
 pub mod validator;
 
-pub use config::{BusinessRules, GtfsComplianceLevel, ValidationConfig};
+pub use cache::CachingValidator;
+pub use config::{BusinessRules, ContinuousDrivingReset, GtfsComplianceLevel, ValidationConfig};
 pub use validator::{ValidationResult, Validator};
@@ -2,6 +2,7 @@
 
 use crate::models::{Block, Schedule};
 use crate::validation::config::ValidationConfig;
+use std::collections::HashMap;
 
 /// Error from block continuity validation.
 #[derive(Debug, Clone)]
@@ -69,11 +70,12 @@ impl<'a> BlockContinuityChecker<'a> {
         let mut result = BlockContinuityResult::default();
 
         // Check chronological ordering
+        let tolerance = self.config.business_rules.chronology_tolerance_seconds;
         let mut prev_end: Option<u32> = None;
         for (idx, row) in block.rows.iter().enumerate() {
             if let Some(start) = row.start_time_seconds() {
                 if let Some(prev) = prev_end {
-                    if start < prev {
+                    if start.saturating_add(tolerance) < prev {
                         result.errors.push(BlockContinuityError {
                             error_type: BlockContinuityErrorType::ChronologyError,
                             block_id: block.block_id.clone(),
@@ -89,27 +91,41 @@ impl<'a> BlockContinuityChecker<'a> {
             prev_end = row.end_time_seconds();
         }
 
-        // Check location continuity
+        // Check location continuity. A discontinuity right next to a
+        // deadhead means the deadhead itself doesn't line up, which stays a
+        // warning; one between two other rows has no movement at all.
+        let discontinuity_is_error = self.config.discontinuity_is_error();
         for disc_idx in block.find_location_discontinuities() {
             let from_place = block.rows[disc_idx].end_place.as_deref().unwrap_or("?");
             let to_place = block.rows[disc_idx + 1]
                 .start_place
                 .as_deref()
                 .unwrap_or("?");
-
-            // This could be an error in strict mode, warning otherwise
-            result.warnings.push(BlockContinuityWarning {
-                code: "W101".to_string(),
-                block_id: block.block_id.clone(),
-                row_index: Some(disc_idx),
-                message: format!(
-                    "Location discontinuity: row {} ends at '{}' but row {} starts at '{}'",
-                    disc_idx,
-                    from_place,
-                    disc_idx + 1,
-                    to_place
-                ),
-            });
+            let message = format!(
+                "Location discontinuity: row {} ends at '{}' but row {} starts at '{}'",
+                disc_idx,
+                from_place,
+                disc_idx + 1,
+                to_place
+            );
+
+            let bridged =
+                block.rows[disc_idx].is_deadhead() || block.rows[disc_idx + 1].is_deadhead();
+            if discontinuity_is_error && !bridged {
+                result.errors.push(BlockContinuityError {
+                    error_type: BlockContinuityErrorType::LocationDiscontinuity,
+                    block_id: block.block_id.clone(),
+                    row_index: Some(disc_idx),
+                    message: format!("{} with no deadhead between them", message),
+                });
+            } else {
+                result.warnings.push(BlockContinuityWarning {
+                    code: "W101".to_string(),
+                    block_id: block.block_id.clone(),
+                    row_index: Some(disc_idx),
+                    message,
+                });
+            }
         }
 
         // Check time gaps
@@ -183,6 +199,26 @@ impl<'a> BlockContinuityChecker<'a> {
             });
         }
 
+        // Check revenue content (as warnings)
+        if self.config.generate_warnings {
+            let revenue_trips = block.revenue_trip_count();
+            if revenue_trips == 0 {
+                result.warnings.push(BlockContinuityWarning {
+                    code: "W105".to_string(),
+                    block_id: block.block_id.clone(),
+                    row_index: None,
+                    message: "Block has no revenue trips".to_string(),
+                });
+            } else if revenue_trips == 1 && !block.rows.iter().any(|r| r.is_deadhead()) {
+                result.warnings.push(BlockContinuityWarning {
+                    code: "W106".to_string(),
+                    block_id: block.block_id.clone(),
+                    row_index: None,
+                    message: "Block has a single revenue trip and no deadheads".to_string(),
+                });
+            }
+        }
+
         result
     }
 
@@ -211,12 +247,42 @@ impl<'a> BlockContinuityChecker<'a> {
 
         combined
     }
+
+    /// Check all blocks in a schedule, keeping each block's findings apart.
+    ///
+    /// Every block is keyed by its ID, including blocks with no findings.
+    /// Like [`check_schedule`](Self::check_schedule), checking stops once
+    /// `max_errors` errors have been found, so later blocks are left out.
+    pub fn check_schedule_grouped(
+        &self,
+        schedule: &mut Schedule,
+    ) -> HashMap<String, BlockContinuityResult> {
+        let mut grouped = HashMap::new();
+        let mut error_count = 0;
+
+        for block_id in schedule.block_ids() {
+            if let Some(block) = schedule.get_block(&block_id) {
+                let result = self.check_block(block);
+                error_count += result.errors.len();
+                grouped.insert(block_id, result);
+
+                if let Some(max) = self.config.max_errors {
+                    if error_count >= max {
+                        break;
+                    }
+                }
+            }
+        }
+
+        grouped
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::{RowType, ScheduleRow};
+    use crate::validation::config::GtfsComplianceLevel;
 
     fn make_block_row(start: &str, end: &str, start_place: &str, end_place: &str) -> ScheduleRow {
         ScheduleRow {
@@ -256,6 +322,42 @@ mod tests {
         let result = checker.check_block(&block);
 
         assert!(result.warnings.iter().any(|w| w.code == "W101"));
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn test_location_discontinuity_strict() {
+        let is_discontinuity_error = |block: &Block, config: &ValidationConfig| {
+            let result = BlockContinuityChecker::new(config).check_block(block);
+            result
+                .errors
+                .iter()
+                .any(|e| e.error_type == BlockContinuityErrorType::LocationDiscontinuity)
+        };
+        let strict = ValidationConfig::new().with_gtfs_compliance(GtfsComplianceLevel::Strict);
+        let flagged = ValidationConfig::new().with_discontinuity_as_error(true);
+
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_block_row("08:00:00", "09:00:00", "A", "B"));
+        block.add_row(make_block_row("09:00:00", "10:00:00", "C", "D"));
+        assert!(is_discontinuity_error(&block, &strict));
+        assert!(is_discontinuity_error(&block, &flagged));
+        assert!(!is_discontinuity_error(&block, &ValidationConfig::new()));
+
+        // A deadhead bridging the gap leaves nothing to report
+        let mut deadhead = make_block_row("09:00:00", "09:00:00", "B", "C");
+        deadhead.row_type = RowType::Deadhead;
+        deadhead.trip_id = None;
+        block.rows.insert(1, deadhead);
+        let result = BlockContinuityChecker::new(&strict).check_block(&block);
+        assert!(result.is_valid());
+        assert!(!result.warnings.iter().any(|w| w.code == "W101"));
+
+        // A deadhead that doesn't line up is still only a warning
+        block.rows[1].end_place = Some("X".to_string());
+        let result = BlockContinuityChecker::new(&strict).check_block(&block);
+        assert!(result.is_valid());
+        assert!(result.warnings.iter().any(|w| w.code == "W101"));
     }
 
     #[test]
@@ -274,6 +376,29 @@ mod tests {
             .any(|e| e.error_type == BlockContinuityErrorType::ChronologyError));
     }
 
+    #[test]
+    fn test_chronology_tolerance() {
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_block_row("08:00:00", "09:00:02", "A", "B"));
+        block.add_row(make_block_row("09:00:00", "10:00:00", "B", "C")); // 2s overlap
+
+        let is_chronology_error =
+            |e: &BlockContinuityError| e.error_type == BlockContinuityErrorType::ChronologyError;
+
+        let mut config = ValidationConfig::new();
+        let result = BlockContinuityChecker::new(&config).check_block(&block);
+        assert!(result.errors.iter().any(is_chronology_error));
+
+        config.business_rules.chronology_tolerance_seconds = 5;
+        let result = BlockContinuityChecker::new(&config).check_block(&block);
+        assert!(!result.errors.iter().any(is_chronology_error));
+
+        // A tolerance near u32::MAX must not overflow.
+        config.business_rules.chronology_tolerance_seconds = u32::MAX;
+        let result = BlockContinuityChecker::new(&config).check_block(&block);
+        assert!(!result.errors.iter().any(is_chronology_error));
+    }
+
     #[test]
     fn test_block_duration_too_long() {
         let mut block = Block::new("B1".to_string());
@@ -290,4 +415,81 @@ mod tests {
             .iter()
             .any(|e| e.error_type == BlockContinuityErrorType::DurationTooLong));
     }
+
+    #[test]
+    fn test_block_without_revenue_trips() {
+        let mut block = Block::new("B1".to_string());
+        let mut pull_out = make_block_row("06:00:00", "06:30:00", "DEPOT", "A");
+        pull_out.row_type = RowType::PullOut;
+        pull_out.trip_id = None;
+        let mut pull_in = make_block_row("06:30:00", "07:00:00", "A", "DEPOT");
+        pull_in.row_type = RowType::PullIn;
+        pull_in.trip_id = None;
+        block.add_row(pull_out);
+        block.add_row(pull_in);
+
+        let config = ValidationConfig::new();
+        let checker = BlockContinuityChecker::new(&config);
+        let result = checker.check_block(&block);
+
+        assert!(result.warnings.iter().any(|w| w.code == "W105"));
+        assert!(!result.warnings.iter().any(|w| w.code == "W106"));
+    }
+
+    #[test]
+    fn test_single_trip_block_without_deadheads() {
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_block_row("08:00:00", "09:00:00", "A", "B"));
+
+        let config = ValidationConfig::new();
+        let checker = BlockContinuityChecker::new(&config);
+        let result = checker.check_block(&block);
+        assert!(result.warnings.iter().any(|w| w.code == "W106"));
+
+        let mut pull_in = make_block_row("09:00:00", "09:20:00", "B", "DEPOT");
+        pull_in.row_type = RowType::PullIn;
+        pull_in.trip_id = None;
+        block.add_row(pull_in);
+
+        let result = checker.check_block(&block);
+        assert!(!result
+            .warnings
+            .iter()
+            .any(|w| w.code == "W105" || w.code == "W106"));
+    }
+
+    #[test]
+    fn test_check_schedule_grouped() {
+        let row = |block: &str, start: &str, end: &str, from: &str, to: &str| ScheduleRow {
+            block: Some(block.to_string()),
+            ..make_block_row(start, end, from, to)
+        };
+        let mut schedule = Schedule::from_rows(vec![
+            row("B1", "08:00:00", "09:00:00", "A", "B"),
+            row("B1", "09:00:00", "10:00:00", "B", "A"),
+            row("B2", "08:00:00", "09:00:00", "A", "B"),
+            row("B2", "08:30:00", "10:00:00", "C", "A"),
+        ]);
+        let config = ValidationConfig::default();
+        let checker = BlockContinuityChecker::new(&config);
+
+        let grouped = checker.check_schedule_grouped(&mut schedule);
+        assert_eq!(grouped.len(), 2);
+        assert!(grouped["B1"].is_valid());
+        assert!(!grouped["B1"].warnings.iter().any(|w| w.code == "W101"));
+
+        let b2 = &grouped["B2"];
+        assert!(b2.errors.iter().all(|e| e.block_id == "B2"));
+        assert!(b2
+            .errors
+            .iter()
+            .any(|e| e.error_type == BlockContinuityErrorType::ChronologyError));
+        assert!(b2.warnings.iter().any(|w| w.code == "W101"));
+
+        let flat = checker.check_schedule(&mut schedule);
+        let grouped_errors: usize = grouped.values().map(|r| r.errors.len()).sum();
+        let grouped_warnings: usize = grouped.values().map(|r| r.warnings.len()).sum();
+        assert_eq!(flat.errors.len(), grouped_errors);
+        assert_eq!(flat.warnings.len(), grouped_warnings);
+    }
 }
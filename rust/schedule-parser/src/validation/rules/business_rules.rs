@@ -1,7 +1,10 @@
 //! Business rules validation.
 
-use crate::models::{Duty, Schedule, ScheduleRow};
-use crate::validation::config::ValidationConfig;
+use crate::models::{Block, BlockKey, Duty, RowType, Schedule, ScheduleRow};
+use crate::progress::ProgressCounter;
+use crate::validation::config::{ContinuousDrivingReset, ValidationConfig};
+use std::collections::{BTreeSet, HashMap};
+use transit_core::RouteType;
 
 /// Error from business rules validation.
 #[derive(Debug, Clone)]
@@ -24,6 +27,8 @@ pub enum BusinessRuleErrorType {
     ContinuousDrivingTooLong,
     /// Break duration too short.
     BreakTooShort,
+    /// Relief doesn't line up with the rows around it in its block.
+    ReliefDiscontinuity,
 }
 
 /// Warning from business rules check.
@@ -50,12 +55,46 @@ impl BusinessRuleResult {
 /// Checks business rules.
 pub struct BusinessRuleChecker<'a> {
     config: &'a ValidationConfig,
+    route_types: Option<&'a HashMap<String, RouteType>>,
+    progress: Option<&'a ProgressCounter>,
 }
 
 impl<'a> BusinessRuleChecker<'a> {
     /// Create a new business rule checker.
     pub fn new(config: &'a ValidationConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            route_types: None,
+            progress: None,
+        }
+    }
+
+    /// Route type of each GTFS trip, keyed by trip ID, used to scope rules
+    /// such as `layover_exempt_route_types`. Without it those rules apply
+    /// to every row.
+    pub fn with_route_types(mut self, route_types: &'a HashMap<String, RouteType>) -> Self {
+        self.route_types = Some(route_types);
+        self
+    }
+
+    /// Whether the row's trip belongs to a route type exempt from layover checks.
+    fn is_layover_exempt(&self, row: &ScheduleRow) -> bool {
+        let exempt = &self.config.business_rules.layover_exempt_route_types;
+        if exempt.is_empty() {
+            return false;
+        }
+        let route_type = self.route_types.and_then(|route_types| {
+            row.trip_id
+                .as_deref()
+                .and_then(|trip_id| route_types.get(trip_id))
+        });
+        matches!(route_type, Some(route_type) if exempt.contains(route_type))
+    }
+
+    /// Advance a progress counter once per row checked by `check_schedule`.
+    pub fn with_progress(mut self, progress: Option<&'a ProgressCounter>) -> Self {
+        self.progress = progress;
+        self
     }
 
     /// Check a single schedule row.
@@ -99,6 +138,65 @@ impl<'a> BusinessRuleChecker<'a> {
             }
         }
 
+        // Check implied deadhead speed
+        if row.is_deadhead() {
+            if let (Some(distance), Some(duration)) =
+                (row.distance_meters(), row.duration_seconds())
+            {
+                if distance > 0.0 && duration < rules.min_deadhead_duration_seconds {
+                    result.warnings.push(BusinessRuleWarning {
+                        code: "W207".to_string(),
+                        context: format!("row {}", row_index),
+                        message: format!(
+                            "Deadhead covers {:.0} meters in only {} seconds (minimum {} seconds)",
+                            distance, duration, rules.min_deadhead_duration_seconds
+                        ),
+                    });
+                } else if duration > 0 {
+                    let speed = distance / duration as f64;
+                    if speed > rules.max_deadhead_speed_mps {
+                        result.warnings.push(BusinessRuleWarning {
+                            code: "W204".to_string(),
+                            context: format!("row {}", row_index),
+                            message: format!(
+                                "Deadhead speed {:.1} m/s ({:.0} km/h) exceeds maximum {:.1} m/s",
+                                speed,
+                                speed * 3.6,
+                                rules.max_deadhead_speed_mps
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Check implied revenue speed, to catch coordinate or time entry errors
+        if let (true, Some(max_speed)) = (row.is_revenue(), rules.max_revenue_speed_mps) {
+            if let (Some(distance), Some(duration)) =
+                (row.distance_meters(), row.duration_seconds())
+            {
+                let speed = distance / duration as f64;
+                if distance > 0.0 && speed > max_speed {
+                    let message = if duration == 0 {
+                        format!("Revenue trip covers {:.0} meters in 0 seconds", distance)
+                    } else {
+                        format!(
+                            "Revenue trip speed {:.1} m/s ({:.0} km/h) over {:.0} meters exceeds maximum {:.1} m/s",
+                            speed,
+                            speed * 3.6,
+                            distance,
+                            max_speed
+                        )
+                    };
+                    result.warnings.push(BusinessRuleWarning {
+                        code: "W206".to_string(),
+                        context: format!("row {}", row_index),
+                        message,
+                    });
+                }
+            }
+        }
+
         // Check coordinates if flagging enabled
         if rules.flag_missing_coordinates && row.is_revenue() {
             if row.start_lat.is_none() || row.start_lon.is_none() {
@@ -121,11 +219,15 @@ impl<'a> BusinessRuleChecker<'a> {
     }
 
     /// Check layover between consecutive rows.
+    ///
+    /// `block_key` decides whether the rows are in the same block, for the
+    /// long layover warning.
     pub fn check_layover(
         &self,
         prev_row: &ScheduleRow,
         curr_row: &ScheduleRow,
         row_index: usize,
+        block_key: &BlockKey,
     ) -> BusinessRuleResult {
         let mut result = BusinessRuleResult::default();
         let rules = &self.config.business_rules;
@@ -135,6 +237,10 @@ impl<'a> BusinessRuleChecker<'a> {
             return result;
         }
 
+        if self.is_layover_exempt(prev_row) || self.is_layover_exempt(curr_row) {
+            return result;
+        }
+
         if let (Some(prev_end), Some(curr_start)) =
             (prev_row.end_time_seconds(), curr_row.start_time_seconds())
         {
@@ -152,6 +258,28 @@ impl<'a> BusinessRuleChecker<'a> {
                         ),
                     });
                 }
+
+                // A long wait at the same place within a block is an idle
+                // vehicle; gaps with repositioning are left to block continuity
+                let prev_block = block_key.key_for(prev_row);
+                let same_block = prev_block.is_some() && prev_block == block_key.key_for(curr_row);
+                let same_place =
+                    prev_row.end_place.is_some() && prev_row.end_place == curr_row.start_place;
+                if let Some(max) = rules.max_layover_seconds {
+                    if same_block && same_place && layover > max {
+                        result.warnings.push(BusinessRuleWarning {
+                            code: "W205".to_string(),
+                            context: format!("rows {}-{}", row_index - 1, row_index),
+                            message: format!(
+                                "Layover {} seconds ({:.1} min) at {} exceeds maximum {} seconds",
+                                layover,
+                                layover as f64 / 60.0,
+                                curr_row.start_place.as_deref().unwrap_or_default(),
+                                max
+                            ),
+                        });
+                    }
+                }
             }
         }
 
@@ -179,20 +307,163 @@ impl<'a> BusinessRuleChecker<'a> {
             }
         }
 
+        if let Some(warning) = self.check_duty_depots(duty) {
+            result.warnings.push(warning);
+        }
+        result.warnings.extend(self.check_break_locations(duty));
+
         // Check continuous driving
-        for (idx, piece) in duty.pieces_of_work().iter().enumerate() {
-            if let Some(duration) = piece.duration_seconds() {
-                if duration > rules.max_continuous_driving_seconds {
-                    result.errors.push(BusinessRuleError {
-                        error_type: BusinessRuleErrorType::ContinuousDrivingTooLong,
-                        context: format!("duty {} piece {}", duty.duty_id, idx),
-                        message: format!(
-                            "Continuous driving {} seconds ({:.1} hours) exceeds maximum {} seconds",
-                            duration,
-                            duration as f64 / 3600.0,
-                            rules.max_continuous_driving_seconds
-                        ),
-                    });
+        let (stretches, label) = match rules.continuous_driving_reset {
+            ContinuousDrivingReset::AnyBreak => (
+                duty.pieces_of_work()
+                    .iter()
+                    .map(|piece| piece.duration_seconds())
+                    .collect(),
+                "piece",
+            ),
+            ContinuousDrivingReset::QualifyingBreakOnly => (
+                qualifying_driving_stretches(duty, rules.min_break_duration_seconds),
+                "stretch",
+            ),
+        };
+        for (idx, duration) in stretches.into_iter().enumerate() {
+            let Some(duration) = duration else { continue };
+            if duration > rules.max_continuous_driving_seconds {
+                result.errors.push(BusinessRuleError {
+                    error_type: BusinessRuleErrorType::ContinuousDrivingTooLong,
+                    context: format!("duty {} {} {}", duty.duty_id, label, idx),
+                    message: format!(
+                        "Continuous driving {} seconds ({:.1} hours) exceeds maximum {} seconds",
+                        duration,
+                        duration as f64 / 3600.0,
+                        rules.max_continuous_driving_seconds
+                    ),
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Warn when a duty signs on at one depot and off at another.
+    ///
+    /// The sign-on depot is the start place of an opening pull-out, else the
+    /// first row's depot, else the duty's depot; the sign-off depot is found
+    /// the same way from the last row and a closing pull-in. Duties with a
+    /// relief row are skipped, since the relief can explain the change.
+    pub fn check_duty_depots(&self, duty: &Duty) -> Option<BusinessRuleWarning> {
+        if duty.rows.iter().any(|r| r.row_type == RowType::Relief) {
+            return None;
+        }
+
+        let (first, last) = (duty.rows.first()?, duty.rows.last()?);
+        let sign_on = match first.row_type {
+            RowType::PullOut => first.start_place.as_ref(),
+            _ => None,
+        }
+        .or(first.depot.as_ref())
+        .or(duty.depot.as_ref())?;
+        let sign_off = match last.row_type {
+            RowType::PullIn => last.end_place.as_ref(),
+            _ => None,
+        }
+        .or(last.depot.as_ref())
+        .or(duty.depot.as_ref())?;
+
+        if sign_on == sign_off {
+            return None;
+        }
+
+        Some(BusinessRuleWarning {
+            code: "W208".to_string(),
+            context: format!("duty {} depots {} -> {}", duty.duty_id, sign_on, sign_off),
+            message: format!(
+                "Duty signs on at depot '{}' but signs off at depot '{}' with no relief",
+                sign_on, sign_off
+            ),
+        })
+    }
+
+    /// Warn when a break or relief starts somewhere other than where the
+    /// duty's previous row ends.
+    ///
+    /// The driver would have to get from one place to the other with no
+    /// deadhead scheduled, which usually means the break is at the wrong
+    /// location. Rows are compared in duty order, across blocks; places are
+    /// compared only when both rows have one.
+    pub fn check_break_locations(&self, duty: &Duty) -> Vec<BusinessRuleWarning> {
+        duty.rows
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[1].is_break_or_relief())
+            .filter_map(|(idx, pair)| {
+                let end_place = pair[0].end_place.as_deref()?;
+                let start_place = pair[1].start_place.as_deref()?;
+                (end_place != start_place).then(|| BusinessRuleWarning {
+                    code: "W209".to_string(),
+                    context: format!("duty {} row {}", duty.duty_id, idx + 1),
+                    message: format!(
+                        "{} starts at '{}' but the previous row ends at '{}' with no deadhead between them",
+                        if pair[1].row_type == RowType::Break { "Break" } else { "Relief" },
+                        start_place,
+                        end_place
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Check that relief rows in a block are contiguous with their neighbours.
+    ///
+    /// A relief must start where and when the previous row ends, and the
+    /// next row must start where and when the relief ends. Times may differ
+    /// by up to `time_tolerance_seconds`; places are compared only when both
+    /// sides have one.
+    pub fn check_block_reliefs(&self, block: &Block) -> BusinessRuleResult {
+        let mut result = BusinessRuleResult::default();
+        let tolerance = self.config.business_rules.time_tolerance_seconds;
+
+        for (idx, relief) in block.rows.iter().enumerate() {
+            if relief.row_type != RowType::Relief {
+                continue;
+            }
+
+            let neighbours = [
+                idx.checked_sub(1).map(|prev| (&block.rows[prev], relief)),
+                block.rows.get(idx + 1).map(|next| (relief, next)),
+            ];
+            for (before, after) in neighbours.into_iter().flatten() {
+                let context = format!("block {} relief row {}", block.block_id, idx);
+
+                if let (Some(end), Some(start)) =
+                    (before.end_time_seconds(), after.start_time_seconds())
+                {
+                    if end.abs_diff(start) > tolerance {
+                        result.errors.push(BusinessRuleError {
+                            error_type: BusinessRuleErrorType::ReliefDiscontinuity,
+                            context: context.clone(),
+                            message: format!(
+                                "Relief time mismatch: row ends at {} but next row starts at {}",
+                                before.end_time.as_deref().unwrap_or("?"),
+                                after.start_time.as_deref().unwrap_or("?"),
+                            ),
+                        });
+                    }
+                }
+
+                if let (Some(end_place), Some(start_place)) =
+                    (&before.end_place, &after.start_place)
+                {
+                    if end_place != start_place {
+                        result.errors.push(BusinessRuleError {
+                            error_type: BusinessRuleErrorType::ReliefDiscontinuity,
+                            context,
+                            message: format!(
+                                "Relief location mismatch: row ends at '{}' but next row starts at '{}'",
+                                end_place, start_place
+                            ),
+                        });
+                    }
                 }
             }
         }
@@ -213,11 +484,16 @@ impl<'a> BusinessRuleChecker<'a> {
 
             // Check layover with previous row
             if idx > 0 {
-                let layover_result = self.check_layover(&schedule.rows[idx - 1], row, idx);
+                let layover_result =
+                    self.check_layover(&schedule.rows[idx - 1], row, idx, schedule.block_key());
                 combined.errors.extend(layover_result.errors);
                 combined.warnings.extend(layover_result.warnings);
             }
 
+            if let Some(progress) = self.progress {
+                progress.increment();
+            }
+
             // Check max errors limit
             if let Some(max) = self.config.max_errors {
                 if combined.errors.len() >= max {
@@ -232,7 +508,7 @@ impl<'a> BusinessRuleChecker<'a> {
                 .rows
                 .iter()
                 .enumerate()
-                .filter(|(_, r)| r.is_revenue() && r.block.is_none())
+                .filter(|(_, r)| r.is_revenue() && schedule.block_key().key_for(r).is_none())
                 .collect();
 
             for (idx, _) in orphans {
@@ -247,6 +523,85 @@ impl<'a> BusinessRuleChecker<'a> {
         combined
     }
 
+    /// Check only the given rows, which must be in range, sorted and unique.
+    ///
+    /// Runs the single-row rules and the orphan trip check on each row, and
+    /// the layover check between each row and its neighbours in schedule
+    /// order.
+    pub fn check_rows(&self, schedule: &Schedule, row_indices: &[usize]) -> BusinessRuleResult {
+        let mut combined = BusinessRuleResult::default();
+        let rules = &self.config.business_rules;
+
+        // Each layover pair is keyed by the index of its later row
+        let mut layover_rows = BTreeSet::new();
+        for &idx in row_indices {
+            let row = &schedule.rows[idx];
+            let result = self.check_row(row, idx);
+            combined.errors.extend(result.errors);
+            combined.warnings.extend(result.warnings);
+
+            if rules.flag_orphan_trips
+                && row.is_revenue()
+                && schedule.block_key().key_for(row).is_none()
+            {
+                combined.warnings.push(BusinessRuleWarning {
+                    code: "W203".to_string(),
+                    context: format!("row {}", idx),
+                    message: "Revenue trip not assigned to any block".to_string(),
+                });
+            }
+
+            if idx > 0 {
+                layover_rows.insert(idx);
+            }
+            if idx + 1 < schedule.rows.len() {
+                layover_rows.insert(idx + 1);
+            }
+        }
+
+        for idx in layover_rows {
+            let result = self.check_layover(
+                &schedule.rows[idx - 1],
+                &schedule.rows[idx],
+                idx,
+                schedule.block_key(),
+            );
+            combined.errors.extend(result.errors);
+            combined.warnings.extend(result.warnings);
+        }
+
+        if let Some(max) = self.config.max_errors {
+            combined.errors.truncate(max);
+        }
+
+        combined
+    }
+
+    /// Check relief continuity in every block if duty validation is enabled.
+    pub fn check_reliefs(&self, schedule: &mut Schedule) -> BusinessRuleResult {
+        if !self.config.validate_duty_constraints {
+            return BusinessRuleResult::default();
+        }
+
+        let mut combined = BusinessRuleResult::default();
+
+        for block_id in schedule.block_ids() {
+            if let Some(block) = schedule.get_block(&block_id) {
+                let result = self.check_block_reliefs(block);
+                combined.errors.extend(result.errors);
+                combined.warnings.extend(result.warnings);
+
+                if let Some(max) = self.config.max_errors {
+                    if combined.errors.len() >= max {
+                        break;
+                    }
+                }
+            }
+        }
+
+        combined
+    }
+
     /// Check duties if duty validation is enabled.
     pub fn check_duties(&self, schedule: &mut Schedule) -> BusinessRuleResult {
         if !self.config.validate_duty_constraints {
@@ -280,10 +635,45 @@ impl<'a> BusinessRuleChecker<'a> {
     }
 }
 
+/// Durations of the stretches of continuous driving in a duty under
+/// [`ContinuousDrivingReset::QualifyingBreakOnly`], in order.
+///
+/// Break and relief rows and gaps between rows are time off, and only a run
+/// of time off lasting `min_break_seconds` or more ends a stretch; layovers,
+/// deadheads and shorter breaks count towards it. Times are normalized
+/// across midnight; rows without both times are skipped.
+fn qualifying_driving_stretches(duty: &Duty, min_break_seconds: u32) -> Vec<Option<u32>> {
+    let driving = duty
+        .rows
+        .iter()
+        .zip(duty.normalized_row_times())
+        .filter(|(row, _)| !row.is_break_or_relief())
+        .filter_map(|(_, times)| match times {
+            (Some(start), Some(end)) => Some((start, end.max(start))),
+            _ => None,
+        });
+
+    let mut stretches = Vec::new();
+    let mut current: Option<(u32, u32)> = None;
+    for (start, end) in driving {
+        current = match current {
+            Some((stretch_start, stretch_end))
+                if start > stretch_end && start - stretch_end >= min_break_seconds =>
+            {
+                stretches.push(Some(stretch_end - stretch_start));
+                Some((start, end))
+            }
+            Some((stretch_start, stretch_end)) => Some((stretch_start, stretch_end.max(end))),
+            None => Some((start, end)),
+        };
+    }
+    stretches.extend(current.map(|(start, end)| Some(end - start)));
+    stretches
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::RowType;
 
     fn make_row(start: &str, end: &str, row_type: RowType) -> ScheduleRow {
         ScheduleRow {
@@ -322,7 +712,7 @@ mod tests {
         let row1 = make_row("08:00:00", "09:00:00", RowType::Revenue);
         let row2 = make_row("09:02:00", "10:00:00", RowType::Revenue); // 2 min layover
 
-        let result = checker.check_layover(&row1, &row2, 1);
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
 
         assert!(result
             .errors
@@ -338,11 +728,47 @@ mod tests {
         let row1 = make_row("08:00:00", "09:00:00", RowType::Revenue);
         let row2 = make_row("09:10:00", "10:00:00", RowType::Revenue); // 10 min layover
 
-        let result = checker.check_layover(&row1, &row2, 1);
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
 
         assert!(result.is_valid());
     }
 
+    #[test]
+    fn test_long_layover_warning() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let mut row1 = make_row("08:00:00", "09:00:00", RowType::Revenue);
+        let mut row2 = make_row("11:30:00", "12:00:00", RowType::Revenue); // 2.5 hr layover
+        row1.block = Some("B1".to_string());
+        row2.block = Some("B1".to_string());
+        row1.end_place = Some("TERMINUS".to_string());
+        row2.start_place = Some("TERMINUS".to_string());
+
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
+        assert!(result.is_valid());
+        assert!(result.warnings.iter().any(|w| w.code == "W205"));
+
+        // Repositioning between places is a continuity gap, not a layover
+        row2.start_place = Some("ELSEWHERE".to_string());
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
+        assert!(!result.warnings.iter().any(|w| w.code == "W205"));
+
+        row2.start_place = Some("TERMINUS".to_string());
+        row2.block = Some("B2".to_string());
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
+        assert!(!result.warnings.iter().any(|w| w.code == "W205"));
+
+        // Under a depot:block key, block B1 at two depots is two vehicles
+        row2.block = Some("B1".to_string());
+        row1.depot = Some("NORTH".to_string());
+        row2.depot = Some("SOUTH".to_string());
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::depot_block());
+        assert!(!result.warnings.iter().any(|w| w.code == "W205"));
+        let result = checker.check_layover(&row1, &row2, 1, &BlockKey::default());
+        assert!(result.warnings.iter().any(|w| w.code == "W205"));
+    }
+
     #[test]
     fn test_duty_too_long() {
         let config = ValidationConfig::new();
@@ -376,6 +802,170 @@ mod tests {
             .any(|e| e.error_type == BusinessRuleErrorType::ContinuousDrivingTooLong));
     }
 
+    #[test]
+    fn test_continuous_driving_across_gaps_and_layovers() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        // A minute's gap between trips doesn't reset the clock
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("06:00:00", "08:30:00", RowType::Revenue));
+        duty.add_row(make_row("08:31:00", "11:00:00", RowType::Revenue));
+        let result = checker.check_duty(&duty);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].context, "duty D1 piece 0");
+
+        // Neither does a layover
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("06:00:00", "08:30:00", RowType::Revenue));
+        duty.add_row(make_row("08:30:00", "08:45:00", RowType::Layover));
+        duty.add_row(make_row("08:45:00", "11:00:00", RowType::Revenue));
+        let result = checker.check_duty(&duty);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].context, "duty D1 piece 0");
+    }
+
+    #[test]
+    fn test_continuous_driving_reset() {
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_row("06:00:00", "08:00:00", RowType::Revenue));
+        duty.add_row(make_row("08:00:00", "08:03:00", RowType::Layover));
+        duty.add_row(make_row("08:03:00", "10:00:00", RowType::Revenue));
+        duty.add_row(make_row("10:00:00", "10:10:00", RowType::Break));
+        duty.add_row(make_row("10:10:00", "12:00:00", RowType::Revenue));
+
+        // Any break ends a piece by default; the layover doesn't
+        let mut config = ValidationConfig::new();
+        config.business_rules.max_continuous_driving_seconds = 3 * 3600;
+        let result = BusinessRuleChecker::new(&config).check_duty(&duty);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].context, "duty D1 piece 0");
+
+        // A 10-minute break isn't a qualifying break
+        config.business_rules.continuous_driving_reset =
+            ContinuousDrivingReset::QualifyingBreakOnly;
+        assert_eq!(qualifying_driving_stretches(&duty, 1800), vec![Some(21600)]);
+        config.business_rules.max_continuous_driving_seconds = 5 * 3600;
+        let result = BusinessRuleChecker::new(&config).check_duty(&duty);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].context, "duty D1 stretch 0");
+
+        // With a 10-minute minimum it is
+        assert_eq!(
+            qualifying_driving_stretches(&duty, 600),
+            vec![Some(14400), Some(6600)]
+        );
+    }
+
+    fn make_deadhead(start: &str, end: &str, to_lat: f64) -> ScheduleRow {
+        ScheduleRow {
+            start_lat: Some(51.5),
+            start_lon: Some(-0.1),
+            end_lat: Some(to_lat),
+            end_lon: Some(-0.1),
+            ..make_row(start, end, RowType::Deadhead)
+        }
+    }
+
+    #[test]
+    fn test_duty_depot_mismatch() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let depot_row = |row_type: RowType, start: &str, end: &str, depot: &str| ScheduleRow {
+            depot: Some(depot.to_string()),
+            ..make_row(start, end, row_type)
+        };
+
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(depot_row(RowType::Revenue, "06:00:00", "08:00:00", "NORTH"));
+        duty.add_row(depot_row(RowType::Revenue, "08:30:00", "10:00:00", "SOUTH"));
+
+        let warning = checker.check_duty_depots(&duty).unwrap();
+        assert_eq!(warning.code, "W208");
+        assert!(warning.context.contains("NORTH -> SOUTH"));
+        assert!(checker
+            .check_duty(&duty)
+            .warnings
+            .iter()
+            .any(|w| w.code == "W208"));
+
+        // A pull-in to the sign-on depot closes the duty where it started
+        duty.add_row(make_placed_row(
+            "10:00:00",
+            "10:20:00",
+            RowType::PullIn,
+            "X",
+            "NORTH",
+        ));
+        assert!(checker.check_duty_depots(&duty).is_none());
+
+        // A relief explains a change of depot
+        let mut relieved = Duty::new("D2".to_string());
+        relieved.add_row(depot_row(RowType::Revenue, "06:00:00", "08:00:00", "NORTH"));
+        relieved.add_row(make_row("08:00:00", "08:10:00", RowType::Relief));
+        relieved.add_row(depot_row(RowType::Revenue, "08:30:00", "10:00:00", "SOUTH"));
+        assert!(checker.check_duty_depots(&relieved).is_none());
+    }
+
+    #[test]
+    fn test_deadhead_too_fast() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        // ~40 km in 3 minutes
+        let row = make_deadhead("08:00:00", "08:03:00", 51.86);
+        let result = checker.check_row(&row, 0);
+        assert!(result.warnings.iter().any(|w| w.code == "W204"));
+
+        // ~4 km in 10 minutes is plausible
+        let row = make_deadhead("08:00:00", "08:10:00", 51.536);
+        let result = checker.check_row(&row, 0);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_revenue_too_fast() {
+        let revenue = |start: &str, end: &str, to_lat: f64| ScheduleRow {
+            row_type: RowType::Revenue,
+            trip_id: Some("T1".to_string()),
+            ..make_deadhead(start, end, to_lat)
+        };
+
+        // Off unless a limit is set
+        let mut config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+        let result = checker.check_row(&revenue("08:00:00", "08:10:00", 51.86), 0);
+        assert!(result.warnings.is_empty());
+
+        config.business_rules.max_revenue_speed_mps = Some(25.0);
+        let checker = BusinessRuleChecker::new(&config);
+
+        // ~40 km in 10 minutes
+        let result = checker.check_row(&revenue("08:00:00", "08:10:00", 51.86), 0);
+        assert!(result.warnings.iter().any(|w| w.code == "W206"));
+
+        // ~4 km in 10 minutes is plausible, as is a loop back to the start
+        let result = checker.check_row(&revenue("08:00:00", "08:10:00", 51.536), 0);
+        assert!(result.warnings.is_empty());
+        let result = checker.check_row(&revenue("08:00:00", "08:00:00", 51.5), 0);
+        assert!(result.warnings.is_empty());
+
+        let result = checker.check_row(&revenue("08:00:00", "08:00:00", 51.51), 0);
+        assert!(result.warnings[0].message.contains("in 0 seconds"));
+    }
+
+    #[test]
+    fn test_deadhead_near_zero_duration() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let row = make_deadhead("08:00:00", "08:00:00", 51.51);
+        let result = checker.check_row(&row, 0);
+        assert!(result.warnings.iter().any(|w| w.code == "W207"));
+        assert!(!result.warnings.iter().any(|w| w.code == "W204"));
+    }
+
     #[test]
     fn test_short_break() {
         let config = ValidationConfig::new();
@@ -390,4 +980,133 @@ mod tests {
             .iter()
             .any(|e| e.error_type == BusinessRuleErrorType::BreakTooShort));
     }
+
+    #[test]
+    fn test_break_location_jump() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let mut duty = Duty::new("D1".to_string());
+        duty.add_row(make_placed_row(
+            "08:00:00",
+            "10:00:00",
+            RowType::Revenue,
+            "A",
+            "X",
+        ));
+        duty.add_row(make_placed_row(
+            "10:00:00",
+            "10:40:00",
+            RowType::Break,
+            "Y",
+            "Y",
+        ));
+        duty.add_row(make_placed_row(
+            "10:40:00",
+            "12:00:00",
+            RowType::Revenue,
+            "Y",
+            "B",
+        ));
+
+        let warnings = checker.check_break_locations(&duty);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "W209");
+        assert_eq!(warnings[0].context, "duty D1 row 1");
+        assert!(warnings[0].message.contains("'Y'") && warnings[0].message.contains("'X'"));
+        assert!(checker
+            .check_duty(&duty)
+            .warnings
+            .iter()
+            .any(|w| w.code == "W209"));
+
+        duty.rows[1].start_place = Some("X".to_string());
+        assert!(checker.check_break_locations(&duty).is_empty());
+    }
+
+    fn make_placed_row(
+        start: &str,
+        end: &str,
+        row_type: RowType,
+        start_place: &str,
+        end_place: &str,
+    ) -> ScheduleRow {
+        ScheduleRow {
+            start_place: Some(start_place.to_string()),
+            end_place: Some(end_place.to_string()),
+            ..make_row(start, end, row_type)
+        }
+    }
+
+    #[test]
+    fn test_contiguous_relief() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_placed_row(
+            "08:00:00",
+            "10:00:00",
+            RowType::Revenue,
+            "A",
+            "X",
+        ));
+        block.add_row(make_placed_row(
+            "10:00:00",
+            "10:05:00",
+            RowType::Relief,
+            "X",
+            "X",
+        ));
+        block.add_row(make_placed_row(
+            "10:05:00",
+            "12:00:00",
+            RowType::Revenue,
+            "X",
+            "B",
+        ));
+
+        assert!(checker.check_block_reliefs(&block).is_valid());
+    }
+
+    #[test]
+    fn test_relief_discontinuity() {
+        let config = ValidationConfig::new();
+        let checker = BusinessRuleChecker::new(&config);
+
+        let mut block = Block::new("B1".to_string());
+        block.add_row(make_placed_row(
+            "08:00:00",
+            "10:00:00",
+            RowType::Revenue,
+            "A",
+            "X",
+        ));
+        block.add_row(make_placed_row(
+            "10:00:00",
+            "10:05:00",
+            RowType::Relief,
+            "Y",
+            "Y",
+        ));
+        block.add_row(make_placed_row(
+            "10:20:00",
+            "12:00:00",
+            RowType::Revenue,
+            "Y",
+            "B",
+        ));
+
+        let result = checker.check_block_reliefs(&block);
+        let relief_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|e| e.error_type == BusinessRuleErrorType::ReliefDiscontinuity)
+            .collect();
+
+        // Location mismatch before the relief, time gap after it
+        assert_eq!(relief_errors.len(), 2);
+        assert!(relief_errors[0].message.contains("location"));
+        assert!(relief_errors[1].message.contains("time"));
+    }
 }
@@ -1,9 +1,11 @@
 //! GTFS referential integrity validation.
 
 use crate::models::{Schedule, ScheduleRow};
+use crate::progress::ProgressCounter;
 use crate::validation::config::{GtfsComplianceLevel, ValidationConfig};
 use gtfs_parser::GtfsFeed;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use transit_core::RouteType;
 
 /// Error from GTFS integrity validation.
 #[derive(Debug, Clone)]
@@ -13,6 +15,8 @@ pub struct GtfsIntegrityError {
     pub field: String,
     pub value: String,
     pub message: String,
+    /// Route type of the row's GTFS trip, when the trip is known.
+    pub route_type: Option<RouteType>,
 }
 
 /// Types of GTFS integrity errors.
@@ -34,6 +38,8 @@ pub struct GtfsIntegrityWarning {
     pub code: String,
     pub row_index: usize,
     pub message: String,
+    /// Route type of the row's GTFS trip, when the trip is known.
+    pub route_type: Option<RouteType>,
 }
 
 /// Result of GTFS integrity checking.
@@ -51,40 +57,114 @@ impl GtfsIntegrityResult {
 
 /// Checks GTFS referential integrity.
 pub struct GtfsIntegrityChecker<'a> {
-    #[allow(dead_code)]
-    gtfs: &'a GtfsFeed,
     config: &'a ValidationConfig,
-    trip_ids: HashSet<String>,
+    /// Trip ID -> index of the first feed that has it.
+    trip_feeds: HashMap<String, usize>,
     stop_ids: HashSet<String>,
     shape_ids: HashSet<String>,
+    trip_route_types: HashMap<String, RouteType>,
+    feed_count: usize,
+    progress: Option<&'a ProgressCounter>,
 }
 
 impl<'a> GtfsIntegrityChecker<'a> {
     /// Create a new integrity checker.
-    pub fn new(gtfs: &'a GtfsFeed, config: &'a ValidationConfig) -> Self {
-        // Pre-compute ID sets for fast lookup
-        let trip_ids: HashSet<String> = gtfs.feed.trips.iter().map(|t| t.id.clone()).collect();
-
-        let stop_ids: HashSet<String> = gtfs.feed.stops.iter().map(|s| s.id.clone()).collect();
+    pub fn new(gtfs: &GtfsFeed, config: &'a ValidationConfig) -> Self {
+        Self::new_multi(&[gtfs], config)
+    }
 
-        let shape_ids: HashSet<String> = gtfs.feed.shapes.iter().map(|s| s.id.clone()).collect();
+    /// Create an integrity checker for a schedule that references several
+    /// feeds, e.g. one per operator.
+    ///
+    /// Trip, stop and shape IDs from all feeds are accepted, so the feeds
+    /// don't need merging first. A trip ID found in more than one feed
+    /// belongs to the first, which also gives its route type. Errors about a
+    /// row's stops or shape name the feed its trip came from.
+    pub fn new_multi(feeds: &[&GtfsFeed], config: &'a ValidationConfig) -> Self {
+        let mut trip_feeds: HashMap<String, usize> = HashMap::new();
+        let mut stop_ids: HashSet<String> = HashSet::new();
+        let mut shape_ids: HashSet<String> = HashSet::new();
+        let mut trip_route_types: HashMap<String, RouteType> = HashMap::new();
+
+        for (feed_index, gtfs) in feeds.iter().enumerate() {
+            stop_ids.extend(gtfs.feed.stops.iter().map(|s| s.id.clone()));
+            shape_ids.extend(gtfs.feed.shapes.iter().map(|s| s.id.clone()));
+
+            // Join trip -> route within the same feed, since route IDs can
+            // collide across feeds
+            let route_types: HashMap<&str, RouteType> = gtfs
+                .feed
+                .routes
+                .iter()
+                .map(|r| (r.id.as_str(), r.route_type))
+                .collect();
+            for trip in &gtfs.feed.trips {
+                if trip_feeds.contains_key(&trip.id) {
+                    continue;
+                }
+                trip_feeds.insert(trip.id.clone(), feed_index);
+                if let Some(&route_type) = route_types.get(trip.route_id.as_str()) {
+                    trip_route_types.insert(trip.id.clone(), route_type);
+                }
+            }
+        }
 
         Self {
-            gtfs,
             config,
-            trip_ids,
+            trip_feeds,
             stop_ids,
             shape_ids,
+            trip_route_types,
+            feed_count: feeds.len(),
+            progress: None,
         }
     }
 
+    /// Index of the feed a GTFS trip came from (always 0 for a single feed).
+    pub fn feed_for_trip(&self, trip_id: &str) -> Option<usize> {
+        self.trip_feeds.get(trip_id).copied()
+    }
+
+    /// Describe the feed of a row's trip for messages, when checking
+    /// several feeds.
+    fn feed_note(&self, row: &ScheduleRow) -> String {
+        if self.feed_count < 2 {
+            return String::new();
+        }
+        match row.trip_id.as_deref().and_then(|t| self.feed_for_trip(t)) {
+            Some(feed_index) => format!(" (trip is from feed {})", feed_index),
+            None => String::new(),
+        }
+    }
+
+    /// Route type of a GTFS trip, if the trip and its route exist.
+    pub fn route_type_for_trip(&self, trip_id: &str) -> Option<RouteType> {
+        self.trip_route_types.get(trip_id).copied()
+    }
+
+    /// Route type of every GTFS trip whose route exists, keyed by trip ID.
+    pub fn trip_route_types(&self) -> &HashMap<String, RouteType> {
+        &self.trip_route_types
+    }
+
+    /// Advance a progress counter once per row checked.
+    pub fn with_progress(mut self, progress: Option<&'a ProgressCounter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Check a single schedule row.
     pub fn check_row(&self, row: &ScheduleRow, row_index: usize) -> GtfsIntegrityResult {
         let mut result = GtfsIntegrityResult::default();
+        let route_type = row
+            .trip_id
+            .as_deref()
+            .and_then(|trip_id| self.route_type_for_trip(trip_id));
+        let feed_note = self.feed_note(row);
 
         // Check trip_id
         if let Some(ref trip_id) = row.trip_id {
-            if !self.trip_ids.contains(trip_id) {
+            if !self.trip_feeds.contains_key(trip_id) {
                 match self.config.gtfs_compliance {
                     GtfsComplianceLevel::Strict => {
                         result.errors.push(GtfsIntegrityError {
@@ -93,6 +173,7 @@ impl<'a> GtfsIntegrityChecker<'a> {
                             field: "trip_id".to_string(),
                             value: trip_id.clone(),
                             message: format!("Trip ID '{}' not found in GTFS trips.txt", trip_id),
+                            route_type: None,
                         });
                     }
                     GtfsComplianceLevel::Standard => {
@@ -100,6 +181,7 @@ impl<'a> GtfsIntegrityChecker<'a> {
                             code: "W001".to_string(),
                             row_index,
                             message: format!("Trip ID '{}' not found in GTFS", trip_id),
+                            route_type: None,
                         });
                     }
                     GtfsComplianceLevel::Lenient => {
@@ -112,14 +194,28 @@ impl<'a> GtfsIntegrityChecker<'a> {
         // Check start_place as stop_id
         if let Some(ref start_place) = row.start_place {
             if !self.stop_ids.contains(start_place) && row.is_revenue() {
-                self.add_stop_warning_or_error(&mut result, row_index, "start_place", start_place);
+                self.add_stop_warning_or_error(
+                    &mut result,
+                    row_index,
+                    "start_place",
+                    start_place,
+                    route_type,
+                    &feed_note,
+                );
             }
         }
 
         // Check end_place as stop_id
         if let Some(ref end_place) = row.end_place {
             if !self.stop_ids.contains(end_place) && row.is_revenue() {
-                self.add_stop_warning_or_error(&mut result, row_index, "end_place", end_place);
+                self.add_stop_warning_or_error(
+                    &mut result,
+                    row_index,
+                    "end_place",
+                    end_place,
+                    route_type,
+                    &feed_note,
+                );
             }
         }
 
@@ -134,16 +230,21 @@ impl<'a> GtfsIntegrityChecker<'a> {
                             field: "route_shape_id".to_string(),
                             value: shape_id.clone(),
                             message: format!(
-                                "Shape ID '{}' not found in GTFS shapes.txt",
-                                shape_id
+                                "Shape ID '{}' not found in GTFS shapes.txt{}",
+                                shape_id, feed_note
                             ),
+                            route_type,
                         });
                     }
                     _ => {
                         result.warnings.push(GtfsIntegrityWarning {
                             code: "W003".to_string(),
                             row_index,
-                            message: format!("Shape ID '{}' not found in GTFS", shape_id),
+                            message: format!(
+                                "Shape ID '{}' not found in GTFS{}",
+                                shape_id, feed_note
+                            ),
+                            route_type,
                         });
                     }
                 }
@@ -159,6 +260,8 @@ impl<'a> GtfsIntegrityChecker<'a> {
         row_index: usize,
         field: &str,
         value: &str,
+        route_type: Option<RouteType>,
+        feed_note: &str,
     ) {
         match self.config.gtfs_compliance {
             GtfsComplianceLevel::Strict => {
@@ -167,14 +270,22 @@ impl<'a> GtfsIntegrityChecker<'a> {
                     row_index,
                     field: field.to_string(),
                     value: value.to_string(),
-                    message: format!("Stop ID '{}' not found in GTFS stops.txt", value),
+                    message: format!(
+                        "Stop ID '{}' not found in GTFS stops.txt{}",
+                        value, feed_note
+                    ),
+                    route_type,
                 });
             }
             GtfsComplianceLevel::Standard => {
                 result.warnings.push(GtfsIntegrityWarning {
                     code: "W002".to_string(),
                     row_index,
-                    message: format!("Stop ID '{}' ({}) not found in GTFS", value, field),
+                    message: format!(
+                        "Stop ID '{}' ({}) not found in GTFS{}",
+                        value, field, feed_note
+                    ),
+                    route_type,
                 });
             }
             GtfsComplianceLevel::Lenient => {}
@@ -190,6 +301,10 @@ impl<'a> GtfsIntegrityChecker<'a> {
             combined.errors.extend(row_result.errors);
             combined.warnings.extend(row_result.warnings);
 
+            if let Some(progress) = self.progress {
+                progress.increment();
+            }
+
             // Check max errors limit
             if let Some(max) = self.config.max_errors {
                 if combined.errors.len() >= max {
@@ -201,13 +316,32 @@ impl<'a> GtfsIntegrityChecker<'a> {
         combined
     }
 
+    /// Check only the given rows, which must be in range.
+    pub fn check_rows(&self, schedule: &Schedule, row_indices: &[usize]) -> GtfsIntegrityResult {
+        let mut combined = GtfsIntegrityResult::default();
+
+        for &idx in row_indices {
+            let row_result = self.check_row(&schedule.rows[idx], idx);
+            combined.errors.extend(row_result.errors);
+            combined.warnings.extend(row_result.warnings);
+
+            if let Some(max) = self.config.max_errors {
+                if combined.errors.len() >= max {
+                    break;
+                }
+            }
+        }
+
+        combined
+    }
+
     /// Get summary of missing references.
     pub fn get_missing_references(&self, schedule: &Schedule) -> MissingReferences {
         let mut missing = MissingReferences::default();
 
         for row in &schedule.rows {
             if let Some(ref trip_id) = row.trip_id {
-                if !self.trip_ids.contains(trip_id) {
+                if !self.trip_feeds.contains_key(trip_id) {
                     missing.trip_ids.insert(trip_id.clone());
                 }
             }
@@ -320,6 +454,77 @@ mod tests {
         assert_eq!(result.warnings.len(), 1);
     }
 
+    #[test]
+    fn test_route_type_join() {
+        use transit_core::Route;
+
+        let mut gtfs = make_gtfs_with_trip("TRIP1", "STOP1");
+        gtfs.feed.routes.push(Route::new("R1", RouteType::Rail));
+        let config = ValidationConfig::strict();
+        let checker = GtfsIntegrityChecker::new(&gtfs, &config);
+
+        assert_eq!(checker.route_type_for_trip("TRIP1"), Some(RouteType::Rail));
+        assert_eq!(checker.route_type_for_trip("MISSING"), None);
+
+        let row = ScheduleRow {
+            trip_id: Some("TRIP1".to_string()),
+            start_place: Some("NOWHERE".to_string()),
+            row_type: RowType::Revenue,
+            ..Default::default()
+        };
+        let result = checker.check_row(&row, 0);
+        assert_eq!(result.errors[0].route_type, Some(RouteType::Rail));
+    }
+
+    #[test]
+    fn test_multiple_feeds() {
+        use transit_core::Route;
+
+        let mut north = make_gtfs_with_trip("N1", "NORTH_STOP");
+        north.feed.routes.push(Route::new("R1", RouteType::Bus));
+        let mut south = make_gtfs_with_trip("S1", "SOUTH_STOP");
+        south.feed.routes.push(Route::new("R1", RouteType::Tram));
+        // Colliding trip ID: the first feed wins
+        south
+            .feed
+            .trips
+            .push(transit_core::Trip::new("N1", "R1", "S1"));
+        let config = ValidationConfig::strict();
+        let checker = GtfsIntegrityChecker::new_multi(&[&north, &south], &config);
+
+        assert_eq!(checker.feed_for_trip("N1"), Some(0));
+        assert_eq!(checker.feed_for_trip("S1"), Some(1));
+        // Routes are joined within each feed
+        assert_eq!(checker.route_type_for_trip("N1"), Some(RouteType::Bus));
+        assert_eq!(checker.route_type_for_trip("S1"), Some(RouteType::Tram));
+
+        let row = |trip: &str, start: &str, end: &str| ScheduleRow {
+            trip_id: Some(trip.to_string()),
+            start_place: Some(start.to_string()),
+            end_place: Some(end.to_string()),
+            row_type: RowType::Revenue,
+            ..Default::default()
+        };
+        // IDs from different feeds are all accepted
+        assert!(checker
+            .check_row(&row("S1", "SOUTH_STOP", "NORTH_STOP"), 0)
+            .is_valid());
+
+        let result = checker.check_row(&row("S1", "SOUTH_STOP", "NOWHERE"), 0);
+        assert_eq!(
+            result.errors[0].message,
+            "Stop ID 'NOWHERE' not found in GTFS stops.txt (trip is from feed 1)"
+        );
+
+        // A single feed doesn't mention feeds
+        let checker = GtfsIntegrityChecker::new(&south, &config);
+        let result = checker.check_row(&row("S1", "SOUTH_STOP", "NOWHERE"), 0);
+        assert_eq!(
+            result.errors[0].message,
+            "Stop ID 'NOWHERE' not found in GTFS stops.txt"
+        );
+    }
+
     #[test]
     fn test_missing_trip_lenient() {
         let gtfs = make_gtfs_with_trip("TRIP1", "STOP1");
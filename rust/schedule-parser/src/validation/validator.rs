@@ -1,14 +1,16 @@
 //! Main schedule validator.
 
 use crate::models::Schedule;
+use crate::progress::ProgressCounter;
 use crate::validation::config::ValidationConfig;
 use crate::validation::rules::{
     block_continuity::{BlockContinuityChecker, BlockContinuityError, BlockContinuityWarning},
     business_rules::{BusinessRuleChecker, BusinessRuleError, BusinessRuleWarning},
-    gtfs_integrity::GtfsIntegrityChecker,
+    gtfs_integrity::{GtfsIntegrityChecker, GtfsIntegrityError, GtfsIntegrityWarning},
 };
 use gtfs_parser::GtfsFeed;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 
 /// A validation error.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,7 +26,7 @@ pub struct ValidationError {
 }
 
 /// Error category.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ErrorCategory {
     /// GTFS referential integrity error.
     GtfsIntegrity,
@@ -73,15 +75,27 @@ pub struct ValidationResult {
     pub duties_validated: usize,
     /// Whether validation was truncated due to max_errors.
     pub truncated: bool,
+    /// Categories that had errors left out because they reached their
+    /// `ValidationConfig::max_errors_per_category` limit.
+    #[serde(default)]
+    pub truncated_categories: HashSet<ErrorCategory>,
+    /// Whether warnings fail validation, copied from
+    /// `ValidationConfig::warnings_as_errors`.
+    #[serde(default)]
+    pub warnings_as_errors: bool,
 }
 
 impl ValidationResult {
-    /// Check if validation passed (no errors).
+    /// Check if validation passed: no errors and, when `warnings_as_errors`
+    /// is set, no warnings either.
     pub fn is_valid(&self) -> bool {
-        self.errors.is_empty()
+        self.errors.is_empty() && (!self.warnings_as_errors || self.warnings.is_empty())
     }
 
     /// Get error count.
+    ///
+    /// Only counts `errors`; warnings promoted by `warnings_as_errors` are
+    /// still counted by `warning_count`.
     pub fn error_count(&self) -> usize {
         self.errors.len()
     }
@@ -111,12 +125,28 @@ impl ValidationResult {
 /// Schedule validator.
 pub struct Validator {
     config: ValidationConfig,
+    progress: Option<ProgressCounter>,
 }
 
 impl Validator {
     /// Create a new validator with the given configuration.
     pub fn new(config: ValidationConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            progress: None,
+        }
+    }
+
+    /// Report progress through a shared counter.
+    ///
+    /// Each validation run resets the counter and advances it once per row
+    /// in each row-level pass: two passes (GTFS integrity and business rules)
+    /// for `validate`/`validate_many`, one for `validate_structure`. The
+    /// total is set upfront, so it can be polled from another thread to
+    /// drive a progress bar.
+    pub fn with_progress(mut self, progress: ProgressCounter) -> Self {
+        self.progress = Some(progress);
+        self
     }
 
     /// Create a validator with default configuration.
@@ -126,36 +156,149 @@ impl Validator {
 
     /// Validate a schedule against a GTFS feed.
     pub fn validate(&self, schedule: &mut Schedule, gtfs: &GtfsFeed) -> ValidationResult {
+        self.start_progress(schedule.len() * 2);
+        let gtfs_checker =
+            GtfsIntegrityChecker::new(gtfs, &self.config).with_progress(self.progress.as_ref());
+        self.validate_with_checker(schedule, &gtfs_checker)
+    }
+
+    /// Validate a schedule whose trips come from several GTFS feeds.
+    ///
+    /// IDs from all feeds are accepted, without merging the feeds first
+    /// (see [`GtfsIntegrityChecker::new_multi`]). Otherwise the same as
+    /// [`validate`](Self::validate).
+    pub fn validate_multi(&self, schedule: &mut Schedule, feeds: &[&GtfsFeed]) -> ValidationResult {
+        self.start_progress(schedule.len() * 2);
+        let gtfs_checker = GtfsIntegrityChecker::new_multi(feeds, &self.config)
+            .with_progress(self.progress.as_ref());
+        self.validate_with_checker(schedule, &gtfs_checker)
+    }
+
+    /// Validate several schedules against the same GTFS feed.
+    ///
+    /// The GTFS ID lookups are built once and shared across all schedules,
+    /// which is considerably cheaper than calling [`validate`](Self::validate)
+    /// per schedule. Results are returned in the same order as `schedules`.
+    pub fn validate_many(
+        &self,
+        schedules: &mut [Schedule],
+        gtfs: &GtfsFeed,
+    ) -> Vec<ValidationResult> {
+        self.start_progress(schedules.iter().map(|s| s.len() * 2).sum());
+        let gtfs_checker =
+            GtfsIntegrityChecker::new(gtfs, &self.config).with_progress(self.progress.as_ref());
+        schedules
+            .iter_mut()
+            .map(|schedule| self.validate_with_checker(schedule, &gtfs_checker))
+            .collect()
+    }
+
+    /// Validate each service in a schedule separately.
+    ///
+    /// Rows that share a block on different service days no longer look like
+    /// overlaps. Segments come from [`Schedule::split_by_service`], so row
+    /// indices in error contexts are relative to that service's rows.
+    pub fn validate_by_service(
+        &self,
+        schedule: &Schedule,
+        gtfs: &GtfsFeed,
+    ) -> Vec<(Option<String>, ValidationResult)> {
+        let (service_ids, mut segments): (Vec<_>, Vec<_>) =
+            schedule.split_by_service().into_iter().unzip();
+        service_ids
+            .into_iter()
+            .zip(self.validate_many(&mut segments, gtfs))
+            .collect()
+    }
+
+    /// Revalidate only the given rows, e.g. after a few edits in an
+    /// interactive editor.
+    ///
+    /// Runs the per-row GTFS integrity and business rule checks on each
+    /// index, plus layover checks between each row and the rows directly
+    /// before and after it. Block continuity, duty and relief checks span
+    /// whole blocks and duties, so they still need a full
+    /// [`validate`](Self::validate) pass. Out-of-range indices are ignored
+    /// and duplicates are checked once; `rows_validated` is the number of
+    /// distinct rows checked. Progress is not reported.
+    pub fn validate_rows(
+        &self,
+        schedule: &Schedule,
+        gtfs: &GtfsFeed,
+        row_indices: &[usize],
+    ) -> ValidationResult {
+        let indices: Vec<usize> = row_indices
+            .iter()
+            .copied()
+            .filter(|&idx| idx < schedule.len())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut result = ValidationResult {
+            rows_validated: indices.len(),
+            warnings_as_errors: self.config.warnings_as_errors,
+            ..Default::default()
+        };
+
+        let gtfs_checker = GtfsIntegrityChecker::new(gtfs, &self.config);
+        let gtfs_result = gtfs_checker.check_rows(schedule, &indices);
+
+        for err in gtfs_result.errors {
+            let code = result.errors.len() + 1;
+            if self.push_error(&mut result, self.convert_gtfs_error(err, code)) {
+                return result;
+            }
+        }
+
+        if self.config.generate_warnings {
+            for warn in gtfs_result.warnings {
+                result.warnings.push(self.convert_gtfs_warning(warn));
+            }
+        }
+
+        let business_checker = BusinessRuleChecker::new(&self.config)
+            .with_route_types(gtfs_checker.trip_route_types());
+        let business_result = business_checker.check_rows(schedule, &indices);
+
+        for err in business_result.errors {
+            if self.push_error(&mut result, self.convert_business_error(err)) {
+                return result;
+            }
+        }
+
+        if self.config.generate_warnings {
+            for warn in business_result.warnings {
+                result.warnings.push(self.convert_business_warning(warn));
+            }
+        }
+
+        result
+    }
+
+    fn validate_with_checker(
+        &self,
+        schedule: &mut Schedule,
+        gtfs_checker: &GtfsIntegrityChecker,
+    ) -> ValidationResult {
         let mut result = ValidationResult {
             rows_validated: schedule.len(),
+            warnings_as_errors: self.config.warnings_as_errors,
             ..Default::default()
         };
 
         // GTFS integrity checks
-        let gtfs_checker = GtfsIntegrityChecker::new(gtfs, &self.config);
         let gtfs_result = gtfs_checker.check_schedule(schedule);
 
         for err in gtfs_result.errors {
-            result.errors.push(ValidationError {
-                code: format!("E{:03}", result.errors.len() + 1),
-                category: ErrorCategory::GtfsIntegrity,
-                message: err.message,
-                context: Some(format!("row {}, field: {}", err.row_index, err.field)),
-            });
-
-            if self.check_truncation(&result) {
+            let code = result.errors.len() + 1;
+            if self.push_error(&mut result, self.convert_gtfs_error(err, code)) {
                 return result;
             }
         }
 
         if self.config.generate_warnings {
             for warn in gtfs_result.warnings {
-                result.warnings.push(ValidationWarning {
-                    code: warn.code,
-                    category: WarningCategory::GtfsReference,
-                    message: warn.message,
-                    context: Some(format!("row {}", warn.row_index)),
-                });
+                result.warnings.push(self.convert_gtfs_warning(warn));
             }
         }
 
@@ -166,9 +309,7 @@ impl Validator {
             result.blocks_validated = schedule.block_ids().len();
 
             for err in block_result.errors {
-                result.errors.push(self.convert_block_error(err));
-
-                if self.check_truncation(&result) {
+                if self.push_error(&mut result, self.convert_block_error(err)) {
                     return result;
                 }
             }
@@ -181,13 +322,13 @@ impl Validator {
         }
 
         // Business rules checks
-        let business_checker = BusinessRuleChecker::new(&self.config);
+        let business_checker = BusinessRuleChecker::new(&self.config)
+            .with_route_types(gtfs_checker.trip_route_types())
+            .with_progress(self.progress.as_ref());
         let business_result = business_checker.check_schedule(schedule);
 
         for err in business_result.errors {
-            result.errors.push(self.convert_business_error(err));
-
-            if self.check_truncation(&result) {
+            if self.push_error(&mut result, self.convert_business_error(err)) {
                 return result;
             }
         }
@@ -198,15 +339,22 @@ impl Validator {
             }
         }
 
+        // Relief continuity within blocks
+        let relief_result = business_checker.check_reliefs(schedule);
+
+        for err in relief_result.errors {
+            if self.push_error(&mut result, self.convert_business_error(err)) {
+                return result;
+            }
+        }
+
         // Duty validation
         if self.config.validate_duty_constraints {
             let duty_result = business_checker.check_duties(schedule);
             result.duties_validated = schedule.run_numbers().len();
 
             for err in duty_result.errors {
-                result.errors.push(self.convert_business_error(err));
-
-                if self.check_truncation(&result) {
+                if self.push_error(&mut result, self.convert_business_error(err)) {
                     return result;
                 }
             }
@@ -223,8 +371,10 @@ impl Validator {
 
     /// Validate a schedule without GTFS (only structural/business rules).
     pub fn validate_structure(&self, schedule: &mut Schedule) -> ValidationResult {
+        self.start_progress(schedule.len());
         let mut result = ValidationResult {
             rows_validated: schedule.len(),
+            warnings_as_errors: self.config.warnings_as_errors,
             ..Default::default()
         };
 
@@ -235,9 +385,7 @@ impl Validator {
             result.blocks_validated = schedule.block_ids().len();
 
             for err in block_result.errors {
-                result.errors.push(self.convert_block_error(err));
-
-                if self.check_truncation(&result) {
+                if self.push_error(&mut result, self.convert_block_error(err)) {
                     return result;
                 }
             }
@@ -250,13 +398,12 @@ impl Validator {
         }
 
         // Business rules checks
-        let business_checker = BusinessRuleChecker::new(&self.config);
+        let business_checker =
+            BusinessRuleChecker::new(&self.config).with_progress(self.progress.as_ref());
         let business_result = business_checker.check_schedule(schedule);
 
         for err in business_result.errors {
-            result.errors.push(self.convert_business_error(err));
-
-            if self.check_truncation(&result) {
+            if self.push_error(&mut result, self.convert_business_error(err)) {
                 return result;
             }
         }
@@ -267,13 +414,24 @@ impl Validator {
             }
         }
 
+        // Relief continuity within blocks
+        let relief_result = business_checker.check_reliefs(schedule);
+
+        for err in relief_result.errors {
+            if self.push_error(&mut result, self.convert_business_error(err)) {
+                return result;
+            }
+        }
+
         // Duty validation
         if self.config.validate_duty_constraints {
             let duty_result = business_checker.check_duties(schedule);
             result.duties_validated = schedule.run_numbers().len();
 
             for err in duty_result.errors {
-                result.errors.push(self.convert_business_error(err));
+                if self.push_error(&mut result, self.convert_business_error(err)) {
+                    return result;
+                }
             }
 
             if self.config.generate_warnings {
@@ -286,6 +444,39 @@ impl Validator {
         result
     }
 
+    fn start_progress(&self, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress.start(total);
+        }
+    }
+
+    /// Add an error unless its category is already at its
+    /// `max_errors_per_category` limit, and report whether validation should
+    /// stop because `max_errors` has been reached.
+    fn push_error(&self, result: &mut ValidationResult, error: ValidationError) -> bool {
+        let category = error.category;
+        if let Some(&max) = self.config.max_errors_per_category.get(&category) {
+            if result.truncated_categories.contains(&category)
+                || result
+                    .errors
+                    .iter()
+                    .filter(|e| e.category == category)
+                    .count()
+                    >= max
+            {
+                result.truncated_categories.insert(category);
+                return false;
+            }
+        }
+
+        result.errors.push(error);
+        if self.check_truncation(result) {
+            result.truncated = true;
+            return true;
+        }
+        false
+    }
+
     fn check_truncation(&self, result: &ValidationResult) -> bool {
         if let Some(max) = self.config.max_errors {
             if result.errors.len() >= max {
@@ -295,6 +486,39 @@ impl Validator {
         false
     }
 
+    fn convert_gtfs_error(&self, err: GtfsIntegrityError, number: usize) -> ValidationError {
+        ValidationError {
+            code: format!("E{:03}", number),
+            category: ErrorCategory::GtfsIntegrity,
+            message: err.message,
+            context: Some(match err.route_type {
+                Some(route_type) => format!(
+                    "row {}, field: {}, route_type: {}",
+                    err.row_index,
+                    err.field,
+                    route_type.as_u16()
+                ),
+                None => format!("row {}, field: {}", err.row_index, err.field),
+            }),
+        }
+    }
+
+    fn convert_gtfs_warning(&self, warn: GtfsIntegrityWarning) -> ValidationWarning {
+        ValidationWarning {
+            code: warn.code,
+            category: WarningCategory::GtfsReference,
+            message: warn.message,
+            context: Some(match warn.route_type {
+                Some(route_type) => format!(
+                    "row {}, route_type: {}",
+                    warn.row_index,
+                    route_type.as_u16()
+                ),
+                None => format!("row {}", warn.row_index),
+            }),
+        }
+    }
+
     fn convert_block_error(&self, err: BlockContinuityError) -> ValidationError {
         ValidationError {
             code: format!("E1{:02}", 0),
@@ -406,6 +630,99 @@ mod tests {
             .any(|e| e.category == ErrorCategory::GtfsIntegrity));
     }
 
+    #[test]
+    fn test_layover_exempt_route_types() {
+        use transit_core::{Route, RouteType, Trip};
+
+        let mut gtfs = make_gtfs();
+        gtfs.feed.trips.push(Trip::new("TRIP2", "R1", "S1"));
+        gtfs.feed.routes.push(Route::new("R1", RouteType::Ferry));
+
+        // Two-minute turnaround is below the default minimum layover
+        let rows = vec![
+            make_row("TRIP1", "B1", "08:00:00", "09:00:00"),
+            make_row("TRIP2", "B1", "09:02:00", "10:00:00"),
+        ];
+
+        let result = Validator::default_config().validate(&mut make_schedule(rows.clone()), &gtfs);
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.message.starts_with("Layover")));
+
+        let mut config = ValidationConfig::new();
+        config.business_rules.layover_exempt_route_types = vec![RouteType::Ferry];
+        let result = Validator::new(config).validate(&mut make_schedule(rows), &gtfs);
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn test_warnings_as_errors() {
+        // An unassigned revenue trip is an orphan warning, not an error
+        let mut orphan = make_row("T1", "B1", "08:00:00", "09:00:00");
+        orphan.block = None;
+
+        let mut config = ValidationConfig::new();
+        config.validate_block_continuity = false;
+        let result = Validator::new(config.clone())
+            .validate_structure(&mut make_schedule(vec![orphan.clone()]));
+        assert!(result.is_valid());
+        assert_eq!(result.warning_count(), 1);
+
+        let config = config.with_warnings_as_errors(true);
+        let result =
+            Validator::new(config.clone()).validate_structure(&mut make_schedule(vec![orphan]));
+        assert!(!result.is_valid());
+        assert!(result.warnings_as_errors);
+        assert_eq!(result.error_count(), 0);
+        assert_eq!(result.warning_count(), 1);
+
+        // A schedule without warnings still passes
+        let row = make_row("T1", "B1", "08:00:00", "09:00:00");
+        let result = Validator::new(config).validate_structure(&mut make_schedule(vec![row]));
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn test_validate_rows() {
+        use transit_core::Trip;
+
+        let mut gtfs = make_gtfs();
+        for trip_id in ["TRIP2", "TRIP3"] {
+            gtfs.feed.trips.push(Trip::new(trip_id, "R1", "S1"));
+        }
+        let mut schedule = make_schedule(vec![
+            make_row("TRIP1", "B1", "08:00:00", "09:00:00"),
+            make_row("TRIP2", "B1", "09:10:00", "10:00:00"),
+            make_row("TRIP3", "B1", "10:10:00", "11:00:00"),
+        ]);
+        let validator = Validator::new(ValidationConfig::strict());
+
+        // Edit the middle row: unknown trip and a one-minute layover after it
+        schedule.rows[1].trip_id = Some("EDITED".to_string());
+        schedule.rows[1].end_time = Some("10:09:00".to_string());
+
+        let result = validator.validate_rows(&schedule, &gtfs, &[1, 1, 99]);
+        assert_eq!(result.rows_validated, 1);
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.category == ErrorCategory::GtfsIntegrity
+                && e.context.as_deref() == Some("row 1, field: trip_id")));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.message.starts_with("Layover") && e.context.as_deref() == Some("rows 1-2")));
+
+        // Untouched rows away from the edit are not rechecked
+        let result = validator.validate_rows(&schedule, &gtfs, &[0]);
+        assert!(result
+            .errors
+            .iter()
+            .all(|e| e.category != ErrorCategory::GtfsIntegrity));
+        assert!(validator.validate_rows(&schedule, &gtfs, &[]).is_valid());
+    }
+
     #[test]
     fn test_structure_only_validation() {
         let mut schedule = make_schedule(vec![
@@ -434,5 +751,124 @@ mod tests {
         let result = validator.validate(&mut schedule, &gtfs);
 
         assert_eq!(result.errors.len(), 2);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_max_errors_per_category() {
+        let gtfs = make_gtfs();
+        let mut schedule = make_schedule(vec![
+            make_row("MISSING1", "B1", "08:00:00", "09:00:00"),
+            make_row("MISSING2", "B1", "09:00:00", "10:00:00"),
+            // Starts before the previous trip ends
+            make_row("MISSING3", "B1", "09:30:00", "10:30:00"),
+        ]);
+
+        let config = ValidationConfig::strict();
+        let all = Validator::new(config.clone()).validate(&mut schedule, &gtfs);
+        let gtfs_errors = all.errors_by_category(ErrorCategory::GtfsIntegrity).len();
+        let block_errors = all.errors_by_category(ErrorCategory::BlockContinuity).len();
+        assert!(gtfs_errors >= 3);
+        assert!(block_errors > 0);
+        assert!(all.truncated_categories.is_empty());
+
+        let config = config.with_max_errors_for(ErrorCategory::GtfsIntegrity, 1);
+        let result = Validator::new(config).validate(&mut schedule, &gtfs);
+        assert_eq!(
+            result
+                .errors_by_category(ErrorCategory::GtfsIntegrity)
+                .len(),
+            1
+        );
+        assert_eq!(
+            result
+                .errors_by_category(ErrorCategory::BlockContinuity)
+                .len(),
+            block_errors
+        );
+        assert_eq!(
+            result.truncated_categories,
+            HashSet::from([ErrorCategory::GtfsIntegrity])
+        );
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_validate_many() {
+        let gtfs = make_gtfs();
+        let mut schedules = vec![
+            make_schedule(vec![make_row("TRIP1", "B1", "08:00:00", "09:00:00")]),
+            make_schedule(vec![make_row("MISSING_TRIP", "B1", "08:00:00", "09:00:00")]),
+        ];
+
+        let validator = Validator::new(ValidationConfig::strict());
+        let results = validator.validate_many(&mut schedules, &gtfs);
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_valid());
+        assert!(!results[1].is_valid());
+
+        // Same outcome as validating each schedule individually
+        let single = validator.validate(&mut schedules[1], &gtfs);
+        assert_eq!(single.error_count(), results[1].error_count());
+    }
+
+    #[test]
+    fn test_validate_multi() {
+        let first = make_gtfs();
+        let mut second = GtfsFeed::new();
+        second
+            .feed
+            .trips
+            .push(transit_core::Trip::new("TRIP2", "R1", "S1"));
+        let mut schedule = make_schedule(vec![
+            make_row("TRIP1", "B1", "08:00:00", "09:00:00"),
+            make_row("TRIP2", "B1", "09:10:00", "10:00:00"),
+        ]);
+
+        let validator = Validator::new(ValidationConfig::strict());
+        assert!(!validator.validate(&mut schedule, &first).is_valid());
+        assert!(validator
+            .validate_multi(&mut schedule, &[&first, &second])
+            .is_valid());
+    }
+
+    #[test]
+    fn test_validate_by_service() {
+        let gtfs = make_gtfs();
+        let on_service = |service: &str| ScheduleRow {
+            service_id: Some(service.to_string()),
+            ..make_row("TRIP1", "B1", "08:00:00", "09:00:00")
+        };
+        let mut schedule = make_schedule(vec![on_service("WKDY"), on_service("SAT")]);
+
+        let validator = Validator::default_config();
+        assert!(!validator.validate(&mut schedule, &gtfs).is_valid());
+
+        let results = validator.validate_by_service(&schedule, &gtfs);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.as_deref(), Some("SAT"));
+        assert_eq!(results[1].0.as_deref(), Some("WKDY"));
+        assert!(results.iter().all(|(_, result)| result.is_valid()));
+    }
+
+    #[test]
+    fn test_validation_progress() {
+        let gtfs = make_gtfs();
+        let mut schedule = make_schedule(vec![
+            make_row("TRIP1", "B1", "08:00:00", "09:00:00"),
+            make_row("TRIP1", "B1", "09:10:00", "10:00:00"),
+        ]);
+
+        let progress = ProgressCounter::new();
+        let validator = Validator::default_config().with_progress(progress.clone());
+
+        validator.validate(&mut schedule, &gtfs);
+        assert_eq!(progress.total(), 4);
+        assert_eq!(progress.processed(), 4);
+
+        validator.validate_structure(&mut schedule);
+        assert_eq!(progress.total(), 2);
+        assert_eq!(progress.processed(), 2);
     }
 }
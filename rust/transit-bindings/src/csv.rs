@@ -1,5 +1,6 @@
 //! CSV Python bindings.
 
+use crate::errors::parse_err;
 use csv_parser::CsvDocument;
 use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
@@ -25,7 +26,7 @@ impl PyCsvDocument {
     fn from_path(path: &str) -> PyResult<Self> {
         CsvDocument::from_path(path)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse CSV from bytes.
@@ -33,7 +34,7 @@ impl PyCsvDocument {
     fn from_bytes(data: &[u8]) -> PyResult<Self> {
         CsvDocument::from_bytes(data)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse CSV from string.
@@ -41,21 +42,17 @@ impl PyCsvDocument {
     fn from_string(csv: &str) -> PyResult<Self> {
         CsvDocument::from_str(csv)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Write CSV to path.
     fn to_path(&self, path: &str) -> PyResult<()> {
-        self.inner
-            .to_path(path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_path(path).map_err(parse_err)
     }
 
     /// Write CSV to string.
     fn to_string(&self) -> PyResult<String> {
-        self.inner
-            .to_string()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_string().map_err(parse_err)
     }
 
     /// Get the number of rows.
@@ -0,0 +1,88 @@
+//! Python exception types for parse and validation failures.
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use transit_core::ParseError;
+
+create_exception!(
+    transit_parser._core,
+    TransitParseError,
+    PyIOError,
+    "Input could not be parsed. Subclasses IOError; `line` is the 1-based \
+     source line when known, else None."
+);
+create_exception!(
+    transit_parser._core,
+    ColumnMappingError,
+    TransitParseError,
+    "A column mapping could not be loaded or a required field has no column."
+);
+create_exception!(
+    transit_parser._core,
+    ScheduleValidationError,
+    PyValueError,
+    "A schedule failed validation. `errors` lists the error messages."
+);
+
+/// Convert a Rust parse error into the matching Python exception.
+///
+/// I/O failures stay `IOError`; malformed input raises `TransitParseError`,
+/// and a missing required field raises `ColumnMappingError`.
+pub(crate) fn parse_err(err: ParseError) -> PyErr {
+    let message = err.to_string();
+    let py_err = match err {
+        ParseError::Io(_) => return PyIOError::new_err(message),
+        ParseError::MissingField(_) => ColumnMappingError::new_err(message),
+        _ => TransitParseError::new_err(message),
+    };
+    with_unknown_line(py_err)
+}
+
+/// Convert a column mapping load failure into `ColumnMappingError`.
+pub(crate) fn mapping_err(err: ParseError) -> PyErr {
+    match err {
+        ParseError::Io(_) => parse_err(err),
+        _ => with_unknown_line(ColumnMappingError::new_err(err.to_string())),
+    }
+}
+
+/// Build a `ScheduleValidationError` carrying each error as `CODE: message`.
+pub(crate) fn validation_err(errors: Vec<String>) -> PyErr {
+    let summary = match errors.first() {
+        Some(first) if errors.len() > 1 => {
+            format!("{} validation errors, first: {}", errors.len(), first)
+        }
+        Some(first) => format!("Validation error: {}", first),
+        None => "Validation failed".to_string(),
+    };
+    let err = ScheduleValidationError::new_err(summary);
+    Python::with_gil(|py| {
+        let _ = err.value(py).setattr("errors", errors);
+    });
+    err
+}
+
+/// Set the `line` attribute of a `TransitParseError` to `None`.
+///
+/// `ParseError` doesn't carry a line number yet, so callers can only rely
+/// on the attribute existing.
+fn with_unknown_line(err: PyErr) -> PyErr {
+    Python::with_gil(|py| {
+        // Setting an attribute on a fresh exception instance cannot fail
+        let _ = err.value(py).setattr("line", py.None());
+    });
+    err
+}
+
+/// Register the exception types on the module.
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("TransitParseError", py.get_type::<TransitParseError>())?;
+    m.add("ColumnMappingError", py.get_type::<ColumnMappingError>())?;
+    m.add(
+        "ScheduleValidationError",
+        py.get_type::<ScheduleValidationError>(),
+    )?;
+    Ok(())
+}
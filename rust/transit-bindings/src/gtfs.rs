@@ -1,8 +1,8 @@
 //! GTFS Python bindings.
 
+use crate::errors::parse_err;
 use crate::models::*;
 use gtfs_parser::GtfsFeed;
-use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
 
 /// Python wrapper for GTFS feed.
@@ -45,7 +45,7 @@ impl PyGtfsFeed {
     fn from_path(path: &str) -> PyResult<Self> {
         GtfsFeed::from_path(path)
             .map(Self::from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a GTFS feed from a ZIP file.
@@ -53,7 +53,7 @@ impl PyGtfsFeed {
     fn from_zip(path: &str) -> PyResult<Self> {
         GtfsFeed::from_zip(path)
             .map(Self::from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a GTFS feed from bytes (ZIP format).
@@ -61,28 +61,22 @@ impl PyGtfsFeed {
     fn from_bytes(data: &[u8]) -> PyResult<Self> {
         GtfsFeed::from_bytes(data)
             .map(Self::from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Write the GTFS feed to a directory.
     fn to_path(&self, path: &str) -> PyResult<()> {
-        self.inner
-            .to_path(path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_path(path).map_err(parse_err)
     }
 
     /// Write the GTFS feed to a ZIP file.
     fn to_zip(&self, path: &str) -> PyResult<()> {
-        self.inner
-            .to_zip(path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_zip(path).map_err(parse_err)
     }
 
     /// Export the GTFS feed as ZIP bytes.
     fn to_bytes(&self) -> PyResult<Vec<u8>> {
-        self.inner
-            .to_bytes()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_bytes().map_err(parse_err)
     }
 
     // ========================================
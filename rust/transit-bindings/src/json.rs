@@ -1,5 +1,6 @@
 //! JSON Python bindings.
 
+use crate::errors::parse_err;
 use json_parser::JsonDocument;
 use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
@@ -17,7 +18,7 @@ impl PyJsonDocument {
     fn from_path(path: &str) -> PyResult<Self> {
         JsonDocument::from_path(path)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse JSON from bytes.
@@ -25,7 +26,7 @@ impl PyJsonDocument {
     fn from_bytes(data: &[u8]) -> PyResult<Self> {
         JsonDocument::from_bytes(data)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse JSON from string.
@@ -33,28 +34,22 @@ impl PyJsonDocument {
     fn from_string(json: &str) -> PyResult<Self> {
         JsonDocument::from_str(json)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Write JSON to path.
     fn to_path(&self, path: &str) -> PyResult<()> {
-        self.inner
-            .to_path(path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_path(path).map_err(parse_err)
     }
 
     /// Write JSON to string.
     fn to_string(&self) -> PyResult<String> {
-        self.inner
-            .to_string()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_string().map_err(parse_err)
     }
 
     /// Write JSON to pretty-printed string.
     fn to_string_pretty(&self) -> PyResult<String> {
-        self.inner
-            .to_string_pretty()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.to_string_pretty().map_err(parse_err)
     }
 
     /// Check if root is an object.
@@ -1,9 +1,9 @@
 //! Lazy GTFS Python bindings.
 
+use crate::errors::parse_err;
 use crate::gtfs::PyGtfsFeed;
 use crate::models::*;
 use gtfs_parser::LazyGtfsFeed;
-use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
 use std::sync::Arc;
 
@@ -35,7 +35,7 @@ impl PyLazyGtfsFeed {
     fn from_path(path: &str) -> PyResult<Self> {
         LazyGtfsFeed::from_path(path)
             .map(Self::new_from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a lazy GTFS feed from a ZIP file.
@@ -46,7 +46,7 @@ impl PyLazyGtfsFeed {
     fn from_zip(path: &str) -> PyResult<Self> {
         LazyGtfsFeed::from_zip(path)
             .map(Self::new_from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a lazy GTFS feed from bytes (ZIP format).
@@ -54,7 +54,7 @@ impl PyLazyGtfsFeed {
     fn from_bytes(data: &[u8]) -> PyResult<Self> {
         LazyGtfsFeed::from_bytes(data.to_vec())
             .map(Self::new_from_inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     // ========================================
@@ -64,65 +64,49 @@ impl PyLazyGtfsFeed {
     /// Number of agencies (fast, counts CSV rows without full parse).
     #[getter]
     fn agency_count(&self) -> PyResult<usize> {
-        self.inner
-            .agency_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.agency_count().map_err(parse_err)
     }
 
     /// Number of stops (fast, counts CSV rows without full parse).
     #[getter]
     fn stop_count(&self) -> PyResult<usize> {
-        self.inner
-            .stop_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.stop_count().map_err(parse_err)
     }
 
     /// Number of routes (fast, counts CSV rows without full parse).
     #[getter]
     fn route_count(&self) -> PyResult<usize> {
-        self.inner
-            .route_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.route_count().map_err(parse_err)
     }
 
     /// Number of trips (fast, counts CSV rows without full parse).
     #[getter]
     fn trip_count(&self) -> PyResult<usize> {
-        self.inner
-            .trip_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.trip_count().map_err(parse_err)
     }
 
     /// Number of stop_times (fast, counts CSV rows without full parse).
     #[getter]
     fn stop_time_count(&self) -> PyResult<usize> {
-        self.inner
-            .stop_time_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.stop_time_count().map_err(parse_err)
     }
 
     /// Number of calendars (fast, counts CSV rows without full parse).
     #[getter]
     fn calendar_count(&self) -> PyResult<usize> {
-        self.inner
-            .calendar_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.calendar_count().map_err(parse_err)
     }
 
     /// Number of calendar dates (fast, counts CSV rows without full parse).
     #[getter]
     fn calendar_date_count(&self) -> PyResult<usize> {
-        self.inner
-            .calendar_date_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.calendar_date_count().map_err(parse_err)
     }
 
     /// Number of shapes (requires full parse of shapes.txt).
     #[getter]
     fn shape_count(&self) -> PyResult<usize> {
-        self.inner
-            .shape_count()
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        self.inner.shape_count().map_err(parse_err)
     }
 
     // ========================================
@@ -136,10 +120,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let agencies = self
-            .inner
-            .agencies()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let agencies = self.inner.agencies().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, agencies.into_iter().map(PyAgency::from))?;
         let cached = list.into();
@@ -154,10 +135,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let stops = self
-            .inner
-            .stops()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let stops = self.inner.stops().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, stops.into_iter().map(PyStop::from))?;
         let cached = list.into();
@@ -172,10 +150,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let routes = self
-            .inner
-            .routes()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let routes = self.inner.routes().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, routes.into_iter().map(PyRoute::from))?;
         let cached = list.into();
@@ -190,10 +165,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let trips = self
-            .inner
-            .trips()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let trips = self.inner.trips().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, trips.into_iter().map(PyTrip::from))?;
         let cached = list.into();
@@ -208,10 +180,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let stop_times = self
-            .inner
-            .stop_times()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let stop_times = self.inner.stop_times().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, stop_times.into_iter().map(PyStopTime::from))?;
         let cached = list.into();
@@ -226,10 +195,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let calendars = self
-            .inner
-            .calendars()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let calendars = self.inner.calendars().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, calendars.into_iter().map(PyCalendar::from))?;
         let cached = list.into();
@@ -244,10 +210,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let dates = self
-            .inner
-            .calendar_dates()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let dates = self.inner.calendar_dates().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, dates.into_iter().map(PyCalendarDate::from))?;
         let cached = list.into();
@@ -262,10 +225,7 @@ impl PyLazyGtfsFeed {
             return Ok(cached.clone_ref(py));
         }
 
-        let shapes = self
-            .inner
-            .shapes()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let shapes = self.inner.shapes().map_err(parse_err)?;
 
         let list = pyo3::types::PyList::new(py, shapes.into_iter().map(PyShape::from))?;
         let cached = list.into();
@@ -277,10 +237,7 @@ impl PyLazyGtfsFeed {
     ///
     /// This parses all files and returns a fully-loaded GtfsFeed.
     fn materialize(&self) -> PyResult<PyGtfsFeed> {
-        let feed = self
-            .inner
-            .materialize()
-            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let feed = self.inner.materialize().map_err(parse_err)?;
 
         Ok(PyGtfsFeed::from_inner(gtfs_parser::GtfsFeed { feed }))
     }
@@ -4,6 +4,7 @@ use pyo3::prelude::*;
 
 mod adapters;
 mod csv;
+mod errors;
 mod gtfs;
 mod json;
 mod lazy_gtfs;
@@ -14,6 +15,8 @@ mod txc;
 /// Transit data parser Python module.
 #[pymodule]
 fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    errors::register(m)?;
+
     // Register submodules
     m.add_class::<models::PyAgency>()?;
     m.add_class::<models::PyStop>()?;
@@ -46,8 +49,13 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Schedule functions
     m.add_class::<schedule::PySchedule>()?;
     m.add_class::<schedule::PyScheduleRow>()?;
+    m.add_class::<schedule::PyScheduleRowIterator>()?;
+    m.add_class::<schedule::PyReadOptions>()?;
+    m.add_class::<schedule::PyProgressCounter>()?;
     m.add_class::<schedule::PyValidationConfig>()?;
     m.add_class::<schedule::PyValidationResult>()?;
+    m.add_class::<schedule::PyCachingValidator>()?;
+    m.add_class::<schedule::PyInferenceConfig>()?;
     m.add_class::<schedule::PyDeadheadInferenceResult>()?;
 
     Ok(())
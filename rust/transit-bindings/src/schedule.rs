@@ -1,18 +1,29 @@
 //! Schedule Python bindings.
 
+use crate::errors::{mapping_err, parse_err, validation_err};
 use crate::gtfs::PyGtfsFeed;
-use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList, PySlice};
 use pyo3::Bound;
+use schedule_parser::formats::generic_csv::LineTerminator;
+use schedule_parser::validation::validator::ValidationWarning;
 use schedule_parser::{
-    ColumnMapping, CsvExporter, DeadheadInferrer, ExportConfig, ExportPreset, GtfsComplianceLevel,
-    ReadOptions, Schedule, ScheduleReader, ScheduleRow, ValidationConfig, ValidationResult,
-    Validator,
+    AnonymizeOptions, BlockKey, BlockingConfig, CachingValidator, ColumnMapping,
+    ContinuousDrivingReset, CsvExporter, DeadheadInferrer, DistanceModel, DutyTotal, ExportConfig,
+    ExportPreset, GtfsComplianceLevel, IdStrategy, InferenceConfig, JsonExporter,
+    NormalizeIdsOptions, PayRules, ProgressCounter, ReadOptions, RowType, Schedule,
+    SchedulePipeline, ScheduleReader, ScheduleRow, ScheduleSummary, ValidationConfig,
+    ValidationResult, Validator,
 };
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use transit_core::ParseError;
 
 /// Python wrapper for ScheduleRow.
-#[pyclass(name = "ScheduleRow")]
+#[pyclass(name = "ScheduleRow", module = "transit_parser._core")]
 #[derive(Clone)]
 pub struct PyScheduleRow {
     inner: ScheduleRow,
@@ -21,10 +32,88 @@ pub struct PyScheduleRow {
 #[pymethods]
 impl PyScheduleRow {
     #[new]
-    fn new() -> Self {
-        Self {
-            inner: ScheduleRow::default(),
-        }
+    #[pyo3(signature = (
+        *,
+        run_number=None,
+        block=None,
+        start_place=None,
+        end_place=None,
+        start_time=None,
+        end_time=None,
+        trip_id=None,
+        depot=None,
+        vehicle_class=None,
+        vehicle_type=None,
+        start_lat=None,
+        start_lon=None,
+        end_lat=None,
+        end_lon=None,
+        route_shape_id=None,
+        row_type=None,
+        duty_id=None,
+        shift_id=None,
+        route_short_name=None,
+        headsign=None,
+        service_id=None,
+        block_sequence=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        run_number: Option<String>,
+        block: Option<String>,
+        start_place: Option<String>,
+        end_place: Option<String>,
+        start_time: Option<String>,
+        end_time: Option<String>,
+        trip_id: Option<String>,
+        depot: Option<String>,
+        vehicle_class: Option<String>,
+        vehicle_type: Option<String>,
+        start_lat: Option<f64>,
+        start_lon: Option<f64>,
+        end_lat: Option<f64>,
+        end_lon: Option<f64>,
+        route_shape_id: Option<String>,
+        row_type: Option<&str>,
+        duty_id: Option<String>,
+        shift_id: Option<String>,
+        route_short_name: Option<String>,
+        headsign: Option<String>,
+        service_id: Option<String>,
+        block_sequence: Option<u32>,
+    ) -> PyResult<Self> {
+        let row_type = match row_type {
+            Some(value) => value
+                .parse::<RowType>()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => RowType::default(),
+        };
+        Ok(Self {
+            inner: ScheduleRow {
+                run_number,
+                block,
+                start_place,
+                end_place,
+                start_time,
+                end_time,
+                trip_id,
+                depot,
+                vehicle_class,
+                vehicle_type,
+                start_lat,
+                start_lon,
+                end_lat,
+                end_lon,
+                route_shape_id,
+                row_type,
+                duty_id,
+                shift_id,
+                route_short_name,
+                headsign,
+                service_id,
+                block_sequence,
+            },
+        })
     }
 
     #[getter]
@@ -112,66 +201,198 @@ impl PyScheduleRow {
         self.inner.vehicle_class.clone()
     }
 
+    #[setter]
+    fn set_vehicle_class(&mut self, value: Option<String>) {
+        self.inner.vehicle_class = value;
+    }
+
     #[getter]
     fn vehicle_type(&self) -> Option<String> {
         self.inner.vehicle_type.clone()
     }
 
+    #[setter]
+    fn set_vehicle_type(&mut self, value: Option<String>) {
+        self.inner.vehicle_type = value;
+    }
+
     #[getter]
     fn start_lat(&self) -> Option<f64> {
         self.inner.start_lat
     }
 
+    #[setter]
+    fn set_start_lat(&mut self, value: Option<f64>) {
+        self.inner.start_lat = value;
+    }
+
     #[getter]
     fn start_lon(&self) -> Option<f64> {
         self.inner.start_lon
     }
 
+    #[setter]
+    fn set_start_lon(&mut self, value: Option<f64>) {
+        self.inner.start_lon = value;
+    }
+
     #[getter]
     fn end_lat(&self) -> Option<f64> {
         self.inner.end_lat
     }
 
+    #[setter]
+    fn set_end_lat(&mut self, value: Option<f64>) {
+        self.inner.end_lat = value;
+    }
+
     #[getter]
     fn end_lon(&self) -> Option<f64> {
         self.inner.end_lon
     }
 
+    #[setter]
+    fn set_end_lon(&mut self, value: Option<f64>) {
+        self.inner.end_lon = value;
+    }
+
     #[getter]
     fn route_shape_id(&self) -> Option<String> {
         self.inner.route_shape_id.clone()
     }
 
+    #[setter]
+    fn set_route_shape_id(&mut self, value: Option<String>) {
+        self.inner.route_shape_id = value;
+    }
+
     #[getter]
     fn row_type(&self) -> String {
         format!("{:?}", self.inner.row_type).to_lowercase()
     }
 
+    #[setter]
+    fn set_row_type(&mut self, value: &str) -> PyResult<()> {
+        self.inner.row_type = value
+            .parse::<RowType>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(())
+    }
+
     #[getter]
     fn duty_id(&self) -> Option<String> {
         self.inner.duty_id.clone()
     }
 
+    #[setter]
+    fn set_duty_id(&mut self, value: Option<String>) {
+        self.inner.duty_id = value;
+    }
+
     #[getter]
     fn shift_id(&self) -> Option<String> {
         self.inner.shift_id.clone()
     }
 
+    #[setter]
+    fn set_shift_id(&mut self, value: Option<String>) {
+        self.inner.shift_id = value;
+    }
+
+    #[getter]
+    fn route_short_name(&self) -> Option<String> {
+        self.inner.route_short_name.clone()
+    }
+
+    #[setter]
+    fn set_route_short_name(&mut self, value: Option<String>) {
+        self.inner.route_short_name = value;
+    }
+
+    #[getter]
+    fn headsign(&self) -> Option<String> {
+        self.inner.headsign.clone()
+    }
+
+    #[setter]
+    fn set_headsign(&mut self, value: Option<String>) {
+        self.inner.headsign = value;
+    }
+
+    #[getter]
+    fn service_id(&self) -> Option<String> {
+        self.inner.service_id.clone()
+    }
+
+    #[setter]
+    fn set_service_id(&mut self, value: Option<String>) {
+        self.inner.service_id = value;
+    }
+
+    #[getter]
+    fn block_sequence(&self) -> Option<u32> {
+        self.inner.block_sequence
+    }
+
+    #[setter]
+    fn set_block_sequence(&mut self, value: Option<u32>) {
+        self.inner.block_sequence = value;
+    }
+
     /// Check if this is a revenue (passenger-carrying) trip.
     fn is_revenue(&self) -> bool {
         self.inner.is_revenue()
     }
 
+    /// Check that the trip ID is set and not blank.
+    fn has_valid_trip_id(&self) -> bool {
+        self.inner.has_valid_trip_id()
+    }
+
     /// Check if this is a deadhead movement.
     fn is_deadhead(&self) -> bool {
         self.inner.is_deadhead()
     }
 
+    /// Check if this is a layover (the vehicle waiting in place).
+    fn is_layover(&self) -> bool {
+        self.inner.is_layover()
+    }
+
     /// Get duration in seconds.
     fn duration_seconds(&self) -> Option<u32> {
         self.inner.duration_seconds()
     }
 
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+
+    /// Hash over the identifying fields (block, run, trip, times, row type).
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.inner.block.hash(&mut hasher);
+        self.inner.run_number.hash(&mut hasher);
+        self.inner.trip_id.hash(&mut hasher);
+        self.inner.start_time.hash(&mut hasher);
+        self.inner.end_time.hash(&mut hasher);
+        self.inner.row_type.hash(&mut hasher);
+        self.inner.service_id.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes =
+            serde_json::to_vec(&self.inner).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner =
+            serde_json::from_slice(state).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ScheduleRow(block={:?}, trip_id={:?}, start_time={:?})",
@@ -187,7 +408,7 @@ impl From<ScheduleRow> for PyScheduleRow {
 }
 
 /// Python wrapper for Schedule.
-#[pyclass(name = "Schedule")]
+#[pyclass(name = "Schedule", module = "transit_parser._core")]
 pub struct PySchedule {
     inner: Schedule,
 }
@@ -201,12 +422,66 @@ impl PySchedule {
         }
     }
 
-    /// Load a schedule from a CSV file.
+    /// Load a schedule from a CSV file, optionally with read options.
+    #[staticmethod]
+    #[pyo3(signature = (path, options=None))]
+    fn from_csv(py: Python<'_>, path: &str, options: Option<&PyReadOptions>) -> PyResult<Self> {
+        let options = match options {
+            Some(o) => o.inner.clone(),
+            None => ReadOptions::new(),
+        };
+        // Release the GIL so other threads can poll a progress counter
+        py.allow_threads(|| ScheduleReader::read_path(path, options))
+            .map(|s| Self { inner: s })
+            .map_err(parse_err)
+    }
+
+    /// Load a schedule from a fixed-width file.
+    ///
+    /// Each spec is `(field, start_col, end_col)` with a 0-based,
+    /// end-exclusive character range.
     #[staticmethod]
-    fn from_csv(path: &str) -> PyResult<Self> {
-        ScheduleReader::read_path(path, ReadOptions::new())
+    #[pyo3(signature = (path, field_specs, options=None))]
+    fn from_fixed_width(
+        py: Python<'_>,
+        path: &str,
+        field_specs: Vec<(String, usize, usize)>,
+        options: Option<&PyReadOptions>,
+    ) -> PyResult<Self> {
+        let options = match options {
+            Some(o) => o.inner.clone(),
+            None => ReadOptions::new(),
+        };
+        py.allow_threads(|| {
+            let file = std::fs::File::open(path).map_err(transit_core::ParseError::Io)?;
+            ScheduleReader::read_fixed_width(file, &field_specs, options)
+        })
+        .map(|s| Self { inner: s })
+        .map_err(parse_err)
+    }
+
+    /// Load a schedule from a JSON Lines (NDJSON) file, one row per line.
+    #[staticmethod]
+    #[pyo3(signature = (path, options=None))]
+    fn from_ndjson(py: Python<'_>, path: &str, options: Option<&PyReadOptions>) -> PyResult<Self> {
+        let options = match options {
+            Some(o) => o.inner.clone(),
+            None => ReadOptions::new(),
+        };
+        py.allow_threads(|| {
+            let file = std::fs::File::open(path).map_err(transit_core::ParseError::Io)?;
+            ScheduleReader::read_ndjson(file, options)
+        })
+        .map(|s| Self { inner: s })
+        .map_err(parse_err)
+    }
+
+    /// Load a schedule from a JSON Lines (NDJSON) string.
+    #[staticmethod]
+    fn from_ndjson_string(ndjson: &str) -> PyResult<Self> {
+        ScheduleReader::read_ndjson(ndjson.as_bytes(), ReadOptions::new())
             .map(|s| Self { inner: s })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a schedule from a CSV string.
@@ -214,7 +489,7 @@ impl PySchedule {
     fn from_csv_string(csv_str: &str) -> PyResult<Self> {
         ScheduleReader::read_str(csv_str, ReadOptions::new())
             .map(|s| Self { inner: s })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Load a schedule with custom column mapping.
@@ -227,18 +502,12 @@ impl PySchedule {
         let mut options = ReadOptions::new();
 
         if let Some(mapping) = column_mapping {
-            let mut cm = ColumnMapping::new();
-            for (key, value) in mapping.iter() {
-                let field: String = key.extract()?;
-                let column: String = value.extract()?;
-                cm.add(field, column);
-            }
-            options = options.with_mapping(cm);
+            options = options.with_mapping(column_mapping_from_dict(mapping)?);
         }
 
         ScheduleReader::read_path(path, options)
             .map(|s| Self { inner: s })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Number of rows in the schedule.
@@ -246,6 +515,48 @@ impl PySchedule {
         self.inner.len()
     }
 
+    /// Get a row by index, or a list of rows by slice.
+    fn __getitem__(&self, py: Python<'_>, index: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let len = self.inner.len();
+
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(len as isize)?;
+            let rows = (0..indices.slicelength).map(|i| {
+                let idx = (indices.start + i as isize * indices.step) as usize;
+                PyScheduleRow::from(self.inner.rows[idx].clone())
+            });
+            return Ok(PyList::new(py, rows)?.into_any().unbind());
+        }
+
+        let i: isize = index.extract()?;
+        let idx = if i < 0 { i + len as isize } else { i };
+        if idx < 0 || idx as usize >= len {
+            return Err(PyIndexError::new_err("schedule index out of range"));
+        }
+
+        let row = PyScheduleRow::from(self.inner.rows[idx as usize].clone());
+        Ok(Py::new(py, row)?.into_any())
+    }
+
+    /// Iterate over rows without copying the whole list up front.
+    fn __iter__(slf: PyRef<'_, Self>) -> PyScheduleRowIterator {
+        PyScheduleRowIterator {
+            schedule: slf.into(),
+            index: 0,
+        }
+    }
+
+    /// Append a copy of a row to the schedule.
+    fn add_row(&mut self, row: &PyScheduleRow) {
+        self.inner.add_row(row.inner.clone());
+    }
+
+    /// Append copies of several rows to the schedule, in order.
+    fn extend(&mut self, rows: Vec<PyRef<'_, PyScheduleRow>>) {
+        self.inner
+            .extend_rows(rows.iter().map(|row| row.inner.clone()));
+    }
+
     /// Get all rows.
     #[getter]
     fn rows(&self, py: Python<'_>) -> PyResult<Py<PyList>> {
@@ -259,11 +570,65 @@ impl PySchedule {
         self.inner.revenue_trips().count()
     }
 
+    /// File-level metadata: source file, name, operator, service dates, any
+    /// captured preamble lines, parse warnings, block depot conflicts and
+    /// what the schedule was extracted from.
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let metadata = &self.inner.metadata;
+        let result = PyDict::new(py);
+        result.set_item("source_file", &metadata.source_file)?;
+        result.set_item("name", &metadata.name)?;
+        result.set_item("operator", &metadata.operator)?;
+        result.set_item("start_date", &metadata.start_date)?;
+        result.set_item("end_date", &metadata.end_date)?;
+        result.set_item("preamble", &metadata.preamble)?;
+        let parse_warnings = PyList::empty(py);
+        for warning in &metadata.parse_warnings {
+            let dict = PyDict::new(py);
+            dict.set_item("row_index", warning.row_index)?;
+            dict.set_item("field", &warning.field)?;
+            dict.set_item("value", &warning.value)?;
+            parse_warnings.append(dict)?;
+        }
+        result.set_item("parse_warnings", parse_warnings)?;
+        let block_depot_conflicts = PyDict::new(py);
+        for (block_id, depots) in &metadata.block_depot_conflicts {
+            block_depot_conflicts.set_item(block_id, depots)?;
+        }
+        result.set_item("block_depot_conflicts", block_depot_conflicts)?;
+        result.set_item("extracted", &metadata.extracted)?;
+        Ok(result)
+    }
+
     /// Get unique block IDs.
     fn block_ids(&self) -> Vec<String> {
         self.inner.block_ids()
     }
 
+    /// Group rows into blocks by the given fields from now on, e.g. ["depot", "block"].
+    fn derive_blocks_by(&mut self, fields: Vec<String>) -> PyResult<()> {
+        let key = BlockKey::from_fields(fields).map_err(|e| match e {
+            ParseError::InvalidData(message) => PyValueError::new_err(message),
+            e => parse_err(e),
+        })?;
+        self.inner.derive_blocks_by(key);
+        Ok(())
+    }
+
+    /// Fields that identify a row's block.
+    #[getter]
+    fn block_key(&self) -> Vec<String> {
+        self.inner.block_key().fields().to_vec()
+    }
+
+    /// Prefix block IDs shared by several depots with the depot, e.g. "D1:B1".
+    ///
+    /// Returns the number of block IDs that were split.
+    fn namespace_blocks_by_depot(&mut self) -> usize {
+        self.inner.namespace_blocks_by_depot()
+    }
+
     /// Get unique run numbers.
     fn run_numbers(&self) -> Vec<String> {
         self.inner.run_numbers()
@@ -274,104 +639,911 @@ impl PySchedule {
         self.inner.depots()
     }
 
+    /// Places ranked by how often they are block endpoints, as likely depots.
+    fn infer_likely_depots(&self) -> Vec<(String, usize)> {
+        self.inner.infer_likely_depots()
+    }
+
     /// Get unique trip IDs.
     fn trip_ids(&self) -> Vec<String> {
         self.inner.trip_ids()
     }
 
+    /// Get a copy of the first row with this trip ID, or None.
+    fn row_for_trip(&mut self, trip_id: &str) -> Option<PyScheduleRow> {
+        self.inner
+            .row_for_trip(trip_id)
+            .cloned()
+            .map(PyScheduleRow::from)
+    }
+
+    /// Map each trip ID to the index of its first row.
+    fn index_by_trip_id(&mut self) -> HashMap<String, usize> {
+        self.inner.index_by_trip_id().clone()
+    }
+
+    /// Get unique service IDs.
+    fn service_ids(&self) -> Vec<String> {
+        self.inner.service_ids()
+    }
+
+    /// Copy the rows for one service into a new schedule.
+    fn filter_service(&self, service_id: &str) -> Self {
+        Self {
+            inner: self.inner.filter_service(service_id),
+        }
+    }
+
+    /// Copy the rows of one block into a new schedule, or None if there are none.
+    fn extract_block(&self, block_id: &str) -> Option<Self> {
+        self.inner
+            .extract_block(block_id)
+            .map(|inner| Self { inner })
+    }
+
+    /// Copy the rows of one duty into a new schedule, or None if there are none.
+    fn extract_duty(&self, duty_id: &str) -> Option<Self> {
+        self.inner.extract_duty(duty_id).map(|inner| Self { inner })
+    }
+
+    /// Sort rows by start time (stable).
+    fn sort_by_time(&mut self) {
+        self.inner.sort_by_time();
+    }
+
+    /// Sort rows by block, then start time (stable). Rows without a block go last.
+    fn sort_by_block_and_time(&mut self) {
+        self.inner.sort_by_block_and_time();
+    }
+
+    /// Number each row by its chronological position within its block.
+    fn assign_block_sequences(&mut self) {
+        self.inner.assign_block_sequences();
+    }
+
+    /// Split a block at a time boundary into `{id}a` and `{id}b`, returning the new IDs.
+    fn split_block(&mut self, block_id: &str, at_seconds: u32) -> PyResult<(String, String)> {
+        if self.inner.rows_for_block(block_id).is_empty() {
+            return Err(PyKeyError::new_err(format!("Unknown block: {}", block_id)));
+        }
+        self.inner
+            .split_block(block_id, at_seconds)
+            .map_err(|e| match e {
+                ParseError::InvalidData(message) => PyValueError::new_err(message),
+                e => parse_err(e),
+            })
+    }
+
+    /// Reassign a revenue trip to another block.
+    fn move_trip(&mut self, trip_id: &str, to_block: &str) -> PyResult<()> {
+        if !self
+            .inner
+            .revenue_trips()
+            .any(|row| row.trip_id.as_deref() == Some(trip_id))
+        {
+            return Err(PyKeyError::new_err(format!("Unknown trip: {}", trip_id)));
+        }
+        self.inner
+            .move_trip(trip_id, to_block)
+            .map_err(|e| match e {
+                ParseError::InvalidData(message) => PyValueError::new_err(message),
+                e => parse_err(e),
+            })
+    }
+
+    /// Indices of rows that duplicate an earlier row (dry run of `deduplicate_rows`).
+    fn duplicate_row_indices(&self) -> Vec<usize> {
+        self.inner.duplicate_row_indices()
+    }
+
+    /// Remove exact duplicate rows, returning how many were removed.
+    fn deduplicate_rows(&mut self) -> usize {
+        self.inner.deduplicate_rows()
+    }
+
+    /// Scrub run numbers, duty/shift IDs and optionally blocks and coordinates.
+    ///
+    /// Returns the key mapping anonymized values back to the originals.
+    #[pyo3(signature = (salt=None, anonymize_blocks=false, coordinate_jitter_meters=None))]
+    fn anonymize(
+        &mut self,
+        py: Python<'_>,
+        salt: Option<String>,
+        anonymize_blocks: bool,
+        coordinate_jitter_meters: Option<f64>,
+    ) -> PyResult<Py<PyDict>> {
+        let mut options = AnonymizeOptions::new().with_anonymize_blocks(anonymize_blocks);
+        if let Some(salt) = salt {
+            options = options.with_strategy(IdStrategy::Hash { salt });
+        }
+        if let Some(meters) = coordinate_jitter_meters {
+            options = options.with_coordinate_jitter(meters);
+        }
+
+        let key = self.inner.anonymize(options);
+        let result = PyDict::new(py);
+        result.set_item("run_numbers", key.run_numbers)?;
+        result.set_item("duty_ids", key.duty_ids)?;
+        result.set_item("shift_ids", key.shift_ids)?;
+        result.set_item("blocks", key.blocks)?;
+        Ok(result.into())
+    }
+
+    /// Renumber block IDs and run numbers into one consistent scheme.
+    ///
+    /// Returns the mapping from original to new values.
+    #[pyo3(signature = (
+        *,
+        normalize_blocks=true,
+        normalize_runs=true,
+        block_prefix="B",
+        run_prefix="R",
+        width=4
+    ))]
+    fn normalize_ids(
+        &mut self,
+        py: Python<'_>,
+        normalize_blocks: bool,
+        normalize_runs: bool,
+        block_prefix: &str,
+        run_prefix: &str,
+        width: usize,
+    ) -> PyResult<Py<PyDict>> {
+        let options = NormalizeIdsOptions::new()
+            .with_normalize_blocks(normalize_blocks)
+            .with_normalize_runs(normalize_runs)
+            .with_block_prefix(block_prefix)
+            .with_run_prefix(run_prefix)
+            .with_width(width);
+
+        let mapping = self.inner.normalize_ids(options);
+        let result = PyDict::new(py);
+        result.set_item("blocks", mapping.blocks)?;
+        result.set_item("run_numbers", mapping.run_numbers)?;
+        Ok(result.into())
+    }
+
+    /// Chain unblocked revenue trips into generated blocks by place and time continuity.
+    #[pyo3(signature = (min_layover_seconds=0, max_layover_seconds=1800, block_id_prefix="AUTO"))]
+    fn assign_blocks_by_vehicle_continuity(
+        &mut self,
+        py: Python<'_>,
+        min_layover_seconds: u32,
+        max_layover_seconds: u32,
+        block_id_prefix: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let config = BlockingConfig::new()
+            .with_layover_range(min_layover_seconds, max_layover_seconds)
+            .with_block_id_prefix(block_id_prefix);
+
+        let report = self.inner.assign_blocks_by_vehicle_continuity(config);
+        let result = PyDict::new(py);
+        result.set_item("trips_blocked", report.trips_blocked)?;
+        result.set_item("trips_unmatched", report.trips_unmatched)?;
+        result.set_item("block_ids", report.block_ids)?;
+        Ok(result.into())
+    }
+
+    /// Indices of rows whose start or end time is missing or unparseable.
+    fn rows_missing_times(&self) -> Vec<usize> {
+        self.inner.rows_missing_times()
+    }
+
+    /// Fill missing revenue trip end times from GTFS stop_times.
+    fn backfill_end_times(&mut self, gtfs: &PyGtfsFeed) -> usize {
+        self.inner.backfill_end_times(&gtfs.inner)
+    }
+
+    /// Connections between consecutive revenue trips in each block, as
+    /// `(from_trip, to_trip, gap_seconds, bridged)` tuples.
+    fn interlining_edges(&mut self) -> Vec<(String, String, Option<u32>, bool)> {
+        self.inner.interlining_edges()
+    }
+
+    /// Add 24 hours to end times that wrap past midnight, returning rows changed.
+    fn normalize_overnight_times(&mut self) -> usize {
+        self.inner.normalize_overnight_times()
+    }
+
+    /// Fill missing row coordinates from GTFS stops, returning rows changed.
+    fn backfill_coordinates(&mut self, gtfs: &PyGtfsFeed) -> usize {
+        self.inner.backfill_coordinates(&gtfs.inner)
+    }
+
+    /// Expand revenue rows into their GTFS stop_times.
+    ///
+    /// Returns a dict with `stop_times` (list of dicts) and `rows_skipped`.
+    fn expand_stop_times(&self, py: Python<'_>, gtfs: &PyGtfsFeed) -> PyResult<Py<PyDict>> {
+        let expanded = self.inner.expand_stop_times(&gtfs.inner);
+        let stop_times = PyList::empty(py);
+        for stop_time in expanded.stop_times {
+            let dict = PyDict::new(py);
+            dict.set_item("row_index", stop_time.row_index)?;
+            dict.set_item("trip_id", stop_time.trip_id)?;
+            dict.set_item("block", stop_time.block)?;
+            dict.set_item("run_number", stop_time.run_number)?;
+            dict.set_item("duty_id", stop_time.duty_id)?;
+            dict.set_item("stop_id", stop_time.stop_id)?;
+            dict.set_item("stop_sequence", stop_time.stop_sequence)?;
+            dict.set_item("arrival_time_seconds", stop_time.arrival_time_seconds)?;
+            dict.set_item("departure_time_seconds", stop_time.departure_time_seconds)?;
+            stop_times.append(dict)?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("stop_times", stop_times)?;
+        result.set_item("rows_skipped", expanded.rows_skipped)?;
+        Ok(result.into())
+    }
+
+    /// Normalize, sort, infer deadheads, backfill coordinates and assign
+    /// sequences in one call, returning a report of what changed.
+    #[pyo3(signature = (
+        gtfs=None,
+        config=None,
+        normalize_overnight=true,
+        sort=true,
+        infer_deadheads=true,
+        backfill_coordinates=true,
+        assign_sequences=true
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn run_pipeline(
+        &mut self,
+        py: Python<'_>,
+        gtfs: Option<&PyGtfsFeed>,
+        config: Option<&PyInferenceConfig>,
+        normalize_overnight: bool,
+        sort: bool,
+        infer_deadheads: bool,
+        backfill_coordinates: bool,
+        assign_sequences: bool,
+    ) -> PyResult<Py<PyDict>> {
+        let mut pipeline = SchedulePipeline::new()
+            .with_normalize_overnight(normalize_overnight)
+            .with_sort(sort)
+            .with_infer_deadheads(infer_deadheads)
+            .with_backfill_coordinates(backfill_coordinates)
+            .with_assign_sequences(assign_sequences);
+        if let Some(config) = config {
+            pipeline = pipeline.with_inference_config(config.inner.clone());
+        }
+        if let Some(gtfs) = gtfs {
+            pipeline = pipeline.with_gtfs(&gtfs.inner);
+        }
+
+        let report = pipeline.run(&mut self.inner);
+        let steps: Vec<&str> = report.steps_run.iter().map(|s| s.as_str()).collect();
+        let dict = PyDict::new(py);
+        dict.set_item("steps_run", steps)?;
+        dict.set_item(
+            "overnight_rows_normalized",
+            report.overnight_rows_normalized,
+        )?;
+        dict.set_item("deadheads_added", report.deadheads_added)?;
+        dict.set_item("coordinates_backfilled", report.coordinates_backfilled)?;
+        dict.set_item("sequences_assigned", report.sequences_assigned)?;
+        Ok(dict.into())
+    }
+
     /// Get summary statistics.
     fn summary(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
-        let summary = self.inner.summary();
+        summary_to_dict(py, &self.inner.summary())
+    }
+
+    /// Summary statistics per service, as (service_id, summary) pairs.
+    fn summary_by_service(&self, py: Python<'_>) -> PyResult<Vec<(Option<String>, Py<PyDict>)>> {
+        self.inner
+            .summary_by_service()
+            .into_iter()
+            .map(|(service_id, summary)| Ok((service_id, summary_to_dict(py, &summary)?)))
+            .collect()
+    }
+
+    /// Deadhead counts and seconds by type: pull-outs, pull-ins and interlinings.
+    fn deadhead_stats(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        let stats = self.inner.deadhead_stats();
         let dict = PyDict::new(py);
-        dict.set_item("total_rows", summary.total_rows)?;
-        dict.set_item("revenue_trips", summary.revenue_trips)?;
-        dict.set_item("deadheads", summary.deadheads)?;
-        dict.set_item("breaks_and_reliefs", summary.breaks_and_reliefs)?;
-        dict.set_item("unique_blocks", summary.unique_blocks)?;
-        dict.set_item("unique_runs", summary.unique_runs)?;
-        dict.set_item("unique_depots", summary.unique_depots)?;
+        dict.set_item("pull_outs", stats.pull_outs)?;
+        dict.set_item("pull_out_seconds", stats.pull_out_seconds)?;
+        dict.set_item("pull_ins", stats.pull_ins)?;
+        dict.set_item("pull_in_seconds", stats.pull_in_seconds)?;
+        dict.set_item("interlinings", stats.interlinings)?;
+        dict.set_item("interlining_seconds", stats.interlining_seconds)?;
+        dict.set_item("total_count", stats.total_count())?;
+        dict.set_item("total_seconds", stats.total_seconds())?;
         Ok(dict.into())
     }
 
+    /// Places ranked by how many interlining deadheads start or end there.
+    fn deadhead_hotspots(&self) -> Vec<(String, usize)> {
+        self.inner.deadhead_hotspots()
+    }
+
+    /// Places ranked by how many pull-outs and pull-ins start or end there.
+    fn depot_deadhead_hotspots(&self) -> Vec<(String, usize)> {
+        self.inner.depot_deadhead_hotspots()
+    }
+
+    /// Peak number of blocks in service at once, as (count, seconds since midnight).
+    fn peak_vehicle_count(&self) -> (u32, u32) {
+        self.inner.peak_vehicle_count()
+    }
+
+    /// Number of blocks in service at the given time (seconds since midnight).
+    fn vehicles_in_service_at(&self, seconds: u32) -> usize {
+        self.inner.vehicles_in_service_at(seconds)
+    }
+
+    /// Rows running at the given time, optionally only those starting or
+    /// ending at `place`.
+    #[pyo3(signature = (seconds, place=None))]
+    fn rows_active_at(&self, seconds: u32, place: Option<&str>) -> Vec<PyScheduleRow> {
+        self.inner
+            .rows_active_at(place, seconds)
+            .into_iter()
+            .cloned()
+            .map(PyScheduleRow::from)
+            .collect()
+    }
+
+    /// Count revenue departures per time bucket, as (bucket start seconds,
+    /// count), optionally only for one route. Empty buckets are left out.
+    #[pyo3(signature = (bucket_seconds=3600, route=None))]
+    fn departure_histogram(
+        &self,
+        bucket_seconds: u32,
+        route: Option<&str>,
+    ) -> PyResult<Vec<(u32, usize)>> {
+        if bucket_seconds == 0 {
+            return Err(PyValueError::new_err(
+                "bucket_seconds must be positive, got 0",
+            ));
+        }
+        Ok(self.inner.departure_histogram(bucket_seconds, route))
+    }
+
+    /// Earliest start and latest end across revenue rows, or None if there are none.
+    fn service_span(&self) -> Option<(u32, u32)> {
+        self.inner.service_span()
+    }
+
+    /// Extent of the schedule's coordinates as (min_lat, min_lon, max_lat,
+    /// max_lon), or None if no row has coordinates.
+    fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+        self.inner.bounding_box()
+    }
+
+    /// Mean (lat, lon) of all start and end points, or None if no row has
+    /// coordinates.
+    fn centroid(&self) -> Option<(f64, f64)> {
+        self.inner.centroid()
+    }
+
+    /// Get summary statistics per route, keyed by route short name.
+    fn route_summaries(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        let result = PyDict::new(py);
+        for (route, summary) in self.inner.route_summaries() {
+            let dict = PyDict::new(py);
+            dict.set_item("trip_count", summary.trip_count)?;
+            dict.set_item("first_departure_seconds", summary.first_departure_seconds)?;
+            dict.set_item("last_arrival_seconds", summary.last_arrival_seconds)?;
+            dict.set_item("revenue_time_seconds", summary.revenue_time_seconds)?;
+            dict.set_item("blocks", summary.blocks)?;
+            result.set_item(route, dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Bands of a route's trips departing at a regular headway, in time order.
+    #[pyo3(signature = (route, min_trips=3, headway_tolerance_seconds=60))]
+    fn detect_frequencies(
+        &self,
+        py: Python<'_>,
+        route: &str,
+        min_trips: usize,
+        headway_tolerance_seconds: u32,
+    ) -> PyResult<Py<PyList>> {
+        let result = PyList::empty(py);
+        for band in self
+            .inner
+            .detect_frequencies(route, min_trips, headway_tolerance_seconds)
+        {
+            let dict = PyDict::new(py);
+            dict.set_item("start_time_seconds", band.start_time_seconds)?;
+            dict.set_item("end_time_seconds", band.end_time_seconds)?;
+            dict.set_item("headway_seconds", band.headway_seconds)?;
+            dict.set_item("trip_count", band.trip_count)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Get one row of KPIs per block, sorted by block ID.
+    fn block_kpi_table(&mut self, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let result = PyList::empty(py);
+        for summary in self.inner.block_kpi_table() {
+            let dict = PyDict::new(py);
+            dict.set_item("block_id", summary.block_id)?;
+            dict.set_item("depot", summary.depot)?;
+            dict.set_item("total_rows", summary.total_rows)?;
+            dict.set_item("revenue_trips", summary.revenue_trips)?;
+            dict.set_item("start_time_seconds", summary.start_time_seconds)?;
+            dict.set_item("end_time_seconds", summary.end_time_seconds)?;
+            dict.set_item("duration_seconds", summary.duration_seconds)?;
+            dict.set_item("revenue_time_seconds", summary.revenue_time_seconds)?;
+            dict.set_item("deadhead_time_seconds", summary.deadhead_time_seconds)?;
+            dict.set_item("layover_time_seconds", summary.layover_time_seconds)?;
+            dict.set_item("deadhead_ratio", summary.deadhead_ratio)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Classified time segments covering one block, for a Gantt-style view.
+    fn block_timeline(&mut self, py: Python<'_>, block_id: &str) -> PyResult<Py<PyList>> {
+        let block = self
+            .inner
+            .get_block(block_id)
+            .ok_or_else(|| PyKeyError::new_err(block_id.to_string()))?;
+        let result = PyList::empty(py);
+        for segment in block.timeline() {
+            let dict = PyDict::new(py);
+            dict.set_item("kind", segment.kind.as_str())?;
+            dict.set_item("start_seconds", segment.start_seconds)?;
+            dict.set_item("end_seconds", segment.end_seconds)?;
+            dict.set_item("start_place", segment.start_place)?;
+            dict.set_item("end_place", segment.end_place)?;
+            dict.set_item("row_index", segment.row_index)?;
+            dict.set_item("trip_id", segment.trip_id)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Duty coverage of each block, with vehicle time no duty covers.
+    fn block_coverage(&mut self, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let result = PyList::empty(py);
+        for coverage in self.inner.block_coverage() {
+            let dict = PyDict::new(py);
+            dict.set_item("block_id", &coverage.block_id)?;
+            dict.set_item("fully_covered", coverage.is_fully_covered())?;
+            dict.set_item("uncovered_seconds", coverage.uncovered_seconds())?;
+            dict.set_item("covered", coverage.covered)?;
+            dict.set_item("uncovered", coverage.uncovered)?;
+            dict.set_item("duty_ids", coverage.duty_ids)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Running distance in meters at the end of each row of one block.
+    fn block_cumulative_distances(&mut self, block_id: &str) -> PyResult<Vec<Option<f64>>> {
+        self.inner
+            .get_block(block_id)
+            .map(|block| block.cumulative_distances())
+            .ok_or_else(|| PyKeyError::new_err(block_id.to_string()))
+    }
+
+    /// Depot, start/end and ordered routes served per block, sorted by block ID.
+    fn compact_block_summaries(&mut self, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let mut summaries: Vec<_> = self
+            .inner
+            .blocks()
+            .values()
+            .map(|block| block.compact_summary())
+            .collect();
+        summaries.sort_by(|a, b| a.block_id.cmp(&b.block_id));
+
+        let result = PyList::empty(py);
+        for summary in summaries {
+            let dict = PyDict::new(py);
+            dict.set_item("block_id", summary.block_id)?;
+            dict.set_item("depot", summary.depot)?;
+            dict.set_item("start_time_seconds", summary.start_time_seconds)?;
+            dict.set_item("end_time_seconds", summary.end_time_seconds)?;
+            dict.set_item("start_place", summary.start_place)?;
+            dict.set_item("end_place", summary.end_place)?;
+            dict.set_item("route_sequence", summary.route_sequence)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Get one row of KPIs per duty, sorted by duty ID.
+    fn duty_kpi_table(&mut self, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let result = PyList::empty(py);
+        for summary in self.inner.duty_kpi_table() {
+            let dict = PyDict::new(py);
+            dict.set_item("duty_id", summary.duty_id)?;
+            dict.set_item("total_rows", summary.total_rows)?;
+            dict.set_item("start_time_seconds", summary.start_time_seconds)?;
+            dict.set_item("end_time_seconds", summary.end_time_seconds)?;
+            dict.set_item("duration_seconds", summary.duration_seconds)?;
+            dict.set_item("driving_time_seconds", summary.driving_time_seconds)?;
+            dict.set_item("break_time_seconds", summary.break_time_seconds)?;
+            dict.set_item("layover_time_seconds", summary.layover_time_seconds)?;
+            dict.set_item("pieces_of_work", summary.pieces_of_work)?;
+            dict.set_item("blocks_worked", summary.blocks_worked)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    }
+
+    /// Whether one duty gets a qualifying break within the working window.
+    fn duty_break_compliance(
+        &mut self,
+        py: Python<'_>,
+        duty_id: &str,
+        max_work_before_break_seconds: u32,
+        min_break_seconds: u32,
+    ) -> PyResult<Py<PyDict>> {
+        let compliance = self
+            .inner
+            .get_duty(duty_id)
+            .map(|duty| duty.break_compliance(max_work_before_break_seconds, min_break_seconds))
+            .ok_or_else(|| PyKeyError::new_err(duty_id.to_string()))?;
+        let result = PyDict::new(py);
+        result.set_item("is_compliant", compliance.is_compliant)?;
+        result.set_item("has_qualifying_break", compliance.has_qualifying_break())?;
+        result.set_item("breaks", compliance.breaks)?;
+        result.set_item("longest_stretch", compliance.longest_stretch)?;
+        result.set_item(
+            "longest_stretch_seconds",
+            compliance.longest_stretch_seconds,
+        )?;
+        Ok(result.into())
+    }
+
+    /// Driving time summed over all duties, with the duties counted and excluded.
+    fn total_driving_seconds(&mut self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        duty_total_to_dict(py, self.inner.total_driving_seconds())
+    }
+
+    /// Break and relief time summed over all duties.
+    fn total_break_seconds(&mut self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        duty_total_to_dict(py, self.inner.total_break_seconds())
+    }
+
+    /// Paid time summed over all duties under the given pay rules.
+    #[pyo3(signature = (paid_breaks=false, minimum_paid_seconds=0))]
+    fn total_paid_seconds(
+        &mut self,
+        py: Python<'_>,
+        paid_breaks: bool,
+        minimum_paid_seconds: u32,
+    ) -> PyResult<Py<PyDict>> {
+        let rules = PayRules {
+            paid_breaks,
+            minimum_paid_seconds,
+        };
+        duty_total_to_dict(py, self.inner.total_paid_seconds(&rules))
+    }
+
+    /// Check the schedule for style and consistency issues, without GTFS.
+    fn lint(&self, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let warnings = self
+            .inner
+            .lint()
+            .iter()
+            .map(|w| warning_to_dict(py, w, "warning"))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new(py, warnings)?.into())
+    }
+
     /// Validate the schedule against GTFS data.
-    #[pyo3(signature = (gtfs, config=None))]
+    #[pyo3(signature = (gtfs, config=None, progress=None))]
     fn validate(
         &mut self,
+        py: Python<'_>,
         gtfs: &PyGtfsFeed,
         config: Option<&PyValidationConfig>,
+        progress: Option<&PyProgressCounter>,
     ) -> PyResult<PyValidationResult> {
         let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
 
-        let validator = Validator::new(cfg);
-        let result = validator.validate(&mut self.inner, &gtfs.inner);
+        let mut validator = Validator::new(cfg);
+        if let Some(progress) = progress {
+            validator = validator.with_progress(progress.inner.clone());
+        }
+        let schedule = &mut self.inner;
+        let result = py.allow_threads(|| validator.validate(schedule, &gtfs.inner));
 
         Ok(PyValidationResult { inner: result })
     }
 
-    /// Validate schedule structure (without GTFS).
-    #[pyo3(signature = (config=None))]
-    fn validate_structure(
+    /// Validate against several GTFS feeds at once, e.g. one per operator.
+    #[pyo3(signature = (feeds, config=None, progress=None))]
+    fn validate_multi(
         &mut self,
+        py: Python<'_>,
+        feeds: Vec<PyRef<'_, PyGtfsFeed>>,
         config: Option<&PyValidationConfig>,
+        progress: Option<&PyProgressCounter>,
     ) -> PyResult<PyValidationResult> {
         let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
 
-        let validator = Validator::new(cfg);
-        let result = validator.validate_structure(&mut self.inner);
+        let mut validator = Validator::new(cfg);
+        if let Some(progress) = progress {
+            validator = validator.with_progress(progress.inner.clone());
+        }
+        let feeds: Vec<&gtfs_parser::GtfsFeed> = feeds.iter().map(|feed| &feed.inner).collect();
+        let schedule = &mut self.inner;
+        let result = py.allow_threads(|| validator.validate_multi(schedule, &feeds));
 
         Ok(PyValidationResult { inner: result })
     }
 
-    /// Infer missing deadheads.
-    #[pyo3(signature = (gtfs=None, default_depot=None))]
-    fn infer_deadheads(
+    /// Apply safe fixes, then validate, returning (fix report, result).
+    #[pyo3(signature = (gtfs=None, config=None))]
+    fn validate_and_fix(
         &mut self,
+        py: Python<'_>,
         gtfs: Option<&PyGtfsFeed>,
-        default_depot: Option<String>,
-    ) -> PyResult<PyDeadheadInferenceResult> {
-        use schedule_parser::deadhead::inferrer::InferenceConfig;
+        config: Option<&PyValidationConfig>,
+    ) -> PyResult<(Py<PyDict>, PyValidationResult)> {
+        let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
+        let gtfs = gtfs.map(|g| &g.inner);
+        let schedule = &mut self.inner;
+        let (report, result) = py.allow_threads(|| schedule.validate_and_fix(gtfs, cfg));
 
-        let mut config = InferenceConfig::new();
-        if let Some(depot) = default_depot {
-            config = config.with_default_depot(depot);
-        }
+        let fixes = PyDict::new(py);
+        fixes.set_item("trimmed_values", report.trimmed_values)?;
+        fixes.set_item("overnight_end_times", report.overnight_end_times)?;
+        fixes.set_item("duplicate_rows_removed", report.duplicate_rows_removed)?;
+        fixes.set_item("rows_moved", report.rows_moved)?;
+        Ok((fixes.into(), PyValidationResult { inner: result }))
+    }
 
-        let inferrer = match gtfs {
-            Some(g) => DeadheadInferrer::with_gtfs(config, &g.inner),
-            None => DeadheadInferrer::new(config),
+    /// Validate each service separately, as (service_id, result) pairs.
+    #[pyo3(signature = (gtfs, config=None))]
+    fn validate_by_service(
+        &self,
+        py: Python<'_>,
+        gtfs: &PyGtfsFeed,
+        config: Option<&PyValidationConfig>,
+    ) -> Vec<(Option<String>, PyValidationResult)> {
+        let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
+        let validator = Validator::new(cfg);
+        let schedule = &self.inner;
+        py.allow_threads(|| validator.validate_by_service(schedule, &gtfs.inner))
+            .into_iter()
+            .map(|(service_id, result)| (service_id, PyValidationResult { inner: result }))
+            .collect()
+    }
+
+    /// Revalidate only the given rows and their layovers with neighbouring rows.
+    #[pyo3(signature = (gtfs, row_indices, config=None))]
+    fn validate_rows(
+        &self,
+        py: Python<'_>,
+        gtfs: &PyGtfsFeed,
+        row_indices: Vec<usize>,
+        config: Option<&PyValidationConfig>,
+    ) -> PyValidationResult {
+        let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
+        let validator = Validator::new(cfg);
+        let schedule = &self.inner;
+        let result =
+            py.allow_threads(|| validator.validate_rows(schedule, &gtfs.inner, &row_indices));
+        PyValidationResult { inner: result }
+    }
+
+    /// Validate schedule structure (without GTFS).
+    #[pyo3(signature = (config=None, progress=None))]
+    fn validate_structure(
+        &mut self,
+        py: Python<'_>,
+        config: Option<&PyValidationConfig>,
+        progress: Option<&PyProgressCounter>,
+    ) -> PyResult<PyValidationResult> {
+        let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
+
+        let mut validator = Validator::new(cfg);
+        if let Some(progress) = progress {
+            validator = validator.with_progress(progress.inner.clone());
+        }
+        let schedule = &mut self.inner;
+        let result = py.allow_threads(|| validator.validate_structure(schedule));
+
+        Ok(PyValidationResult { inner: result })
+    }
+
+    /// Infer missing deadheads.
+    #[pyo3(signature = (gtfs=None, default_depot=None, config=None))]
+    fn infer_deadheads(
+        &self,
+        gtfs: Option<&PyGtfsFeed>,
+        default_depot: Option<String>,
+        config: Option<&PyInferenceConfig>,
+    ) -> PyResult<PyDeadheadInferenceResult> {
+        let mut config = config.map(|c| c.inner.clone()).unwrap_or_default();
+        if let Some(depot) = default_depot {
+            config = config.with_default_depot(depot);
+        }
+
+        let inferrer = match gtfs {
+            Some(g) => DeadheadInferrer::with_gtfs(config, &g.inner),
+            None => DeadheadInferrer::new(config),
         };
 
-        let result = inferrer.infer(&mut self.inner);
+        let result = inferrer.infer(&self.inner);
         Ok(PyDeadheadInferenceResult { inner: result })
     }
 
+    /// Export one CSV file per block into a directory, returning the paths.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        dir,
+        filename_template="{block_id}",
+        columns=None,
+        preset=None,
+        header_prefix=None,
+        line_terminator="lf",
+        encoding=None,
+        lossy_encoding=false,
+        exclude_columns=None,
+        round_to_seconds=None,
+        coordinate_precision=Some(6),
+    ))]
+    fn to_csv_by_block(
+        &self,
+        dir: &str,
+        filename_template: &str,
+        columns: Option<Vec<String>>,
+        preset: Option<&str>,
+        header_prefix: Option<&str>,
+        line_terminator: &str,
+        encoding: Option<&str>,
+        lossy_encoding: bool,
+        exclude_columns: Option<Vec<String>>,
+        round_to_seconds: Option<u32>,
+        coordinate_precision: Option<usize>,
+    ) -> PyResult<Vec<String>> {
+        let mut config = Self::build_export_config(
+            columns,
+            preset,
+            header_prefix,
+            line_terminator,
+            exclude_columns,
+            round_to_seconds,
+            coordinate_precision,
+        )?;
+        config.encoding = encoding.map(String::from);
+        config.lossy_encoding = lossy_encoding;
+        let exporter = CsvExporter::new(config);
+        let paths = exporter
+            .export_by_block(&self.inner, Path::new(dir), filename_template)
+            .map_err(parse_err)?;
+        Ok(paths
+            .into_iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect())
+    }
+
     /// Export to CSV file.
-    #[pyo3(signature = (path, columns=None, preset=None))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        path,
+        columns=None,
+        preset=None,
+        header_prefix=None,
+        line_terminator="lf",
+        encoding=None,
+        lossy_encoding=false,
+        exclude_columns=None,
+        round_to_seconds=None,
+        coordinate_precision=Some(6),
+    ))]
     fn to_csv(
         &self,
         path: &str,
         columns: Option<Vec<String>>,
         preset: Option<&str>,
+        header_prefix: Option<&str>,
+        line_terminator: &str,
+        encoding: Option<&str>,
+        lossy_encoding: bool,
+        exclude_columns: Option<Vec<String>>,
+        round_to_seconds: Option<u32>,
+        coordinate_precision: Option<usize>,
     ) -> PyResult<()> {
-        let config = Self::build_export_config(columns, preset)?;
+        let mut config = Self::build_export_config(
+            columns,
+            preset,
+            header_prefix,
+            line_terminator,
+            exclude_columns,
+            round_to_seconds,
+            coordinate_precision,
+        )?;
+        config.encoding = encoding.map(String::from);
+        config.lossy_encoding = lossy_encoding;
         let exporter = CsvExporter::new(config);
         exporter
             .export_to_path(&self.inner, path)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Export to CSV string.
-    #[pyo3(signature = (columns=None, preset=None))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        columns=None,
+        preset=None,
+        header_prefix=None,
+        line_terminator="lf",
+        exclude_columns=None,
+        round_to_seconds=None,
+        coordinate_precision=Some(6),
+    ))]
     fn to_csv_string(
         &self,
         columns: Option<Vec<String>>,
         preset: Option<&str>,
+        header_prefix: Option<&str>,
+        line_terminator: &str,
+        exclude_columns: Option<Vec<String>>,
+        round_to_seconds: Option<u32>,
+        coordinate_precision: Option<usize>,
     ) -> PyResult<String> {
-        let config = Self::build_export_config(columns, preset)?;
+        let config = Self::build_export_config(
+            columns,
+            preset,
+            header_prefix,
+            line_terminator,
+            exclude_columns,
+            round_to_seconds,
+            coordinate_precision,
+        )?;
         let exporter = CsvExporter::new(config);
-        exporter
-            .export_to_string(&self.inner)
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+        exporter.export_to_string(&self.inner).map_err(parse_err)
+    }
+
+    /// Export rows as JSON Lines (NDJSON), optionally appending to the file.
+    #[pyo3(signature = (path, append=false))]
+    fn to_ndjson(&self, path: &str, append: bool) -> PyResult<()> {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        JsonExporter::export_ndjson(&self.inner, file).map_err(parse_err)
+    }
+
+    /// Export rows as a JSON Lines (NDJSON) string.
+    fn to_ndjson_string(&self) -> PyResult<String> {
+        JsonExporter::export_ndjson_string(&self.inner).map_err(parse_err)
+    }
+
+    /// Stable hash of all rows, for detecting changes. Depends on row order.
+    fn content_hash(&self) -> u64 {
+        self.inner.content_hash()
+    }
+
+    /// Whether both schedules hold the same rows in any order, optionally
+    /// also requiring equal metadata.
+    #[pyo3(signature = (other, compare_metadata=false))]
+    fn equivalent_to(&self, other: &Self, compare_metadata: bool) -> bool {
+        self.inner.equivalent_to(&other.inner)
+            && (!compare_metadata || self.inner.metadata == other.inner.metadata)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner.rows == other.inner.rows && self.inner.metadata == other.inner.metadata
+    }
+
+    /// Serialize for pickling (derived blocks/duties are rebuilt on demand;
+    /// the block key is not stored and resets to the default).
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes =
+            serde_json::to_vec(&self.inner).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner =
+            serde_json::from_slice(state).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(())
     }
 
     fn __repr__(&self) -> String {
@@ -383,10 +1555,62 @@ impl PySchedule {
     }
 }
 
+/// Iterator over the rows of a Schedule.
+#[pyclass(name = "ScheduleRowIterator")]
+pub struct PyScheduleRowIterator {
+    schedule: Py<PySchedule>,
+    index: usize,
+}
+
+#[pymethods]
+impl PyScheduleRowIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> Option<PyScheduleRow> {
+        let row = slf.schedule.borrow(py).inner.rows.get(slf.index).cloned()?;
+        slf.index += 1;
+        Some(row.into())
+    }
+}
+
 impl PySchedule {
     fn build_export_config(
         columns: Option<Vec<String>>,
         preset: Option<&str>,
+        header_prefix: Option<&str>,
+        line_terminator: &str,
+        exclude_columns: Option<Vec<String>>,
+        round_to_seconds: Option<u32>,
+        coordinate_precision: Option<usize>,
+    ) -> PyResult<ExportConfig> {
+        let terminator = match line_terminator.to_lowercase().as_str() {
+            "lf" => LineTerminator::Lf,
+            "crlf" => LineTerminator::Crlf,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown line terminator: {} (expected 'lf' or 'crlf')",
+                    line_terminator
+                )))
+            }
+        };
+        let mut config = Self::build_export_columns(columns, preset)?.line_terminator(terminator);
+        if let Some(excluded) = exclude_columns {
+            let excluded: Vec<&str> = excluded.iter().map(String::as_str).collect();
+            config = config.without(&excluded);
+        }
+        config.round_to_seconds = round_to_seconds;
+        config.coordinate_precision = coordinate_precision;
+        Ok(match header_prefix {
+            Some(prefix) => config.header_prefix(prefix),
+            None => config,
+        })
+    }
+
+    fn build_export_columns(
+        columns: Option<Vec<String>>,
+        preset: Option<&str>,
     ) -> PyResult<ExportConfig> {
         if let Some(preset_name) = preset {
             let preset = match preset_name.to_lowercase().as_str() {
@@ -415,6 +1639,439 @@ impl PySchedule {
     }
 }
 
+/// Convert schedule summary statistics to a Python dict.
+fn summary_to_dict(py: Python<'_>, summary: &ScheduleSummary) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("total_rows", summary.total_rows)?;
+    dict.set_item("revenue_trips", summary.revenue_trips)?;
+    dict.set_item("deadheads", summary.deadheads)?;
+    dict.set_item("breaks_and_reliefs", summary.breaks_and_reliefs)?;
+    dict.set_item("unique_blocks", summary.unique_blocks)?;
+    dict.set_item("unique_runs", summary.unique_runs)?;
+    dict.set_item("unique_depots", summary.unique_depots)?;
+    Ok(dict.into())
+}
+
+/// Convert a total over duties to a Python dict.
+fn duty_total_to_dict(py: Python<'_>, total: DutyTotal) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("seconds", total.seconds)?;
+    dict.set_item("duties_counted", total.duties_counted)?;
+    dict.set_item("duties_excluded", total.duties_excluded)?;
+    Ok(dict.into())
+}
+
+/// Build a ColumnMapping from a Python dict of field -> column name.
+fn column_mapping_from_dict(mapping: &Bound<'_, PyDict>) -> PyResult<ColumnMapping> {
+    let mut cm = ColumnMapping::new();
+    for (key, value) in mapping.iter() {
+        let field: String = key.extract()?;
+        let column: String = value.extract()?;
+        cm.add(field, column);
+    }
+    Ok(cm)
+}
+
+/// Python wrapper for ReadOptions.
+#[pyclass(name = "ReadOptions")]
+#[derive(Clone)]
+pub struct PyReadOptions {
+    inner: ReadOptions,
+}
+
+#[pymethods]
+impl PyReadOptions {
+    #[new]
+    #[pyo3(signature = (
+        column_mapping=None,
+        auto_detect_columns=true,
+        delimiter=None,
+        has_headers=true,
+        skip_empty_rows=true,
+        progress=None,
+        fill_missing_end_times=false,
+        null_tokens=None,
+        untrimmed_fields=None,
+        max_total_rows=None,
+        max_file_bytes=None,
+        row_type_aliases=None,
+        skip_preamble_lines=0,
+        capture_preamble=false,
+        stop_list_column=None,
+        stop_list_delimiter=None,
+        stop_time_separator=None,
+        strict_coordinates=false,
+        comma_decimal_coordinates=false
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        column_mapping: Option<&Bound<'_, PyDict>>,
+        auto_detect_columns: bool,
+        delimiter: Option<&str>,
+        has_headers: bool,
+        skip_empty_rows: bool,
+        progress: Option<&PyProgressCounter>,
+        fill_missing_end_times: bool,
+        null_tokens: Option<Vec<String>>,
+        untrimmed_fields: Option<HashSet<String>>,
+        max_total_rows: Option<usize>,
+        max_file_bytes: Option<u64>,
+        row_type_aliases: Option<HashMap<String, String>>,
+        skip_preamble_lines: usize,
+        capture_preamble: bool,
+        stop_list_column: Option<String>,
+        stop_list_delimiter: Option<char>,
+        stop_time_separator: Option<char>,
+        strict_coordinates: bool,
+        comma_decimal_coordinates: bool,
+    ) -> PyResult<Self> {
+        let mut options = ReadOptions::new();
+        if let Some(mapping) = column_mapping {
+            options.column_mapping = Some(column_mapping_from_dict(mapping)?);
+        }
+        options.auto_detect_columns = auto_detect_columns;
+        options.delimiter = delimiter.map(parse_delimiter).transpose()?;
+        options.has_headers = has_headers;
+        options.skip_empty_rows = skip_empty_rows;
+        options.progress = progress.map(|p| p.inner.clone());
+        options.fill_missing_end_times = fill_missing_end_times;
+        options.null_tokens = null_tokens.unwrap_or_default();
+        options.untrimmed_fields = untrimmed_fields.unwrap_or_default();
+        options.max_total_rows = max_total_rows;
+        options.max_file_bytes = max_file_bytes;
+        options.row_type_aliases = row_type_aliases_from_map(row_type_aliases.unwrap_or_default())?;
+        options.skip_preamble_lines = skip_preamble_lines;
+        options.capture_preamble = capture_preamble;
+        options.stop_list_column = stop_list_column;
+        options.stop_list_delimiter = stop_list_delimiter;
+        options.stop_time_separator = stop_time_separator;
+        options.strict_coordinates = strict_coordinates;
+        options.comma_decimal_coordinates = comma_decimal_coordinates;
+        Ok(Self { inner: options })
+    }
+
+    /// Create options from a column mapping file (`.toml`, otherwise JSON).
+    #[staticmethod]
+    fn from_mapping_file(path: &str) -> PyResult<Self> {
+        let mapping = if path.to_ascii_lowercase().ends_with(".toml") {
+            ColumnMapping::from_toml_file(path)
+        } else {
+            ColumnMapping::from_json_file(path)
+        }
+        .map_err(mapping_err)?;
+        Ok(Self {
+            inner: ReadOptions::new().with_mapping(mapping),
+        })
+    }
+
+    #[getter]
+    fn auto_detect_columns(&self) -> bool {
+        self.inner.auto_detect_columns
+    }
+
+    #[setter]
+    fn set_auto_detect_columns(&mut self, value: bool) {
+        self.inner.auto_detect_columns = value;
+    }
+
+    #[getter]
+    fn delimiter(&self) -> Option<String> {
+        self.inner.delimiter.map(|d| (d as char).to_string())
+    }
+
+    #[setter]
+    fn set_delimiter(&mut self, value: Option<&str>) -> PyResult<()> {
+        self.inner.delimiter = value.map(parse_delimiter).transpose()?;
+        Ok(())
+    }
+
+    #[getter]
+    fn has_headers(&self) -> bool {
+        self.inner.has_headers
+    }
+
+    #[setter]
+    fn set_has_headers(&mut self, value: bool) {
+        self.inner.has_headers = value;
+    }
+
+    #[getter]
+    fn skip_empty_rows(&self) -> bool {
+        self.inner.skip_empty_rows
+    }
+
+    #[setter]
+    fn set_skip_empty_rows(&mut self, value: bool) {
+        self.inner.skip_empty_rows = value;
+    }
+
+    #[getter]
+    fn fill_missing_end_times(&self) -> bool {
+        self.inner.fill_missing_end_times
+    }
+
+    #[setter]
+    fn set_fill_missing_end_times(&mut self, value: bool) {
+        self.inner.fill_missing_end_times = value;
+    }
+
+    #[getter]
+    fn null_tokens(&self) -> Vec<String> {
+        self.inner.null_tokens.clone()
+    }
+
+    #[setter]
+    fn set_null_tokens(&mut self, value: Vec<String>) {
+        self.inner.null_tokens = value;
+    }
+
+    #[getter]
+    fn untrimmed_fields(&self) -> HashSet<String> {
+        self.inner.untrimmed_fields.clone()
+    }
+
+    #[setter]
+    fn set_untrimmed_fields(&mut self, value: HashSet<String>) {
+        self.inner.untrimmed_fields = value;
+    }
+
+    #[getter]
+    fn max_total_rows(&self) -> Option<usize> {
+        self.inner.max_total_rows
+    }
+
+    #[setter]
+    fn set_max_total_rows(&mut self, value: Option<usize>) {
+        self.inner.max_total_rows = value;
+    }
+
+    #[getter]
+    fn max_file_bytes(&self) -> Option<u64> {
+        self.inner.max_file_bytes
+    }
+
+    #[setter]
+    fn set_max_file_bytes(&mut self, value: Option<u64>) {
+        self.inner.max_file_bytes = value;
+    }
+
+    #[getter]
+    fn row_type_aliases(&self) -> HashMap<String, String> {
+        self.inner
+            .row_type_aliases
+            .iter()
+            .map(|(alias, row_type)| (alias.clone(), format!("{:?}", row_type).to_lowercase()))
+            .collect()
+    }
+
+    #[setter]
+    fn set_row_type_aliases(&mut self, value: HashMap<String, String>) -> PyResult<()> {
+        self.inner.row_type_aliases = row_type_aliases_from_map(value)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn skip_preamble_lines(&self) -> usize {
+        self.inner.skip_preamble_lines
+    }
+
+    #[setter]
+    fn set_skip_preamble_lines(&mut self, value: usize) {
+        self.inner.skip_preamble_lines = value;
+    }
+
+    #[getter]
+    fn capture_preamble(&self) -> bool {
+        self.inner.capture_preamble
+    }
+
+    #[setter]
+    fn set_capture_preamble(&mut self, value: bool) {
+        self.inner.capture_preamble = value;
+    }
+
+    #[getter]
+    fn stop_list_column(&self) -> Option<String> {
+        self.inner.stop_list_column.clone()
+    }
+
+    #[setter]
+    fn set_stop_list_column(&mut self, value: Option<String>) {
+        self.inner.stop_list_column = value;
+    }
+
+    #[getter]
+    fn stop_list_delimiter(&self) -> Option<char> {
+        self.inner.stop_list_delimiter
+    }
+
+    #[setter]
+    fn set_stop_list_delimiter(&mut self, value: Option<char>) {
+        self.inner.stop_list_delimiter = value;
+    }
+
+    #[getter]
+    fn stop_time_separator(&self) -> Option<char> {
+        self.inner.stop_time_separator
+    }
+
+    #[setter]
+    fn set_stop_time_separator(&mut self, value: Option<char>) {
+        self.inner.stop_time_separator = value;
+    }
+
+    #[getter]
+    fn strict_coordinates(&self) -> bool {
+        self.inner.strict_coordinates
+    }
+
+    #[setter]
+    fn set_strict_coordinates(&mut self, value: bool) {
+        self.inner.strict_coordinates = value;
+    }
+
+    #[getter]
+    fn comma_decimal_coordinates(&self) -> bool {
+        self.inner.comma_decimal_coordinates
+    }
+
+    #[setter]
+    fn set_comma_decimal_coordinates(&mut self, value: bool) {
+        self.inner.comma_decimal_coordinates = value;
+    }
+
+    #[getter]
+    fn progress(&self) -> Option<PyProgressCounter> {
+        self.inner
+            .progress
+            .clone()
+            .map(|inner| PyProgressCounter { inner })
+    }
+
+    #[setter]
+    fn set_progress(&mut self, value: Option<&PyProgressCounter>) {
+        self.inner.progress = value.map(|p| p.inner.clone());
+    }
+
+    /// Map a standard field name to a CSV column name.
+    fn add_mapping(&mut self, field: String, column: String) {
+        self.inner
+            .column_mapping
+            .get_or_insert_with(ColumnMapping::new)
+            .add(field, column);
+    }
+
+    /// Give a standard field the same literal value on every row.
+    fn add_constant(&mut self, field: String, value: String) {
+        self.inner
+            .column_mapping
+            .get_or_insert_with(ColumnMapping::new)
+            .add_constant(field, value);
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ReadOptions(delimiter={:?}, has_headers={}, auto_detect_columns={})",
+            self.inner.delimiter.map(|d| d as char),
+            self.inner.has_headers,
+            self.inner.auto_detect_columns
+        )
+    }
+}
+
+/// Python wrapper for ProgressCounter.
+#[pyclass(name = "ProgressCounter")]
+#[derive(Clone)]
+pub struct PyProgressCounter {
+    inner: ProgressCounter,
+}
+
+#[pymethods]
+impl PyProgressCounter {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: ProgressCounter::new(),
+        }
+    }
+
+    /// Units of work processed so far.
+    #[getter]
+    fn processed(&self) -> usize {
+        self.inner.processed()
+    }
+
+    /// Total units of work expected, or 0 if unknown.
+    #[getter]
+    fn total(&self) -> usize {
+        self.inner.total()
+    }
+
+    /// Reset both counts to zero.
+    fn reset(&self) {
+        self.inner.reset();
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ProgressCounter(processed={}, total={})",
+            self.inner.processed(),
+            self.inner.total()
+        )
+    }
+}
+
+/// Parse a single-byte delimiter from a Python string.
+fn parse_delimiter(value: &str) -> PyResult<u8> {
+    match value.as_bytes() {
+        [b] => Ok(*b),
+        _ => Err(PyValueError::new_err(format!(
+            "Delimiter must be a single ASCII character, got {:?}",
+            value
+        ))),
+    }
+}
+
+/// Convert a validation warning to the dict shape used by `ValidationResult.warnings`.
+fn warning_to_dict<'py>(
+    py: Python<'py>,
+    warning: &ValidationWarning,
+    severity: &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("code", &warning.code)?;
+    dict.set_item("category", format!("{:?}", warning.category))?;
+    dict.set_item("message", &warning.message)?;
+    dict.set_item("context", &warning.context)?;
+    dict.set_item("severity", severity)?;
+    Ok(dict)
+}
+
+/// Parse row type aliases from alias -> row type name pairs.
+///
+/// Aliases are matched ignoring case and surrounding whitespace, so two that
+/// differ only in that way must name the same row type.
+fn row_type_aliases_from_map(
+    aliases: HashMap<String, String>,
+) -> PyResult<HashMap<String, RowType>> {
+    let mut parsed = HashMap::new();
+    for (alias, name) in aliases {
+        let row_type = name
+            .parse::<RowType>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let key = alias.trim().to_lowercase();
+        if let Some(other) = parsed.insert(key, row_type) {
+            if other != row_type {
+                return Err(PyValueError::new_err(format!(
+                    "Row type alias '{}' is given for more than one row type",
+                    alias.trim()
+                )));
+            }
+        }
+    }
+    Ok(parsed)
+}
+
 /// Python wrapper for ValidationConfig.
 #[pyclass(name = "ValidationConfig")]
 #[derive(Clone)]
@@ -435,7 +2092,22 @@ impl PyValidationConfig {
         time_tolerance_seconds=None,
         validate_block_continuity=None,
         validate_duty_constraints=None,
-        generate_warnings=None
+        generate_warnings=None,
+        *,
+        min_block_duration_seconds=None,
+        max_block_duration_seconds=None,
+        flag_orphan_trips=None,
+        flag_missing_coordinates=None,
+        headway_deviation_threshold=None,
+        max_layover_seconds=None,
+        max_deadhead_speed_mps=None,
+        min_deadhead_duration_seconds=None,
+        max_revenue_speed_mps=None,
+        chronology_tolerance_seconds=None,
+        layover_exempt_route_types=None,
+        continuous_driving_reset=None,
+        warnings_as_errors=None,
+        discontinuity_as_error=None
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -449,6 +2121,20 @@ impl PyValidationConfig {
         validate_block_continuity: Option<bool>,
         validate_duty_constraints: Option<bool>,
         generate_warnings: Option<bool>,
+        min_block_duration_seconds: Option<u32>,
+        max_block_duration_seconds: Option<u32>,
+        flag_orphan_trips: Option<bool>,
+        flag_missing_coordinates: Option<bool>,
+        headway_deviation_threshold: Option<f64>,
+        max_layover_seconds: Option<u32>,
+        max_deadhead_speed_mps: Option<f64>,
+        min_deadhead_duration_seconds: Option<u32>,
+        max_revenue_speed_mps: Option<f64>,
+        chronology_tolerance_seconds: Option<u32>,
+        layover_exempt_route_types: Option<Vec<u16>>,
+        continuous_driving_reset: Option<&str>,
+        warnings_as_errors: Option<bool>,
+        discontinuity_as_error: Option<bool>,
     ) -> PyResult<Self> {
         let mut config = ValidationConfig::new();
 
@@ -484,6 +2170,59 @@ impl PyValidationConfig {
         if let Some(v) = time_tolerance_seconds {
             config.business_rules.time_tolerance_seconds = v;
         }
+        if let Some(v) = min_block_duration_seconds {
+            config.business_rules.min_block_duration_seconds = v;
+        }
+        if let Some(v) = max_block_duration_seconds {
+            config.business_rules.max_block_duration_seconds = v;
+        }
+        if let Some(v) = flag_orphan_trips {
+            config.business_rules.flag_orphan_trips = v;
+        }
+        if let Some(v) = flag_missing_coordinates {
+            config.business_rules.flag_missing_coordinates = v;
+        }
+        if let Some(v) = headway_deviation_threshold {
+            config.business_rules.headway_deviation_threshold = Some(v);
+        }
+        if let Some(v) = max_layover_seconds {
+            config.business_rules.max_layover_seconds = Some(v);
+        }
+        if let Some(v) = max_deadhead_speed_mps {
+            config.business_rules.max_deadhead_speed_mps = v;
+        }
+        if let Some(v) = min_deadhead_duration_seconds {
+            config.business_rules.min_deadhead_duration_seconds = v;
+        }
+        if let Some(v) = max_revenue_speed_mps {
+            config.business_rules.max_revenue_speed_mps = Some(v);
+        }
+        if let Some(v) = chronology_tolerance_seconds {
+            config.business_rules.chronology_tolerance_seconds = v;
+        }
+        if let Some(codes) = layover_exempt_route_types {
+            config.business_rules.layover_exempt_route_types = codes
+                .into_iter()
+                .map(|code| {
+                    transit_core::RouteType::from_u16(code).ok_or_else(|| {
+                        PyValueError::new_err(format!("Unknown route type: {}", code))
+                    })
+                })
+                .collect::<PyResult<_>>()?;
+        }
+        if let Some(reset) = continuous_driving_reset {
+            config.business_rules.continuous_driving_reset = match reset.to_lowercase().as_str()
+            {
+                "any_break" => ContinuousDrivingReset::AnyBreak,
+                "qualifying_break_only" => ContinuousDrivingReset::QualifyingBreakOnly,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown continuous driving reset: {} (expected 'any_break' or 'qualifying_break_only')",
+                        reset
+                    )))
+                }
+            };
+        }
         if let Some(v) = validate_block_continuity {
             config.validate_block_continuity = v;
         }
@@ -493,6 +2232,12 @@ impl PyValidationConfig {
         if let Some(v) = generate_warnings {
             config.generate_warnings = v;
         }
+        if let Some(v) = warnings_as_errors {
+            config.warnings_as_errors = v;
+        }
+        if let Some(v) = discontinuity_as_error {
+            config.discontinuity_as_error = v;
+        }
 
         Ok(Self { inner: config })
     }
@@ -513,6 +2258,18 @@ impl PyValidationConfig {
         }
     }
 
+    /// Headway deviation threshold in standard deviations, or None when the
+    /// check is off.
+    #[getter]
+    fn headway_deviation_threshold(&self) -> Option<f64> {
+        self.inner.business_rules.headway_deviation_threshold
+    }
+
+    #[setter]
+    fn set_headway_deviation_threshold(&mut self, value: Option<f64>) {
+        self.inner.business_rules.headway_deviation_threshold = value;
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ValidationConfig(gtfs_compliance={:?})",
@@ -561,6 +2318,7 @@ impl PyValidationResult {
                     .unwrap();
                 dict.set_item("message", &e.message).unwrap();
                 dict.set_item("context", &e.context).unwrap();
+                dict.set_item("severity", "error").unwrap();
                 dict
             })
             .collect();
@@ -570,20 +2328,17 @@ impl PyValidationResult {
     /// Get all warnings.
     #[getter]
     fn warnings(&self, py: Python<'_>) -> PyResult<Py<PyList>> {
-        let warnings: Vec<Bound<'_, PyDict>> = self
+        let severity = if self.inner.warnings_as_errors {
+            "error"
+        } else {
+            "warning"
+        };
+        let warnings = self
             .inner
             .warnings
             .iter()
-            .map(|w| {
-                let dict = PyDict::new(py);
-                dict.set_item("code", &w.code).unwrap();
-                dict.set_item("category", format!("{:?}", w.category))
-                    .unwrap();
-                dict.set_item("message", &w.message).unwrap();
-                dict.set_item("context", &w.context).unwrap();
-                dict
-            })
-            .collect();
+            .map(|w| warning_to_dict(py, w, severity))
+            .collect::<PyResult<Vec<_>>>()?;
         Ok(PyList::new(py, warnings)?.into())
     }
 
@@ -599,6 +2354,34 @@ impl PyValidationResult {
         self.inner.blocks_validated
     }
 
+    /// Whether warnings fail validation.
+    #[getter]
+    fn warnings_as_errors(&self) -> bool {
+        self.inner.warnings_as_errors
+    }
+
+    /// Raise `ScheduleValidationError` if validation failed.
+    fn raise_for_errors(&self) -> PyResult<()> {
+        if self.inner.is_valid() {
+            return Ok(());
+        }
+        let mut errors: Vec<String> = self
+            .inner
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.code, e.message))
+            .collect();
+        if self.inner.warnings_as_errors {
+            errors.extend(
+                self.inner
+                    .warnings
+                    .iter()
+                    .map(|w| format!("{}: {}", w.code, w.message)),
+            );
+        }
+        Err(validation_err(errors))
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ValidationResult(valid={}, errors={}, warnings={})",
@@ -609,6 +2392,285 @@ impl PyValidationResult {
     }
 }
 
+/// Python wrapper for CachingValidator.
+#[pyclass(name = "CachingValidator")]
+pub struct PyCachingValidator {
+    inner: CachingValidator,
+}
+
+#[pymethods]
+impl PyCachingValidator {
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn new(config: Option<&PyValidationConfig>) -> Self {
+        let cfg = config.map(|c| c.inner.clone()).unwrap_or_default();
+        Self {
+            inner: CachingValidator::new(Validator::new(cfg)),
+        }
+    }
+
+    /// Validate, reusing the last result if the schedule and GTFS hash are unchanged.
+    fn validate(
+        &mut self,
+        py: Python<'_>,
+        mut schedule: PyRefMut<'_, PySchedule>,
+        gtfs: &PyGtfsFeed,
+        gtfs_hash: u64,
+    ) -> PyValidationResult {
+        let validator = &mut self.inner;
+        let schedule = &mut schedule.inner;
+        let result = py.allow_threads(|| validator.validate(schedule, &gtfs.inner, gtfs_hash));
+        PyValidationResult { inner: result }
+    }
+
+    /// Number of validations answered from the cache.
+    #[getter]
+    fn cache_hits(&self) -> usize {
+        self.inner.cache_hits()
+    }
+
+    /// Forget the cached result.
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CachingValidator(cache_hits={})", self.inner.cache_hits())
+    }
+}
+
+/// Python wrapper for InferenceConfig.
+#[pyclass(name = "InferenceConfig")]
+#[derive(Clone)]
+pub struct PyInferenceConfig {
+    inner: InferenceConfig,
+}
+
+#[pymethods]
+impl PyInferenceConfig {
+    #[new]
+    #[pyo3(signature = (
+        default_depot=None,
+        average_speed_mps=None,
+        min_gap_seconds=None,
+        infer_interlining=None,
+        distance_model=None,
+        road_factor=None,
+        depot_locations=None,
+        symmetric_overrides=None,
+        infer_default_depot=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        default_depot: Option<String>,
+        average_speed_mps: Option<f64>,
+        min_gap_seconds: Option<u32>,
+        infer_interlining: Option<bool>,
+        distance_model: Option<&str>,
+        road_factor: Option<f64>,
+        depot_locations: Option<std::collections::HashMap<String, String>>,
+        symmetric_overrides: Option<bool>,
+        infer_default_depot: Option<bool>,
+    ) -> PyResult<Self> {
+        let mut config = Self {
+            inner: InferenceConfig::new(),
+        };
+
+        config.inner.default_depot = default_depot;
+        if let Some(v) = average_speed_mps {
+            config.set_average_speed_mps(v)?;
+        }
+        if let Some(v) = min_gap_seconds {
+            config.inner.min_gap_seconds = v;
+        }
+        if let Some(v) = infer_interlining {
+            config.inner.infer_interlining = v;
+        }
+        if let Some(model) = distance_model {
+            config.set_distance_model(model)?;
+        }
+        if let Some(factor) = road_factor {
+            if !matches!(
+                config.inner.distance_model,
+                DistanceModel::HaversineWithFactor(_)
+            ) {
+                return Err(PyValueError::new_err(format!(
+                    "road_factor only applies to the haversine_with_factor model, not {}",
+                    config.distance_model()
+                )));
+            }
+            config.set_road_factor(Some(factor));
+        }
+        if let Some(locations) = depot_locations {
+            config.inner.depot_locations = locations;
+        }
+        if let Some(v) = symmetric_overrides {
+            config.inner.symmetric_overrides = v;
+        }
+        if let Some(v) = infer_default_depot {
+            config.inner.infer_default_depot = v;
+        }
+
+        Ok(config)
+    }
+
+    #[getter]
+    fn default_depot(&self) -> Option<String> {
+        self.inner.default_depot.clone()
+    }
+
+    #[setter]
+    fn set_default_depot(&mut self, value: Option<String>) {
+        self.inner.default_depot = value;
+    }
+
+    #[getter]
+    fn average_speed_mps(&self) -> f64 {
+        self.inner.average_speed_mps
+    }
+
+    #[setter]
+    fn set_average_speed_mps(&mut self, value: f64) -> PyResult<()> {
+        if value.is_nan() || value <= 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "average_speed_mps must be positive, got {}",
+                value
+            )));
+        }
+        self.inner.average_speed_mps = value;
+        Ok(())
+    }
+
+    #[getter]
+    fn min_gap_seconds(&self) -> u32 {
+        self.inner.min_gap_seconds
+    }
+
+    #[setter]
+    fn set_min_gap_seconds(&mut self, value: u32) {
+        self.inner.min_gap_seconds = value;
+    }
+
+    #[getter]
+    fn infer_interlining(&self) -> bool {
+        self.inner.infer_interlining
+    }
+
+    #[setter]
+    fn set_infer_interlining(&mut self, value: bool) {
+        self.inner.infer_interlining = value;
+    }
+
+    /// Distance model: "haversine", "haversine_with_factor" or "manhattan".
+    #[getter]
+    fn distance_model(&self) -> &'static str {
+        match self.inner.distance_model {
+            DistanceModel::Haversine => "haversine",
+            DistanceModel::HaversineWithFactor(_) => "haversine_with_factor",
+            DistanceModel::Manhattan => "manhattan",
+        }
+    }
+
+    #[setter]
+    fn set_distance_model(&mut self, value: &str) -> PyResult<()> {
+        self.inner.distance_model = match value.to_lowercase().as_str() {
+            "haversine" => DistanceModel::Haversine,
+            "haversine_with_factor" => match self.inner.distance_model {
+                DistanceModel::HaversineWithFactor(factor) => {
+                    DistanceModel::HaversineWithFactor(factor)
+                }
+                _ => DistanceModel::default(),
+            },
+            "manhattan" => DistanceModel::Manhattan,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown distance model: {}",
+                    value
+                )))
+            }
+        };
+        Ok(())
+    }
+
+    /// Road circuity factor, set only for the "haversine_with_factor" model.
+    #[getter]
+    fn road_factor(&self) -> Option<f64> {
+        match self.inner.distance_model {
+            DistanceModel::HaversineWithFactor(factor) => Some(factor),
+            _ => None,
+        }
+    }
+
+    /// Setting a factor selects "haversine_with_factor"; clearing it falls
+    /// back to "haversine" from that model and leaves any other model alone.
+    #[setter]
+    fn set_road_factor(&mut self, value: Option<f64>) {
+        self.inner.distance_model = match (value, self.inner.distance_model) {
+            (Some(factor), _) => DistanceModel::HaversineWithFactor(factor),
+            (None, DistanceModel::HaversineWithFactor(_)) => DistanceModel::Haversine,
+            (None, model) => model,
+        };
+    }
+
+    #[getter]
+    fn depot_locations(&self) -> std::collections::HashMap<String, String> {
+        self.inner.depot_locations.clone()
+    }
+
+    /// Mark a stop as a depot location.
+    fn add_depot(&mut self, stop_id: String, depot_code: String) {
+        self.inner.depot_locations.insert(stop_id, depot_code);
+    }
+
+    /// Measured deadheads as {(from, to): (meters, seconds)}.
+    #[getter]
+    fn distance_overrides(&self) -> std::collections::HashMap<(String, String), (f64, u32)> {
+        self.inner.distance_overrides.clone()
+    }
+
+    #[getter]
+    fn symmetric_overrides(&self) -> bool {
+        self.inner.symmetric_overrides
+    }
+
+    #[setter]
+    fn set_symmetric_overrides(&mut self, value: bool) {
+        self.inner.symmetric_overrides = value;
+    }
+
+    #[getter]
+    fn infer_default_depot(&self) -> bool {
+        self.inner.infer_default_depot
+    }
+
+    #[setter]
+    fn set_infer_default_depot(&mut self, value: bool) {
+        self.inner.infer_default_depot = value;
+    }
+
+    /// Use a measured distance and time for deadheads from one place to another.
+    fn add_distance_override(
+        &mut self,
+        from_place: String,
+        to_place: String,
+        meters: f64,
+        seconds: u32,
+    ) {
+        self.inner
+            .distance_overrides
+            .insert((from_place, to_place), (meters, seconds));
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "InferenceConfig(default_depot={:?}, average_speed_mps={}, distance_model={:?})",
+            self.inner.default_depot,
+            self.inner.average_speed_mps,
+            self.distance_model()
+        )
+    }
+}
+
 /// Python wrapper for DeadheadInferenceResult.
 #[pyclass(name = "DeadheadInferenceResult")]
 pub struct PyDeadheadInferenceResult {
@@ -641,12 +2703,52 @@ impl PyDeadheadInferenceResult {
         self.inner.total_count()
     }
 
+    /// Total duration of the inferred deadheads in seconds.
+    #[getter]
+    fn total_added_seconds(&self) -> u32 {
+        self.inner.total_added_seconds()
+    }
+
+    /// Total estimated distance of the inferred deadheads in meters.
+    #[getter]
+    fn total_added_meters(&self) -> f64 {
+        self.inner.total_added_meters()
+    }
+
+    /// Added (seconds, meters) per block ID.
+    fn totals_by_block(&self) -> BTreeMap<String, (u32, f64)> {
+        self.inner.totals_by_block()
+    }
+
     /// Blocks that couldn't have deadheads inferred.
     #[getter]
     fn incomplete_blocks(&self) -> Vec<String> {
         self.inner.incomplete_blocks.clone()
     }
 
+    /// Trip pairs whose interlining couldn't be checked, as (block, row index, reason).
+    #[getter]
+    fn skipped_interlinings(&self) -> Vec<(String, usize, String)> {
+        self.inner.skipped_interlinings.clone()
+    }
+
+    /// Write the inferred deadheads to a CSV file for review.
+    fn to_csv(&self, path: &str) -> PyResult<()> {
+        let file = std::fs::File::create(path)?;
+        CsvExporter::default_config()
+            .export_deadheads(&self.inner, file)
+            .map_err(parse_err)
+    }
+
+    /// Export the inferred deadheads to a CSV string.
+    fn to_csv_string(&self) -> PyResult<String> {
+        let mut buffer = Vec::new();
+        CsvExporter::default_config()
+            .export_deadheads(&self.inner, &mut buffer)
+            .map_err(parse_err)?;
+        String::from_utf8(buffer).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "DeadheadInferenceResult(pull_outs={}, pull_ins={}, interlinings={})",
@@ -1,6 +1,6 @@
 //! TXC Python bindings.
 
-use pyo3::exceptions::PyIOError;
+use crate::errors::parse_err;
 use pyo3::prelude::*;
 use txc_parser::TxcDocument;
 
@@ -17,7 +17,7 @@ impl PyTxcDocument {
     fn from_path(path: &str) -> PyResult<Self> {
         TxcDocument::from_path(path)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse a TXC document from bytes.
@@ -25,7 +25,7 @@ impl PyTxcDocument {
     fn from_bytes(data: &[u8]) -> PyResult<Self> {
         TxcDocument::from_bytes(data)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Parse a TXC document from a string.
@@ -33,7 +33,7 @@ impl PyTxcDocument {
     fn from_string(xml: &str) -> PyResult<Self> {
         TxcDocument::from_str(xml)
             .map(|inner| Self { inner })
-            .map_err(|e| PyIOError::new_err(e.to_string()))
+            .map_err(parse_err)
     }
 
     /// Schema version of the document.
@@ -76,7 +76,7 @@ impl Route {
 }
 
 /// Type of transportation on a route.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 #[repr(u16)]
 pub enum RouteType {
     /// Tram, streetcar, light rail.
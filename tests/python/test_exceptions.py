@@ -157,3 +157,64 @@ class TestExceptionUsage:
 
         assert exc_info.value.date_string == "not-a-valid-date"
         assert "YYYY-MM-DD" in exc_info.value.expected_format
+
+
+class TestCoreExceptions:
+    """Tests for exceptions raised by the compiled core."""
+
+    def test_hierarchy(self) -> None:
+        """Test that core exceptions extend the built-in types."""
+        from transit_parser import (
+            ColumnMappingError,
+            ScheduleValidationError,
+            TransitParseError,
+        )
+
+        assert issubclass(TransitParseError, IOError)
+        assert issubclass(ColumnMappingError, TransitParseError)
+        assert issubclass(ScheduleValidationError, ValueError)
+
+    def test_malformed_csv_raises_parse_error(self, tmp_path) -> None:
+        """Test that a malformed schedule raises TransitParseError."""
+        from transit_parser import Schedule, TransitParseError
+
+        path = tmp_path / "schedule.csv"
+        path.write_text("block,trip_id,start_time\nB1,T1\n")
+
+        with pytest.raises(TransitParseError) as exc_info:
+            Schedule.from_csv(str(path))
+
+        assert exc_info.value.line is None
+
+    def test_missing_file_raises_plain_io_error(self, tmp_path) -> None:
+        """Test that an unreadable file is not reported as a parse error."""
+        from transit_parser import Schedule, TransitParseError
+
+        with pytest.raises(IOError) as exc_info:
+            Schedule.from_csv(str(tmp_path / "missing.csv"))
+
+        assert not isinstance(exc_info.value, TransitParseError)
+
+    def test_invalid_mapping_file(self, tmp_path) -> None:
+        """Test that an invalid mapping file raises ColumnMappingError."""
+        from transit_parser import ColumnMappingError, ReadOptions
+
+        path = tmp_path / "mapping.json"
+        path.write_text('{"columns": 5}')
+
+        with pytest.raises(ColumnMappingError):
+            ReadOptions.from_mapping_file(str(path))
+
+    def test_raise_for_errors(self) -> None:
+        """Test that failed validation raises ScheduleValidationError."""
+        from transit_parser import Schedule, ScheduleValidationError
+
+        schedule = Schedule.from_csv_string(
+            "block,trip_id,start_time,end_time\nB1,T1,08:00:00,14:00:00\n"
+        )
+        result = schedule.validate_structure()
+
+        with pytest.raises(ScheduleValidationError) as exc_info:
+            result.raise_for_errors()
+
+        assert exc_info.value.errors[0].startswith("E200")
@@ -0,0 +1,69 @@
+"""Unit tests for the schedule API."""
+
+from __future__ import annotations
+
+import pickle
+from pathlib import Path
+
+
+SCHEDULE_CSV = """Operator: Metro Transit
+block,run_number,depot,trip_id,start_time,end_time,start_place,end_place,row_type
+B1,R1,NORTH,T1,08:00:00,08:30:00,A,B,revenue
+B1,R1,NORTH,T2,08:40:00,09:10:00,B,A,revenue
+B1,R2,SOUTH,T3,09:00:00,09:30:00,C,D,revenue
+"""
+
+
+class TestSchedulePickling:
+    """Tests for pickling schedules and rows."""
+
+    def test_row_round_trip(self) -> None:
+        """Test that a row survives pickling unchanged."""
+        from transit_parser import ScheduleRow
+
+        row = ScheduleRow(
+            block="B1",
+            trip_id="T1",
+            start_time="08:00:00",
+            end_time="08:30:00",
+            start_lat=40.7128,
+            start_lon=-74.006,
+            row_type="deadhead",
+            block_sequence=3,
+        )
+
+        restored = pickle.loads(pickle.dumps(row))
+        assert restored == row
+        assert hash(restored) == hash(row)
+
+    def test_schedule_round_trip(self, tmp_path: Path) -> None:
+        """Test that a schedule survives pickling with its rows and metadata."""
+        from transit_parser import ReadOptions, Schedule
+
+        path = tmp_path / "schedule.csv"
+        path.write_text(SCHEDULE_CSV)
+        schedule = Schedule.from_csv(
+            str(path), ReadOptions(skip_preamble_lines=1, capture_preamble=True)
+        )
+        assert schedule.metadata["preamble"] == ["Operator: Metro Transit"]
+
+        restored = pickle.loads(pickle.dumps(schedule))
+        assert restored == schedule
+        assert len(restored) == 3
+        assert restored.metadata == schedule.metadata
+
+    def test_schedule_block_key_not_pickled(self) -> None:
+        """Test that a custom block key resets to the default after pickling."""
+        from transit_parser import Schedule
+
+        schedule = Schedule.from_csv_string(SCHEDULE_CSV.split("\n", 1)[1])
+        schedule.derive_blocks_by(["depot", "block"])
+        assert schedule.block_ids() == ["NORTH:B1", "SOUTH:B1"]
+
+        restored = pickle.loads(pickle.dumps(schedule))
+        assert restored == schedule
+        assert restored.block_key == ["block"]
+        assert restored.block_ids() == ["B1"]
+
+        restored.derive_blocks_by(schedule.block_key)
+        assert restored.block_ids() == schedule.block_ids()